@@ -0,0 +1,27 @@
+//! Parses arbitrary bytes with the default pattern and builds a network
+//! from whatever was accepted.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate network;
+
+use network::{ Error, Network };
+use network::compact_star::try_compact_star_from_edge_vec;
+use network::parse::{ DEFAULT_PATTERN, parse_edges_from_bytes };
+
+fuzz_target!(|data: &[u8]| {
+    let is_undirected = data.first().map_or(false, |b| b & 1 == 1);
+    if let Ok(mut parsed) = parse_edges_from_bytes(data, DEFAULT_PATTERN, is_undirected, 0) {
+        let num_nodes = parsed.num_nodes();
+        match try_compact_star_from_edge_vec(num_nodes, &mut parsed.edges) {
+            Ok(network) => {
+                assert_eq!(num_nodes, network.num_nodes());
+                assert_eq!(parsed.edges.len(), network.num_arcs());
+            },
+            // input without a single edge names no nodes
+            Err(Error::NoNodes) => assert!(parsed.edges.is_empty()),
+            Err(e) => panic!("parsed edges were rejected: {}", e),
+        }
+    }
+});
@@ -0,0 +1,17 @@
+//! Uses the first line of the input as pattern and the rest as edge list.
+
+#![no_main]
+#[macro_use]
+extern crate libfuzzer_sys;
+extern crate network;
+
+use network::parse::parse_edges;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(text) = ::std::str::from_utf8(data) {
+        let mut parts = text.splitn(2, '\n');
+        let pattern = parts.next().unwrap_or("");
+        let input = parts.next().unwrap_or("");
+        let _ = parse_edges(input, pattern, false, 0);
+    }
+});
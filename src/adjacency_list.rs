@@ -0,0 +1,225 @@
+//! A mutable network representation for building networks incrementally.
+//!
+//! Other than `CompactStar`, an `AdjacencyList` supports adding nodes and
+//! adding, removing and updating arcs at any time. Once complete, it is
+//! frozen into a `CompactStar` for fast queries. Changes can be recorded in
+//! a `Journal` to replay them elsewhere.
+
+use super::{ Capacity, Cost, Error, Network, NodeId };
+use super::compact_star::{ CompactStar, try_compact_star_from_edge_vec };
+use super::journal::{ Change, Journal, JournalError };
+
+/// A network stored as one list of `(head, cost, capacity)` arcs per node.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AdjacencyList {
+    arcs:     Vec<Vec<(NodeId, Cost, Capacity)>>,
+    num_arcs: usize,
+    cost_sum: Cost,
+    journal:  Option<Journal>,
+}
+
+impl AdjacencyList {
+    pub fn new() -> AdjacencyList {
+        AdjacencyList::default()
+    }
+
+    /// A network with `nodes` nodes and no arcs.
+    pub fn with_nodes(nodes: usize) -> AdjacencyList {
+        AdjacencyList {
+            arcs: vec![Vec::new(); nodes],
+            ..AdjacencyList::default()
+        }
+    }
+
+    /// A copy of `network`, with the arcs of every node in the same order.
+    pub fn from_network<N: Network>(network: &N) -> AdjacencyList {
+        let mut list = AdjacencyList::with_nodes(network.num_nodes());
+        for (from, to, cost, capacity) in network.arc_values() {
+            list.arcs[from as usize].push((to, cost, capacity));
+            list.num_arcs += 1;
+            list.cost_sum += cost;
+        }
+        list
+    }
+
+    /// Starts recording all following changes in a new journal.
+    pub fn start_journal(&mut self) {
+        self.journal = Some(Journal::new());
+    }
+
+    /// Stops recording and returns the recorded changes, if recording was
+    /// started.
+    pub fn take_journal(&mut self) -> Option<Journal> {
+        self.journal.take()
+    }
+
+    fn record(&mut self, change: Change) {
+        if let Some(journal) = self.journal.as_mut() {
+            journal.record(change);
+        }
+    }
+
+    fn check_node(&self, node: NodeId) -> Result<(), JournalError> {
+        if (node as usize) < self.arcs.len() {
+            Ok(())
+        } else {
+            Err(JournalError::InvalidNode { node: node })
+        }
+    }
+
+    fn find_arc(&self, from: NodeId, to: NodeId) -> Result<usize, JournalError> {
+        self.check_node(from)?;
+        self.arcs[from as usize].iter()
+            .position(|arc| arc.0 == to)
+            .ok_or(JournalError::MissingArc { from: from, to: to })
+    }
+
+    /// Appends a node and returns its id.
+    pub fn add_node(&mut self) -> NodeId {
+        self.arcs.push(Vec::new());
+        self.record(Change::AddNode);
+        (self.arcs.len() - 1) as NodeId
+    }
+
+    /// Appends an arc to the arcs leaving `from`. Parallel arcs are allowed.
+    pub fn add_arc(&mut self, from: NodeId, to: NodeId, cost: Cost, capacity: Capacity) -> Result<(), JournalError> {
+        self.check_node(from)?;
+        self.check_node(to)?;
+        self.arcs[from as usize].push((to, cost, capacity));
+        self.num_arcs += 1;
+        self.cost_sum += cost;
+        self.record(Change::AddArc { from: from, to: to, cost: cost, capacity: capacity });
+        Ok(())
+    }
+
+    /// Removes the first arc from `from` to `to`, keeping the order of the
+    /// other arcs.
+    pub fn remove_arc(&mut self, from: NodeId, to: NodeId) -> Result<(), JournalError> {
+        let k = self.find_arc(from, to)?;
+        let (_, cost, _) = self.arcs[from as usize].remove(k);
+        self.num_arcs -= 1;
+        self.cost_sum -= cost;
+        self.record(Change::RemoveArc { from: from, to: to });
+        Ok(())
+    }
+
+    /// Sets the cost of the first arc from `from` to `to`.
+    pub fn update_cost(&mut self, from: NodeId, to: NodeId, cost: Cost) -> Result<(), JournalError> {
+        let k = self.find_arc(from, to)?;
+        let capacity = self.arcs[from as usize][k].2;
+        self.update_arc(from, to, cost, capacity)
+    }
+
+    /// Sets cost and capacity of the first arc from `from` to `to`.
+    pub fn update_arc(&mut self, from: NodeId, to: NodeId, cost: Cost, capacity: Capacity) -> Result<(), JournalError> {
+        let k = self.find_arc(from, to)?;
+        let arc = &mut self.arcs[from as usize][k];
+        self.cost_sum += cost - arc.1;
+        *arc = (to, cost, capacity);
+        self.record(Change::UpdateArc { from: from, to: to, cost: cost, capacity: capacity });
+        Ok(())
+    }
+
+    /// Freezes the network into a `CompactStar` with the same arcs in the
+    /// same order.
+    /// # Errors
+    /// `Error::NoNodes` if the network has no nodes.
+    pub fn to_compact_star(&self) -> Result<CompactStar, Error> {
+        let mut edges = Vec::with_capacity(self.num_arcs);
+        for (from, arcs) in self.arcs.iter().enumerate() {
+            for &(to, cost, capacity) in arcs {
+                edges.push((from as NodeId, to, cost, capacity));
+            }
+        }
+        try_compact_star_from_edge_vec(self.arcs.len(), &mut edges)
+    }
+
+    fn first_arc(&self, from: NodeId, to: NodeId) -> Option<&(NodeId, Cost, Capacity)> {
+        self.arcs.get(from as usize).and_then(|arcs| arcs.iter().find(|arc| arc.0 == to))
+    }
+}
+
+impl Network for AdjacencyList {
+    fn adjacent(&self, i: NodeId) -> Vec<NodeId> {
+        self.arcs[i as usize].iter().map(|arc| arc.0).collect()
+    }
+
+    fn cost(&self, from: NodeId, to: NodeId) -> Option<Cost> {
+        self.first_arc(from, to).map(|arc| arc.1)
+    }
+
+    fn capacity(&self, from: NodeId, to: NodeId) -> Option<Capacity> {
+        self.first_arc(from, to).map(|arc| arc.2)
+    }
+
+    fn num_nodes(&self) -> usize {
+        self.arcs.len()
+    }
+
+    fn num_arcs(&self) -> usize {
+        self.num_arcs
+    }
+
+    fn invalid_id(&self) -> NodeId {
+        self.arcs.len() as NodeId
+    }
+
+    fn infinity(&self) -> Cost {
+        self.cost_sum
+    }
+
+    fn out_arcs(&self, i: NodeId) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        self.arcs[i as usize].iter().cloned()
+    }
+
+    fn out_degree(&self, i: NodeId) -> usize {
+        self.arcs[i as usize].len()
+    }
+}
+
+#[test]
+fn test_adjacency_list() {
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut list = AdjacencyList::with_nodes(2);
+    assert_eq!(2, list.add_node());
+    list.add_arc(0, 1, 2.0, 5.0).unwrap();
+    list.add_arc(1, 2, 3.0, 5.0).unwrap();
+    list.add_arc(0, 2, 7.0, 1.0).unwrap();
+    assert_eq!(Err(JournalError::InvalidNode { node: 3 }), list.add_arc(0, 3, 1.0, 1.0));
+    assert_eq!(vec![1, 2], list.adjacent(0));
+    assert_eq!(vec![(1, 2.0, 5.0), (2, 7.0, 1.0)], list.out_arcs(0).collect::<Vec<_>>());
+    assert_eq!(vec![(0, 7.0, 1.0), (1, 3.0, 5.0)], list.incoming(2).collect::<Vec<_>>());
+    assert_eq!(3, list.num_arcs());
+    assert_eq!(12.0, list.infinity());
+
+    list.start_journal();
+    list.update_cost(0, 2, 4.0).unwrap();
+    list.remove_arc(0, 1).unwrap();
+    assert_eq!(Err(JournalError::MissingArc { from: 0, to: 1 }), list.remove_arc(0, 1));
+    assert_eq!(Some(4.0), list.cost(0, 2));
+    assert_eq!(Some(1.0), list.capacity(0, 2));
+    assert_eq!(7.0, list.infinity());
+
+    let mut edges = vec![(0,2,4.0,1.0), (1,2,3.0,5.0)];
+    let frozen = list.to_compact_star().unwrap();
+    assert_eq!(compact_star_from_edge_vec(3, &mut edges), frozen);
+    assert_eq!(frozen, AdjacencyList::from_network(&frozen).to_compact_star().unwrap());
+    assert!(match AdjacencyList::new().to_compact_star() { Err(Error::NoNodes) => true, _ => false });
+
+    // the journal turns the original network into the changed one
+    let journal = list.take_journal().unwrap();
+    assert_eq!(2, journal.len());
+    let mut original = vec![(0,1,2.0,5.0), (1,2,3.0,5.0), (0,2,7.0,1.0)];
+    assert_eq!(Ok(frozen), journal.replay(&compact_star_from_edge_vec(3, &mut original)));
+}
+
+#[test]
+fn test_from_network_parallel_arcs() {
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.0,10.0), (0,1,2.0,20.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    let list = AdjacencyList::from_network(&compact_star);
+    assert_eq!(vec![(1, 1.0, 10.0), (1, 2.0, 20.0)], list.out_arcs(0).collect::<Vec<_>>());
+    assert_eq!(3.0, list.infinity());
+    assert_eq!(compact_star, list.to_compact_star().unwrap());
+}
@@ -1,16 +1,93 @@
 use std::collections::HashMap;
+use std::fs::File;
+use std::io::{ self, BufWriter };
 
 use network::{ DoubleVec, Network, NodeId };
-use network::algorithms::{ dijkstra, pagerank };
-use usage::{ DEFAULT_BETA, DEFAULT_EPS, DEFAULT_START_ID, Args };
+use network::algorithms::{ bellman_ford, betweenness, bipartition, breadth_first_search, budget_constrained_path, critical_path_method, distance_table, max_weight_closure, dijkstra, dinic, hits, hopcroft_karp, k_shortest_paths, kruskal, min_cost_assignment, pagerank,
+                                pagerank_parallel, reconstruct_path };
+use network::generators::{ self, ArcWeights, Distribution };
+use network::io::{ distance_matrix, dot, edge_list };
+use network::query::{ QueryResult, parse_query };
+use network::pretty::{ GeoJsonPaths, LabeledDisplay, Ranking };
+use parse_text::{ coordinates_from_file, node_values_from_file, nodes_from_file };
+use usage::{ DEFAULT_BETA, DEFAULT_EPS, DEFAULT_K, DEFAULT_MAX_ITER, DEFAULT_P, DEFAULT_SCALE, DEFAULT_SEED, DEFAULT_START_ID, DEFAULT_TOP, DEFAULT_WS_K, Args };
 
 #[derive(Debug, RustcDecodable)]
-pub enum Algorithm { dijkstra, pagerank }
+pub enum Algorithm { bellman_ford, betweenness, bfs, closure, cpm, dijkstra, generate, hits, kshortest, matching, matrix, maxflow, mst, pagerank, query }
 
 pub fn run_algorithm<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
     match args.arg_algorithm {
+        Algorithm::bellman_ford => run_bellman_ford(network, args, node_to_id),
+        Algorithm::betweenness => run_betweenness(network, args, node_to_id),
+        Algorithm::bfs => run_bfs(network, args, node_to_id),
+        Algorithm::closure => run_closure(network, args, node_to_id),
+        Algorithm::cpm => run_cpm(network, node_to_id),
         Algorithm::dijkstra => run_dijkstra(network, args, node_to_id),
+        Algorithm::generate => run_generate(args),
+        Algorithm::hits => run_hits(network, args, node_to_id),
+        Algorithm::kshortest => run_k_shortest(network, args, node_to_id),
+        Algorithm::matching => run_matching(network, args, node_to_id),
+        Algorithm::matrix => run_matrix(network, args, node_to_id),
+        Algorithm::maxflow => run_maxflow(network, args, node_to_id),
+        Algorithm::mst => run_mst(network, args, node_to_id),
         Algorithm::pagerank => run_pagerank(network, args, node_to_id),
+        Algorithm::query => run_query(network, args, node_to_id),
+    }
+}
+
+/// Writes a random network to the file given as input file name. Runs
+/// before any input is read.
+pub fn run_generate(args: &Args) {
+    let n = match args.flag_n {
+        Some(n) if n > 0 => n,
+        Some(_) => {
+            eprintln!("Generating a network needs at least one node.");
+            return;
+        },
+        None => {
+            eprintln!("Generating a network needs the number of nodes, given by --n.");
+            return;
+        },
+    };
+    let weights = ArcWeights {
+        cost:     args.flag_max_cost.map(|c| Distribution::Uniform(1.0, c)).unwrap_or(Distribution::Constant(1.0)),
+        capacity: Distribution::Constant(1.0),
+    };
+    let p = args.flag_p.unwrap_or(DEFAULT_P);
+    if !(p >= 0.0 && p <= 1.0) {
+        eprintln!("The probability --p has to be between 0 and 1.");
+        return;
+    }
+    let seed = args.flag_seed.unwrap_or(DEFAULT_SEED);
+    let network = match args.flag_model.as_ref().map(|m| m.as_str()) {
+        Some("gnp") => generators::gnp(n, p, &weights, seed),
+        Some("ba") => {
+            let k = args.flag_k.unwrap_or(DEFAULT_K);
+            if k == 0 || k >= n {
+                eprintln!("ba needs between 1 and n - 1 edges per new node, given by --k.");
+                return;
+            }
+            generators::barabasi_albert(n, k, &weights, seed)
+        },
+        Some("ws") => {
+            let k = args.flag_k.unwrap_or(DEFAULT_WS_K);
+            if k % 2 != 0 || k >= n {
+                eprintln!("ws needs an even number of neighbors per node less than n, given by --k.");
+                return;
+            }
+            generators::watts_strogatz(n, k, p, &weights, seed)
+        },
+        Some("complete") => generators::complete(n, &weights, seed),
+        Some("grid") => generators::grid(n, n, &weights, seed),
+        _ => {
+            eprintln!("Generating a network needs a model, given by --model: gnp, ba, ws, complete or grid.");
+            return;
+        },
+    };
+    let written = File::create(&args.arg_filename).and_then(|file| edge_list::write(&network, &mut BufWriter::new(file)));
+    match written {
+        Ok(()) => println!("Wrote {} nodes and {} arcs to {}", network.num_nodes(), network.num_arcs(), args.arg_filename),
+        Err(e) => eprintln!("Could not write {}: {}", args.arg_filename, e),
     }
 }
 
@@ -19,24 +96,372 @@ fn run_dijkstra<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<Strin
         Some(name) => node_to_id[name],
         None       => DEFAULT_START_ID,
     };
+    if let Some(budget) = args.flag_budget {
+        return run_budget_path(network, start_id, budget, args, node_to_id);
+    }
     let use_heap = args.flag_use_heap;
     let (pred, cost) = dijkstra(network, start_id, use_heap);
-    print_dijkstra_result(&pred, &cost, &node_to_id)
+    print_shortest_paths(network, &pred, &cost, start_id, args, &node_to_id);
+    write_dot(network, &pred, start_id, args, node_to_id);
+}
+
+fn run_budget_path<N: Network>(network: &N, source: NodeId, budget: f64, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let target = match args.flag_target_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => {
+            println!("A path within a budget needs a target, given by --target-node.");
+            return;
+        },
+    };
+    let tolls: Vec<f64> = network.arc_values().map(|(_, _, _, toll)| toll).collect();
+    let names = node_names(network, node_to_id);
+    match budget_constrained_path(network, &tolls, budget, source, target) {
+        Some(route) => println!("{} (toll: {}, lower bound: {})", route.path.with_labels(&names), route.toll, route.lower_bound),
+        None => println!("No path within a toll budget of {}.", budget),
+    }
+}
+
+fn run_bfs<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let start_id = match args.flag_start_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => DEFAULT_START_ID,
+    };
+    let (pred, order) = breadth_first_search(network, start_id);
+    let id_to_node: HashMap<NodeId, String> = node_to_id.iter()
+        .map(|(k,v)| (*v,k.clone()))
+        .collect();
+    for i in order.iter().take(100) {
+        println!("{} -> {}", get_node_name(&pred[*i as usize], &id_to_node), get_node_name(i, &id_to_node));
+    }
+    write_dot(network, &pred, start_id, args, node_to_id);
+}
+
+fn run_bellman_ford<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let start_id = match args.flag_start_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => DEFAULT_START_ID,
+    };
+    match bellman_ford(network, start_id) {
+        Ok((pred, cost)) => {
+            print_shortest_paths(network, &pred, &cost, start_id, args, &node_to_id);
+            write_dot(network, &pred, start_id, args, node_to_id);
+        },
+        Err(cycle) => {
+            let id_to_node: HashMap<NodeId, String> = node_to_id.iter()
+                .map(|(k,v)| (*v,k.clone()))
+                .collect();
+            let names: Vec<String> = cycle.cycle.iter().map(|i| get_node_name(i, &id_to_node)).collect();
+            println!("Negative cycle: {}", names.join(" -> "));
+        },
+    }
+}
+
+fn run_betweenness<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let scores = betweenness(network, args.flag_weighted);
+    let names = node_names(network, node_to_id);
+    let top = args.flag_k.unwrap_or(DEFAULT_TOP);
+    println!("Top {} nodes by betweenness:", top);
+    print!("{}", Ranking::new(&scores, top).with_labels(&names));
+}
+
+fn run_k_shortest<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let source = match args.flag_start_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => DEFAULT_START_ID,
+    };
+    let target = match args.flag_target_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => {
+            println!("K shortest paths need a target, given by --target-node.");
+            return;
+        },
+    };
+    let k = args.flag_k.unwrap_or(DEFAULT_K);
+    let paths = k_shortest_paths(network, source, target, k);
+    let names = node_names(network, node_to_id);
+
+    if paths.len() < k {
+        println!("Found {} of {} requested loopless paths.", paths.len(), k);
+    }
+    if !args.flag_geojson {
+        for (rank, path) in paths.iter().enumerate() {
+            println!("{:>4}. {}", rank + 1, path.with_labels(&names));
+        }
+        return;
+    }
+    let file_name = match args.flag_coordinates.as_ref() {
+        Some(file_name) => file_name,
+        None => {
+            eprintln!("GeoJSON output needs node coordinates, given by --coordinates.");
+            return;
+        },
+    };
+    let coordinates = match coordinates_from_file(file_name, node_to_id) {
+        Ok(coordinates) => coordinates,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", file_name, e);
+            return;
+        },
+    };
+    let missing = paths.iter().flat_map(|path| path.nodes().iter()).find(|i| coordinates[**i as usize].0.is_nan());
+    match missing {
+        Some(i) => eprintln!("No coordinates for node {}.", names[*i as usize]),
+        None => print!("{}", GeoJsonPaths::new(&paths, &coordinates).with_labels(&names)),
+    }
+}
+
+fn run_maxflow<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let source = match args.flag_start_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => DEFAULT_START_ID,
+    };
+    let sink = match args.flag_target_node.as_ref() {
+        Some(name) => node_to_id[name],
+        None       => {
+            println!("Maximum flow needs a sink, given by --target-node.");
+            return;
+        },
+    };
+    let flow = dinic(network, source, sink);
+    let id_to_node: HashMap<NodeId, String> = node_to_id.iter()
+        .map(|(k,v)| (*v,k.clone()))
+        .collect();
+    println!("Maximum flow from {} to {}: {}",
+             get_node_name(&source, &id_to_node), get_node_name(&sink, &id_to_node), flow.value);
+    for ((i, j), f) in network.arcs().zip(&flow.flows).filter(|&(_, f)| *f > 0.0).take(100) {
+        println!("{} -> {} : {:4}", get_node_name(&i, &id_to_node), get_node_name(&j, &id_to_node), f);
+    }
+}
+
+fn run_mst<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    if !args.flag_undirected {
+        eprintln!("Arc directions are ignored, the tree spans the network as if read with --undirected.");
+    }
+    let tree = kruskal(network);
+    let id_to_node: HashMap<NodeId, String> = node_to_id.iter()
+        .map(|(k,v)| (*v,k.clone()))
+        .collect();
+    println!("Minimum spanning tree weight: {}", tree.weight);
+    if tree.arcs.len() + 1 < network.num_nodes() {
+        println!("The network is not connected, the tree is a spanning forest.");
+    }
+    for &(i, j) in tree.arcs.iter().take(100) {
+        println!("{} -> {} : {:4}", get_node_name(&i, &id_to_node), get_node_name(&j, &id_to_node),
+                 network.cheapest_cost(i, j).unwrap());
+    }
 }
 
 fn run_pagerank<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
     let beta = args.flag_beta.unwrap_or(DEFAULT_BETA);
     let eps = args.flag_eps.unwrap_or(DEFAULT_EPS);
-    let ranks = pagerank(network, beta, eps);
+    let ranks = match args.flag_threads {
+        Some(threads) if threads > 1 => pagerank_parallel(network, beta, eps, threads),
+        _ => pagerank(network, beta, eps),
+    };
     let target_node = args.flag_target_node.as_ref();
-    print_pagerank_results(&ranks, node_to_id, target_node);
+    print_pagerank_results(network, &ranks, node_to_id, target_node);
+}
+
+fn run_closure<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let file_name = match args.flag_profits.as_ref() {
+        Some(file_name) => file_name,
+        None => {
+            eprintln!("The closure needs node profits, given by --profits.");
+            return;
+        },
+    };
+    let profits = match node_values_from_file(file_name, node_to_id) {
+        Ok(profits) => profits,
+        Err(e) => {
+            eprintln!("Could not read {}: {}", file_name, e);
+            return;
+        },
+    };
+    let names = node_names(network, node_to_id);
+    let closure = max_weight_closure(network, &profits);
+    let chosen: Vec<&str> = network.nodes().filter(|i| closure.nodes[*i as usize]).map(|i| names[i as usize].as_str()).collect();
+    println!("Maximum profit: {} with {} of {} nodes", closure.profit, chosen.len(), network.num_nodes());
+    for name in chosen {
+        println!("{}", name);
+    }
+}
+
+fn run_matching<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let mut left = match bipartition(network) {
+        Some(left) => left,
+        None => {
+            println!("The network is not bipartite.");
+            return;
+        },
+    };
+    let names = node_names(network, node_to_id);
+    let matching = if args.flag_weighted {
+        // assign the smaller side
+        if 2 * left.iter().filter(|l| **l).count() > left.len() {
+            left = left.iter().map(|l| !l).collect();
+        }
+        match min_cost_assignment(network, &left) {
+            Some(assignment) => {
+                println!("Assignment of cost {}", assignment.cost);
+                assignment.matching
+            },
+            None => {
+                println!("Not every node of the smaller side can be assigned.");
+                return;
+            },
+        }
+    } else {
+        hopcroft_karp(network, &left)
+    };
+    println!("Matching of {} pairs", matching.size);
+    for (i, j) in matching.pairs(&left).into_iter().take(100) {
+        println!("{} - {}", names[i as usize], names[j as usize]);
+    }
+}
+
+fn run_matrix<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let nodes: Vec<NodeId> = match args.flag_nodes.as_ref() {
+        Some(file_name) => match nodes_from_file(file_name, node_to_id) {
+            Ok(nodes) => nodes,
+            Err(e) => {
+                eprintln!("Could not read {}: {}", file_name, e);
+                return;
+            },
+        },
+        None => network.nodes().collect(),
+    };
+    let names = node_names(network, node_to_id);
+    let table = distance_table(network, &nodes, &nodes);
+    let stdout = io::stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+    let written = if let Some(threshold) = args.flag_threshold {
+        let labels: Vec<String> = nodes.iter().map(|i| names[*i as usize].clone()).collect();
+        distance_matrix::write_sparse(&table, threshold, &labels, &labels, &mut writer)
+    } else {
+        match args.flag_matrix_format.as_ref().map(|f| f.as_str()) {
+            None | Some("plain") => distance_matrix::write_plain(&table, &mut writer),
+            Some("tsplib") => {
+                let scale = args.flag_scale.unwrap_or(DEFAULT_SCALE);
+                distance_matrix::write_tsplib(&table, &args.arg_filename, scale, &mut writer)
+            },
+            Some(format) => {
+                eprintln!("Unknown matrix format {}, expected plain or tsplib.", format);
+                return;
+            },
+        }
+    };
+    if let Err(e) = written {
+        eprintln!("Could not write the distance matrix: {}", e);
+    }
+}
+
+fn run_cpm<N: Network>(network: &N, node_to_id: &HashMap<String, NodeId>) {
+    let names = node_names(network, node_to_id);
+    let cpm = match critical_path_method(network) {
+        Ok(cpm) => cpm,
+        Err(cycle) => {
+            let cycle: Vec<&str> = cycle.cycle.iter().map(|i| names[*i as usize].as_str()).collect();
+            println!("The activities are cyclic: {}", cycle.join(" -> "));
+            return;
+        },
+    };
+    println!("Project duration: {}", cpm.duration);
+    for path in cpm.critical_paths(network) {
+        let path: Vec<&str> = path.iter().map(|i| names[*i as usize].as_str()).collect();
+        println!("Critical path: {}", path.join(" -> "));
+    }
+    println!("activity : duration, earliest start, latest start, total float, free float");
+    let activities = network.nodes().flat_map(|i| network.out_arcs(i).map(move |(j, duration, _)| (i, j, duration)));
+    for (arc, (i, j, duration)) in activities.enumerate().take(100) {
+        println!("{} -> {} : {}, {}, {}, {}, {}{}", names[i as usize], names[j as usize], duration, cpm.earliest[i as usize],
+                 cpm.latest[j as usize] - duration, cpm.total_float[arc], cpm.free_float[arc],
+                 if cpm.is_critical(arc) { " (critical)" } else { "" });
+    }
+}
+
+fn run_hits<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let eps = args.flag_eps.unwrap_or(DEFAULT_EPS);
+    let (hubs, authorities) = hits(network, eps, DEFAULT_MAX_ITER);
+    if let Some(name) = args.flag_target_node.as_ref() {
+        let id = node_to_id[name] as usize;
+        println!("Hub score of node {}: {:e}, authority score: {:e}", name, hubs[id], authorities[id]);
+        return;
+    }
+    let names = node_names(network, node_to_id);
+    let top = args.flag_k.unwrap_or(DEFAULT_TOP);
+    println!("Top {} hubs:", top);
+    print!("{}", Ranking::new(&hubs, top).with_labels(&names));
+    println!("Top {} authorities:", top);
+    print!("{}", Ranking::new(&authorities, top).with_labels(&names));
+}
+
+fn run_query<N: Network>(network: &N, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let text = match args.flag_query.as_ref() {
+        Some(text) => text,
+        None => {
+            println!("The query algorithm needs a query, given by --query.");
+            return;
+        },
+    };
+    let result = parse_query(text).and_then(|query| query.evaluate(network, node_to_id));
+    let names = node_names(network, node_to_id);
+    match result {
+        Ok(QueryResult::Path(Some(path))) => println!("{}", path.with_labels(&names)),
+        Ok(QueryResult::Path(None)) => println!("No path matches the query."),
+        Ok(QueryResult::Reachable(nodes)) => {
+            for (node, cost) in nodes {
+                println!("{} : {:4}", names[node as usize], cost);
+            }
+        },
+        Err(e) => eprintln!("Invalid query: {}", e),
+    }
+}
+
+/// Writes the network with the search result in `pred` highlighted to the
+/// file given by --dot-out, if any: the path to --target-node if given,
+/// the whole search tree otherwise.
+fn write_dot<N: Network>(network: &N, pred: &[NodeId], source: NodeId, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    let file_name = match args.flag_dot_out.as_ref() {
+        Some(file_name) => file_name,
+        None => return,
+    };
+    let names = node_names(network, node_to_id);
+    let written = File::create(file_name).and_then(|file| {
+        let mut writer = BufWriter::new(file);
+        match args.flag_target_node.as_ref() {
+            Some(name) => dot::write_path(network, pred, source, node_to_id[name], Some(&names), &mut writer),
+            None => dot::write_tree(network, pred, Some(&names), &mut writer),
+        }
+    });
+    if let Err(e) = written {
+        eprintln!("Could not write {}: {}", file_name, e);
+    }
+}
+
+/// The name of every node, indexed by node id.
+fn node_names<N: Network>(network: &N, node_to_id: &HashMap<String, NodeId>) -> Vec<String> {
+    let mut names = vec![String::new(); network.num_nodes()];
+    for (name, id) in node_to_id {
+        names[*id as usize] = name.clone();
+    }
+    names
 }
 
 fn get_node_name(i: &NodeId, id_to_node: &HashMap<NodeId, String>) -> String {
     id_to_node.get(i).unwrap_or(&"NONE".to_string()).to_string()
 }
 
-fn print_dijkstra_result(pred: &Vec<NodeId>, cost: &DoubleVec, node_to_id: &HashMap<String, NodeId>) {
+/// Prints the route to the target node if one is given, otherwise the
+/// predecessor and distance of the first 100 nodes.
+fn print_shortest_paths<N: Network>(network: &N, pred: &Vec<NodeId>, cost: &DoubleVec, source: NodeId, args: &Args, node_to_id: &HashMap<String, NodeId>) {
+    if let Some(target_name) = args.flag_target_node.as_ref() {
+        let names = node_names(network, node_to_id);
+        match reconstruct_path(pred, cost, source, node_to_id[target_name]) {
+            Some(path) => println!("{} ({} hops)", path.with_labels(&names), path.hops()),
+            None => println!("No path to {}.", target_name),
+        }
+        return;
+    }
     let id_to_node: HashMap<NodeId, String> = node_to_id.iter()
         .map(|(k,v)| (*v,k.clone()))
         .collect();
@@ -49,9 +474,13 @@ fn print_dijkstra_result(pred: &Vec<NodeId>, cost: &DoubleVec, node_to_id: &Hash
     }
 }
 
-fn print_pagerank_results(ranks: &Vec<f64>, node_to_id: &HashMap<String, NodeId>, target_node: Option<&String>) {
+fn print_pagerank_results<N: Network>(network: &N, ranks: &Vec<f64>, node_to_id: &HashMap<String, NodeId>, target_node: Option<&String>) {
     match target_node {
-        None => println!("No target node given."),
+        None => {
+            let names = node_names(network, node_to_id);
+            println!("No target node given, top ranks:");
+            print!("{}", Ranking::new(ranks, 10).with_labels(&names));
+        },
         Some(name) => {
             let id = node_to_id[name] as usize;
             println!("Rank of node {}: {} ({:e})", name, ranks[id], ranks[id]);
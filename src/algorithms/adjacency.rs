@@ -0,0 +1,117 @@
+use std::f64;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::super::heaps::{ BinaryHeap, Heap };
+
+/// Adjacency lists with the arc costs attached, one `(node, cost)` list
+/// per node. Searches that run many times over the same network use these
+/// instead of looking up every arc cost in the network again.
+pub(crate) type CostAdjacency = Vec<Vec<(NodeId, Cost)>>;
+
+/// Collects the outgoing arcs of every node as `(head, cost)` pairs.
+pub(crate) fn forward_adjacency<N: Network>(network: &N) -> CostAdjacency {
+    let n = network.num_nodes();
+    let mut forward = Vec::with_capacity(n);
+    for i in 0..n {
+        let from = i as NodeId;
+        forward.push(network.out_arcs(from).map(|(to, cost, _)| (to, cost)).collect());
+    }
+    forward
+}
+
+/// Collects the incoming arcs of every node as `(tail, cost)` pairs.
+pub(crate) fn reverse_adjacency<N: Network>(network: &N) -> CostAdjacency {
+    let mut reverse = vec![Vec::new(); network.num_nodes()];
+    for from in network.nodes() {
+        for (to, cost, _) in network.out_arcs(from) {
+            reverse[to as usize].push((from, cost));
+        }
+    }
+    reverse
+}
+
+/// Collects the neighbors of every node when arc directions are ignored,
+/// sorted and without duplicates or self-loops.
+pub(crate) fn undirected_adjacency<N: Network>(network: &N) -> Vec<NodeVec> {
+    let mut neighbors = vec![NodeVec::new(); network.num_nodes()];
+    for (from, to) in network.arcs() {
+        if from != to {
+            neighbors[from as usize].push(to);
+            neighbors[to as usize].push(from);
+        }
+    }
+    for list in &mut neighbors {
+        list.sort();
+        list.dedup();
+    }
+    neighbors
+}
+
+/// Lists all arcs as `(tail, head)` pairs. The position of an arc in this
+/// list is its arc number: arcs are numbered in the order they are
+/// enumerated by `network.adjacent(i)` for all nodes `i` in ascending order,
+/// which is the order of the arc arrays of a `CompactStar`.
+pub(crate) fn arc_list<N: Network>(network: &N) -> Vec<(NodeId, NodeId)> {
+    network.arcs().collect()
+}
+
+/// Heap based Dijkstra directly on adjacency lists, for searches on
+/// modified costs. Stops early once `target` is settled, if given.
+/// Returns the predecessor and distance lists; unreached nodes keep `n`
+/// as predecessor and an infinite distance.
+pub(crate) fn adjacency_dijkstra(adjacency: &CostAdjacency, source: NodeId, target: Option<NodeId>) -> (NodeVec, DoubleVec) {
+    let n = adjacency.len();
+    let mut heap = BinaryHeap::new();
+    let mut pred = vec![n as NodeId; n];
+    let mut d = vec![f64::INFINITY; n];
+    let mut marked = vec![false; n];
+
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+
+    while !heap.is_empty() {
+        let next_node = heap.find_min().unwrap();
+        heap.delete_min();
+        let i = next_node as usize;
+
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if target == Some(next_node) {
+            break;
+        }
+
+        for &(adjacent_node, cost) in &adjacency[i] {
+            let j = adjacent_node as usize;
+            if d[j] > d[i] + cost {
+                pred[j] = next_node;
+                d[j] = d[i] + cost;
+                heap.insert(adjacent_node, d[j]);
+            }
+        }
+    }
+    (pred, d)
+}
+
+#[test]
+fn test_forward_and_reverse_adjacency() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (0,2,2.0,0.0),
+        (2,1,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(vec![vec![(1,1.0),(2,2.0)], vec![], vec![(1,3.0)]], forward_adjacency(&compact_star));
+    assert_eq!(vec![vec![], vec![(0,1.0),(2,3.0)], vec![(0,2.0)]], reverse_adjacency(&compact_star));
+}
+
+#[test]
+fn test_adjacency_dijkstra() {
+    let adjacency = vec![vec![(1,6.0),(2,4.0)], vec![(2,2.0),(3,2.0)], vec![(3,1.0)], vec![]];
+    let (pred, dist) = adjacency_dijkstra(&adjacency, 0, None);
+    assert_eq!(vec![4,0,0,2], pred);
+    assert_eq!(vec![0.0,6.0,4.0,5.0], dist);
+    let (pred, _) = adjacency_dijkstra(&adjacency, 1, Some(2));
+    assert_eq!(vec![4,4,1,1], pred);
+}
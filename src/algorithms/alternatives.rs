@@ -0,0 +1,125 @@
+use super::super::{ Cost, Network, NodeId, NodeVec };
+use super::adjacency::{ CostAdjacency, adjacency_dijkstra, forward_adjacency };
+use super::path::{ Path, trace_path };
+
+/// Computes up to `max_alternatives` alternatives to the shortest path from
+/// `source` to `target` with the penalty method.
+///
+/// After every search, the costs of the arcs on the path just found are
+/// multiplied by `penalty` (which should be greater than `1.0`) and the
+/// search is repeated on the penalized costs. A newly found path is accepted
+/// as alternative if at most the fraction `max_overlap` of its (original)
+/// cost is spent on arcs that are already part of an accepted route.
+/// The number of searches is limited to `2 * max_alternatives + 1`.
+///
+/// The result starts with the shortest path, followed by the alternatives in
+/// the order they were found. All costs are reported with the original arc
+/// costs, taking the cheapest of parallel arcs, and penalties apply to all
+/// arcs between two consecutive nodes of a path. The result is empty if the
+/// target can't be reached.
+pub fn alternative_routes<N: Network>(network: &N, source: NodeId, target: NodeId,
+                                      max_alternatives: usize, penalty: f64, max_overlap: f64) -> Vec<Path> {
+    let mut adjacency = forward_adjacency(network);
+    let mut routes: Vec<Path> = Vec::with_capacity(max_alternatives + 1);
+    let mut used = vec![Vec::new(); network.num_nodes()];
+
+    for _ in 0..(2 * max_alternatives + 1) {
+        let (pred, _) = adjacency_dijkstra(&adjacency, source, Some(target));
+        let nodes = match trace_path(&pred, source, target) {
+            Some(nodes) => nodes,
+            None => break
+        };
+
+        let cost = path_cost(network, &nodes);
+        let is_new = routes.iter().all(|route| route.nodes() != &nodes);
+        if is_new && (routes.is_empty() || overlap(network, &nodes, &used) <= max_overlap * cost) {
+            for arc in nodes.windows(2) {
+                used[arc[0] as usize].push(arc[1]);
+            }
+            routes.push(Path::new(nodes.clone(), cost));
+            if routes.len() > max_alternatives {
+                break;
+            }
+        }
+        penalize(&mut adjacency, &nodes, penalty);
+    }
+    routes
+}
+
+fn path_cost<N: Network>(network: &N, nodes: &NodeVec) -> Cost {
+    nodes.windows(2)
+        .map(|arc| network.cheapest_cost(arc[0], arc[1]).unwrap())
+        .sum()
+}
+
+/// The cost of the arcs on `nodes` that are already in use by a route.
+fn overlap<N: Network>(network: &N, nodes: &NodeVec, used: &Vec<NodeVec>) -> Cost {
+    nodes.windows(2)
+        .filter(|arc| used[arc[0] as usize].contains(&arc[1]))
+        .map(|arc| network.cheapest_cost(arc[0], arc[1]).unwrap())
+        .sum()
+}
+
+fn penalize(adjacency: &mut CostAdjacency, nodes: &NodeVec, penalty: f64) {
+    for arc in nodes.windows(2) {
+        for entry in adjacency[arc[0] as usize].iter_mut() {
+            if entry.0 == arc[1] {
+                entry.1 *= penalty;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_alternative_routes() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // two disjoint routes 0-1-3 and 0-2-3 and a detour 1-2
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (1,3,1.0,0.0),
+        (0,2,1.5,0.0),
+        (2,3,1.5,0.0),
+        (1,2,0.1,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let routes = alternative_routes(&compact_star, 0, 3, 2, 2.0, 0.5);
+    assert_eq!(2, routes.len());
+    assert_eq!(&vec![0,1,3], routes[0].nodes());
+    assert_eq!(2.0, routes[0].cost());
+    assert_eq!(&vec![0,2,3], routes[1].nodes());
+    assert_eq!(3.0, routes[1].cost());
+
+    assert!(alternative_routes(&compact_star, 3, 0, 2, 2.0, 0.5).is_empty());
+
+    // the only alternative 0-1-2-3 shares the arc 0-1 with the best route
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (1,3,1.0,0.0),
+        (1,2,1.0,0.0),
+        (2,3,1.5,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(1, alternative_routes(&compact_star, 0, 3, 1, 2.0, 0.2).len());
+    let routes = alternative_routes(&compact_star, 0, 3, 1, 2.0, 0.5);
+    assert_eq!(2, routes.len());
+    assert_eq!(&vec![0,1,2,3], routes[1].nodes());
+    assert_eq!(3.5, routes[1].cost());
+}
+
+#[test]
+fn test_alternative_routes_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the cheaper parallel arc 0-1 gives the cost of the route via 1, and
+    // the penalty on both parallel arcs makes room for the route via 2
+    let mut edges = vec![
+        (0,1,5.0,0.0),
+        (0,1,1.0,0.0),
+        (1,3,1.0,0.0),
+        (0,2,2.0,0.0),
+        (2,3,2.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let routes = alternative_routes(&compact_star, 0, 3, 1, 10.0, 0.5);
+    assert_eq!(2, routes.len());
+    assert_eq!(&vec![0,1,3], routes[0].nodes());
+    assert_eq!(2.0, routes[0].cost());
+    assert_eq!(&vec![0,2,3], routes[1].nodes());
+    assert_eq!(4.0, routes[1].cost());
+}
@@ -0,0 +1,166 @@
+use super::super::{ Network, NodeId, NodeVec };
+use super::super::random::Rng;
+#[cfg(test)]
+use super::super::generators::{ ArcWeights, stochastic_block_model };
+#[cfg(test)]
+use super::partition_metrics::adjusted_rand_index;
+
+/// A block assignment fitted by `fit_block_model`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockModelFit {
+    /// The block of every node.
+    pub blocks:         NodeVec,
+    /// Maximum likelihood estimate of the probability of an arc from a
+    /// node in block `r` to another node in block `s`.
+    pub probabilities:  Vec<Vec<f64>>,
+    /// Log-likelihood of the network under the fitted model.
+    pub log_likelihood: f64,
+    /// The number of sweeps over all nodes until no move improved the fit.
+    pub sweeps:         usize,
+}
+
+/// Arc counts between and sizes of the blocks of an assignment.
+struct BlockCounts {
+    arcs:  Vec<Vec<f64>>,
+    sizes: Vec<f64>,
+}
+
+impl BlockCounts {
+    /// Bernoulli log-likelihood with every probability at its maximum
+    /// likelihood estimate.
+    fn log_likelihood(&self) -> f64 {
+        let mut total = 0.0;
+        for r in 0..self.sizes.len() {
+            for s in 0..self.sizes.len() {
+                let pairs = self.pairs(r, s);
+                let present = self.arcs[r][s];
+                if present > 0.0 && present < pairs {
+                    let p = present / pairs;
+                    total += present * p.ln() + (pairs - present) * (1.0 - p).ln();
+                }
+            }
+        }
+        total
+    }
+
+    fn pairs(&self, r: usize, s: usize) -> f64 {
+        if r == s { self.sizes[r] * (self.sizes[r] - 1.0) } else { self.sizes[r] * self.sizes[s] }
+    }
+
+    /// Moves `node` to block `to`, `out` and `inc` are its outgoing and
+    /// incoming arcs. A self-loop is in both, so it is counted via `out` only.
+    fn move_node(&mut self, blocks: &mut NodeVec, node: usize, to: usize, out: &[NodeId], inc: &[NodeId]) {
+        let from = blocks[node] as usize;
+        for j in out {
+            self.arcs[from][blocks[*j as usize] as usize] -= 1.0;
+        }
+        for i in inc.iter().filter(|i| **i as usize != node) {
+            self.arcs[blocks[*i as usize] as usize][from] -= 1.0;
+        }
+        self.sizes[from] -= 1.0;
+        blocks[node] = to as NodeId;
+        self.sizes[to] += 1.0;
+        for j in out {
+            self.arcs[to][blocks[*j as usize] as usize] += 1.0;
+        }
+        for i in inc.iter().filter(|i| **i as usize != node) {
+            self.arcs[blocks[*i as usize] as usize][to] += 1.0;
+        }
+    }
+}
+
+/// Fits a stochastic block model with `num_blocks` blocks to `network` by
+/// greedy local search on the log-likelihood: starting from a random
+/// assignment, every node is moved to the block that improves the
+/// likelihood the most, until a sweep over all nodes makes no move or
+/// `max_sweeps` is reached. Finds a local optimum only, so different seeds
+/// can give different results.
+pub fn fit_block_model<N: Network>(network: &N, num_blocks: usize, max_sweeps: usize, seed: u64) -> BlockModelFit {
+    assert!(num_blocks > 0);
+    let n = network.num_nodes();
+    let mut rng = Rng::new(seed);
+    let mut blocks: NodeVec = (0..n).map(|_| rng.below(num_blocks) as NodeId).collect();
+    let out: Vec<NodeVec> = network.nodes().map(|i| network.adjacent(i)).collect();
+    let mut inc = vec![NodeVec::new(); n];
+    for (i, j) in network.arcs() {
+        inc[j as usize].push(i);
+    }
+
+    let mut counts = BlockCounts {
+        arcs:  vec![vec![0.0; num_blocks]; num_blocks],
+        sizes: vec![0.0; num_blocks],
+    };
+    for block in &blocks {
+        counts.sizes[*block as usize] += 1.0;
+    }
+    for (i, j) in network.arcs() {
+        counts.arcs[blocks[i as usize] as usize][blocks[j as usize] as usize] += 1.0;
+    }
+
+    let mut log_likelihood = counts.log_likelihood();
+    let mut sweeps = 0;
+    while sweeps < max_sweeps {
+        sweeps += 1;
+        let mut moved = false;
+        for node in 0..n {
+            let current = blocks[node] as usize;
+            let (mut best, mut best_likelihood) = (current, log_likelihood);
+            for block in (0..num_blocks).filter(|b| *b != current) {
+                counts.move_node(&mut blocks, node, block, &out[node], &inc[node]);
+                let likelihood = counts.log_likelihood();
+                if likelihood > best_likelihood + 1e-9 {
+                    best = block;
+                    best_likelihood = likelihood;
+                }
+            }
+            counts.move_node(&mut blocks, node, best, &out[node], &inc[node]);
+            if best != current {
+                moved = true;
+                log_likelihood = best_likelihood;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    let probabilities = (0..num_blocks)
+        .map(|r| (0..num_blocks).map(|s| {
+            let pairs = counts.pairs(r, s);
+            if pairs > 0.0 { counts.arcs[r][s] / pairs } else { 0.0 }
+        }).collect())
+        .collect();
+    BlockModelFit {
+        blocks:         blocks,
+        probabilities:  probabilities,
+        log_likelihood: log_likelihood,
+        sweeps:         sweeps,
+    }
+}
+
+#[test]
+fn test_fit_block_model() {
+    let probabilities = vec![vec![0.4, 0.02], vec![0.02, 0.4]];
+    let (network, planted) = stochastic_block_model(&[20, 20], &probabilities, &ArcWeights::default(), 11);
+    let fit = fit_block_model(&network, 2, 50, 5);
+    assert!(fit.sweeps < 50);
+    assert_eq!(1.0, adjusted_rand_index(&planted, &fit.blocks));
+    let within = fit.probabilities[fit.blocks[0] as usize][fit.blocks[0] as usize];
+    assert!(within > 0.3 && within < 0.5);
+
+    let single = fit_block_model(&network, 1, 50, 5);
+    assert_eq!(vec![0; 40], single.blocks);
+    assert!(single.log_likelihood < fit.log_likelihood);
+}
+
+#[test]
+fn test_move_node_self_loop() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let network = compact_star_from_edge_vec(3, &mut vec![(0,0,1.0,1.0), (0,1,1.0,1.0), (2,0,1.0,1.0)]);
+    let mut blocks = vec![0, 0, 1];
+    let mut counts = BlockCounts { arcs: vec![vec![2.0, 0.0], vec![1.0, 0.0]], sizes: vec![2.0, 1.0] };
+    counts.move_node(&mut blocks, 0, 1, &network.adjacent(0), &[0, 2]);
+    assert_eq!(vec![1, 0, 1], blocks);
+    assert_eq!(vec![vec![0.0, 0.0], vec![1.0, 2.0]], counts.arcs);
+    assert_eq!(vec![1.0, 2.0], counts.sizes);
+}
@@ -0,0 +1,245 @@
+use std::f64;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::label_search::LabelSearch;
+use super::path::{ Path, trace_path };
+
+/// Tolerance for comparing Lagrangian path costs.
+const LAGRANGE_EPS: f64 = 1e-9;
+
+/// A path that respects a toll budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetPath {
+    /// The path with its travel time as cost.
+    pub path:        Path,
+    /// The sum of the tolls along the path.
+    pub toll:        Cost,
+    /// The Lagrangian lower bound on the travel time of any path within the
+    /// budget, found before the exact search.
+    pub lower_bound: Cost,
+}
+
+/// Finds the fastest path from `source` to `target` whose tolls sum up to
+/// at most `budget`. Arc costs are travel times, `tolls` holds the toll of
+/// every arc in arc order. Times and tolls must not be negative.
+///
+/// First the Lagrangian relaxation `min time + lambda * (toll - budget)` is
+/// solved for the best multiplier `lambda` by the LARAC method (Jüttner et
+/// al.: "Lagrange relaxation based method for the QoS routing problem"),
+/// which gives a lower bound and the fastest path found within the budget.
+/// Then a label setting search over `(time, toll)` labels closes the gap:
+/// labels are pruned if they are dominated at their node, if they cannot
+/// reach `target` within the budget, or if they cannot beat the path from
+/// the relaxation, and the label with the least time plus remaining time
+/// to `target` is extended first, so the first label at `target` is
+/// optimal.
+///
+/// Returns `None` if no path within the budget exists.
+pub fn budget_constrained_path<N: Network>(network: &N, tolls: &[Cost], budget: Cost, source: NodeId,
+                                           target: NodeId) -> Option<BudgetPath> {
+    assert_eq!(network.num_arcs(), tolls.len(), "one toll per arc is required");
+    let n = network.num_nodes();
+    let mut forward: Vec<Vec<(NodeId, Cost, Cost)>> = vec![Vec::new(); n];
+    let mut reverse: Vec<Vec<(NodeId, Cost, Cost)>> = vec![Vec::new(); n];
+    for ((from, to, time, _), toll) in network.arc_values().zip(tolls) {
+        forward[from as usize].push((to, time, *toll));
+        reverse[to as usize].push((from, time, *toll));
+    }
+
+    // remaining time and toll to the target, bounds for the label search
+    let time_to_target = lagrangian_distances(&reverse, target, 1.0, 0.0);
+    let toll_to_target = lagrangian_distances(&reverse, target, 0.0, 1.0);
+    if toll_to_target[source as usize] > budget {
+        return None;
+    }
+
+    let (best, lower_bound) = larac(&forward, source, target, budget);
+    if best.1 <= lower_bound + LAGRANGE_EPS {
+        return Some(BudgetPath {
+            path:        Path::new(best.0, best.1),
+            toll:        best.2,
+            lower_bound: lower_bound,
+        });
+    }
+    let (nodes, time, toll) = label_search(&forward, &time_to_target, &toll_to_target, budget, best.1, source, target)
+        .unwrap_or(best);
+    Some(BudgetPath {
+        path:        Path::new(nodes, time),
+        toll:        toll,
+        lower_bound: lower_bound,
+    })
+}
+
+/// Runs LARAC and returns the fastest path within the budget it found as
+/// `(nodes, time, toll)`, and the Lagrangian lower bound on the time. The
+/// budget must be feasible.
+fn larac(forward: &[Vec<(NodeId, Cost, Cost)>], source: NodeId, target: NodeId,
+         budget: Cost) -> ((NodeVec, Cost, Cost), Cost) {
+    let fastest = lagrangian_path(forward, source, target, 1.0, 0.0);
+    if fastest.2 <= budget {
+        let time = fastest.1;
+        return (fastest, time);
+    }
+    let mut over = fastest;
+    let mut within = lagrangian_path(forward, source, target, 0.0, 1.0);
+    loop {
+        let lambda = (within.1 - over.1) / (over.2 - within.2);
+        let candidate = lagrangian_path(forward, source, target, 1.0, lambda);
+        let combined = candidate.1 + lambda * candidate.2;
+        if combined >= over.1 + lambda * over.2 - LAGRANGE_EPS {
+            let lower_bound = combined - lambda * budget;
+            return (within, lower_bound);
+        }
+        if candidate.2 <= budget {
+            within = candidate;
+        } else {
+            over = candidate;
+        }
+    }
+}
+
+/// Dijkstra on the arc weights `alpha * time + beta * toll`. Returns the
+/// path to `target` as `(nodes, time, toll)`; `target` must be reachable.
+pub(crate) fn lagrangian_path(forward: &[Vec<(NodeId, Cost, Cost)>], source: NodeId, target: NodeId, alpha: f64,
+                   beta: f64) -> (NodeVec, Cost, Cost) {
+    let n = forward.len();
+    let mut heap = BinaryHeap::new();
+    let mut pred = vec![n as NodeId; n];
+    let mut d = vec![f64::INFINITY; n];
+    let mut sums = vec![(0.0, 0.0); n];
+    let mut marked = vec![false; n];
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+    while let Some(next_node) = heap.find_min() {
+        heap.delete_min();
+        let i = next_node as usize;
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if next_node == target {
+            break;
+        }
+        for &(adjacent_node, time, toll) in &forward[i] {
+            let j = adjacent_node as usize;
+            let candidate = d[i] + alpha * time + beta * toll;
+            if candidate < d[j] {
+                d[j] = candidate;
+                pred[j] = next_node;
+                sums[j] = (sums[i].0 + time, sums[i].1 + toll);
+                heap.insert(adjacent_node, candidate);
+            }
+        }
+    }
+    let nodes = trace_path(&pred, source, target).expect("target is reachable");
+    let (time, toll) = sums[target as usize];
+    (nodes, time, toll)
+}
+
+/// Distances on the arc weights `alpha * time + beta * toll` from every
+/// node to the root of the reversed arcs `reverse`.
+pub(crate) fn lagrangian_distances(reverse: &[Vec<(NodeId, Cost, Cost)>], root: NodeId, alpha: f64, beta: f64) -> DoubleVec {
+    let n = reverse.len();
+    let mut heap = BinaryHeap::new();
+    let mut d = vec![f64::INFINITY; n];
+    let mut marked = vec![false; n];
+    d[root as usize] = 0.0;
+    heap.insert(root, 0.0);
+    while let Some(next_node) = heap.find_min() {
+        heap.delete_min();
+        let i = next_node as usize;
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        for &(adjacent_node, time, toll) in &reverse[i] {
+            let j = adjacent_node as usize;
+            let candidate = d[i] + alpha * time + beta * toll;
+            if candidate < d[j] {
+                d[j] = candidate;
+                heap.insert(adjacent_node, candidate);
+            }
+        }
+    }
+    d
+}
+
+/// Label setting search for a path within the budget that is faster than
+/// `upper_bound`, with the toll as resource. Returns `None` if there is none.
+fn label_search(forward: &[Vec<(NodeId, Cost, Cost)>], time_to_target: &[Cost], toll_to_target: &[Cost], budget: Cost,
+                upper_bound: Cost, source: NodeId, target: NodeId) -> Option<(NodeVec, Cost, Cost)> {
+    let mut search = LabelSearch::new(forward.len());
+    search.add(source, 0.0, 0.0, (), None, time_to_target[source as usize]);
+
+    while let Some(l) = search.settle() {
+        let (node, time, toll) = (search.label(l).node, search.label(l).time, search.label(l).resource);
+        if node == target {
+            let nodes = search.trace(l).iter().map(|label| label.node).collect();
+            return Some((nodes, time, toll));
+        }
+        for &(adjacent_node, arc_time, arc_toll) in &forward[node as usize] {
+            let j = adjacent_node as usize;
+            let (time, toll) = (time + arc_time, toll + arc_toll);
+            if toll + toll_to_target[j] > budget || time + time_to_target[j] >= upper_bound {
+                continue;
+            }
+            search.add(adjacent_node, time, toll, (), Some(l), time + time_to_target[j]);
+        }
+    }
+    None
+}
+
+#[test]
+fn test_budget_constrained_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // fast over the toll road 0 -> 1 -> 3, slower over 0 -> 2 -> 3 with
+    // small tolls, slowest on the free road 0 -> 3
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,2.0,0.0),
+                         (0,3,10.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,2.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let tolls = vec![10.0, 2.0, 0.0, 0.0, 2.0];
+
+    let route = budget_constrained_path(&network, &tolls, 20.0, 0, 3).unwrap();
+    assert_eq!(&vec![0,1,3], route.path.nodes());
+    assert_eq!((2.0, 10.0), (route.path.cost(), route.toll));
+
+    let route = budget_constrained_path(&network, &tolls, 5.0, 0, 3).unwrap();
+    assert_eq!(&vec![0,2,3], route.path.nodes());
+    assert_eq!((4.0, 4.0), (route.path.cost(), route.toll));
+    assert!(route.lower_bound <= 4.0);
+
+    let route = budget_constrained_path(&network, &tolls, 3.0, 0, 3).unwrap();
+    assert_eq!(&vec![0,3], route.path.nodes());
+    assert_eq!(None, budget_constrained_path(&network, &tolls, 5.0, 3, 0));
+
+    // a fast toll road parallel to a slow free road
+    let mut edges = vec![(0,1,1.0,0.0), (0,1,5.0,0.0)];
+    let network = compact_star_from_edge_vec(2, &mut edges);
+    let route = budget_constrained_path(&network, &[3.0, 0.0], 10.0, 0, 1).unwrap();
+    assert_eq!((1.0, 3.0), (route.path.cost(), route.toll));
+    let route = budget_constrained_path(&network, &[3.0, 0.0], 1.0, 0, 1).unwrap();
+    assert_eq!((5.0, 0.0), (route.path.cost(), route.toll));
+}
+
+#[test]
+fn test_budget_constrained_path_closes_gap() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the optimum within budget 6 is 0 -> 2 -> 3 with time 7, which no
+    // Lagrangian multiplier finds: it lies above the lower convex hull
+    // spanned by 0 -> 1 -> 3 (time 2, toll 10) and 0 -> 3 (time 10, toll 0)
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,4.0,0.0),
+                         (0,3,10.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,3.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let tolls = vec![5.0, 3.0, 0.0, 5.0, 3.0];
+    let route = budget_constrained_path(&network, &tolls, 6.0, 0, 3).unwrap();
+    assert_eq!(&vec![0,2,3], route.path.nodes());
+    assert_eq!((7.0, 6.0), (route.path.cost(), route.toll));
+    assert!((route.lower_bound - 5.2).abs() < 1e-9);
+}
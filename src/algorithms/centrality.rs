@@ -0,0 +1,166 @@
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::super::collections::{ Collection, Queue };
+use super::super::heaps::{ BinaryHeap, Heap };
+
+/// Tolerance for recognizing equally short paths in weighted networks.
+const TIGHT_EPS: f64 = 1e-9;
+
+/// Betweenness centrality of every node: the number of shortest paths
+/// between all ordered pairs of other nodes that pass through the node,
+/// where pairs with several shortest paths split their share evenly.
+///
+/// Uses Brandes' algorithm ("A faster algorithm for betweenness
+/// centrality"): one search per source, then the dependencies are
+/// accumulated in order of decreasing distance. If `weighted` is set, path
+/// lengths are the arc costs and Dijkstra is used, in `O(nm + n^2 log n)`
+/// time; otherwise every arc has length one and a breadth first search
+/// suffices, in `O(nm)` time. Costs must not be negative.
+///
+/// The values are not normalized. In undirected networks, given with two
+/// arcs per edge, every pair is counted in both directions, so the values
+/// are twice the undirected betweenness.
+pub fn betweenness<N: Network>(network: &N, weighted: bool) -> DoubleVec {
+    brandes(network, weighted).0
+}
+
+/// The arcs out of every node as `(head, cost, arc index)`.
+type ArcAdjacency = Vec<Vec<(NodeId, Cost, usize)>>;
+
+/// Brandes' accumulation shared by node and arc betweenness, see
+/// `betweenness`. Returns the betweenness of every node and of every arc,
+/// in arc order. Parallel arcs on shortest paths each count as a path of
+/// their own and take their share.
+pub(crate) fn brandes<N: Network>(network: &N, weighted: bool) -> (DoubleVec, DoubleVec) {
+    let n = network.num_nodes();
+    let mut adjacency: ArcAdjacency = vec![Vec::new(); n];
+    for (a, (from, to, cost, _)) in network.arc_values().enumerate() {
+        adjacency[from as usize].push((to, cost, a));
+    }
+    let mut nodes = vec![0.0; n];
+    let mut arcs = vec![0.0; network.num_arcs()];
+    for source in network.nodes() {
+        let search = if weighted {
+            weighted_search(&adjacency, source)
+        } else {
+            unweighted_search(&adjacency, source)
+        };
+        let mut delta = vec![0.0; n];
+        for w in search.order.iter().rev() {
+            let w = *w as usize;
+            for &(v, a) in &search.predecessors[w] {
+                let v = v as usize;
+                let share = search.sigma[v] / search.sigma[w] * (1.0 + delta[w]);
+                arcs[a] += share;
+                delta[v] += share;
+            }
+            if w != source as usize {
+                nodes[w] += delta[w];
+            }
+        }
+    }
+    (nodes, arcs)
+}
+
+/// Shortest paths from one source as needed by the accumulation.
+struct SingleSource {
+    /// Settled nodes in order of non-decreasing distance.
+    order:        Vec<NodeId>,
+    /// The tail and index of the arcs into every node that lie on shortest
+    /// paths.
+    predecessors: Vec<Vec<(NodeId, usize)>>,
+    /// The number of shortest paths to every node.
+    sigma:        DoubleVec,
+}
+
+fn unweighted_search(adjacency: &ArcAdjacency, source: NodeId) -> SingleSource {
+    let n = adjacency.len();
+    let mut hops = vec![usize::max_value(); n];
+    let mut search = SingleSource {
+        order:        Vec::new(),
+        predecessors: vec![Vec::new(); n],
+        sigma:        vec![0.0; n],
+    };
+    let mut queue = Queue::with_capacity(n);
+    hops[source as usize] = 0;
+    search.sigma[source as usize] = 1.0;
+    queue.push(source);
+    while let Some(v) = queue.pop() {
+        search.order.push(v);
+        let i = v as usize;
+        for &(w, _, a) in &adjacency[i] {
+            let j = w as usize;
+            if hops[j] == usize::max_value() {
+                hops[j] = hops[i] + 1;
+                queue.push(w);
+            }
+            if hops[j] == hops[i] + 1 {
+                search.sigma[j] += search.sigma[i];
+                search.predecessors[j].push((v, a));
+            }
+        }
+    }
+    search
+}
+
+fn weighted_search(adjacency: &ArcAdjacency, source: NodeId) -> SingleSource {
+    let n = adjacency.len();
+    let mut d: Vec<Cost> = vec![Cost::INFINITY; n];
+    let mut settled = vec![false; n];
+    let mut search = SingleSource {
+        order:        Vec::new(),
+        predecessors: vec![Vec::new(); n],
+        sigma:        vec![0.0; n],
+    };
+    let mut heap = BinaryHeap::new();
+    d[source as usize] = 0.0;
+    search.sigma[source as usize] = 1.0;
+    heap.insert(source, 0.0);
+    while let Some(v) = heap.find_min() {
+        heap.delete_min();
+        let i = v as usize;
+        if settled[i] {
+            continue;
+        }
+        settled[i] = true;
+        search.order.push(v);
+        for &(w, cost, a) in &adjacency[i] {
+            let j = w as usize;
+            if settled[j] {
+                continue;
+            }
+            let candidate = d[i] + cost;
+            if candidate < d[j] - TIGHT_EPS {
+                d[j] = candidate;
+                search.sigma[j] = 0.0;
+                search.predecessors[j].clear();
+                heap.insert(w, candidate);
+            }
+            if (candidate - d[j]).abs() <= TIGHT_EPS {
+                search.sigma[j] += search.sigma[i];
+                search.predecessors[j].push((v, a));
+            }
+        }
+    }
+    search
+}
+
+#[test]
+fn test_betweenness() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // undirected path 0 - 1 - 2 - 3
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0),
+                         (1,2,1.0,0.0), (2,1,1.0,0.0),
+                         (2,3,1.0,0.0), (3,2,1.0,0.0)];
+    let path = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(vec![0.0, 4.0, 4.0, 0.0], betweenness(&path, false));
+    assert_eq!(vec![0.0, 4.0, 4.0, 0.0], betweenness(&path, true));
+
+    // two routes 0 -> 1 -> 3 and 0 -> 2 -> 3, the one over 2 is longer
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,1.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,2.0,0.0)];
+    let diamond = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(vec![0.0, 0.5, 0.5, 0.0], betweenness(&diamond, false));
+    assert_eq!(vec![0.0, 1.0, 0.0, 0.0], betweenness(&diamond, true));
+}
@@ -0,0 +1,68 @@
+use super::super::{ Network, NodeId };
+use super::super::compact_star::compact_star_from_edge_vec;
+use super::max_flow::dinic;
+
+/// Result of `max_weight_closure`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure {
+    /// Whether every node is in the closure.
+    pub nodes:  Vec<bool>,
+    /// The total profit of the nodes in the closure.
+    pub profit: f64,
+}
+
+/// A closure of maximum total profit: a node set that contains the heads of
+/// all arcs leaving it, i.e. every arc `i -> j` reads "`i` requires `j`".
+/// In open pit mining, nodes are blocks with the value of their ore minus
+/// the cost of digging them out, and every block requires the blocks above
+/// it. Nodes may have negative profits.
+///
+/// Solved by a minimum cut (Picard: "Maximal closure of a graph and
+/// applications to combinatorial problems"): the source supplies every
+/// profitable node with its profit, every unprofitable node drains its loss
+/// to the sink, and the precedences can't be cut. The source side of the
+/// minimum cut is the closure, the smallest one if there are several.
+/// # Panics
+/// If there isn't one profit per node.
+pub fn max_weight_closure<N: Network>(network: &N, profits: &[f64]) -> Closure {
+    let n = network.num_nodes();
+    assert_eq!(n, profits.len(), "one profit per node is required");
+    let (source, sink) = (n as NodeId, n as NodeId + 1);
+    // larger than any cut that avoids the precedences
+    let uncuttable = 1.0 + profits.iter().map(|p| p.abs()).sum::<f64>();
+    let mut edges: Vec<_> = network.arcs().map(|(i, j)| (i, j, 0.0, uncuttable)).collect();
+    for (i, profit) in profits.iter().enumerate() {
+        if *profit > 0.0 {
+            edges.push((source, i as NodeId, 0.0, *profit));
+        } else if *profit < 0.0 {
+            edges.push((i as NodeId, sink, 0.0, -*profit));
+        }
+    }
+    let flow = dinic(&compact_star_from_edge_vec(n + 2, &mut edges), source, sink);
+    let nodes: Vec<bool> = flow.source_side[..n].to_vec();
+    let profit = profits.iter().zip(&nodes).filter(|&(_, chosen)| *chosen).map(|(p, _)| *p).sum();
+    Closure {
+        nodes:  nodes,
+        profit: profit,
+    }
+}
+
+#[test]
+fn test_max_weight_closure() {
+    // a pit of two layers: the valuable blocks 3 and 4 below need the
+    // waste blocks 0, 1 and 2 above them, 3 needs 0 and 1, 4 needs 1 and 2
+    let mut edges = vec![(3,0,0.0,0.0), (3,1,0.0,0.0), (4,1,0.0,0.0), (4,2,0.0,0.0)];
+    let pit = compact_star_from_edge_vec(5, &mut edges);
+    let closure = max_weight_closure(&pit, &[-1.0, -1.0, -1.0, 3.0, 1.0]);
+    // digging everything gains as much, the smaller closure is preferred
+    assert_eq!(vec![true, true, false, true, false], closure.nodes);
+    assert_eq!(1.0, closure.profit);
+    // with a more valuable block 4, both are worth it
+    let closure = max_weight_closure(&pit, &[-1.0, -1.0, -1.0, 3.0, 2.5]);
+    assert_eq!(vec![true; 5], closure.nodes);
+    assert_eq!(2.5, closure.profit);
+    // nothing is worth digging
+    let closure = max_weight_closure(&pit, &[-2.0, -2.0, -2.0, 3.0, 1.0]);
+    assert_eq!(vec![false; 5], closure.nodes);
+    assert_eq!(0.0, closure.profit);
+}
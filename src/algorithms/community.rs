@@ -0,0 +1,162 @@
+use super::super::{ Cost, DoubleVec, Network, NodeVec, UnionFind };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::centrality::brandes;
+
+/// Tolerance for comparing betweenness values.
+const TIGHT_EPS: f64 = 1e-9;
+
+/// Computes the betweenness of every arc, in arc order: the number of
+/// shortest paths between all ordered pairs of nodes that use the arc, where
+/// pairs with several shortest paths split their share evenly, also among
+/// parallel arcs. Arc costs are the path lengths and must not be negative.
+/// Shares Brandes' algorithm with `betweenness`, in `O(nm + n^2 log n)` time.
+pub fn edge_betweenness<N: Network>(network: &N) -> DoubleVec {
+    brandes(network, true).1
+}
+
+/// Modularity of the partition of `network` into communities given by
+/// `labels`, for directed networks (Leicht, Newman: "Community structure in
+/// directed networks"):
+/// `Q = 1/m * sum_ij (A_ij - out_i * in_j / m) * [labels_i == labels_j]`.
+pub fn modularity<N: Network>(network: &N, labels: &NodeVec) -> f64 {
+    let m = network.num_arcs() as f64;
+    if m == 0.0 {
+        return 0.0;
+    }
+    let communities = labels.iter().max().map_or(0, |l| *l as usize + 1);
+    let mut out_sum = vec![0.0; communities];
+    let mut in_sum = vec![0.0; communities];
+    let mut inside = 0.0;
+    for (i, j) in network.arcs() {
+        let (ci, cj) = (labels[i as usize] as usize, labels[j as usize] as usize);
+        out_sum[ci] += 1.0;
+        in_sum[cj] += 1.0;
+        if ci == cj {
+            inside += 1.0;
+        }
+    }
+    let expected: f64 = (0..communities).map(|c| out_sum[c] * in_sum[c]).sum();
+    inside / m - expected / (m * m)
+}
+
+/// One level of the Girvan-Newman dendrogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommunityLevel {
+    /// The number of arcs removed to reach this level.
+    pub removed_arcs: usize,
+    /// The community of every node, numbered in order of the smallest node
+    /// id of each community.
+    pub labels:       NodeVec,
+    pub communities:  usize,
+    /// Modularity of `labels` on the original network.
+    pub modularity:   f64,
+}
+
+/// Girvan-Newman divisive clustering ("Community structure in social and
+/// biological networks"): repeatedly removes the arc of highest
+/// betweenness, recomputing all betweenness values after every removal.
+/// Communities are the weakly connected components of the remaining
+/// network. Returns one level for the initial components and one for every
+/// removal that splits a component, ending with all nodes isolated.
+///
+/// A network without nodes has a single level without communities.
+///
+/// Every removal costs a full betweenness computation, so this is meant
+/// for networks of up to a few hundred nodes.
+pub fn girvan_newman<N: Network>(network: &N) -> Vec<CommunityLevel> {
+    let n = network.num_nodes();
+    let mut remaining: Vec<(u32, u32, Cost, Cost)> = network.arc_values().collect();
+
+    let mut levels = Vec::new();
+    let mut removed_arcs = 0;
+    loop {
+        let current = if n == 0 {
+            empty_compact_star()
+        } else {
+            compact_star_from_edge_vec(n, &mut remaining.clone())
+        };
+        let labels = component_labels(&current);
+        let communities = labels.iter().max().map_or(0, |l| *l as usize + 1);
+        if levels.last().map_or(true, |level: &CommunityLevel| level.communities < communities) {
+            levels.push(CommunityLevel {
+                removed_arcs: removed_arcs,
+                modularity:   modularity(network, &labels),
+                labels:       labels,
+                communities:  communities,
+            });
+        }
+        if remaining.is_empty() {
+            break;
+        }
+
+        let betweenness = edge_betweenness(&current);
+        let mut highest = 0;
+        for a in 1..betweenness.len() {
+            if betweenness[a] > betweenness[highest] + TIGHT_EPS {
+                highest = a;
+            }
+        }
+        remaining.remove(highest);
+        removed_arcs += 1;
+    }
+    levels
+}
+
+fn component_labels(network: &CompactStar) -> NodeVec {
+    let mut components = UnionFind::new(network.num_nodes());
+    for (i, j) in network.arcs() {
+        components.union(i, j);
+    }
+    components.labels()
+}
+
+/// Two triangles joined by the bridge `2 -> 3`.
+#[cfg(test)]
+fn two_triangles(symmetric: bool) -> CompactStar {
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,0,1.0,0.0),
+                         (3,4,1.0,0.0), (4,5,1.0,0.0), (5,3,1.0,0.0),
+                         (2,3,1.0,0.0)];
+    if symmetric {
+        let reversed: Vec<_> = edges.iter().map(|&(i, j, c, u)| (j, i, c, u)).collect();
+        edges.extend(reversed);
+    }
+    compact_star_from_edge_vec(6, &mut edges)
+}
+
+#[test]
+fn test_edge_betweenness() {
+    let network = two_triangles(false);
+    // arc order: 0->1, 1->2, 2->0, 2->3, 3->4, 4->5, 5->3
+    // the bridge carries all 9 paths from {0, 1, 2} to {3, 4, 5}
+    assert_eq!(vec![6.0, 9.0, 3.0, 9.0, 9.0, 6.0, 3.0], edge_betweenness(&network));
+
+    // two equally short paths 0 -> {1, 2} -> 3 share the pair (0, 3)
+    let mut edges = vec![(0,1,1.0,0.0), (0,2,1.0,0.0), (1,3,1.0,0.0), (2,3,1.0,0.0)];
+    let diamond = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(vec![1.5, 1.5, 1.5, 1.5], edge_betweenness(&diamond));
+
+    // two parallel arcs 0 -> 1 split the paths from 0 to 1 and 2
+    let mut edges = vec![(0,1,1.0,0.0), (0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let parallel = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(vec![1.0, 1.0, 2.0], edge_betweenness(&parallel));
+}
+
+#[test]
+fn test_girvan_newman() {
+    let network = two_triangles(true);
+    let levels = girvan_newman(&network);
+    assert_eq!(1, levels[0].communities);
+    assert_eq!(0, levels[0].removed_arcs);
+    assert_eq!(0.0, levels[0].modularity);
+    // both directions of the bridge go first
+    assert_eq!(vec![0,0,0,1,1,1], levels[1].labels);
+    assert_eq!(2, levels[1].removed_arcs);
+    assert!((levels[1].modularity - (12.0 / 14.0 - 0.5)).abs() < 1e-9);
+    assert_eq!(6, levels.last().unwrap().communities);
+    assert_eq!(network.num_arcs(), levels.last().unwrap().removed_arcs);
+
+    let levels = girvan_newman(&empty_compact_star());
+    assert_eq!(1, levels.len());
+    assert_eq!(0, levels[0].communities);
+    assert!(levels[0].labels.is_empty());
+}
@@ -0,0 +1,227 @@
+use super::super::{ Direction, Network, NodeId, NodeVec };
+use super::super::collections::UnionFind;
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec };
+
+/// Strongly connected components of `network` by Tarjan's algorithm,
+/// without recursion. Returns the number of components and the component
+/// of every node. Components are numbered in reverse topological order of
+/// the condensation: arcs between components always lead from a higher to
+/// a lower number, so component `0` is a sink.
+pub fn strongly_connected_components<N: Network>(network: &N) -> (usize, NodeVec) {
+    let n = network.num_nodes();
+    let unvisited = n;
+    let mut index = vec![unvisited; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut labels = vec![n as NodeId; n];
+    let mut count = 0;
+    let mut next_index = 0;
+    let adjacency: Vec<NodeVec> = network.nodes().map(|i| network.adjacent(i)).collect();
+
+    for root in 0..n {
+        if index[root] != unvisited {
+            continue;
+        }
+        // call stack of (node, position in its adjacency list)
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        low_link[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+        while let Some(&mut (node, ref mut position)) = calls.last_mut() {
+            if *position < adjacency[node].len() {
+                let next = adjacency[node][*position] as usize;
+                *position += 1;
+                if index[next] == unvisited {
+                    index[next] = next_index;
+                    low_link[next] = next_index;
+                    next_index += 1;
+                    stack.push(next);
+                    on_stack[next] = true;
+                    calls.push((next, 0));
+                } else if on_stack[next] {
+                    low_link[node] = ::std::cmp::min(low_link[node], index[next]);
+                }
+                continue;
+            }
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low_link[parent] = ::std::cmp::min(low_link[parent], low_link[node]);
+            }
+            if low_link[node] == index[node] {
+                loop {
+                    let member = stack.pop().unwrap();
+                    on_stack[member] = false;
+                    labels[member] = count as NodeId;
+                    if member == node {
+                        break;
+                    }
+                }
+                count += 1;
+            }
+        }
+    }
+    (count, labels)
+}
+
+/// The components of `network` when arcs are followed in `direction`.
+/// With `Direction::Both` these are the weakly connected components, found
+/// over the reverse star without duplicating arcs, and numbered in the
+/// order of their lowest node. Following arcs one way only, two nodes are
+/// in the same component if each reaches the other, so `Forward` and
+/// `Reverse` both give `strongly_connected_components`.
+pub fn components_with_direction<N: Network>(network: &N, direction: Direction) -> (usize, NodeVec) {
+    if direction != Direction::Both {
+        return strongly_connected_components(network);
+    }
+    let n = network.num_nodes();
+    let mut labels = vec![n as NodeId; n];
+    let mut count = 0;
+    let mut stack = Vec::new();
+    for root in network.nodes() {
+        if labels[root as usize] != n as NodeId {
+            continue;
+        }
+        labels[root as usize] = count as NodeId;
+        stack.push(root);
+        while let Some(i) = stack.pop() {
+            for (j, _, _) in network.followed_arcs(i, direction) {
+                if labels[j as usize] == n as NodeId {
+                    labels[j as usize] = count as NodeId;
+                    stack.push(j);
+                }
+            }
+        }
+        count += 1;
+    }
+    (count, labels)
+}
+
+/// Components of a network with their sizes, see `connected_components`.
+/// Components are numbered in order of their lowest node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectedComponents {
+    /// The component of every node.
+    pub labels: NodeVec,
+    /// The number of nodes in every component.
+    pub sizes:  Vec<usize>,
+}
+
+impl ConnectedComponents {
+    fn from_labels(count: usize, labels: NodeVec) -> ConnectedComponents {
+        let mut sizes = vec![0; count];
+        for label in &labels {
+            sizes[*label as usize] += 1;
+        }
+        ConnectedComponents {
+            labels: labels,
+            sizes:  sizes,
+        }
+    }
+
+    /// The number of components.
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// The number of nodes in the largest component, `0` for an empty
+    /// network.
+    pub fn largest(&self) -> usize {
+        self.sizes.iter().cloned().max().unwrap_or(0)
+    }
+}
+
+/// The connected components of `network` with arcs treated as undirected,
+/// found by merging the ends of every arc in a disjoint-set forest. Only
+/// the leaving arcs of every node are read, so this suits networks of any
+/// representation; on a directed network, these are the weakly connected
+/// components.
+pub fn connected_components<N: Network>(network: &N) -> ConnectedComponents {
+    let n = network.num_nodes();
+    let mut sets = UnionFind::new(n);
+    for (i, j) in network.arcs() {
+        sets.union(i, j);
+    }
+    ConnectedComponents::from_labels(sets.count(), sets.labels())
+}
+
+/// The weakly connected components of a directed `network`: the same
+/// partition as `connected_components`, found by a traversal over the
+/// leaving and entering arcs of every node, which is faster on networks
+/// with a reverse star like `CompactStar`.
+pub fn weakly_connected_components<N: Network>(network: &N) -> ConnectedComponents {
+    let (count, labels) = components_with_direction(network, Direction::Both);
+    ConnectedComponents::from_labels(count, labels)
+}
+
+/// The condensation of `network`: one node per component of `labels`
+/// (with `count` components) and one arc of cost and capacity `0.0` for
+/// every pair of components connected by at least one arc.
+pub fn condensation<N: Network>(network: &N, count: usize, labels: &NodeVec) -> CompactStar {
+    let mut pairs: Vec<(NodeId, NodeId)> = network.arcs()
+        .map(|(i, j)| (labels[i as usize], labels[j as usize]))
+        .filter(|&(a, b)| a != b)
+        .collect();
+    pairs.sort();
+    pairs.dedup();
+    let mut edges = pairs.into_iter().map(|(a, b)| (a, b, 0.0, 0.0)).collect();
+    compact_star_from_edge_vec(count, &mut edges)
+}
+
+/// The components of `labels` (with `count` components) that no arc
+/// leaves, in ascending order. Random walks end up in these components.
+pub fn sink_components<N: Network>(network: &N, count: usize, labels: &NodeVec) -> NodeVec {
+    let mut is_sink = vec![true; count];
+    for (i, j) in network.arcs() {
+        if labels[i as usize] != labels[j as usize] {
+            is_sink[labels[i as usize] as usize] = false;
+        }
+    }
+    (0..count as NodeId).filter(|c| is_sink[*c as usize]).collect()
+}
+
+#[test]
+fn test_strongly_connected_components() {
+    // cycle 0 -> 1 -> 2 -> 0, which leads to the cycle 3 <-> 4 and to 5
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,0,1.0,0.0), (2,3,1.0,0.0),
+                         (3,4,1.0,0.0), (4,3,1.0,0.0), (1,5,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let (count, labels) = strongly_connected_components(&network);
+    assert_eq!(3, count);
+    assert_eq!(labels[0], labels[1]);
+    assert_eq!(labels[0], labels[2]);
+    assert_eq!(labels[3], labels[4]);
+    assert!(network.arcs().all(|(i, j)| labels[i as usize] >= labels[j as usize]));
+    assert_eq!(2, labels[0]);
+
+    let condensed = condensation(&network, count, &labels);
+    assert_eq!(2, condensed.num_arcs());
+    assert_eq!(vec![0, 1], sink_components(&network, count, &labels));
+}
+
+#[test]
+fn test_components_with_direction() {
+    // 0 -> 1 <- 2 and the cycle 3 <-> 4
+    let mut edges = vec![(0,1,1.0,0.0), (2,1,1.0,0.0), (3,4,1.0,0.0), (4,3,1.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    assert_eq!((2, vec![0,0,0,1,1]), components_with_direction(&network, Direction::Both));
+    let (count, labels) = components_with_direction(&network, Direction::Forward);
+    assert_eq!(4, count);
+    assert_eq!(labels[3], labels[4]);
+    assert_eq!((count, labels), components_with_direction(&network, Direction::Reverse));
+}
+
+#[test]
+fn test_connected_components() {
+    // 0 -> 1 <- 2, 3 -> 4 and the isolated node 5
+    let mut edges = vec![(0,1,1.0,0.0), (2,1,1.0,0.0), (3,4,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let components = connected_components(&network);
+    assert_eq!(vec![0,0,0,1,1,2], components.labels);
+    assert_eq!(vec![3,2,1], components.sizes);
+    assert_eq!((3, 3), (components.count(), components.largest()));
+    assert_eq!(components, weakly_connected_components(&network));
+    assert_eq!(0, ConnectedComponents::from_labels(0, Vec::new()).largest());
+}
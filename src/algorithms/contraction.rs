@@ -0,0 +1,411 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap as PriorityQueue;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::adjacency::{ CostAdjacency, forward_adjacency };
+use super::distance_table::DistanceTable;
+use super::od_matrix::ODMatrix;
+
+/// Witness searches give up after settling this many nodes. Giving up early
+/// only adds shortcuts that are not strictly needed, distances stay exact.
+const WITNESS_SETTLE_LIMIT: usize = 64;
+
+/// Contraction hierarchy of a network (Geisberger et al.: "Contraction
+/// Hierarchies: Faster and Simpler Hierarchical Routing in Road Networks").
+///
+/// Nodes are contracted one by one in order of increasing importance, and
+/// shortcut arcs preserve all shortest path distances between the
+/// remaining nodes. Afterwards every shortest path can be found going only
+/// upward in the hierarchy from the source and, backward, from the target.
+#[derive(Debug, Clone)]
+pub struct ContractionHierarchy {
+    /// Position of every node in the contraction order.
+    rank:      Vec<usize>,
+    /// Arcs to nodes of higher rank, original arcs and shortcuts.
+    upward:    CostAdjacency,
+    /// Arcs from nodes of higher rank, stored at their head as `(tail, cost)`.
+    downward:  CostAdjacency,
+    shortcuts: usize,
+    inf:       Cost,
+}
+
+/// Builds the contraction hierarchy of `network`. Nodes are ordered lazily
+/// by edge difference, the number of shortcuts a contraction adds minus
+/// the arcs it removes, plus the number of already contracted neighbors to
+/// spread contractions evenly over the network.
+pub fn contraction_hierarchy<N: Network>(network: &N) -> ContractionHierarchy {
+    let n = network.num_nodes();
+    let mut out: CostAdjacency = vec![Vec::new(); n];
+    let mut inc: CostAdjacency = vec![Vec::new(); n];
+    for (from, arcs) in forward_adjacency(network).into_iter().enumerate() {
+        for (to, cost) in arcs {
+            if to as usize != from {
+                add_arc(&mut out, &mut inc, from as NodeId, to, cost);
+            }
+        }
+    }
+
+    let mut contracted = vec![false; n];
+    let mut contracted_neighbors = vec![0isize; n];
+    let mut rank = vec![0; n];
+    let mut witness = WitnessSearch::new(n);
+    let mut shortcuts = 0;
+
+    let mut queue = PriorityQueue::new();
+    for v in 0..n {
+        let priority = priority(&out, &inc, &contracted, &contracted_neighbors, &mut witness, v as NodeId);
+        queue.push(Reverse((priority, v as NodeId)));
+    }
+
+    let mut next_rank = 0;
+    while let Some(Reverse((_, v))) = queue.pop() {
+        let vi = v as usize;
+        if contracted[vi] {
+            continue;
+        }
+        // lazy update: contract only if `v` is still the least important node
+        let priority = priority(&out, &inc, &contracted, &contracted_neighbors, &mut witness, v);
+        if let Some(&Reverse((next_priority, _))) = queue.peek() {
+            if priority > next_priority {
+                queue.push(Reverse((priority, v)));
+                continue;
+            }
+        }
+
+        for (from, to, cost) in required_shortcuts(&out, &inc, &contracted, &mut witness, v) {
+            add_arc(&mut out, &mut inc, from, to, cost);
+            shortcuts += 1;
+        }
+        contracted[vi] = true;
+        rank[vi] = next_rank;
+        next_rank += 1;
+        for &(neighbor, _) in out[vi].iter().chain(inc[vi].iter()) {
+            contracted_neighbors[neighbor as usize] += 1;
+        }
+    }
+
+    let mut upward = vec![Vec::new(); n];
+    let mut downward = vec![Vec::new(); n];
+    for (from, arcs) in out.into_iter().enumerate() {
+        for (to, cost) in arcs {
+            if rank[from] < rank[to as usize] {
+                upward[from].push((to, cost));
+            } else {
+                downward[to as usize].push((from as NodeId, cost));
+            }
+        }
+    }
+
+    ContractionHierarchy {
+        rank:      rank,
+        upward:    upward,
+        downward:  downward,
+        shortcuts: shortcuts,
+        inf:       network.infinity(),
+    }
+}
+
+impl ContractionHierarchy {
+    pub fn num_nodes(&self) -> usize {
+        self.rank.len()
+    }
+
+    /// Position of `node` in the contraction order, `0` was contracted first.
+    pub fn rank(&self, node: NodeId) -> usize {
+        self.rank[node as usize]
+    }
+
+    /// Number of shortcut arcs added during the contraction.
+    pub fn num_shortcuts(&self) -> usize {
+        self.shortcuts
+    }
+
+    /// Shortest path distance from `source` to `target`, or the network's
+    /// infinity if `target` is unreachable. Runs an upward search from
+    /// `source` and a backward upward search from `target` and returns the
+    /// best meeting node.
+    pub fn distance(&self, source: NodeId, target: NodeId) -> Cost {
+        let mut scratch = Scratch::new(self.num_nodes());
+        let forward = scratch.search_space(&self.upward, source);
+        for &(node, dist) in &forward {
+            scratch.d[node as usize] = dist;
+        }
+        let mut best = Cost::INFINITY;
+        let backward = search_space_of(&self.downward, target);
+        for (node, dist) in backward {
+            let d = scratch.d[node as usize];
+            if d + dist < best {
+                best = d + dist;
+            }
+        }
+        if best.is_finite() { best } else { self.inf }
+    }
+
+    /// Computes the table of shortest path distances between every node in
+    /// `sources` and every node in `targets` with the bucket-based
+    /// many-to-many algorithm (Knopp et al.: "Computing Many-to-Many Shortest
+    /// Paths Using Highway Hierarchies"). Backward searches from the targets
+    /// leave their distances in buckets at every node they settle, forward
+    /// searches from the sources only scan the buckets of their own search
+    /// space. The result has the layout of `distance_table`.
+    pub fn many_to_many(&self, sources: &[NodeId], targets: &[NodeId]) -> DistanceTable {
+        let mut scratch = Scratch::new(self.num_nodes());
+        let mut buckets: Vec<Vec<(usize, Cost)>> = vec![Vec::new(); self.num_nodes()];
+        for (t, target) in targets.iter().enumerate() {
+            for (node, dist) in scratch.search_space(&self.downward, *target) {
+                buckets[node as usize].push((t, dist));
+            }
+        }
+
+        sources.iter()
+            .map(|source| {
+                let mut row = vec![Cost::INFINITY; targets.len()];
+                for (node, dist) in scratch.search_space(&self.upward, *source) {
+                    for &(t, to_target) in &buckets[node as usize] {
+                        if dist + to_target < row[t] {
+                            row[t] = dist + to_target;
+                        }
+                    }
+                }
+                row.iter().map(|&dist| if dist.is_finite() { dist } else { self.inf }).collect()
+            })
+            .collect()
+    }
+
+    /// The shortest path cost of every pair of `demand`, in the order of
+    /// `ODMatrix::iter`, from one `many_to_many` table between its origins
+    /// and destinations. Like `ODMatrix::travel_costs`, but on the
+    /// hierarchy.
+    pub fn od_costs(&self, demand: &ODMatrix) -> DoubleVec {
+        let (origins, destinations) = (demand.origins(), demand.destinations());
+        let table = self.many_to_many(&origins, &destinations);
+        demand.pair_costs(&origins, &destinations, &table)
+    }
+}
+
+/// Adds the arc `from -> to`, or lowers the cost of an existing one.
+fn add_arc(out: &mut CostAdjacency, inc: &mut CostAdjacency, from: NodeId, to: NodeId, cost: Cost) {
+    match out[from as usize].iter().position(|&(head, _)| head == to) {
+        Some(position) => {
+            if cost < out[from as usize][position].1 {
+                out[from as usize][position].1 = cost;
+                let back = inc[to as usize].iter().position(|&(tail, _)| tail == from).unwrap();
+                inc[to as usize][back].1 = cost;
+            }
+        }
+        None => {
+            out[from as usize].push((to, cost));
+            inc[to as usize].push((from, cost));
+        }
+    }
+}
+
+fn priority(out: &CostAdjacency, inc: &CostAdjacency, contracted: &[bool], contracted_neighbors: &[isize],
+            witness: &mut WitnessSearch, v: NodeId) -> isize {
+    let removed = out[v as usize].iter().chain(inc[v as usize].iter())
+        .filter(|&&(neighbor, _)| !contracted[neighbor as usize])
+        .count();
+    let added = required_shortcuts(out, inc, contracted, witness, v).len();
+    added as isize - removed as isize + contracted_neighbors[v as usize]
+}
+
+/// The shortcuts needed to contract `v`: for every remaining pair `u -> v
+/// -> w` without a witness path of at most the same cost that avoids `v`.
+fn required_shortcuts(out: &CostAdjacency, inc: &CostAdjacency, contracted: &[bool], witness: &mut WitnessSearch,
+                      v: NodeId) -> Vec<(NodeId, NodeId, Cost)> {
+    let mut shortcuts = Vec::new();
+    let heads: Vec<(NodeId, Cost)> = out[v as usize].iter()
+        .filter(|&&(w, _)| !contracted[w as usize])
+        .cloned()
+        .collect();
+    if heads.is_empty() {
+        return shortcuts;
+    }
+    let max_out = heads.iter().fold(0.0, |max: Cost, &(_, cost)| max.max(cost));
+    for &(u, to_v) in &inc[v as usize] {
+        if contracted[u as usize] {
+            continue;
+        }
+        witness.run(out, contracted, u, v, to_v + max_out);
+        for &(w, from_v) in &heads {
+            if w != u && witness.distance(w) > to_v + from_v {
+                shortcuts.push((u, w, to_v + from_v));
+            }
+        }
+    }
+    shortcuts
+}
+
+/// Reusable local Dijkstra among the remaining nodes, bounded in distance
+/// and in the number of settled nodes.
+struct WitnessSearch {
+    d:       DoubleVec,
+    settled: Vec<bool>,
+    touched: NodeVec,
+    heap:    BinaryHeap,
+}
+
+impl WitnessSearch {
+    fn new(n: usize) -> WitnessSearch {
+        WitnessSearch {
+            d:       vec![Cost::INFINITY; n],
+            settled: vec![false; n],
+            touched: Vec::new(),
+            heap:    BinaryHeap::new(),
+        }
+    }
+
+    fn distance(&self, node: NodeId) -> Cost {
+        self.d[node as usize]
+    }
+
+    fn run(&mut self, out: &CostAdjacency, contracted: &[bool], source: NodeId, avoid: NodeId, bound: Cost) {
+        for node in self.touched.drain(..) {
+            self.d[node as usize] = Cost::INFINITY;
+            self.settled[node as usize] = false;
+        }
+        self.heap.clear();
+        self.d[source as usize] = 0.0;
+        self.touched.push(source);
+        self.heap.insert(source, 0.0);
+
+        let mut settled = 0;
+        while let Some(next_node) = self.heap.find_min() {
+            self.heap.delete_min();
+            let i = next_node as usize;
+            if self.settled[i] {
+                continue;
+            }
+            if self.d[i] > bound || settled >= WITNESS_SETTLE_LIMIT {
+                break;
+            }
+            self.settled[i] = true;
+            settled += 1;
+            for &(adjacent_node, cost) in &out[i] {
+                let j = adjacent_node as usize;
+                if adjacent_node == avoid || contracted[j] {
+                    continue;
+                }
+                if self.d[j] > self.d[i] + cost {
+                    if self.d[j] == Cost::INFINITY {
+                        self.touched.push(adjacent_node);
+                    }
+                    self.d[j] = self.d[i] + cost;
+                    self.heap.insert(adjacent_node, self.d[j]);
+                }
+            }
+        }
+    }
+}
+
+/// Scratch space for the upward searches of the queries. Distances start
+/// at `Cost::INFINITY`, not at the network's infinity, which the longest
+/// paths reach.
+struct Scratch {
+    d:       DoubleVec,
+    settled: Vec<bool>,
+}
+
+impl Scratch {
+    fn new(n: usize) -> Scratch {
+        Scratch {
+            d:       vec![Cost::INFINITY; n],
+            settled: vec![false; n],
+        }
+    }
+
+    /// Runs a complete Dijkstra from `source` over `graph` and returns all
+    /// settled nodes with their distances, leaving the scratch space clean.
+    fn search_space(&mut self, graph: &CostAdjacency, source: NodeId) -> Vec<(NodeId, Cost)> {
+        let mut heap = BinaryHeap::new();
+        let mut space = Vec::new();
+        self.d[source as usize] = 0.0;
+        heap.insert(source, 0.0);
+        while let Some(next_node) = heap.find_min() {
+            heap.delete_min();
+            let i = next_node as usize;
+            if self.settled[i] {
+                continue;
+            }
+            self.settled[i] = true;
+            space.push((next_node, self.d[i]));
+            for &(adjacent_node, cost) in &graph[i] {
+                let j = adjacent_node as usize;
+                if self.d[j] > self.d[i] + cost {
+                    self.d[j] = self.d[i] + cost;
+                    heap.insert(adjacent_node, self.d[j]);
+                }
+            }
+        }
+        for &(node, _) in &space {
+            self.d[node as usize] = Cost::INFINITY;
+            self.settled[node as usize] = false;
+        }
+        space
+    }
+}
+
+fn search_space_of(graph: &CostAdjacency, source: NodeId) -> Vec<(NodeId, Cost)> {
+    Scratch::new(graph.len()).search_space(graph, source)
+}
+
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+#[cfg(test)]
+use super::distance_table::distance_table;
+
+#[test]
+fn test_contraction_hierarchy() {
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let hierarchy = contraction_hierarchy(&compact_star);
+    let mut ranks: Vec<usize> = (0..6).map(|node| hierarchy.rank(node)).collect();
+    ranks.sort();
+    assert_eq!(vec![0, 1, 2, 3, 4, 5], ranks);
+    assert_eq!(9.0, hierarchy.distance(0, 5));
+    assert_eq!(5.0, hierarchy.distance(0, 3));
+    assert_eq!(0.0, hierarchy.distance(3, 3));
+    assert_eq!(compact_star.infinity(), hierarchy.distance(5, 0));
+
+    // the path over every arc is as long as the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,3,0.0,0.0)];
+    let path = compact_star_from_edge_vec(4, &mut edges);
+    let hierarchy = contraction_hierarchy(&path);
+    assert_eq!(2.0, hierarchy.distance(0, 2));
+    assert_eq!(2.0, hierarchy.distance(0, 3));
+    assert_eq!(path.infinity(), hierarchy.distance(3, 0));
+    assert_eq!(vec![vec![1.0, 2.0, 2.0], vec![0.0, 1.0, 1.0]], hierarchy.many_to_many(&[0, 1], &[1, 2, 3]));
+}
+
+#[test]
+fn test_many_to_many() {
+    use super::super::generators::{ ArcWeights, Distribution, gnp };
+    let weights = ArcWeights { cost: Distribution::Uniform(1.0, 10.0), ..ArcWeights::default() };
+    let network = gnp(60, 0.06, &weights, 7);
+    let hierarchy = contraction_hierarchy(&network);
+    let sources: NodeVec = (0..60).filter(|node| node % 3 == 0).collect();
+    let targets: NodeVec = (0..60).filter(|node| node % 4 == 1).collect();
+    let expected = distance_table(&network, &sources, &targets);
+    let table = hierarchy.many_to_many(&sources, &targets);
+    for (row, expected_row) in table.iter().zip(expected.iter()) {
+        for (dist, expected_dist) in row.iter().zip(expected_row.iter()) {
+            assert!((dist - expected_dist).abs() < 1e-9);
+        }
+    }
+    assert_eq!(expected[2][3], hierarchy.distance(sources[2], targets[3]));
+
+    let demand = ODMatrix::from(&vec![(0, 1, 1.0), (3, 5, 2.0), (3, 1, 0.5)]);
+    for (cost, expected) in hierarchy.od_costs(&demand).iter().zip(demand.travel_costs(&network)) {
+        assert!((cost - expected).abs() < 1e-9);
+    }
+}
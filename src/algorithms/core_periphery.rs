@@ -0,0 +1,177 @@
+use super::super::{ DoubleVec, Network, NodeVec };
+use super::adjacency::undirected_adjacency;
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// Continuous core-periphery fit (Borgatti, Everett: "Models of
+/// core/periphery structures"): finds coreness scores `c` such that
+/// `c_i * c_j` approximates the undirected adjacency matrix in the least
+/// squares sense, ignoring the diagonal. Iterates the stationarity
+/// condition `c_i = sum_j a_ij c_j / sum_{j != i} c_j^2` until no score
+/// changes by more than `eps` or `max_iterations` is reached.
+/// The scores are scaled to a maximum of `1.0`; nodes without neighbors get
+/// `0.0`.
+pub fn coreness<N: Network>(network: &N, eps: f64, max_iterations: usize) -> DoubleVec {
+    let neighbors = undirected_adjacency(network);
+    let n = neighbors.len();
+    let mut scores: DoubleVec = neighbors.iter().map(|list| list.len() as f64).collect();
+    normalize(&mut scores);
+    for _ in 0..max_iterations {
+        let squares: f64 = scores.iter().map(|c| c * c).sum();
+        let mut next = vec![0.0; n];
+        for i in 0..n {
+            let denominator = squares - scores[i] * scores[i];
+            if denominator > 0.0 {
+                let sum: f64 = neighbors[i].iter().map(|j| scores[*j as usize]).sum();
+                next[i] = sum / denominator;
+            }
+        }
+        normalize(&mut next);
+        let change = scores.iter().zip(&next).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        scores = next;
+        if change <= eps {
+            break;
+        }
+    }
+    scores
+}
+
+fn normalize(scores: &mut DoubleVec) {
+    let max = scores.iter().cloned().fold(0.0, f64::max);
+    if max > 0.0 {
+        for score in scores.iter_mut() {
+            *score /= max;
+        }
+    }
+}
+
+/// A discrete partition into core and periphery.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorePeriphery {
+    /// Whether each node belongs to the core.
+    pub is_core:     Vec<bool>,
+    /// The core nodes in descending order of their scores.
+    pub core:        NodeVec,
+    /// Correlation of the undirected adjacency matrix with the ideal
+    /// pattern of a complete core and an empty periphery.
+    pub correlation: f64,
+}
+
+/// Discrete core-periphery fit: takes the nodes in descending order of
+/// `scores` (usually from `coreness`) and picks the core size whose ideal
+/// pattern correlates best with the undirected adjacency matrix. As in the
+/// model of Borgatti and Everett, pairs between core and periphery are not
+/// taken into account. Ties go to the smaller core.
+pub fn core_periphery<N: Network>(network: &N, scores: &DoubleVec) -> CorePeriphery {
+    let neighbors = undirected_adjacency(network);
+    let n = neighbors.len();
+    assert_eq!(n, scores.len());
+    let mut order: NodeVec = network.nodes().collect();
+    order.sort_by(|a, b| scores[*b as usize].total_cmp(&scores[*a as usize]).then(a.cmp(b)));
+
+    let mut is_core = vec![false; n];
+    let mut core_edges = 0.0;
+    let mut periphery_edges = neighbors.iter().map(|list| list.len()).sum::<usize>() as f64 / 2.0;
+    let (mut best_size, mut best_correlation) = (0, 0.0);
+    for (k, node) in order.iter().enumerate() {
+        for j in &neighbors[*node as usize] {
+            if is_core[*j as usize] {
+                core_edges += 1.0;
+            } else {
+                periphery_edges -= 1.0;
+            }
+        }
+        is_core[*node as usize] = true;
+        let size = (k + 1) as f64;
+        let rest = (n - k - 1) as f64;
+        let core_pairs = size * (size - 1.0) / 2.0;
+        let pairs = core_pairs + rest * (rest - 1.0) / 2.0;
+        let edges = core_edges + periphery_edges;
+        let variance = core_pairs * (pairs - core_pairs) * edges * (pairs - edges);
+        if variance > 0.0 {
+            let correlation = (pairs * core_edges - core_pairs * edges) / variance.sqrt();
+            if correlation > best_correlation {
+                best_size = k + 1;
+                best_correlation = correlation;
+            }
+        }
+    }
+
+    let core: NodeVec = order[..best_size].to_vec();
+    let mut is_core = vec![false; n];
+    for node in &core {
+        is_core[*node as usize] = true;
+    }
+    CorePeriphery {
+        is_core:     is_core,
+        core:        core,
+        correlation: best_correlation,
+    }
+}
+
+/// Rich-club coefficients `phi(k) = 2 E_k / (N_k (N_k - 1))` for
+/// `k = 0..max_degree`, where `N_k` nodes have an undirected degree above
+/// `k` and `E_k` edges connect two of them (Zhou, Mondragón: "The rich-club
+/// phenomenon in the Internet topology"). `phi(k)` is `0.0` where fewer
+/// than two nodes are left.
+pub fn rich_club_coefficients<N: Network>(network: &N) -> DoubleVec {
+    let neighbors = undirected_adjacency(network);
+    let degrees: Vec<usize> = neighbors.iter().map(|list| list.len()).collect();
+    let max_degree = degrees.iter().cloned().max().unwrap_or(0);
+    // counts by the degree at which nodes and edges drop out
+    let mut nodes_at = vec![0.0; max_degree + 1];
+    let mut edges_at = vec![0.0; max_degree + 1];
+    for (i, list) in neighbors.iter().enumerate() {
+        nodes_at[degrees[i]] += 1.0;
+        for j in list.iter().filter(|j| **j as usize > i) {
+            edges_at[::std::cmp::min(degrees[i], degrees[*j as usize])] += 1.0;
+        }
+    }
+
+    let mut coefficients = vec![0.0; max_degree];
+    let (mut nodes, mut edges) = (0.0, 0.0);
+    for k in (0..max_degree).rev() {
+        nodes += nodes_at[k + 1];
+        edges += edges_at[k + 1];
+        if nodes > 1.0 {
+            coefficients[k] = 2.0 * edges / (nodes * (nodes - 1.0));
+        }
+    }
+    coefficients
+}
+
+/// A complete core of four nodes, each with one pendant periphery node.
+#[cfg(test)]
+fn core_with_pendants() -> super::super::compact_star::CompactStar {
+    let mut edges = Vec::new();
+    for i in 0..4 {
+        for j in (i + 1)..4 {
+            edges.push((i, j, 1.0, 0.0));
+        }
+        edges.push((i + 4, i, 1.0, 0.0));
+    }
+    compact_star_from_edge_vec(8, &mut edges)
+}
+
+#[test]
+fn test_core_periphery() {
+    let network = core_with_pendants();
+    let scores = coreness(&network, 1e-9, 1000);
+    assert!((0..4).all(|i| scores[i] == 1.0));
+    assert!((4..8).all(|i| scores[i] > 0.0 && scores[i] < 0.5));
+
+    let partition = core_periphery(&network, &scores);
+    assert_eq!(vec![0,1,2,3], partition.core);
+    assert_eq!(vec![true, true, true, true, false, false, false, false], partition.is_core);
+    assert_eq!(1.0, partition.correlation);
+}
+
+#[test]
+fn test_rich_club_coefficients() {
+    let network = core_with_pendants();
+    // degrees 4 in the core, 1 in the periphery
+    let coefficients = rich_club_coefficients(&network);
+    assert_eq!(4, coefficients.len());
+    assert_eq!(10.0 / 28.0, coefficients[0]);
+    assert_eq!(vec![1.0, 1.0, 1.0], coefficients[1..].to_vec());
+}
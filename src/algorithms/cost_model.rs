@@ -0,0 +1,138 @@
+use std::f64;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::path::{ Path, trace_path };
+
+/// Computes arc costs at query time from per arc attributes, so that
+/// different users (vehicle types, energy models, ...) can route over one
+/// shared network instead of one copy with baked in costs per user.
+///
+/// An infinite cost closes the arc, e.g. a bridge lower than the vehicle.
+/// Costs must not be negative.
+///
+/// Closures `Fn(usize, &A) -> Cost` are cost models.
+pub trait CostModel<A> {
+    /// The cost of the arc with number `arc` (see `Network::arcs`) and the
+    /// attributes `attributes`.
+    fn arc_cost(&self, arc: usize, attributes: &A) -> Cost;
+}
+
+impl<A, F: Fn(usize, &A) -> Cost> CostModel<A> for F {
+    fn arc_cost(&self, arc: usize, attributes: &A) -> Cost {
+        self(arc, attributes)
+    }
+}
+
+/// Dijkstra with the arc costs given by `model` instead of the network's
+/// costs. `attributes` holds the attributes of every arc in arc order.
+/// Returns predecessors and distances like `dijkstra`; unreachable nodes
+/// keep an infinite distance.
+pub fn dijkstra_with_model<N: Network, A, M: CostModel<A>>(network: &N, attributes: &[A], model: &M,
+                                                          source: NodeId) -> (NodeVec, DoubleVec) {
+    model_search(network, attributes, model, source, None)
+}
+
+/// The shortest path from `source` to `target` under the arc costs given
+/// by `model`, with its cost under the model. Returns `None` if `target`
+/// can't be reached over open arcs.
+pub fn shortest_path_with_model<N: Network, A, M: CostModel<A>>(network: &N, attributes: &[A], model: &M,
+                                                               source: NodeId, target: NodeId) -> Option<Path> {
+    let (pred, d) = model_search(network, attributes, model, source, Some(target));
+    if d[target as usize] == f64::INFINITY {
+        return None;
+    }
+    trace_path(&pred, source, target).map(|nodes| Path::new(nodes, d[target as usize]))
+}
+
+fn model_search<N: Network, A, M: CostModel<A>>(network: &N, attributes: &[A], model: &M,
+                                               source: NodeId, target: Option<NodeId>) -> (NodeVec, DoubleVec) {
+    assert_eq!(network.num_arcs(), attributes.len(), "one attribute set per arc is required");
+    let n = network.num_nodes();
+    let first_arcs = network.first_arcs();
+
+    let mut heap = BinaryHeap::new();
+    let mut pred = vec![network.invalid_id(); n];
+    let mut d = vec![f64::INFINITY; n];
+    let mut marked = vec![false; n];
+
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+
+    while let Some(next_node) = heap.find_min() {
+        heap.delete_min();
+        let i = next_node as usize;
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if Some(next_node) == target {
+            break;
+        }
+
+        for (k, (adjacent_node, _, _)) in network.out_arcs(next_node).enumerate() {
+            let arc = first_arcs[i] + k;
+            let cost = model.arc_cost(arc, &attributes[arc]);
+            if cost == f64::INFINITY {
+                continue;
+            }
+            let j = adjacent_node as usize;
+            if d[j] > d[i] + cost {
+                pred[j] = next_node;
+                d[j] = d[i] + cost;
+                heap.insert(adjacent_node, d[j]);
+            }
+        }
+    }
+    (pred, d)
+}
+
+#[cfg(test)]
+struct Road {
+    length:    f64,
+    clearance: f64,
+    climb:     f64,
+}
+
+#[cfg(test)]
+struct Truck {
+    height: f64,
+}
+
+#[cfg(test)]
+impl CostModel<Road> for Truck {
+    fn arc_cost(&self, _arc: usize, road: &Road) -> Cost {
+        if road.clearance < self.height { f64::INFINITY } else { road.length }
+    }
+}
+
+#[test]
+fn test_shortest_path_with_model() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 3 is short, but passes a low bridge and climbs a lot,
+    // 0 -> 2 -> 3 is longer and flat
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,1.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,1.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let roads = vec![Road { length: 2.0, clearance: 3.0, climb: 50.0 },
+                     Road { length: 3.0, clearance: 5.0, climb: 0.0 },
+                     Road { length: 2.0, clearance: 5.0, climb: 50.0 },
+                     Road { length: 3.0, clearance: 5.0, climb: 0.0 }];
+
+    let car = |_arc: usize, road: &Road| road.length;
+    let route = shortest_path_with_model(&network, &roads, &car, 0, 3).unwrap();
+    assert_eq!(&vec![0,1,3], route.nodes());
+    assert_eq!(4.0, route.cost());
+
+    let route = shortest_path_with_model(&network, &roads, &Truck { height: 4.0 }, 0, 3).unwrap();
+    assert_eq!(&vec![0,2,3], route.nodes());
+    assert_eq!(6.0, route.cost());
+    assert_eq!(None, shortest_path_with_model(&network, &roads, &Truck { height: 6.0 }, 0, 3));
+
+    let energy = |_arc: usize, road: &Road| 0.1 * road.length + 0.05 * road.climb;
+    let (pred, d) = dijkstra_with_model(&network, &roads, &energy, 0);
+    assert_eq!(2, pred[3]);
+    assert!((d[3] - 0.6).abs() < 1e-9);
+}
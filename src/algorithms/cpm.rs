@@ -0,0 +1,378 @@
+use std::f64;
+
+use super::super::{ DoubleVec, Network, NodeId, NodeVec };
+use super::super::random::Rng;
+use super::dag::{ CycleFound, topological_sort };
+
+/// Result of `critical_path_method` on an activity-on-arc network. Event
+/// times are per node, floats per arc in arc order (see `Network::arcs`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpmAnalysis {
+    /// The earliest time every event can occur.
+    pub earliest:    DoubleVec,
+    /// The latest time every event can occur without delaying the project.
+    pub latest:      DoubleVec,
+    /// How long every activity can be delayed without delaying the project.
+    pub total_float: DoubleVec,
+    /// How long every activity can be delayed without delaying any
+    /// following activity.
+    pub free_float:  DoubleVec,
+    /// The length of the project, i.e. of its critical paths.
+    pub duration:    f64,
+}
+
+impl CpmAnalysis {
+    /// Whether the activity with number `arc` is critical, i.e. has no
+    /// total float up to rounding errors.
+    pub fn is_critical(&self, arc: usize) -> bool {
+        self.total_float[arc] <= 1e-9 * self.duration.max(1.0)
+    }
+
+    /// All critical paths as sequences of events, from an event without
+    /// predecessors to one without successors, in lexicographic order.
+    /// Parallel critical activities between the same events give the path
+    /// only once. There may be exponentially many critical paths.
+    pub fn critical_paths<N: Network>(&self, network: &N) -> Vec<NodeVec> {
+        let mut critical = vec![Vec::new(); network.num_nodes()];
+        for (arc, (i, j)) in network.arcs().enumerate() {
+            if self.is_critical(arc) {
+                critical[i as usize].push(j);
+            }
+        }
+        for successors in critical.iter_mut() {
+            successors.sort();
+            successors.dedup();
+        }
+        let mut paths = Vec::new();
+        for start in network.nodes().filter(|i| network.in_degree(*i) == 0 && !critical[*i as usize].is_empty()) {
+            // depth first over the critical activities, with the position
+            // in the successor list of every event on the path
+            let mut path = vec![start];
+            let mut positions = vec![0];
+            while let Some(&i) = path.last() {
+                let position = *positions.last().unwrap();
+                if critical[i as usize].is_empty() {
+                    paths.push(path.clone());
+                }
+                if position < critical[i as usize].len() {
+                    *positions.last_mut().unwrap() += 1;
+                    path.push(critical[i as usize][position]);
+                    positions.push(0);
+                } else {
+                    path.pop();
+                    positions.pop();
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// The critical path method for an activity-on-arc project network: every
+/// arc is an activity whose duration is its cost, every node an event that
+/// occurs when all activities into it are finished. Events without
+/// predecessors occur at time `0.0`, and the project is finished when all
+/// events without successors have occurred.
+///
+/// A forward pass in topological order gives the earliest, a backward pass
+/// the latest event times, both in `O(n + m)` time.
+/// # Errors
+/// `CycleFound` if the network is not acyclic.
+pub fn critical_path_method<N: Network>(network: &N) -> Result<CpmAnalysis, CycleFound> {
+    let order = topological_sort(network)?;
+    let durations: DoubleVec = network.nodes().flat_map(|i| network.out_arcs(i).map(|(_, cost, _)| cost)).collect();
+    Ok(analyze(network, &order, &durations))
+}
+
+/// The forward and backward pass of the critical path method with one
+/// duration per arc in arc order, the nodes in topological `order`.
+fn analyze<N: Network>(network: &N, order: &[NodeId], durations: &[f64]) -> CpmAnalysis {
+    let n = network.num_nodes();
+    let first_arcs = network.first_arcs();
+    let mut earliest = vec![0.0; n];
+    for i in order {
+        for (k, (j, _, _)) in network.out_arcs(*i).enumerate() {
+            let duration = durations[first_arcs[*i as usize] + k];
+            earliest[j as usize] = f64::max(earliest[j as usize], earliest[*i as usize] + duration);
+        }
+    }
+    let project = earliest.iter().cloned().fold(0.0, f64::max);
+    let mut latest = vec![project; n];
+    for i in order.iter().rev() {
+        for (k, (j, _, _)) in network.out_arcs(*i).enumerate() {
+            let duration = durations[first_arcs[*i as usize] + k];
+            latest[*i as usize] = f64::min(latest[*i as usize], latest[j as usize] - duration);
+        }
+    }
+
+    let mut total_float = Vec::with_capacity(network.num_arcs());
+    let mut free_float = Vec::with_capacity(network.num_arcs());
+    for (arc, (i, j)) in network.arcs().enumerate() {
+        total_float.push(latest[j as usize] - earliest[i as usize] - durations[arc]);
+        free_float.push(earliest[j as usize] - earliest[i as usize] - durations[arc]);
+    }
+    CpmAnalysis {
+        earliest:    earliest,
+        latest:      latest,
+        total_float: total_float,
+        free_float:  free_float,
+        duration:    project,
+    }
+}
+
+/// The PERT estimate of an uncertain activity duration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThreePointEstimate {
+    pub optimistic:  f64,
+    pub most_likely: f64,
+    pub pessimistic: f64,
+}
+
+impl ThreePointEstimate {
+    /// # Panics
+    /// If the durations are not ordered `optimistic <= most_likely <= pessimistic`.
+    pub fn new(optimistic: f64, most_likely: f64, pessimistic: f64) -> ThreePointEstimate {
+        assert!(optimistic <= most_likely && most_likely <= pessimistic, "three point estimates must be ordered");
+        ThreePointEstimate {
+            optimistic:  optimistic,
+            most_likely: most_likely,
+            pessimistic: pessimistic,
+        }
+    }
+
+    /// The expected duration `(a + 4m + b) / 6` of the PERT beta distribution.
+    pub fn mean(&self) -> f64 {
+        (self.optimistic + 4.0 * self.most_likely + self.pessimistic) / 6.0
+    }
+
+    /// The PERT variance `((b - a) / 6)^2`.
+    pub fn variance(&self) -> f64 {
+        let spread = (self.pessimistic - self.optimistic) / 6.0;
+        spread * spread
+    }
+
+    /// A random duration from the PERT beta distribution over `[a, b]` with
+    /// the shapes `1 + 4 (m - a) / (b - a)` and `1 + 4 (b - m) / (b - a)`,
+    /// whose mean is `mean()`. The beta variate is drawn as the ratio of two
+    /// gamma variates.
+    pub fn sample(&self, rng: &mut Rng) -> f64 {
+        let (a, m, b) = (self.optimistic, self.most_likely, self.pessimistic);
+        if b <= a {
+            return a;
+        }
+        let x = sample_gamma(1.0 + 4.0 * (m - a) / (b - a), rng);
+        let y = sample_gamma(1.0 + 4.0 * (b - m) / (b - a), rng);
+        a + (b - a) * x / (x + y)
+    }
+}
+
+/// A gamma distributed value with the given `shape >= 1` and scale `1`, by
+/// Marsaglia, Tsang: "A simple method for generating gamma variables".
+fn sample_gamma(shape: f64, rng: &mut Rng) -> f64 {
+    let d = shape - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = sample_normal(rng);
+        let v = 1.0 + c * x;
+        if v <= 0.0 {
+            continue;
+        }
+        let v = v * v * v;
+        let u = rng.next_f64();
+        if u < 1.0 - 0.0331 * x * x * x * x || u.ln() < 0.5 * x * x + d * (1.0 - v + v.ln()) {
+            return d * v;
+        }
+    }
+}
+
+/// A standard normally distributed value by the Box-Muller transform.
+fn sample_normal(rng: &mut Rng) -> f64 {
+    let radius = (-2.0 * (1.0 - rng.next_f64()).ln()).sqrt();
+    radius * (2.0 * f64::consts::PI * rng.next_f64()).cos()
+}
+
+/// Result of `pert`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PertAnalysis {
+    /// The critical path method on the expected durations.
+    pub cpm:               CpmAnalysis,
+    /// The expected length of the project, that of its critical paths.
+    pub expected_duration: f64,
+    /// The variance of the project length: the sum of the variances along
+    /// a critical path, the largest if there are several.
+    pub variance:          f64,
+}
+
+impl PertAnalysis {
+    /// The probability to finish the project by `deadline`, assuming its
+    /// length is normally distributed with `expected_duration` and
+    /// `variance`. Like every PERT estimate, this ignores near critical
+    /// paths and is optimistic if there are many of them; see
+    /// `simulate_completion_probability`.
+    pub fn completion_probability(&self, deadline: f64) -> f64 {
+        if self.variance <= 0.0 {
+            return if deadline >= self.expected_duration { 1.0 } else { 0.0 };
+        }
+        normal_cdf((deadline - self.expected_duration) / self.variance.sqrt())
+    }
+}
+
+/// The program evaluation and review technique: the critical path method on
+/// the expected durations of the three point `estimates`, one per arc in
+/// arc order. The variances are summed along the critical paths in
+/// topological order.
+/// # Errors
+/// `CycleFound` if the network is not acyclic.
+/// # Panics
+/// If there isn't one estimate per arc.
+pub fn pert<N: Network>(network: &N, estimates: &[ThreePointEstimate]) -> Result<PertAnalysis, CycleFound> {
+    assert_eq!(network.num_arcs(), estimates.len(), "one estimate per activity is required");
+    let order = topological_sort(network)?;
+    let means: DoubleVec = estimates.iter().map(|e| e.mean()).collect();
+    let cpm = analyze(network, &order, &means);
+    let first_arcs = network.first_arcs();
+
+    // the largest variance of a critical path to every event
+    let mut variance = vec![0.0; network.num_nodes()];
+    for i in &order {
+        for (k, (j, _, _)) in network.out_arcs(*i).enumerate() {
+            let arc = first_arcs[*i as usize] + k;
+            if cpm.is_critical(arc) {
+                variance[j as usize] = f64::max(variance[j as usize], variance[*i as usize] + estimates[arc].variance());
+            }
+        }
+    }
+    // critical paths end at the events finishing the project
+    let tolerance = 1e-9 * cpm.duration.max(1.0);
+    let project_variance = network.nodes()
+        .filter(|i| cpm.earliest[*i as usize] >= cpm.duration - tolerance)
+        .map(|i| variance[i as usize])
+        .fold(0.0, f64::max);
+    Ok(PertAnalysis {
+        expected_duration: cpm.duration,
+        variance:          project_variance,
+        cpm:               cpm,
+    })
+}
+
+/// Estimates the probability to finish the project by `deadline` by
+/// simulating it `runs` times with durations sampled from the three point
+/// `estimates` (see `ThreePointEstimate::sample`). Unlike
+/// `PertAnalysis::completion_probability`, this accounts for near critical
+/// paths becoming critical.
+/// # Errors
+/// `CycleFound` if the network is not acyclic.
+/// # Panics
+/// If there isn't one estimate per arc or `runs` is `0`.
+pub fn simulate_completion_probability<N: Network>(network: &N, estimates: &[ThreePointEstimate], deadline: f64,
+                                                   runs: usize, seed: u64) -> Result<f64, CycleFound> {
+    assert_eq!(network.num_arcs(), estimates.len(), "one estimate per activity is required");
+    assert!(runs > 0, "at least one run is required");
+    let order = topological_sort(network)?;
+    let first_arcs = network.first_arcs();
+    let mut rng = Rng::new(seed);
+    let mut earliest = vec![0.0; network.num_nodes()];
+    let mut in_time = 0;
+    for _ in 0..runs {
+        for time in earliest.iter_mut() {
+            *time = 0.0;
+        }
+        for i in &order {
+            for (k, (j, _, _)) in network.out_arcs(*i).enumerate() {
+                let duration = estimates[first_arcs[*i as usize] + k].sample(&mut rng);
+                earliest[j as usize] = f64::max(earliest[j as usize], earliest[*i as usize] + duration);
+            }
+        }
+        if earliest.iter().all(|time| *time <= deadline) {
+            in_time += 1;
+        }
+    }
+    Ok(in_time as f64 / runs as f64)
+}
+
+/// The standard normal distribution function, by the approximation 7.1.26
+/// of the error function in Abramowitz, Stegun: "Handbook of mathematical
+/// functions", accurate to about `1e-7`.
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+#[test]
+fn test_critical_path_method() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 3 takes 3 + 4, 0 -> 2 -> 3 takes 2 + 5, 1 -> 2 takes 1 and
+    // 0 -> 3 directly takes 2
+    let mut edges = vec![(0,1,3.0,0.0), (0,2,2.0,0.0), (0,3,2.0,0.0), (1,2,1.0,0.0), (1,3,4.0,0.0), (2,3,5.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let cpm = critical_path_method(&network).unwrap();
+    assert_eq!(9.0, cpm.duration);
+    assert_eq!(vec![0.0, 3.0, 4.0, 9.0], cpm.earliest);
+    assert_eq!(vec![0.0, 3.0, 4.0, 9.0], cpm.latest);
+    // arcs in order (0,1) (0,2) (0,3) (1,2) (1,3) (2,3)
+    assert_eq!(vec![0.0, 2.0, 7.0, 0.0, 2.0, 0.0], cpm.total_float);
+    assert_eq!(vec![0.0, 2.0, 7.0, 0.0, 2.0, 0.0], cpm.free_float);
+    assert_eq!(vec![vec![0, 1, 2, 3]], cpm.critical_paths(&network));
+
+    // two parallel critical chains 0 -> 1 -> 3 and 0 -> 2 -> 3, and the
+    // activity 2 -> 4, which can be delayed by 1 but not without delaying event 4
+    let mut edges = vec![(0,1,2.0,0.0), (0,2,2.0,0.0), (1,3,2.0,0.0), (2,3,2.0,0.0), (2,4,1.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let cpm = critical_path_method(&network).unwrap();
+    assert_eq!(vec![vec![0, 1, 3], vec![0, 2, 3]], cpm.critical_paths(&network));
+    assert_eq!((1.0, 0.0), (cpm.total_float[4], cpm.free_float[4]));
+    assert!(!cpm.is_critical(4));
+}
+
+#[test]
+fn test_pert() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 3 expects 4 + 5, 0 -> 2 -> 3 expects 2 + 3
+    let mut edges = vec![(0,1,0.0,0.0), (0,2,0.0,0.0), (1,3,0.0,0.0), (2,3,0.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let estimates = [ThreePointEstimate::new(1.0, 4.0, 7.0), ThreePointEstimate::new(1.0, 2.0, 3.0),
+                     ThreePointEstimate::new(2.0, 5.0, 8.0), ThreePointEstimate::new(3.0, 3.0, 3.0)];
+    assert_eq!(1.0, estimates[0].variance());
+    let analysis = pert(&network, &estimates).unwrap();
+    assert_eq!(9.0, analysis.expected_duration);
+    assert_eq!(2.0, analysis.variance);
+    assert!(analysis.cpm.is_critical(0) && !analysis.cpm.is_critical(1));
+    assert!((analysis.completion_probability(9.0) - 0.5).abs() < 1e-6);
+    // one standard deviation above the expected length
+    assert!((analysis.completion_probability(9.0 + 2f64.sqrt()) - 0.841345).abs() < 1e-5);
+    assert!(analysis.completion_probability(5.0) < 0.01);
+
+    let simulated = simulate_completion_probability(&network, &estimates, 9.0, 20000, 7).unwrap();
+    assert!((simulated - 0.5).abs() < 0.02);
+    assert_eq!(1.0, simulate_completion_probability(&network, &estimates, 15.0, 100, 7).unwrap());
+
+    let mut rng = Rng::new(3);
+    for _ in 0..100 {
+        let duration = estimates[0].sample(&mut rng);
+        assert!(duration >= 1.0 && duration <= 7.0);
+    }
+}
+
+#[test]
+fn test_pert_asymmetric() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a single activity skewed to the right, expected to take 2.5 with a
+    // standard deviation of 1.5; the PERT beta distribution over [1, 10]
+    // has the shapes 1 and 5, so it ends by 3 with 1 - (7/9)^5 = 0.7154
+    let mut edges = vec![(0,1,0.0,0.0)];
+    let network = compact_star_from_edge_vec(2, &mut edges);
+    let estimates = [ThreePointEstimate::new(1.0, 1.0, 10.0)];
+    let analysis = pert(&network, &estimates).unwrap();
+    assert_eq!(2.5, analysis.expected_duration);
+    assert_eq!(2.25, analysis.variance);
+
+    let mut rng = Rng::new(5);
+    let runs = 20000;
+    let mean = (0..runs).map(|_| estimates[0].sample(&mut rng)).sum::<f64>() / runs as f64;
+    assert!((mean - estimates[0].mean()).abs() < 0.05);
+    let simulated = simulate_completion_probability(&network, &estimates, 3.0, runs, 7).unwrap();
+    assert!((simulated - 0.7154).abs() < 0.02);
+}
@@ -0,0 +1,163 @@
+use std::error::Error;
+use std::f64;
+use std::fmt;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::adjacency::{ forward_adjacency, reverse_adjacency };
+
+/// A directed cycle, found where an acyclic network was required. The
+/// cycle lists its nodes in the order of its arcs; the last node leads back
+/// to the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleFound {
+    pub cycle: NodeVec,
+}
+
+impl fmt::Display for CycleFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cycle through nodes {:?}", self.cycle)
+    }
+}
+
+impl Error for CycleFound {}
+
+/// Orders the nodes so that every arc leads from an earlier to a later
+/// node, by Kahn's algorithm in `O(n + m)` time.
+/// # Errors
+/// `CycleFound` with one of the cycles, starting at its smallest node, if
+/// the network is not acyclic.
+pub fn topological_sort<N: Network>(network: &N) -> Result<NodeVec, CycleFound> {
+    let n = network.num_nodes();
+    let forward = forward_adjacency(network);
+    let mut in_degree = vec![0; n];
+    for arcs in &forward {
+        for &(j, _) in arcs {
+            in_degree[j as usize] += 1;
+        }
+    }
+
+    let mut order = Vec::with_capacity(n);
+    let mut ready: NodeVec = (0..n as NodeId).filter(|i| in_degree[*i as usize] == 0).rev().collect();
+    while let Some(i) = ready.pop() {
+        order.push(i);
+        for &(j, _) in &forward[i as usize] {
+            in_degree[j as usize] -= 1;
+            if in_degree[j as usize] == 0 {
+                ready.push(j);
+            }
+        }
+    }
+    if order.len() == n {
+        return Ok(order);
+    }
+
+    // every node left over has an arc from another left over node, so
+    // walking these arcs backwards must run into a cycle
+    let reverse = reverse_adjacency(network);
+    let start = (0..n).find(|i| in_degree[*i] > 0).unwrap();
+    let mut visited = vec![false; n];
+    let mut walk = Vec::new();
+    let mut current = start as NodeId;
+    while !visited[current as usize] {
+        visited[current as usize] = true;
+        walk.push(current);
+        current = reverse[current as usize].iter()
+            .map(|&(i, _)| i)
+            .find(|i| in_degree[*i as usize] > 0)
+            .unwrap();
+    }
+    let first = walk.iter().position(|i| *i == current).unwrap();
+    let mut cycle = walk.split_off(first);
+    cycle.reverse();
+    let smallest = (0..cycle.len()).min_by_key(|k| cycle[*k]).unwrap();
+    cycle.rotate_left(smallest);
+    Err(CycleFound { cycle: cycle })
+}
+
+/// Shortest paths from `source` in an acyclic network, relaxing the arcs
+/// in topological order in `O(n + m)` time. Negative costs are allowed.
+/// Returns predecessors and distances like `dijkstra`; unreachable nodes
+/// keep the network's invalid id and an infinite distance.
+/// # Errors
+/// `CycleFound` if the network is not acyclic.
+pub fn dag_shortest_paths<N: Network>(network: &N, source: NodeId) -> Result<(NodeVec, DoubleVec), CycleFound> {
+    dag_paths(network, source, 1.0)
+}
+
+/// Longest paths from `source` in an acyclic network, e.g. the earliest
+/// start times in a project network, in `O(n + m)` time. Unreachable nodes
+/// keep the network's invalid id as predecessor and a distance of negative
+/// infinity.
+/// # Errors
+/// `CycleFound` if the network is not acyclic, where longest paths are
+/// unbounded.
+pub fn dag_longest_paths<N: Network>(network: &N, source: NodeId) -> Result<(NodeVec, DoubleVec), CycleFound> {
+    dag_paths(network, source, -1.0).map(|(pred, d)| (pred, d.into_iter().map(|d| -d).collect()))
+}
+
+/// Shortest paths on the costs multiplied by `sign`.
+fn dag_paths<N: Network>(network: &N, source: NodeId, sign: f64) -> Result<(NodeVec, DoubleVec), CycleFound> {
+    let order = topological_sort(network)?;
+    let forward = forward_adjacency(network);
+    let n = network.num_nodes();
+    let mut pred = vec![network.invalid_id(); n];
+    let mut d = vec![f64::INFINITY; n];
+    d[source as usize] = 0.0;
+    for i in order {
+        let i = i as usize;
+        if d[i] == f64::INFINITY {
+            continue;
+        }
+        for &(j, cost) in &forward[i] {
+            let candidate: Cost = d[i] + sign * cost;
+            if candidate < d[j as usize] {
+                d[j as usize] = candidate;
+                pred[j as usize] = i as NodeId;
+            }
+        }
+    }
+    Ok((pred, d))
+}
+
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+#[test]
+fn test_topological_sort() {
+    let mut edges = vec![(0,2,1.0,0.0),
+                         (1,2,1.0,0.0),
+                         (2,3,1.0,0.0),
+                         (4,1,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+    assert_eq!(Ok(vec![0,4,1,2,3]), topological_sort(&compact_star));
+
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (1,2,1.0,0.0),
+                         (2,3,1.0,0.0),
+                         (3,1,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let cycle = topological_sort(&compact_star).unwrap_err();
+    assert_eq!(vec![1,2,3], cycle.cycle);
+    assert_eq!("cycle through nodes [1, 2, 3]", format!("{}", cycle));
+}
+
+#[test]
+fn test_dag_paths() {
+    let mut edges = vec![(0,1,3.0,0.0),
+                         (0,2,2.0,0.0),
+                         (1,3,4.0,0.0),
+                         (2,1,-2.0,0.0),
+                         (2,3,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+    let (pred, d) = dag_shortest_paths(&compact_star, 0).unwrap();
+    assert_eq!(vec![5,2,0,1,5], pred);
+    assert_eq!(vec![0.0, 0.0, 2.0, 4.0, f64::INFINITY], d);
+
+    let (pred, d) = dag_longest_paths(&compact_star, 0).unwrap();
+    assert_eq!(vec![5,0,0,1,5], pred);
+    assert_eq!(vec![0.0, 3.0, 2.0, 7.0, f64::NEG_INFINITY], d);
+
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0)];
+    let cyclic = compact_star_from_edge_vec(2, &mut edges);
+    assert!(dag_longest_paths(&cyclic, 0).is_err());
+}
@@ -0,0 +1,163 @@
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::super::cancel::{ Cancellation, Cancelled };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::adjacency::{ CostAdjacency, forward_adjacency, reverse_adjacency };
+
+/// Dense distance matrix with one row per source and one column per target.
+pub type DistanceTable = Vec<DoubleVec>;
+
+/// Returns the distances from `source` to each of `targets`, in the order
+/// of `targets`. The search stops once all targets are settled.
+/// Unreachable targets get the network's infinity.
+pub fn one_to_many<N: Network>(network: &N, source: NodeId, targets: &[NodeId]) -> DoubleVec {
+    let forward = forward_adjacency(network);
+    settle_targets(&forward, source, targets, network.infinity())
+}
+
+/// Computes the table of shortest path distances between every node in
+/// `sources` and every node in `targets`. The result has
+/// `sources.len()` rows of `targets.len()` entries each; unreachable pairs
+/// get the network's infinity.
+///
+/// The adjacency lists are built once and shared by all searches. One
+/// search is run per element of the smaller of the two sets: forward from
+/// each source, or backward along the reversed arcs from each target. Every
+/// search stops as soon as all nodes of the other set are settled.
+pub fn distance_table<N: Network>(network: &N, sources: &[NodeId], targets: &[NodeId]) -> DistanceTable {
+    distance_table_cancellable(network, sources, targets, &Cancellation::new())
+        .expect("distance tables cannot be cancelled without a token")
+}
+
+/// Like `distance_table`, but checks `cancellation` before every search.
+pub fn distance_table_cancellable<N: Network>(network: &N, sources: &[NodeId], targets: &[NodeId],
+                                              cancellation: &Cancellation) -> Result<DistanceTable, Cancelled> {
+    let inf = network.infinity();
+    if sources.len() <= targets.len() {
+        let forward = forward_adjacency(network);
+        sources.iter()
+            .map(|source| {
+                cancellation.check()?;
+                Ok(settle_targets(&forward, *source, targets, inf))
+            })
+            .collect()
+    } else {
+        let reverse = reverse_adjacency(network);
+        let mut table = vec![vec![inf; targets.len()]; sources.len()];
+        for (t, target) in targets.iter().enumerate() {
+            cancellation.check()?;
+            let column = settle_targets(&reverse, *target, sources, inf);
+            for (s, dist) in column.iter().enumerate() {
+                table[s][t] = *dist;
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Dijkstra over plain adjacency lists that stops when all `targets` are
+/// settled and returns their distances in the order of `targets`, with
+/// `inf` for the unreached ones.
+fn settle_targets(adjacency: &CostAdjacency, source: NodeId, targets: &[NodeId], inf: Cost) -> DoubleVec {
+    let n = adjacency.len();
+    let mut is_target = vec![false; n];
+    let mut remaining = 0;
+    for target in targets {
+        if !is_target[*target as usize] {
+            is_target[*target as usize] = true;
+            remaining += 1;
+        }
+    }
+
+    let mut heap = BinaryHeap::new();
+    // not `inf`, which the longest paths of the network reach
+    let mut d = vec![Cost::INFINITY; n];
+    let mut marked = vec![false; n];
+
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+
+    while remaining > 0 && !heap.is_empty() {
+        let next_node = heap.find_min().unwrap();
+        heap.delete_min();
+        let i = next_node as usize;
+
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if is_target[i] {
+            remaining -= 1;
+        }
+
+        for &(adjacent_node, cost) in &adjacency[i] {
+            let j = adjacent_node as usize;
+            if d[j] > d[i] + cost {
+                d[j] = d[i] + cost;
+                heap.insert(adjacent_node, d[j]);
+            }
+        }
+    }
+
+    targets.iter()
+        .map(|target| if d[*target as usize].is_finite() { d[*target as usize] } else { inf })
+        .collect()
+}
+
+#[test]
+fn test_one_to_many() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    assert_eq!(vec![9.0, 5.0, 0.0], one_to_many(&compact_star, 0, &[5,3,0]));
+    assert_eq!(vec![28.0], one_to_many(&compact_star, 5, &[0]));
+}
+
+#[test]
+fn test_distance_table() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    assert_eq!(vec![vec![5.0, 9.0], vec![2.0, 7.0]],
+               distance_table(&compact_star, &[0,1], &[3,5]));
+    // more sources than targets runs the backward searches
+    assert_eq!(vec![vec![9.0], vec![7.0], vec![5.0], vec![7.0], vec![0.0]],
+               distance_table(&compact_star, &[0,1,2,3,5], &[5]));
+    assert_eq!(vec![vec![28.0, 0.0]], distance_table(&compact_star, &[5], &[0,5]));
+
+    // the path over every arc is as long as the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,3,0.0,0.0)];
+    let path = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(vec![vec![0.0, 1.0, 2.0, 2.0]], distance_table(&path, &[0], &[0,1,2,3]));
+    assert_eq!(vec![vec![2.0], vec![1.0], vec![0.0], vec![0.0]],
+               distance_table(&path, &[0,1,2,3], &[3]));
+}
+
+#[test]
+fn test_distance_table_cancellable() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let cancellation = Cancellation::new();
+    assert_eq!(Ok(vec![vec![0.0, 2.0]]), distance_table_cancellable(&compact_star, &[0], &[0, 2], &cancellation));
+    cancellation.cancel();
+    assert_eq!(Err(Cancelled), distance_table_cancellable(&compact_star, &[0], &[0, 2], &cancellation));
+    assert_eq!(Err(Cancelled), distance_table_cancellable(&compact_star, &[0, 1, 2], &[2], &cancellation));
+}
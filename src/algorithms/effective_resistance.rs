@@ -0,0 +1,175 @@
+use std::f64;
+
+use super::super::{ DoubleVec, Network, NodeId, UnionFind };
+use super::super::compact_star::CompactStar;
+use super::super::linalg::{ Entries, Laplacian, conjugate_gradient, entry_values };
+use super::super::random::Rng;
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// Iteration limit of the Laplacian solves, relative to the number of nodes.
+const CG_ITERATIONS_PER_NODE: usize = 10;
+
+fn components(network: &CompactStar) -> UnionFind {
+    let mut components = UnionFind::new(network.num_nodes());
+    for (i, j) in network.arcs() {
+        components.union(i, j);
+    }
+    components
+}
+
+/// Effective resistances between the node `pairs` of the undirected
+/// network whose edge conductances are the arc `entries` (see
+/// `linalg::Laplacian` for how arcs become edges). Solves one Laplacian
+/// system `L x = e_a - e_b` per pair by conjugate gradients to the
+/// residual `eps`; the resistance is `x_a - x_b`. Pairs in different
+/// components have an infinite resistance.
+pub fn effective_resistances(network: &CompactStar, entries: Entries, pairs: &[(NodeId, NodeId)], eps: f64) -> DoubleVec {
+    let laplacian = Laplacian::new(network, entries);
+    let mut components = components(network);
+    let n = network.num_nodes();
+    pairs.iter().map(|&(a, b)| {
+        if a == b {
+            0.0
+        } else if components.find(a) != components.find(b) {
+            f64::INFINITY
+        } else {
+            let mut rhs = vec![0.0; n];
+            rhs[a as usize] = 1.0;
+            rhs[b as usize] = -1.0;
+            let x = conjugate_gradient(&laplacian, &rhs, eps, CG_ITERATIONS_PER_NODE * n).x;
+            x[a as usize] - x[b as usize]
+        }
+    }).collect()
+}
+
+/// Effective resistance between `a` and `b`, see `effective_resistances`.
+pub fn effective_resistance(network: &CompactStar, entries: Entries, a: NodeId, b: NodeId, eps: f64) -> f64 {
+    effective_resistances(network, entries, &[(a, b)], eps)[0]
+}
+
+/// Spanning edge centrality of every arc, in arc order: the conductance of
+/// its edge times the effective resistance between its end nodes. For
+/// unit conductances, this is the fraction of spanning trees that contain
+/// the edge, which is `1` for bridges. Needs one Laplacian solve per
+/// distinct edge; `approximate_arc_resistances` scales to larger networks.
+pub fn spanning_edge_centrality(network: &CompactStar, entries: Entries, eps: f64) -> DoubleVec {
+    let pairs: Vec<(NodeId, NodeId)> = network.arcs().collect();
+    let mut distinct: Vec<(NodeId, NodeId)> = pairs.iter()
+        .map(|&(i, j)| if i < j { (i, j) } else { (j, i) })
+        .collect();
+    distinct.sort();
+    distinct.dedup();
+    let resistances = effective_resistances(network, entries, &distinct, eps);
+    let conductances = symmetric_conductances(network, entries);
+    pairs.iter().map(|&(i, j)| {
+        let key = if i < j { (i, j) } else { (j, i) };
+        let index = distinct.binary_search(&key).unwrap();
+        conductances[index] * resistances[index]
+    }).collect()
+}
+
+/// The conductance of every undirected edge between distinct nodes, in the
+/// order of sorted `(min, max)` end node pairs: half the sum of the entries
+/// of all arcs between them, in either direction.
+fn symmetric_conductances(network: &CompactStar, entries: Entries) -> DoubleVec {
+    let values = entry_values(network, entries);
+    let mut keyed: Vec<((NodeId, NodeId), f64)> = network.arcs().zip(values)
+        .map(|((i, j), w)| (if i < j { (i, j) } else { (j, i) }, w / 2.0))
+        .collect();
+    keyed.sort_by(|a, b| a.0.cmp(&b.0));
+    let mut conductances: Vec<((NodeId, NodeId), f64)> = Vec::new();
+    for (key, w) in keyed {
+        match conductances.last_mut() {
+            Some(last) if last.0 == key => last.1 += w,
+            _ => conductances.push((key, w)),
+        }
+    }
+    conductances.into_iter().map(|(_, w)| w).collect()
+}
+
+/// Approximates the effective resistance between the end nodes of every
+/// arc, in arc order, by random projections (Spielman, Srivastava: "Graph
+/// sparsification by effective resistances"). Needs only `projections`
+/// Laplacian solves instead of one per edge; the relative error of every
+/// value is about `1 / sqrt(projections)`. Arcs between different
+/// components get an infinite resistance, self-loops zero.
+pub fn approximate_arc_resistances(network: &CompactStar, entries: Entries, projections: usize, eps: f64, seed: u64) -> DoubleVec {
+    assert!(projections > 0);
+    let n = network.num_nodes();
+    let laplacian = Laplacian::new(network, entries);
+    let values = entry_values(network, entries);
+    let arcs: Vec<(NodeId, NodeId)> = network.arcs().collect();
+    let mut rng = Rng::new(seed);
+    let scale = 1.0 / (projections as f64).sqrt();
+
+    let mut resistances = vec![0.0; arcs.len()];
+    for _ in 0..projections {
+        // y = B^T W^(1/2) q for a random sign vector q over the arcs
+        let mut y = vec![0.0; n];
+        for (a, &(i, j)) in arcs.iter().enumerate() {
+            let sign = if rng.chance(0.5) { scale } else { -scale };
+            let value = sign * (values[a] / 2.0).sqrt();
+            y[i as usize] += value;
+            y[j as usize] -= value;
+        }
+        let z = conjugate_gradient(&laplacian, &y, eps, CG_ITERATIONS_PER_NODE * n).x;
+        for (a, &(i, j)) in arcs.iter().enumerate() {
+            let difference = z[i as usize] - z[j as usize];
+            resistances[a] += difference * difference;
+        }
+    }
+    let mut components = components(network);
+    for (a, &(i, j)) in arcs.iter().enumerate() {
+        if components.find(i) != components.find(j) {
+            resistances[a] = f64::INFINITY;
+        }
+    }
+    resistances
+}
+
+#[test]
+fn test_effective_resistance() {
+    // a triangle 0 - 1 - 2 with the pendant node 3 at 2, and the isolated
+    // node 4
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,0,1.0,0.0), (2,3,2.0,0.0)];
+    let mut undirected: Vec<_> = edges.iter().map(|&(i, j, c, u)| (j, i, c, u)).collect();
+    edges.append(&mut undirected);
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let resistance = |a, b| effective_resistance(&network, Entries::InverseCosts, a, b, 1e-12);
+    // one resistor parallel to two in series
+    assert!((resistance(0, 1) - 2.0 / 3.0).abs() < 1e-9);
+    assert!((resistance(0, 3) - (2.0 / 3.0 + 2.0)).abs() < 1e-9);
+    assert!(resistance(0, 4).is_infinite());
+    assert_eq!(0.0, resistance(3, 3));
+
+    let centrality = spanning_edge_centrality(&network, Entries::Ones, 1e-12);
+    // arc order: 0->1, 0->2, 1->2, 1->0, 2->0, 2->3, 2->1, 3->2
+    // every triangle edge is in 2 of the 3 spanning trees, the bridge in all
+    for (a, (i, j)) in network.arcs().enumerate() {
+        let expected = if i == 3 || j == 3 { 1.0 } else { 2.0 / 3.0 };
+        assert!((centrality[a] - expected).abs() < 1e-9);
+    }
+    let total: f64 = centrality.iter().sum::<f64>() / 2.0;
+    // the centralities of all edges add up to n - 1 in a connected network
+    assert!((total - 3.0).abs() < 1e-9);
+}
+
+#[test]
+fn test_approximate_arc_resistances() {
+    let network = super::super::generators::gnp(40, 0.3, &Default::default(), 5);
+    let exact = spanning_edge_centrality(&network, Entries::Ones, 1e-12);
+    let approximate = approximate_arc_resistances(&network, Entries::Ones, 400, 1e-12, 9);
+    for a in 0..network.num_arcs() {
+        // the arc plus its possible reverse arc make up the conductance
+        assert!(approximate[a] > 0.0);
+        assert!((approximate[a] - exact[a] / conductance_of(&network, a)).abs() < 0.25 * approximate[a]);
+    }
+}
+
+#[cfg(test)]
+fn conductance_of(network: &CompactStar, arc: usize) -> f64 {
+    let (i, j) = network.arcs().nth(arc).unwrap();
+    let both = network.arcs().filter(|&(a, b)| (a, b) == (i, j) || (a, b) == (j, i)).count();
+    both as f64 / 2.0
+}
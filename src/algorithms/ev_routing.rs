@@ -0,0 +1,142 @@
+use super::super::{ Cost, Network, NodeId };
+use super::label_search::LabelSearch;
+use super::path::Path;
+
+/// Battery of an electric vehicle, in the units of the arc consumptions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Battery {
+    pub capacity: f64,
+    /// State of charge at the source.
+    pub initial:  f64,
+}
+
+/// A stop at a charging node on an `EvRoute`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChargingStop {
+    pub node:     NodeId,
+    /// Energy charged.
+    pub energy:   f64,
+    /// Time spent charging, included in the route's cost.
+    pub duration: Cost,
+}
+
+/// A feasible route for an electric vehicle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvRoute {
+    /// The visited nodes, with the travel time plus all charging time as cost.
+    pub path:   Path,
+    pub stops:  Vec<ChargingStop>,
+    /// State of charge at the target.
+    pub charge: f64,
+}
+
+/// Finds the fastest route from `source` to `target` on which the battery
+/// never runs empty. Arc costs are travel times; `consumption` holds the
+/// energy every arc consumes, in arc order. Negative consumptions, e.g.
+/// from recuperation downhill, charge the battery up to its capacity.
+/// `charging_rates` holds the energy charged per time unit at every node,
+/// `0.0` for nodes without a charger.
+///
+/// A label setting search over `(time, charge)` labels: every node keeps
+/// all labels not dominated by one that arrives no later with at least as
+/// much charge, and labels are settled in order of time, so the first label
+/// settled at `target` is the fastest. At a charger a label may charge up
+/// to full capacity; partial charging is not considered, so on rare
+/// networks a route that charges less at a slow charger is missed.
+///
+/// Returns `None` if `target` can't be reached without running empty.
+///
+/// # Panics
+/// If `consumption` or `charging_rates` don't match the network.
+pub fn ev_route<N: Network>(network: &N, consumption: &[f64], charging_rates: &[f64], battery: Battery,
+                            source: NodeId, target: NodeId) -> Option<EvRoute> {
+    assert_eq!(network.num_arcs(), consumption.len(), "one consumption per arc is required");
+    assert_eq!(network.num_nodes(), charging_rates.len(), "one charging rate per node is required");
+    let n = network.num_nodes();
+    let first_arcs = network.first_arcs();
+
+    // the charge is the resource, negated as more is better; the label data
+    // is the energy charged at the node to get from the predecessor to the
+    // label, or `0.0` if the label was reached over an arc
+    let mut search = LabelSearch::new(n);
+    let initial = battery.initial.min(battery.capacity);
+    search.add(source, 0.0, -initial, 0.0, None, 0.0);
+
+    while let Some(l) = search.settle() {
+        let (node, time, charge) = (search.label(l).node, search.label(l).time, -search.label(l).resource);
+        if node == target {
+            return Some(route(&search, l));
+        }
+
+        let rate = charging_rates[node as usize];
+        if rate > 0.0 && charge < battery.capacity && search.label(l).data == 0.0 {
+            let energy = battery.capacity - charge;
+            let arrival = time + energy / rate;
+            search.add(node, arrival, -battery.capacity, energy, Some(l), arrival);
+        }
+
+        for (k, (adjacent_node, cost, _)) in network.out_arcs(node).enumerate() {
+            let left = (charge - consumption[first_arcs[node as usize] + k]).min(battery.capacity);
+            if left < 0.0 {
+                continue;
+            }
+            let arrival = time + cost;
+            search.add(adjacent_node, arrival, -left, 0.0, Some(l), arrival);
+        }
+    }
+    None
+}
+
+fn route(search: &LabelSearch<f64>, last: usize) -> EvRoute {
+    let trace = search.trace(last);
+    let mut nodes = Vec::new();
+    let mut stops = Vec::new();
+    for (k, label) in trace.iter().enumerate() {
+        if label.data > 0.0 {
+            stops.push(ChargingStop {
+                node:     label.node,
+                energy:   label.data,
+                duration: label.time - trace[k - 1].time,
+            });
+        } else {
+            nodes.push(label.node);
+        }
+    }
+    EvRoute {
+        path:   Path::new(nodes, search.label(last).time),
+        stops:  stops,
+        charge: -search.label(last).resource,
+    }
+}
+
+#[test]
+fn test_ev_route() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a corridor 0 -> 1 -> 2 -> 3 using 4 units per arc and a direct arc
+    // 0 -> 3 that takes long and needs more than a full battery
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,3,20.0,0.0),
+                         (1,2,1.0,0.0),
+                         (2,3,1.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let consumption = vec![4.0, 11.0, 4.0, 4.0];
+    let battery = Battery { capacity: 10.0, initial: 10.0 };
+
+    // charging at 1 takes 4, at the faster charger 2 only 2 time units
+    let rates = vec![0.0, 1.0, 4.0, 0.0];
+    let route = ev_route(&network, &consumption, &rates, battery, 0, 3).unwrap();
+    assert_eq!(&vec![0,1,2,3], route.path.nodes());
+    assert_eq!(5.0, route.path.cost());
+    assert_eq!(vec![ChargingStop { node: 2, energy: 8.0, duration: 2.0 }], route.stops);
+    assert_eq!(6.0, route.charge);
+
+    // without chargers the target is out of range
+    assert_eq!(None, ev_route(&network, &consumption, &[0.0; 4], battery, 0, 3));
+
+    // recuperation on the way makes the corridor feasible without charging
+    let consumption = vec![4.0, 11.0, -3.0, 4.0];
+    let route = ev_route(&network, &consumption, &[0.0; 4], battery, 0, 3).unwrap();
+    assert_eq!(3.0, route.path.cost());
+    assert!(route.stops.is_empty());
+    assert_eq!(5.0, route.charge);
+}
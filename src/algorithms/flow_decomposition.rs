@@ -0,0 +1,206 @@
+use std::fmt;
+
+use super::super::{ DoubleVec, Network, NodeId, NodeVec };
+use super::super::pretty::{ LabeledDisplay, node_label };
+use super::adjacency::arc_list;
+
+/// Flows below this value are treated as zero.
+const FLOW_EPS: f64 = 1e-9;
+
+/// A path or cycle carrying a constant amount of flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowComponent {
+    /// The arc numbers along the path or cycle.
+    pub arcs:     Vec<usize>,
+    /// The visited nodes. For a cycle, the first node is repeated at the end.
+    pub nodes:    NodeVec,
+    pub flow:     f64,
+    pub is_cycle: bool,
+}
+
+/// Prints the component as `path 0 -> 1 -> 2: 2` or `cycle 1 -> 2 -> 1: 1`.
+impl LabeledDisplay for FlowComponent {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        let names: Vec<String> = self.nodes.iter().map(|i| node_label(labels, *i)).collect();
+        let kind = if self.is_cycle { "cycle" } else { "path" };
+        write!(f, "{} {}: {}", kind, names.join(" -> "), self.flow)
+    }
+}
+
+impl fmt::Display for FlowComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
+}
+
+/// Decomposes an arc flow into path and cycle flows, following the flow
+/// decomposition theorem in Ahuja, Magnati, Orlin: "Network Flows", 3.5.
+///
+/// `flow` holds the flow on every arc, numbered as in `frank_wolfe`. Nodes
+/// with more outflow than inflow are supplies, nodes with more inflow than
+/// outflow are demands. Every path leads from a supply to a demand node, and
+/// the remaining circulation is split into cycles. Every component removes
+/// the flow from at least one arc or balances one node, so there are at most
+/// `n + m` components, and at most `m` if the flow is a circulation.
+/// Summing up the components reproduces the original flow.
+///
+/// # Panics
+/// If `flow` doesn't hold one non-negative value per arc.
+pub fn decompose_flow<N: Network>(network: &N, flow: &DoubleVec) -> Vec<FlowComponent> {
+    let arcs = arc_list(network);
+    assert!(arcs.len() == flow.len());
+    let n = network.num_nodes();
+
+    let mut residual = flow.clone();
+    let mut out_arcs = vec![Vec::new(); n];
+    let mut imbalance = vec![0.0; n];
+    for (a, &(from, to)) in arcs.iter().enumerate() {
+        assert!(flow[a] >= 0.0);
+        out_arcs[from as usize].push(a);
+        imbalance[from as usize] += flow[a];
+        imbalance[to as usize] -= flow[a];
+    }
+
+    let mut components = Vec::new();
+    let mut next_arc = vec![0; n];
+
+    // paths from supply to demand nodes, cancelling cycles found on the way
+    for s in 0..n {
+        while imbalance[s] > FLOW_EPS {
+            let mut nodes = vec![s as NodeId];
+            let mut walk = Vec::new();
+            loop {
+                let current = *nodes.last().unwrap() as usize;
+                if current != s && imbalance[current] < -FLOW_EPS {
+                    break;
+                }
+                let a = match next_flow_arc(&out_arcs[current], &residual, &mut next_arc[current]) {
+                    Some(a) => a,
+                    None => break
+                };
+                let head = arcs[a].1;
+                walk.push(a);
+                match nodes.iter().position(|node| *node == head) {
+                    Some(p) => {
+                        let mut cycle_nodes = nodes.split_off(p);
+                        cycle_nodes.push(head);
+                        let cycle_arcs = walk.split_off(p);
+                        components.push(extract(cycle_arcs, cycle_nodes, &mut residual, true, None));
+                        nodes.push(head);
+                    },
+                    None => nodes.push(head)
+                }
+            }
+
+            let t = *nodes.last().unwrap() as usize;
+            if walk.is_empty() || t == s {
+                // the supply can't be routed any further, inconsistent input
+                imbalance[s] = 0.0;
+                break;
+            }
+            let bound = imbalance[s].min(-imbalance[t]);
+            let component = extract(walk, nodes, &mut residual, false, Some(bound));
+            imbalance[s] -= component.flow;
+            imbalance[t] += component.flow;
+            components.push(component);
+        }
+    }
+
+    // the remaining flow is a circulation
+    for s in 0..n {
+        while let Some(first) = next_flow_arc(&out_arcs[s], &residual, &mut next_arc[s]) {
+            let mut nodes = vec![s as NodeId, arcs[first].1];
+            let mut walk = vec![first];
+            loop {
+                let current = *nodes.last().unwrap() as usize;
+                let a = next_flow_arc(&out_arcs[current], &residual, &mut next_arc[current]).unwrap();
+                let head = arcs[a].1;
+                walk.push(a);
+                if let Some(p) = nodes.iter().position(|node| *node == head) {
+                    let mut cycle_nodes = nodes.split_off(p);
+                    cycle_nodes.push(head);
+                    let cycle_arcs = walk.split_off(p);
+                    components.push(extract(cycle_arcs, cycle_nodes, &mut residual, true, None));
+                    break;
+                }
+                nodes.push(head);
+            }
+        }
+    }
+    components
+}
+
+/// Returns the next arc in `out_arcs` that still carries flow, starting the
+/// scan at `*position`.
+fn next_flow_arc(out_arcs: &Vec<usize>, residual: &DoubleVec, position: &mut usize) -> Option<usize> {
+    while *position < out_arcs.len() {
+        let a = out_arcs[*position];
+        if residual[a] > FLOW_EPS {
+            return Some(a);
+        }
+        *position += 1;
+    }
+    None
+}
+
+/// Removes the bottleneck flow (capped at `bound`) along `arcs` and returns
+/// the corresponding component.
+fn extract(arcs: Vec<usize>, nodes: NodeVec, residual: &mut DoubleVec, is_cycle: bool, bound: Option<f64>) -> FlowComponent {
+    let mut amount = arcs.iter().map(|a| residual[*a]).fold(bound.unwrap_or(f64::MAX), f64::min);
+    if amount < 0.0 {
+        amount = 0.0;
+    }
+    for a in &arcs {
+        residual[*a] -= amount;
+    }
+    FlowComponent {
+        arcs:     arcs,
+        nodes:    nodes,
+        flow:     amount,
+        is_cycle: is_cycle,
+    }
+}
+
+#[test]
+fn test_decompose_flow() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,0.0,0.0),
+        (0,2,0.0,0.0),
+        (1,2,0.0,0.0),
+        (2,1,0.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let flow = vec![2.0, 1.0, 3.0, 1.0];
+    let components = decompose_flow(&compact_star, &flow);
+    assert_eq!(3, components.len());
+    assert_eq!(FlowComponent { arcs: vec![0,2], nodes: vec![0,1,2], flow: 2.0, is_cycle: false }, components[0]);
+    assert_eq!(FlowComponent { arcs: vec![1], nodes: vec![0,2], flow: 1.0, is_cycle: false }, components[1]);
+    assert_eq!(FlowComponent { arcs: vec![2,3], nodes: vec![1,2,1], flow: 1.0, is_cycle: true }, components[2]);
+    assert_eq!("cycle 1 -> 2 -> 1: 1", format!("{}", components[2]));
+
+    let mut recomposed = vec![0.0; flow.len()];
+    for component in &components {
+        for a in &component.arcs {
+            recomposed[*a] += component.flow;
+        }
+    }
+    assert_eq!(flow, recomposed);
+}
+
+#[test]
+fn test_decompose_circulation() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,0.0,0.0),
+        (1,0,0.0,0.0),
+        (1,2,0.0,0.0),
+        (2,0,0.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let components = decompose_flow(&compact_star, &vec![3.0, 1.0, 2.0, 2.0]);
+    assert_eq!(2, components.len());
+    assert!(components.iter().all(|c| c.is_cycle));
+    assert_eq!(vec![0,1,0], components[0].nodes);
+    assert_eq!(1.0, components[0].flow);
+    assert_eq!(vec![0,1,2,0], components[1].nodes);
+    assert_eq!(2.0, components[1].flow);
+}
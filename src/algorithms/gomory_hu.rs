@@ -0,0 +1,136 @@
+use std::f64;
+use std::io::{ self, Write };
+
+use super::super::{ DoubleVec, Network, NodeId, NodeVec };
+use super::max_flow::dinic;
+
+/// Flow equivalent tree of an undirected network, given with two arcs of
+/// equal capacity per edge: the maximum flow between any two nodes is the
+/// smallest capacity on the tree path between them. Computed with `n - 1`
+/// maximum flows by Gusfield's simplification of the Gomory-Hu algorithm
+/// ("Very simple methods for all pairs network flow analysis").
+#[derive(Debug, Clone, PartialEq)]
+pub struct GomoryHuTree {
+    /// The parent of every node, `n` for the root `0`.
+    parent:   NodeVec,
+    /// The capacity of the tree edge from every node to its parent.
+    capacity: DoubleVec,
+}
+
+/// Builds the flow equivalent tree of `network`, see `GomoryHuTree`.
+pub fn gomory_hu_tree<N: Network>(network: &N) -> GomoryHuTree {
+    let n = network.num_nodes();
+    let mut parent = vec![0; n];
+    let mut capacity = vec![f64::INFINITY; n];
+    if n > 0 {
+        parent[0] = n as NodeId;
+    }
+    for s in 1..n {
+        let t = parent[s];
+        let flow = dinic(network, s as NodeId, t);
+        capacity[s] = flow.value;
+        for i in s + 1..n {
+            if flow.source_side[i] && parent[i] == t {
+                parent[i] = s as NodeId;
+            }
+        }
+    }
+    GomoryHuTree {
+        parent:   parent,
+        capacity: capacity,
+    }
+}
+
+impl GomoryHuTree {
+    pub fn num_nodes(&self) -> usize {
+        self.parent.len()
+    }
+
+    /// The parent of `node` in the tree and the capacity of the edge to it,
+    /// `None` for the root.
+    pub fn parent(&self, node: NodeId) -> Option<(NodeId, f64)> {
+        let i = node as usize;
+        if (self.parent[i] as usize) < self.num_nodes() {
+            Some((self.parent[i], self.capacity[i]))
+        } else {
+            None
+        }
+    }
+
+    /// The maximum flow value between `a` and `b`, infinite for `a == b`.
+    pub fn max_flow(&self, a: NodeId, b: NodeId) -> f64 {
+        self.flow_row(a)[b as usize]
+    }
+
+    /// The maximum flow values between `a` and every node, in `O(n)` time.
+    /// The entry of `a` itself is infinite.
+    pub fn flow_row(&self, a: NodeId) -> DoubleVec {
+        let n = self.num_nodes();
+        let mut neighbors = vec![Vec::new(); n];
+        for i in 0..n {
+            if let Some((p, capacity)) = self.parent(i as NodeId) {
+                neighbors[i].push((p, capacity));
+                neighbors[p as usize].push((i as NodeId, capacity));
+            }
+        }
+        let mut row = vec![f64::NAN; n];
+        row[a as usize] = f64::INFINITY;
+        let mut stack = vec![a];
+        while let Some(i) = stack.pop() {
+            for &(j, capacity) in &neighbors[i as usize] {
+                if row[j as usize].is_nan() {
+                    row[j as usize] = row[i as usize].min(capacity);
+                    stack.push(j);
+                }
+            }
+        }
+        row
+    }
+
+    /// The full `n x n` matrix of maximum flow values, with an infinite
+    /// diagonal. Takes `O(n^2)` memory, see `write_flow_matrix` for large
+    /// networks.
+    pub fn flow_matrix(&self) -> Vec<DoubleVec> {
+        (0..self.num_nodes()).map(|a| self.flow_row(a as NodeId)).collect()
+    }
+
+    /// Writes the matrix of maximum flow values one row at a time, with
+    /// the values of a row separated by spaces and `-` on the diagonal.
+    /// Only one row is kept in memory.
+    pub fn write_flow_matrix<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for a in 0..self.num_nodes() {
+            let row: Vec<String> = self.flow_row(a as NodeId).iter()
+                .enumerate()
+                .map(|(b, value)| if a == b { "-".to_string() } else { value.to_string() })
+                .collect();
+            writeln!(writer, "{}", row.join(" "))?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_gomory_hu_tree() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // undirected: 0 - 1 (3), 1 - 2 (2), 0 - 2 (1), 2 - 3 (4)
+    let mut edges = Vec::new();
+    for &(i, j, c) in &[(0,1,3.0), (1,2,2.0), (0,2,1.0), (2,3,4.0)] {
+        edges.push((i, j, 0.0, c));
+        edges.push((j, i, 0.0, c));
+    }
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let tree = gomory_hu_tree(&network);
+    assert_eq!(None, tree.parent(0));
+    for a in 0..4 {
+        for b in 0..4 {
+            if a != b {
+                assert_eq!(dinic(&network, a, b).value, tree.max_flow(a, b));
+            }
+        }
+    }
+    assert_eq!(vec![f64::INFINITY, 4.0, 3.0, 3.0], tree.flow_matrix()[0]);
+
+    let mut written = Vec::new();
+    tree.write_flow_matrix(&mut written).unwrap();
+    assert_eq!("- 4 3 3\n4 - 3 3\n3 3 - 4\n3 3 4 -\n", String::from_utf8(written).unwrap());
+}
@@ -0,0 +1,111 @@
+use super::super::{ Cost, Network, NodeId };
+use super::search_algorithms::reverse_dijkstra;
+
+/// A place where a heuristic estimate `h(node, target)` breaks the
+/// guarantees A* relies on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeuristicViolation {
+    /// The estimate exceeds the exact distance from `node` to `target`.
+    Inadmissible { node: NodeId, target: NodeId, estimate: Cost, distance: Cost },
+    /// The estimate drops by more than the cost of the arc `(from, to)`,
+    /// i.e. `h(from, target) > cost + h(to, target)`.
+    Inconsistent { from: NodeId, to: NodeId, target: NodeId, cost: Cost, estimate_from: Cost, estimate_to: Cost },
+}
+
+/// The result of `check_heuristic`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HeuristicReport {
+    /// Number of `(node, target)` pairs compared against exact distances.
+    pub pairs_checked: usize,
+    /// Number of `(arc, target)` pairs checked for consistency.
+    pub arcs_checked:  usize,
+    pub violations:    Vec<HeuristicViolation>,
+}
+
+impl HeuristicReport {
+    /// Whether the heuristic never overestimated a distance. A* then finds
+    /// optimal paths.
+    pub fn is_admissible(&self) -> bool {
+        !self.violations.iter().any(|v| match *v {
+            HeuristicViolation::Inadmissible { .. } => true,
+            _ => false,
+        })
+    }
+
+    /// Whether the heuristic satisfied the triangle inequality on every
+    /// arc. A* then never has to settle a node twice.
+    pub fn is_consistent(&self) -> bool {
+        !self.violations.iter().any(|v| match *v {
+            HeuristicViolation::Inconsistent { .. } => true,
+            _ => false,
+        })
+    }
+}
+
+/// Checks the heuristic `heuristic(node, target)` for all nodes against each
+/// of `targets`, which is usually a random sample of the nodes. For every
+/// target, the exact distances from all nodes are computed with
+/// `reverse_dijkstra` and compared to the estimates of all nodes that can
+/// reach the target, and the consistency condition is checked on every arc.
+/// Deviations up to `eps` are tolerated to allow for rounding.
+pub fn check_heuristic<N, H>(network: &N, heuristic: H, targets: &[NodeId], eps: f64) -> HeuristicReport
+where N: Network, H: Fn(NodeId, NodeId) -> Cost {
+    let mut report = HeuristicReport::default();
+    for &target in targets {
+        let (succ, dist) = reverse_dijkstra(network, target);
+        let can_reach = |node: NodeId| node == target || succ[node as usize] != network.invalid_id();
+        let estimates: Vec<Cost> = network.nodes().map(|node| heuristic(node, target)).collect();
+
+        for node in network.nodes().filter(|node| can_reach(*node)) {
+            report.pairs_checked += 1;
+            let estimate = estimates[node as usize];
+            let distance = dist[node as usize];
+            if estimate > distance + eps {
+                report.violations.push(HeuristicViolation::Inadmissible {
+                    node: node, target: target, estimate: estimate, distance: distance,
+                });
+            }
+        }
+
+        for (from, to, cost, _) in network.arc_values() {
+            report.arcs_checked += 1;
+            let estimate_from = estimates[from as usize];
+            let estimate_to = estimates[to as usize];
+            if estimate_from > cost + estimate_to + eps {
+                report.violations.push(HeuristicViolation::Inconsistent {
+                    from: from, to: to, target: target, cost: cost,
+                    estimate_from: estimate_from, estimate_to: estimate_to,
+                });
+            }
+        }
+    }
+    report
+}
+
+#[test]
+fn test_check_heuristic() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a path 0 -> 1 -> 2 -> 3 with unit costs
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,3,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let hops = |node: NodeId, target: NodeId| if node <= target { (target - node) as Cost } else { 0.0 };
+
+    let report = check_heuristic(&compact_star, &hops, &[3], 1e-9);
+    assert_eq!(4, report.pairs_checked);
+    assert_eq!(3, report.arcs_checked);
+    assert!(report.is_admissible() && report.is_consistent());
+
+    let doubled = |node: NodeId, target: NodeId| 2.0 * hops(node, target);
+    let report = check_heuristic(&compact_star, &doubled, &[3], 1e-9);
+    assert!(!report.is_admissible() && !report.is_consistent());
+    assert_eq!(HeuristicViolation::Inadmissible { node: 2, target: 3, estimate: 2.0, distance: 1.0 },
+               report.violations[2]);
+
+    // admissible, but drops by 2 on the arc (1, 2)
+    let jumpy = |node: NodeId, _: NodeId| [2.0, 2.0, 0.0, 0.0][node as usize];
+    let report = check_heuristic(&compact_star, &jumpy, &[3], 1e-9);
+    assert!(report.is_admissible());
+    assert_eq!(vec![HeuristicViolation::Inconsistent { from: 1, to: 2, target: 3, cost: 1.0,
+                                                       estimate_from: 2.0, estimate_to: 0.0 }],
+               report.violations);
+}
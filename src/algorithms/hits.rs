@@ -0,0 +1,75 @@
+use super::super::Network;
+use super::pagerank::build_adj_list;
+
+/// Hub and authority scores by Kleinberg's HITS algorithm ("Authoritative
+/// sources in a hyperlinked environment"): a good authority is pointed to
+/// by good hubs, and a good hub points to good authorities. Starting from
+/// uniform scores, the authority of every node is set to the sum of the hub
+/// scores of its predecessors, then the hub score of every node to the sum
+/// of the authorities of its successors, and both are normalized to sum up
+/// to `1.0`. Iterates until both change by less than `eps` in their
+/// L1-norm, but at most `max_iter` times.
+///
+/// Returns the hub and the authority scores. Nodes without incoming arcs
+/// have no authority and nodes without outgoing arcs are no hubs; if the
+/// network has no arcs at all, all scores are `0.0`.
+pub fn hits<N: Network>(network: &N, eps: f64, max_iter: usize) -> (Vec<f64>, Vec<f64>) {
+    let n = network.num_nodes();
+    let adj_lists = build_adj_list(network);
+    let mut hubs = vec![1.0 / n as f64; n];
+    let mut authorities = vec![1.0 / n as f64; n];
+    for _ in 0..max_iter {
+        let mut new_authorities = vec![0.0; n];
+        for i in 0..n {
+            for j in &adj_lists[i] {
+                new_authorities[*j] += hubs[i];
+            }
+        }
+        normalize(&mut new_authorities);
+        let mut new_hubs: Vec<f64> = adj_lists.iter()
+            .map(|adj_list| adj_list.iter().map(|j| new_authorities[*j]).sum())
+            .collect();
+        normalize(&mut new_hubs);
+
+        let change = l1_distance(&hubs, &new_hubs).max(l1_distance(&authorities, &new_authorities));
+        hubs = new_hubs;
+        authorities = new_authorities;
+        if change < eps {
+            break;
+        }
+    }
+    (hubs, authorities)
+}
+
+/// Scales `scores` to sum up to `1.0`, unless they are all `0.0`.
+fn normalize(scores: &mut Vec<f64>) {
+    let sum: f64 = scores.iter().sum();
+    if sum > 0.0 {
+        for score in scores.iter_mut() {
+            *score /= sum;
+        }
+    }
+}
+
+fn l1_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum()
+}
+
+#[test]
+fn test_hits() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the hubs 0 and 1 both point to 2 and 3, only 0 also points to 4
+    let mut edges = vec![(0,2,0.0,0.0), (0,3,0.0,0.0), (0,4,0.0,0.0), (1,2,0.0,0.0), (1,3,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let (hubs, authorities) = hits(&network, 1e-12, 100);
+    assert!((hubs.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!((authorities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    assert!(hubs[0] > hubs[1] && hubs[1] > 0.0);
+    assert_eq!(vec![0.0; 3], hubs[2..].to_vec());
+    assert_eq!(authorities[2], authorities[3]);
+    assert!(authorities[2] > authorities[4] && authorities[4] > 0.0);
+    assert_eq!(0.0, authorities[0] + authorities[1]);
+
+    let (hubs, authorities) = hits(&compact_star_from_edge_vec(3, &mut Vec::new()), 1e-12, 100);
+    assert_eq!((vec![0.0; 3], vec![0.0; 3]), (hubs, authorities));
+}
@@ -0,0 +1,97 @@
+use std::f64;
+
+use super::super::{ DoubleVec, Network, NodeId };
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// Expected number of steps a random walk from every node needs to reach
+/// `target` for the first time, where every step follows one of the
+/// outgoing arcs with equal probability. Solves
+/// `h(i) = 1 + sum_j h(j) / out_degree(i)` with `h(target) = 0` by
+/// Gauss-Seidel iteration, until no value changes by more than `eps` or
+/// `max_iterations` sweeps are done.
+///
+/// Nodes from which the walk reaches `target` with probability less than
+/// one, because it can get stuck where `target` can't be reached anymore,
+/// have an infinite hitting time.
+pub fn hitting_times<N: Network>(network: &N, target: NodeId, eps: f64, max_iterations: usize) -> DoubleVec {
+    let n = network.num_nodes();
+    let t = target as usize;
+    let adjacency: Vec<Vec<usize>> = network.nodes()
+        .map(|i| network.adjacent(i).into_iter().map(|j| j as usize).collect())
+        .collect();
+    let mut incoming = vec![Vec::new(); n];
+    for (i, j) in network.arcs() {
+        incoming[j as usize].push(i as usize);
+    }
+
+    // nodes that can reach the target
+    let mut reaches = vec![false; n];
+    reaches[t] = true;
+    let mut stack = vec![t];
+    while let Some(j) = stack.pop() {
+        for i in &incoming[j] {
+            if !reaches[*i] {
+                reaches[*i] = true;
+                stack.push(*i);
+            }
+        }
+    }
+    // nodes that can get stuck before reaching the target
+    let mut stuck: Vec<bool> = reaches.iter().map(|r| !r).collect();
+    let mut stack: Vec<usize> = (0..n).filter(|i| stuck[*i]).collect();
+    while let Some(j) = stack.pop() {
+        for i in &incoming[j] {
+            if !stuck[*i] && *i != t {
+                stuck[*i] = true;
+                stack.push(*i);
+            }
+        }
+    }
+
+    let mut h: DoubleVec = (0..n).map(|i| if stuck[i] { f64::INFINITY } else { 0.0 }).collect();
+    for _ in 0..max_iterations {
+        let mut change: f64 = 0.0;
+        for i in (0..n).filter(|i| *i != t && !stuck[*i]) {
+            let sum: f64 = adjacency[i].iter().map(|j| h[*j]).sum();
+            let value = 1.0 + sum / adjacency[i].len() as f64;
+            change = change.max((value - h[i]).abs());
+            h[i] = value;
+        }
+        if change <= eps {
+            break;
+        }
+    }
+    h
+}
+
+/// Expected number of steps of a random walk from `a` to `b` and back to
+/// `a`, the sum of both hitting times. See `hitting_times` for the
+/// parameters. On undirected networks this is `2m` times the effective
+/// resistance between `a` and `b`.
+pub fn commute_time<N: Network>(network: &N, a: NodeId, b: NodeId, eps: f64, max_iterations: usize) -> f64 {
+    hitting_times(network, b, eps, max_iterations)[a as usize]
+        + hitting_times(network, a, eps, max_iterations)[b as usize]
+}
+
+#[test]
+fn test_hitting_times() {
+    // undirected path 0 - 1 - 2
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0), (1,2,1.0,0.0), (2,1,1.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    let h = hitting_times(&path, 2, 1e-12, 10000);
+    assert!((h[0] - 4.0).abs() < 1e-9);
+    assert!((h[1] - 3.0).abs() < 1e-9);
+    assert_eq!(0.0, h[2]);
+    assert!((commute_time(&path, 0, 2, 1e-12, 10000) - 8.0).abs() < 1e-9);
+
+    // from 0 the walk can get stuck in 3, which can't reach 2
+    let mut edges = vec![(0,1,1.0,0.0), (0,3,1.0,0.0), (1,2,1.0,0.0), (4,2,1.0,0.0), (2,4,1.0,0.0), (5,1,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let h = hitting_times(&network, 2, 1e-12, 100);
+    assert!(h[0].is_infinite());
+    assert_eq!(1.0, h[1]);
+    assert_eq!(1.0, h[4]);
+    assert_eq!(2.0, h[5]);
+    assert!(h[3].is_infinite());
+}
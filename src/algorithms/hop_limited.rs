@@ -0,0 +1,95 @@
+use std::f64;
+
+use super::super::{ Cost, Network, NodeId };
+use super::path::Path;
+
+/// Returns a cheapest path from `source` to `target` with at most
+/// `max_hops` arcs, or `None` if there is none, e.g. for "at most two
+/// transfers" queries on networks with one arc per ride.
+///
+/// Runs `max_hops` rounds of Bellman-Ford, where round `h` computes the
+/// cheapest cost of every node using at most `h` arcs from the costs of
+/// round `h - 1`. The predecessors of every round are kept to recover the
+/// path, which takes `O(max_hops * n)` memory besides the `O(max_hops * m)`
+/// time. Negative arc costs are allowed; with negative cycles the result is
+/// a cheapest walk with at most `max_hops` arcs.
+pub fn hop_limited_shortest_path<N: Network>(network: &N, source: NodeId, target: NodeId, max_hops: usize) -> Option<Path> {
+    let n = network.num_nodes();
+    let arcs: Vec<(NodeId, NodeId, Cost)> = network.arc_values()
+        .map(|(i, j, cost, _)| (i, j, cost))
+        .collect();
+
+    let mut dist = vec![f64::INFINITY; n];
+    dist[source as usize] = 0.0;
+    // improved_by[h][j]: the tail of the arc that improved `j` in round
+    // `h + 1`, if any
+    let mut improved_by: Vec<Vec<Option<NodeId>>> = Vec::with_capacity(max_hops);
+    for _ in 0..max_hops {
+        let mut next = dist.clone();
+        let mut improved = vec![None; n];
+        for &(i, j, cost) in &arcs {
+            let candidate = dist[i as usize] + cost;
+            if candidate < next[j as usize] {
+                next[j as usize] = candidate;
+                improved[j as usize] = Some(i);
+            }
+        }
+        let changed = improved.iter().any(|i| i.is_some());
+        dist = next;
+        improved_by.push(improved);
+        if !changed {
+            break;
+        }
+    }
+
+    if dist[target as usize].is_infinite() {
+        return None;
+    }
+    let mut nodes = vec![target];
+    let mut current = target;
+    for improved in improved_by.iter().rev() {
+        if let Some(i) = improved[current as usize] {
+            current = i;
+            nodes.push(current);
+        }
+    }
+    nodes.reverse();
+    Some(Path::new(nodes, dist[target as usize]))
+}
+
+#[test]
+fn test_hop_limited_shortest_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 4 directly (10), with two hops via 1 (4 + 4) or with four hops
+    // via 1, 2, 3 (1 each)
+    let mut edges = vec![(0,4,10.0,0.0),
+                         (0,1,1.0,0.0),
+                         (1,4,7.0,0.0),
+                         (1,2,1.0,0.0),
+                         (2,3,1.0,0.0),
+                         (3,4,1.0,0.0),
+                         (0,5,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+
+    let expect = |hops, nodes: Vec<NodeId>, cost| {
+        let path = hop_limited_shortest_path(&compact_star, 0, 4, hops).unwrap();
+        assert_eq!(&nodes, path.nodes());
+        assert_eq!(cost, path.cost());
+    };
+    expect(1, vec![0,4], 10.0);
+    expect(2, vec![0,1,4], 8.0);
+    expect(3, vec![0,1,4], 8.0);
+    expect(4, vec![0,1,2,3,4], 4.0);
+    expect(10, vec![0,1,2,3,4], 4.0);
+    assert_eq!(None, hop_limited_shortest_path(&compact_star, 0, 3, 2));
+    assert_eq!(Some(Path::new(vec![0], 0.0)), hop_limited_shortest_path(&compact_star, 0, 0, 0));
+    assert_eq!(None, hop_limited_shortest_path(&compact_star, 4, 0, 5));
+}
+
+#[test]
+fn test_hop_limited_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,5.0,0.0), (0,1,2.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    assert_eq!(2.0, hop_limited_shortest_path(&compact_star, 0, 1, 1).unwrap().cost());
+}
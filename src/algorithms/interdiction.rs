@@ -0,0 +1,174 @@
+use std::f64;
+
+use super::super::{ Capacity, Cost, DoubleVec, Network, NodeId };
+use super::super::compact_star::compact_star_from_edge_vec;
+use super::cost_model::shortest_path_with_model;
+use super::max_flow::dinic;
+use super::residual::RESIDUAL_EPS;
+
+/// The arcs whose removal hurts a source-target connection the most, see
+/// `most_vital_arcs_path` and `most_vital_arcs_flow`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VitalArcs {
+    /// The shortest path cost or maximum flow value of the intact network.
+    pub base:   f64,
+    /// The removed arcs by number (see `Network::arcs`), in the order of
+    /// removal.
+    pub arcs:   Vec<usize>,
+    /// The shortest path cost or maximum flow value after removing
+    /// `arcs[0]` to `arcs[k]`.
+    pub values: DoubleVec,
+}
+
+/// Finds up to `k` arcs whose removal lengthens the shortest path from
+/// `source` to `target` the most. Only the arcs of the current shortest
+/// path are candidates, since removing any other arc leaves it intact; each
+/// candidate costs one shortest path search.
+///
+/// For `k > 1` the arcs are removed greedily, the most vital one of the
+/// remaining network at a time, since finding the best set of `k` arcs is
+/// NP-hard. Stops early once `target` is cut off, which shows as an
+/// infinite value.
+pub fn most_vital_arcs_path<N: Network>(network: &N, source: NodeId, target: NodeId, k: usize) -> VitalArcs {
+    let arcs = arc_table(network);
+    let first_arcs = network.first_arcs();
+
+    let mut removed = vec![false; arcs.len()];
+    let path_arcs = |removed: &[bool]| -> (f64, Vec<usize>) {
+        let model = |arc: usize, removed: &bool| if *removed { f64::INFINITY } else { arcs[arc].2 };
+        let path = match shortest_path_with_model(network, removed, &model, source, target) {
+            Some(path) => path,
+            None => return (f64::INFINITY, Vec::new()),
+        };
+        // the cheapest open arc between every two consecutive path nodes
+        let on_path = path.nodes().windows(2)
+            .map(|pair| {
+                let (from, to) = (pair[0] as usize, pair[1]);
+                (first_arcs[from]..first_arcs[from + 1])
+                    .filter(|a| arcs[*a].1 == to && !removed[*a])
+                    .min_by(|a, b| arcs[*a].2.partial_cmp(&arcs[*b].2).unwrap())
+                    .unwrap()
+            })
+            .collect();
+        (path.cost(), on_path)
+    };
+
+    let (base, mut on_path) = path_arcs(&removed);
+    let mut vital = VitalArcs {
+        base:   base,
+        arcs:   Vec::new(),
+        values: Vec::new(),
+    };
+    while vital.arcs.len() < k && !on_path.is_empty() {
+        let mut best: Option<(usize, f64, Vec<usize>)> = None;
+        for &a in &on_path {
+            removed[a] = true;
+            let (value, next_path) = path_arcs(&removed);
+            removed[a] = false;
+            if best.as_ref().map_or(true, |b| value > b.1) {
+                best = Some((a, value, next_path));
+            }
+        }
+        let (a, value, next_path) = best.unwrap();
+        removed[a] = true;
+        vital.arcs.push(a);
+        vital.values.push(value);
+        on_path = next_path;
+    }
+    vital
+}
+
+/// Finds up to `k` arcs whose removal decreases the maximum flow from
+/// `source` to `sink` the most. Removing an arc lowers the flow value by at
+/// most the flow it carries, so only arcs with flow are candidates, tried by
+/// decreasing flow until no remaining candidate can beat the best one.
+///
+/// For `k > 1` the arcs are removed greedily, as in `most_vital_arcs_path`.
+/// Stops early once the flow is zero.
+pub fn most_vital_arcs_flow<N: Network>(network: &N, source: NodeId, sink: NodeId, k: usize) -> VitalArcs {
+    let mut arcs = arc_table(network);
+    let n = network.num_nodes();
+    // removed arcs keep their place with zero capacity, so that the arc
+    // numbers stay valid
+    let max_flow = |arcs: &Vec<(NodeId, NodeId, Cost, Capacity)>| {
+        let mut edges = arcs.clone();
+        dinic(&compact_star_from_edge_vec(n, &mut edges), source, sink)
+    };
+
+    let mut flow = max_flow(&arcs);
+    let mut vital = VitalArcs {
+        base:   flow.value,
+        arcs:   Vec::new(),
+        values: Vec::new(),
+    };
+    while vital.arcs.len() < k && flow.value > RESIDUAL_EPS {
+        let mut candidates: Vec<usize> = (0..arcs.len()).filter(|a| flow.flows[*a] > RESIDUAL_EPS).collect();
+        candidates.sort_by(|a, b| flow.flows[*b].partial_cmp(&flow.flows[*a]).unwrap());
+        let mut best = None;
+        let mut best_decrease = 0.0;
+        for a in candidates {
+            if flow.flows[a] <= best_decrease {
+                break;
+            }
+            let capacity = arcs[a].3;
+            arcs[a].3 = 0.0;
+            let reduced = max_flow(&arcs);
+            arcs[a].3 = capacity;
+            if best.is_none() || flow.value - reduced.value > best_decrease {
+                best_decrease = flow.value - reduced.value;
+                best = Some((a, reduced));
+            }
+        }
+        let (a, reduced) = best.unwrap();
+        arcs[a].3 = 0.0;
+        vital.arcs.push(a);
+        vital.values.push(reduced.value);
+        flow = reduced;
+    }
+    vital
+}
+
+/// All arcs as `(from, to, cost, capacity)` in arc order.
+fn arc_table<N: Network>(network: &N) -> Vec<(NodeId, NodeId, Cost, Capacity)> {
+    network.arc_values().collect()
+}
+
+#[test]
+fn test_most_vital_arcs_path() {
+    // 0 -> 1 -> 3 costs 2, the detour 0 -> 2 -> 3 costs 4 and the direct
+    // arc 0 -> 3 costs 10
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,2.0,0.0),
+                         (0,3,10.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,2.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let vital = most_vital_arcs_path(&network, 0, 3, 1);
+    assert_eq!(2.0, vital.base);
+    assert_eq!(vec![0], vital.arcs);
+    assert_eq!(vec![4.0], vital.values);
+
+    let vital = most_vital_arcs_path(&network, 0, 3, 5);
+    assert_eq!(vec![0, 1, 2], vital.arcs);
+    assert_eq!(vec![4.0, 10.0, f64::INFINITY], vital.values);
+}
+
+#[test]
+fn test_most_vital_arcs_flow() {
+    // most of the flow enters through 0 -> 1 and leaves over 1 -> 3 or
+    // the link 1 -> 2
+    let mut edges = vec![(0,1,0.0,6.0),
+                         (0,2,0.0,1.0),
+                         (1,2,0.0,1.0),
+                         (1,3,0.0,3.0),
+                         (2,3,0.0,2.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let vital = most_vital_arcs_flow(&network, 0, 3, 1);
+    assert_eq!(5.0, vital.base);
+    assert_eq!(vec![0], vital.arcs);
+    assert_eq!(vec![1.0], vital.values);
+
+    let vital = most_vital_arcs_flow(&network, 0, 3, 3);
+    assert_eq!(vec![0, 1], vital.arcs);
+    assert_eq!(vec![1.0, 0.0], vital.values);
+}
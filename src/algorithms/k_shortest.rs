@@ -0,0 +1,116 @@
+use std::f64;
+
+use super::super::{ Cost, Network, NodeId, NodeVec };
+use super::adjacency::{ CostAdjacency, adjacency_dijkstra, forward_adjacency };
+use super::path::{ Path, trace_path };
+
+/// Computes up to `k` loopless paths from `source` to `target` in order of
+/// increasing cost, with Yen's algorithm ("Finding the k shortest loopless
+/// paths in a network"). Every further path deviates from one of the paths
+/// found so far at some spur node; the best deviation is found by Dijkstra
+/// from the spur node, with the arcs that would repeat a known path and the
+/// nodes before the spur node blocked.
+///
+/// Paths of equal cost are ordered by the number of nodes, then by the node
+/// sequence. The result is empty if the target can't be reached. Of
+/// parallel arcs, only the cheapest is used.
+pub fn k_shortest_paths<N: Network>(network: &N, source: NodeId, target: NodeId, k: usize) -> Vec<Path> {
+    let adjacency = forward_adjacency(network);
+    let mut paths: Vec<Path> = Vec::with_capacity(k);
+    if k == 0 {
+        return paths;
+    }
+    let (pred, d) = adjacency_dijkstra(&adjacency, source, Some(target));
+    match trace_path(&pred, source, target) {
+        Some(nodes) => paths.push(Path::new(nodes, d[target as usize])),
+        None => return paths,
+    }
+
+    let mut candidates: Vec<Path> = Vec::new();
+    while paths.len() < k {
+        let last = paths.last().unwrap().nodes().clone();
+        for i in 0..last.len() - 1 {
+            let spur = last[i];
+            let root = &last[..i + 1];
+            let mut blocked = adjacency.clone();
+            for path in &paths {
+                let nodes = path.nodes();
+                if nodes.len() > i + 1 && &nodes[..i + 1] == root {
+                    block_arc(&mut blocked, nodes[i], nodes[i + 1]);
+                }
+            }
+            for node in &root[..i] {
+                for arc in blocked[*node as usize].iter_mut() {
+                    arc.1 = f64::INFINITY;
+                }
+            }
+
+            let (pred, d) = adjacency_dijkstra(&blocked, spur, Some(target));
+            if !d[target as usize].is_finite() {
+                continue;
+            }
+            let spur_path = trace_path(&pred, spur, target).unwrap();
+            let mut nodes = root[..i].to_vec();
+            nodes.extend(spur_path);
+            let cost = path_cost(&adjacency, &root.to_vec()) + d[target as usize];
+            let candidate = Path::new(nodes, cost);
+            if !candidates.contains(&candidate) && !paths.iter().any(|p| p.nodes() == candidate.nodes()) {
+                candidates.push(candidate);
+            }
+        }
+        if candidates.is_empty() {
+            break;
+        }
+        let best = (0..candidates.len()).min_by(|a, b| {
+            let (a, b) = (&candidates[*a], &candidates[*b]);
+            a.cost().partial_cmp(&b.cost()).unwrap()
+                .then(a.nodes().len().cmp(&b.nodes().len()))
+                .then(a.nodes().cmp(b.nodes()))
+        }).unwrap();
+        paths.push(candidates.swap_remove(best));
+    }
+    paths
+}
+
+fn block_arc(adjacency: &mut CostAdjacency, from: NodeId, to: NodeId) {
+    for arc in adjacency[from as usize].iter_mut() {
+        if arc.0 == to {
+            arc.1 = f64::INFINITY;
+        }
+    }
+}
+
+/// The cost of walking `nodes`, over the cheapest of parallel arcs.
+fn path_cost(adjacency: &CostAdjacency, nodes: &NodeVec) -> Cost {
+    nodes.windows(2)
+        .map(|arc| adjacency[arc[0] as usize].iter()
+             .filter(|entry| entry.0 == arc[1])
+             .map(|entry| entry.1)
+             .fold(f64::INFINITY, f64::min))
+        .sum()
+}
+
+#[test]
+fn test_k_shortest_paths() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the example of Yen's algorithm on Wikipedia, C=0 D=1 E=2 F=3 G=4 H=5
+    let mut edges = vec![(0,1,3.0,0.0), (0,2,2.0,0.0), (1,3,4.0,0.0), (2,1,1.0,0.0),
+                         (2,3,2.0,0.0), (2,4,3.0,0.0), (3,4,2.0,0.0), (3,5,1.0,0.0),
+                         (4,5,2.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let paths = k_shortest_paths(&network, 0, 5, 3);
+    assert_eq!(3, paths.len());
+    assert_eq!(&vec![0,2,3,5], paths[0].nodes());
+    assert_eq!(5.0, paths[0].cost());
+    assert_eq!(&vec![0,2,4,5], paths[1].nodes());
+    assert_eq!(7.0, paths[1].cost());
+    assert_eq!(&vec![0,1,3,5], paths[2].nodes());
+    assert_eq!(8.0, paths[2].cost());
+
+    // there are only 7 loopless paths
+    let all = k_shortest_paths(&network, 0, 5, 100);
+    assert_eq!(7, all.len());
+    assert!(all.windows(2).all(|w| w[0].cost() <= w[1].cost()));
+    assert!(k_shortest_paths(&network, 5, 0, 3).is_empty());
+    assert!(k_shortest_paths(&network, 0, 5, 0).is_empty());
+}
@@ -0,0 +1,91 @@
+use super::super::{ Cost, NodeId };
+use super::super::heaps::{ BinaryHeap, Heap };
+
+/// A partial path in a resource constrained label search: arrival at
+/// `node` at `time` having used `resource`, with `data` of the caller.
+pub(crate) struct Label<T> {
+    pub node:     NodeId,
+    pub time:     Cost,
+    pub resource: f64,
+    pub data:     T,
+    pub pred:     Option<usize>,
+    dominated:    bool,
+}
+
+/// Label setting search over `(time, resource)` labels, shared by the
+/// budget constrained and the electric vehicle routing. Less of both is
+/// better: every node keeps the labels not dominated by one that is no
+/// later and uses no more resource. The caller extends the settled labels
+/// and decides the key by which they are settled.
+pub(crate) struct LabelSearch<T> {
+    labels:  Vec<Label<T>>,
+    at_node: Vec<Vec<usize>>,
+    // the heap holds label indices
+    heap:    BinaryHeap,
+}
+
+impl<T> LabelSearch<T> {
+    pub fn new(num_nodes: usize) -> LabelSearch<T> {
+        LabelSearch {
+            labels:  Vec::new(),
+            at_node: vec![Vec::new(); num_nodes],
+            heap:    BinaryHeap::new(),
+        }
+    }
+
+    /// Adds a label to be settled by `key` unless one at the same node
+    /// dominates it, and drops the labels it dominates.
+    pub fn add(&mut self, node: NodeId, time: Cost, resource: f64, data: T, pred: Option<usize>, key: Cost) {
+        let labels = &mut self.labels;
+        let existing = &mut self.at_node[node as usize];
+        if existing.iter().any(|&l| labels[l].time <= time && labels[l].resource <= resource) {
+            return;
+        }
+        existing.retain(|&l| {
+            if labels[l].time >= time && labels[l].resource >= resource {
+                labels[l].dominated = true;
+                false
+            } else {
+                true
+            }
+        });
+        existing.push(labels.len());
+        self.heap.insert(labels.len() as NodeId, key);
+        labels.push(Label {
+            node:      node,
+            time:      time,
+            resource:  resource,
+            data:      data,
+            pred:      pred,
+            dominated: false,
+        });
+    }
+
+    /// Removes the label with the least key that is not dominated and
+    /// returns its index.
+    pub fn settle(&mut self) -> Option<usize> {
+        while let Some(next) = self.heap.find_min() {
+            self.heap.delete_min();
+            if !self.labels[next as usize].dominated {
+                return Some(next as usize);
+            }
+        }
+        None
+    }
+
+    pub fn label(&self, l: usize) -> &Label<T> {
+        &self.labels[l]
+    }
+
+    /// The labels from the first one to `last`.
+    pub fn trace(&self, last: usize) -> Vec<&Label<T>> {
+        let mut trace = Vec::new();
+        let mut current = Some(last);
+        while let Some(l) = current {
+            trace.push(&self.labels[l]);
+            current = self.labels[l].pred;
+        }
+        trace.reverse();
+        trace
+    }
+}
@@ -0,0 +1,149 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use super::super::{ Cost, Network, NodeId };
+use super::path::{ Path, trace_path };
+
+/// Weights used to break ties between paths of equal cost, one per arc in
+/// arc order, e.g. all `1.0` to prefer fewer hops. Parallel arcs can have
+/// different weights.
+pub type TieBreaker<'a> = &'a [Cost];
+
+/// A path with the sums of its tie breaking weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicographicPath {
+    pub path:    Path,
+    /// The sum of each tie breaking weight along the path, in the order of
+    /// the tie breakers.
+    pub weights: Vec<Cost>,
+}
+
+/// Heap entry, ordered such that the max heap pops the lexicographically
+/// smallest label first.
+struct Label {
+    values: Vec<Cost>,
+    node:   NodeId,
+}
+
+impl PartialEq for Label {
+    fn eq(&self, other: &Label) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Label {}
+
+impl PartialOrd for Label {
+    fn partial_cmp(&self, other: &Label) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Label {
+    fn cmp(&self, other: &Label) -> Ordering {
+        compare(&other.values, &self.values)
+    }
+}
+
+fn compare(a: &[Cost], b: &[Cost]) -> Ordering {
+    for (x, y) in a.iter().zip(b) {
+        match x.partial_cmp(y).unwrap_or(Ordering::Equal) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+    Ordering::Equal
+}
+
+/// Hierarchical shortest path: among all paths from `source` to `target` of
+/// minimum cost, finds one that minimizes the sum of the first tie breaking
+/// weight, among those one that minimizes the second, and so on.
+///
+/// Runs Dijkstra's algorithm on label vectors `(cost, w_1, ..., w_k)`
+/// compared lexicographically, which is correct as long as all arc costs
+/// and weights are non-negative. Costs are compared exactly, so ties are
+/// only recognized reliably for costs without rounding errors, e.g. integral
+/// ones. Returns `None` if `target` can't be reached.
+///
+/// # Panics
+/// If a tie breaker doesn't have one weight per arc.
+pub fn lexicographic_shortest_path<N: Network>(network: &N, source: NodeId, target: NodeId,
+                                                tie_breakers: &[TieBreaker]) -> Option<LexicographicPath> {
+    assert!(tie_breakers.iter().all(|weights| weights.len() == network.num_arcs()), "one weight per arc is required");
+    let n = network.num_nodes();
+    let first_arcs = network.first_arcs();
+    let mut labels: Vec<Option<Vec<Cost>>> = vec![None; n];
+    let mut pred = vec![network.invalid_id(); n];
+    let mut marked = vec![false; n];
+    let mut heap = BinaryHeap::new();
+
+    let initial = vec![0.0; tie_breakers.len() + 1];
+    labels[source as usize] = Some(initial.clone());
+    heap.push(Label { values: initial, node: source });
+
+    while let Some(Label { values, node }) = heap.pop() {
+        let i = node as usize;
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if node == target {
+            let nodes = trace_path(&pred, source, target)?;
+            return Some(LexicographicPath {
+                path:    Path::new(nodes, values[0]),
+                weights: values[1..].to_vec(),
+            });
+        }
+
+        for (a, (adjacent_node, cost, _)) in network.out_arcs(node).enumerate() {
+            let j = adjacent_node as usize;
+            let arc = first_arcs[i] + a;
+            let mut candidate = Vec::with_capacity(values.len());
+            candidate.push(values[0] + cost);
+            for (k, weights) in tie_breakers.iter().enumerate() {
+                candidate.push(values[k + 1] + weights[arc]);
+            }
+            let improves = match labels[j] {
+                Some(ref current) => compare(&candidate, current) == Ordering::Less,
+                None => true,
+            };
+            if improves && !marked[j] {
+                pred[j] = node;
+                labels[j] = Some(candidate.clone());
+                heap.push(Label { values: candidate, node: adjacent_node });
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_lexicographic_shortest_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 3 directly (cost 4) or via 1 (2 + 2) or via 1 and 2 (2 + 1 + 1)
+    let mut edges = vec![(0,3,4.0,0.0),
+                         (0,1,2.0,0.0),
+                         (1,3,2.0,0.0),
+                         (1,2,1.0,0.0),
+                         (2,3,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+
+    let fewest_hops = lexicographic_shortest_path(&compact_star, 0, 3, &[&[1.0; 5]]).unwrap();
+    assert_eq!(&vec![0,3], fewest_hops.path.nodes());
+    assert_eq!(4.0, fewest_hops.path.cost());
+    assert_eq!(vec![1.0], fewest_hops.weights);
+
+    // prefer arcs touching node 1, then fewest hops
+    let via_one = lexicographic_shortest_path(&compact_star, 0, 3, &[&[1.0, 0.0, 0.0, 0.0, 1.0],
+                                                                     &[1.0; 5]]).unwrap();
+    assert_eq!(&vec![0,1,3], via_one.path.nodes());
+    assert_eq!(vec![0.0, 2.0], via_one.weights);
+
+    assert_eq!(None, lexicographic_shortest_path(&compact_star, 3, 0, &[]));
+    assert_eq!(Some(4.0), lexicographic_shortest_path(&compact_star, 0, 3, &[]).map(|p| p.path.cost()));
+
+    // parallel arcs of equal cost are told apart by their weights
+    let mut edges = vec![(0,1,1.0,0.0), (0,1,1.0,0.0)];
+    let parallel = compact_star_from_edge_vec(2, &mut edges);
+    assert_eq!(vec![2.0], lexicographic_shortest_path(&parallel, 0, 1, &[&[3.0, 2.0]]).unwrap().weights);
+}
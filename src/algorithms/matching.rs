@@ -0,0 +1,349 @@
+use std::collections::VecDeque;
+use std::f64;
+
+use super::super::{ Direction, DoubleVec, Network, NodeId, NodeVec };
+use super::adjacency::undirected_adjacency;
+
+/// A matching: pairs of nodes joined by an arc, every node in at most one
+/// pair.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matching {
+    /// The node every node is matched with, or the network's invalid id.
+    pub mates: NodeVec,
+    /// The number of pairs.
+    pub size:  usize,
+}
+
+impl Matching {
+    /// The pairs `(i, mate)` with `i` on the `left` side, by ascending `i`.
+    pub fn pairs(&self, left: &[bool]) -> Vec<(NodeId, NodeId)> {
+        let n = self.mates.len();
+        (0..n).filter(|i| left[*i] && (self.mates[*i] as usize) < n).map(|i| (i as NodeId, self.mates[i])).collect()
+    }
+}
+
+/// Result of `min_cost_assignment`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCostAssignment {
+    pub matching: Matching,
+    /// The total cost of the arcs between the pairs.
+    pub cost:     f64,
+}
+
+/// Splits the nodes into two sides such that every arc, in either
+/// direction, joins the two sides: `true` for the left side, which holds the
+/// lowest node of every weakly connected component. `None` if there is no
+/// such split, i.e. the network has a cycle of odd length when arc
+/// directions are ignored.
+pub fn bipartition<N: Network>(network: &N) -> Option<Vec<bool>> {
+    let n = network.num_nodes();
+    let mut side: Vec<Option<bool>> = vec![None; n];
+    let mut queue = VecDeque::new();
+    for start in network.nodes() {
+        if side[start as usize].is_some() {
+            continue;
+        }
+        side[start as usize] = Some(true);
+        queue.push_back(start);
+        while let Some(i) = queue.pop_front() {
+            let other = !side[i as usize].unwrap();
+            for (j, _, _) in network.followed_arcs(i, Direction::Both) {
+                match side[j as usize] {
+                    None => {
+                        side[j as usize] = Some(other);
+                        queue.push_back(j);
+                    },
+                    Some(s) if s != other => return None,
+                    Some(_) => {},
+                }
+            }
+        }
+    }
+    Some(side.into_iter().map(|s| s.unwrap()).collect())
+}
+
+/// A maximum cardinality matching between the `left` nodes and the others
+/// (Hopcroft, Karp: "An n^5/2 algorithm for maximum matchings in bipartite
+/// graphs"). Arcs count in either direction, arcs within a side are
+/// ignored.
+///
+/// Every phase finds a maximal set of disjoint shortest augmenting paths by
+/// a breadth first search from all free left nodes and depth first searches
+/// along its layers. There are `O(sqrt(n))` phases, so the matching takes
+/// `O(m sqrt(n))` time.
+/// # Panics
+/// If there isn't one side per node.
+pub fn hopcroft_karp<N: Network>(network: &N, left: &[bool]) -> Matching {
+    let n = network.num_nodes();
+    assert_eq!(n, left.len(), "one side per node is required");
+    let invalid = network.invalid_id();
+    let neighbors: Vec<NodeVec> = undirected_adjacency(network).into_iter().enumerate()
+        .map(|(i, adjacent)| if left[i] { adjacent.into_iter().filter(|j| !left[*j as usize]).collect() } else { Vec::new() })
+        .collect();
+
+    let mut mates = vec![invalid; n];
+    let mut size = 0;
+    let mut layer = vec![usize::max_value(); n];
+    loop {
+        // layers of the left nodes, starting with the free ones
+        let mut queue = VecDeque::new();
+        for i in 0..n {
+            layer[i] = usize::max_value();
+            if left[i] && mates[i] == invalid {
+                layer[i] = 0;
+                queue.push_back(i);
+            }
+        }
+        let mut found = false;
+        while let Some(i) = queue.pop_front() {
+            for j in &neighbors[i] {
+                let mate = mates[*j as usize];
+                if mate == invalid {
+                    found = true;
+                } else if layer[mate as usize] == usize::max_value() {
+                    layer[mate as usize] = layer[i] + 1;
+                    queue.push_back(mate as usize);
+                }
+            }
+        }
+        if !found {
+            break;
+        }
+        for i in 0..n {
+            if left[i] && mates[i] == invalid && augment(i, &neighbors, &mut mates, &mut layer, invalid) {
+                size += 1;
+            }
+        }
+    }
+    Matching {
+        mates: mates,
+        size:  size,
+    }
+}
+
+/// Searches an augmenting path from the left node `root` along the layers
+/// by an iterative depth first search and flips it. Left nodes without a
+/// path are taken out of the layers.
+fn augment(root: usize, neighbors: &[NodeVec], mates: &mut NodeVec, layer: &mut Vec<usize>, invalid: NodeId) -> bool {
+    // the left nodes of the path so far, each with the number of its
+    // neighbors tried
+    let mut path = vec![(root, 0)];
+    while let Some(&(i, tried)) = path.last() {
+        if tried == neighbors[i].len() {
+            layer[i] = usize::max_value();
+            path.pop();
+            continue;
+        }
+        path.last_mut().unwrap().1 += 1;
+        let mate = mates[neighbors[i][tried] as usize];
+        if mate == invalid {
+            // every left node on the path takes the neighbor it tried last
+            for &(i, tried) in &path {
+                let j = neighbors[i][tried - 1];
+                mates[i] = j;
+                mates[j as usize] = i as NodeId;
+            }
+            return true;
+        }
+        if layer[mate as usize] == layer[i] + 1 {
+            path.push((mate as usize, 0));
+        }
+    }
+    false
+}
+
+/// Assigns every `left` node to a distinct other node, minimizing the total
+/// cost of the arcs between them; parallel arcs and arcs in either direction
+/// count with their cheapest cost. `None` if not every left node can be
+/// assigned.
+///
+/// Solves the assignment with `hungarian` on a dense matrix of the left
+/// nodes by the others, which takes quadratic memory and cubic time in the
+/// number of nodes even for sparse networks. It suits networks of up to a
+/// few thousand nodes; `hopcroft_karp` handles large unweighted ones.
+/// # Panics
+/// If there isn't one side per node.
+pub fn min_cost_assignment<N: Network>(network: &N, left: &[bool]) -> Option<MinCostAssignment> {
+    let n = network.num_nodes();
+    assert_eq!(n, left.len(), "one side per node is required");
+    let rows: NodeVec = network.nodes().filter(|i| left[*i as usize]).collect();
+    let columns: NodeVec = network.nodes().filter(|i| !left[*i as usize]).collect();
+    if rows.len() > columns.len() {
+        return None;
+    }
+    let mut column_of = vec![usize::max_value(); n];
+    for (c, j) in columns.iter().enumerate() {
+        column_of[*j as usize] = c;
+    }
+    let mut costs = vec![vec![f64::INFINITY; columns.len()]; rows.len()];
+    for (r, i) in rows.iter().enumerate() {
+        for (j, cost, _) in network.followed_arcs(*i, Direction::Both) {
+            let c = column_of[j as usize];
+            if c != usize::max_value() {
+                costs[r][c] = costs[r][c].min(cost);
+            }
+        }
+    }
+    // missing pairs cost more than any assignment of existing arcs
+    let present: Vec<Vec<bool>> = costs.iter().map(|row| row.iter().map(|c| c.is_finite()).collect()).collect();
+    let penalty = 1.0 + costs.iter().flat_map(|row| row.iter()).filter(|c| c.is_finite()).map(|c| c.abs()).sum::<f64>();
+    for cost in costs.iter_mut().flat_map(|row| row.iter_mut()) {
+        if !cost.is_finite() {
+            *cost = penalty;
+        }
+    }
+
+    let assigned = hungarian(&costs);
+    let mut mates = vec![network.invalid_id(); n];
+    let mut total = 0.0;
+    for (r, c) in assigned.iter().enumerate() {
+        if !present[r][*c] {
+            return None;
+        }
+        total += costs[r][*c];
+        mates[rows[r] as usize] = columns[*c];
+        mates[columns[*c] as usize] = rows[r];
+    }
+    Some(MinCostAssignment {
+        matching: Matching { mates: mates, size: rows.len() },
+        cost:     total,
+    })
+}
+
+/// The Hungarian method for the assignment problem with at most as many
+/// rows as columns: the column of every row, distinct and of minimum total
+/// cost (Kuhn: "The Hungarian method for the assignment problem"). Adds the
+/// rows one at a time, each by a shortest augmenting path with reduced
+/// costs, in `O(n^2 m)` time for `n` rows and `m` columns.
+/// # Panics
+/// If there are more rows than columns or the rows differ in length.
+pub fn hungarian(costs: &[DoubleVec]) -> Vec<usize> {
+    let rows = costs.len();
+    if rows == 0 {
+        return Vec::new();
+    }
+    let columns = costs[0].len();
+    assert!(rows <= columns && costs.iter().all(|row| row.len() == columns), "expected at most as many rows as columns");
+    // potentials of rows and columns, column 0 is a virtual start
+    let mut u = vec![0.0; rows + 1];
+    let mut v = vec![0.0; columns + 1];
+    // the row assigned to every column, 0 for none
+    let mut row_of = vec![0; columns + 1];
+    let mut way = vec![0; columns + 1];
+    for r in 1..rows + 1 {
+        row_of[0] = r;
+        let mut c0 = 0;
+        let mut min_to = vec![f64::INFINITY; columns + 1];
+        let mut used = vec![false; columns + 1];
+        loop {
+            used[c0] = true;
+            let r0 = row_of[c0];
+            let mut delta = f64::INFINITY;
+            let mut c1 = 0;
+            for c in 1..columns + 1 {
+                if used[c] {
+                    continue;
+                }
+                let reduced = costs[r0 - 1][c - 1] - u[r0] - v[c];
+                if reduced < min_to[c] {
+                    min_to[c] = reduced;
+                    way[c] = c0;
+                }
+                if min_to[c] < delta {
+                    delta = min_to[c];
+                    c1 = c;
+                }
+            }
+            for c in 0..columns + 1 {
+                if used[c] {
+                    u[row_of[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min_to[c] -= delta;
+                }
+            }
+            c0 = c1;
+            if row_of[c0] == 0 {
+                break;
+            }
+        }
+        // flip the augmenting path back to the start
+        while c0 != 0 {
+            let c1 = way[c0];
+            row_of[c0] = row_of[c1];
+            c0 = c1;
+        }
+    }
+    let mut column_of = vec![0; rows];
+    for c in 1..columns + 1 {
+        if row_of[c] != 0 {
+            column_of[row_of[c] - 1] = c - 1;
+        }
+    }
+    column_of
+}
+
+#[test]
+fn test_bipartition() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the path 0 - 1 - 2 with mixed directions and the arc 4 -> 3
+    let mut edges = vec![(0,1,0.0,0.0), (2,1,0.0,0.0), (4,3,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    assert_eq!(Some(vec![true, false, true, true, false]), bipartition(&network));
+    let mut triangle = vec![(0,1,0.0,0.0), (1,2,0.0,0.0), (0,2,0.0,0.0)];
+    assert_eq!(None, bipartition(&compact_star_from_edge_vec(3, &mut triangle)));
+}
+
+#[test]
+fn test_hopcroft_karp() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // workers 0, 1, 2 and jobs 3, 4, 5: 0 can do 3 and 4, 1 only 3, 2 only 4
+    let mut edges = vec![(0,3,0.0,0.0), (0,4,0.0,0.0), (1,3,0.0,0.0), (2,4,0.0,0.0), (5,2,0.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let left = bipartition(&network).unwrap();
+    let matching = hopcroft_karp(&network, &left);
+    assert_eq!(3, matching.size);
+    assert_eq!(vec![(0, 4), (1, 3), (2, 5)], matching.pairs(&left));
+
+    // 0 and 1 compete for 3
+    let mut edges = vec![(0,3,0.0,0.0), (1,3,0.0,0.0), (2,4,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let left = vec![true, true, true, false, false];
+    let matching = hopcroft_karp(&network, &left);
+    assert_eq!(2, matching.size);
+    assert_eq!(network.invalid_id(), matching.mates[1]);
+
+    // left nodes i = 0..=k prefer the right node k + 1 + i of their two
+    // neighbors, so the last one needs an augmenting path through all others
+    let k = 100000;
+    let right = |i: usize| (2 * k + 1 - i) as NodeId;
+    let mut edges = vec![(k as NodeId, right(k), 0.0, 0.0)];
+    for i in 0..k {
+        edges.push((i as NodeId, right(i), 0.0, 0.0));
+        edges.push((i as NodeId, right(i + 1), 0.0, 0.0));
+    }
+    let network = compact_star_from_edge_vec(2 * k + 2, &mut edges);
+    let left: Vec<bool> = (0..2 * k + 2).map(|i| i <= k).collect();
+    let matching = hopcroft_karp(&network, &left);
+    assert_eq!(k + 1, matching.size);
+    assert!((0..k + 1).all(|i| matching.mates[i] == right(i)));
+}
+
+#[test]
+fn test_min_cost_assignment() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the greedy choice 0 -> 3 forces 1 -> 4 for a total of 1 + 10
+    let mut edges = vec![(0,3,1.0,0.0), (0,4,2.0,0.0), (1,3,2.0,0.0), (1,4,10.0,0.0), (2,5,4.0,0.0), (2,3,7.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let left = vec![true, true, true, false, false, false];
+    let assignment = min_cost_assignment(&network, &left).unwrap();
+    assert_eq!(8.0, assignment.cost);
+    assert_eq!(vec![(0, 4), (1, 3), (2, 5)], assignment.matching.pairs(&left));
+
+    // 0 and 1 can only be assigned to 3
+    let mut edges = vec![(0,3,1.0,0.0), (1,3,1.0,0.0), (2,4,1.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    assert_eq!(None, min_cost_assignment(&network, &[true, true, false, false, false]));
+
+    assert_eq!(vec![1, 0, 2], hungarian(&[vec![4.0, 1.0, 3.0, 9.0], vec![2.0, 5.0, 8.0, 9.0], vec![3.0, 2.0, 2.0, 9.0]]));
+}
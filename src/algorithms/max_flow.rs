@@ -0,0 +1,200 @@
+use std::collections::VecDeque;
+use std::f64;
+
+use super::super::{ DoubleVec, Network, NodeId };
+use super::residual::ResidualNetwork;
+
+/// A maximum flow from `source` to `sink`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaxFlow {
+    pub value:       f64,
+    /// The flow on every arc, in arc order.
+    pub flows:       DoubleVec,
+    /// The nodes on the source side of a minimum cut: those still
+    /// reachable from the source in the residual network.
+    pub source_side: Vec<bool>,
+}
+
+impl MaxFlow {
+    fn from_residual(residual: &ResidualNetwork, source: NodeId, value: f64) -> MaxFlow {
+        MaxFlow {
+            value:       value,
+            flows:       residual.flows(),
+            source_side: residual.reachable(source),
+        }
+    }
+}
+
+/// Edmonds-Karp maximum flow: augments along shortest paths (by number of
+/// arcs) in the residual network, found by breadth first search, which
+/// bounds the number of augmentations by `O(nm)`. Runs in `O(nm^2)`.
+pub fn edmonds_karp<N: Network>(network: &N, source: NodeId, sink: NodeId) -> MaxFlow {
+    let mut residual = ResidualNetwork::new(network);
+    let n = residual.num_nodes();
+    let mut value = 0.0;
+    if source == sink {
+        return MaxFlow::from_residual(&residual, source, value);
+    }
+    loop {
+        // the residual arc each node was reached by
+        let mut pred_arc = vec![None; n];
+        let mut queue = VecDeque::new();
+        queue.push_back(source);
+        while let Some(i) = queue.pop_front() {
+            if i == sink {
+                break;
+            }
+            for arc in residual.out_arcs(i) {
+                let j = residual.head(*arc);
+                if residual.is_open(*arc) && j != source && pred_arc[j as usize].is_none() {
+                    pred_arc[j as usize] = Some(*arc);
+                    queue.push_back(j);
+                }
+            }
+        }
+        if pred_arc[sink as usize].is_none() {
+            break;
+        }
+
+        let mut path = Vec::new();
+        let mut node = sink;
+        while let Some(arc) = pred_arc[node as usize] {
+            path.push(arc);
+            node = residual.tail(arc);
+        }
+        let bottleneck = path.iter().map(|a| residual.residual_capacity(*a)).fold(f64::INFINITY, f64::min);
+        for arc in path {
+            residual.push(arc, bottleneck);
+        }
+        value += bottleneck;
+    }
+    MaxFlow::from_residual(&residual, source, value)
+}
+
+/// Dinic's maximum flow: repeatedly builds the level graph of the residual
+/// network by breadth first search and saturates it with a blocking flow,
+/// found by depth first search that never retries a dead arc. Runs in
+/// `O(n^2 m)` and is usually much faster than Edmonds-Karp.
+pub fn dinic<N: Network>(network: &N, source: NodeId, sink: NodeId) -> MaxFlow {
+    let mut residual = ResidualNetwork::new(network);
+    let n = residual.num_nodes();
+    let mut value = 0.0;
+    if source == sink {
+        return MaxFlow::from_residual(&residual, source, value);
+    }
+    loop {
+        let mut level = vec![n; n];
+        level[source as usize] = 0;
+        let mut queue = VecDeque::new();
+        queue.push_back(source);
+        while let Some(i) = queue.pop_front() {
+            for arc in residual.out_arcs(i) {
+                let j = residual.head(*arc) as usize;
+                if residual.is_open(*arc) && level[j] == n {
+                    level[j] = level[i as usize] + 1;
+                    queue.push_back(j as NodeId);
+                }
+            }
+        }
+        if level[sink as usize] == n {
+            break;
+        }
+
+        // blocking flow; current[i] is the next arc of node i to try
+        let mut current = vec![0; n];
+        let mut path: Vec<usize> = Vec::new();
+        loop {
+            let node = path.last().map_or(source, |arc| residual.head(*arc));
+            if node == sink {
+                let bottleneck = path.iter().map(|a| residual.residual_capacity(*a)).fold(f64::INFINITY, f64::min);
+                for arc in &path {
+                    residual.push(*arc, bottleneck);
+                }
+                value += bottleneck;
+                // retreat to the tail of the first saturated arc
+                let saturated = path.iter().position(|a| !residual.is_open(*a)).unwrap();
+                path.truncate(saturated);
+                continue;
+            }
+            let i = node as usize;
+            let arcs = residual.out_arcs(node);
+            while current[i] < arcs.len() {
+                let arc = arcs[current[i]];
+                if residual.is_open(arc) && level[residual.head(arc) as usize] == level[i] + 1 {
+                    break;
+                }
+                current[i] += 1;
+            }
+            if current[i] < arcs.len() {
+                path.push(arcs[current[i]]);
+            } else {
+                // dead end, never enter this node again
+                match path.pop() {
+                    Some(arc) => current[residual.tail(arc) as usize] += 1,
+                    None => break,
+                }
+            }
+        }
+    }
+    MaxFlow::from_residual(&residual, source, value)
+}
+
+#[cfg(test)]
+fn flow_network() -> super::super::compact_star::CompactStar {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // Cormen et al., figure 26.1, maximum flow 23
+    let mut edges = vec![(0,1,0.0,16.0), (0,2,0.0,13.0), (1,3,0.0,12.0), (2,1,0.0,4.0),
+                         (2,4,0.0,14.0), (3,2,0.0,9.0), (3,5,0.0,20.0), (4,3,0.0,7.0),
+                         (4,5,0.0,4.0)];
+    compact_star_from_edge_vec(6, &mut edges)
+}
+
+#[cfg(test)]
+fn check_flow<N: Network>(network: &N, flow: &MaxFlow, source: NodeId, sink: NodeId) {
+    let mut balance = vec![0.0; network.num_nodes()];
+    for ((i, j, _, capacity), f) in network.arc_values().zip(&flow.flows) {
+        assert!(*f >= 0.0 && *f <= capacity);
+        balance[i as usize] -= *f;
+        balance[j as usize] += *f;
+    }
+    for i in network.nodes() {
+        let expected = if i == source { -flow.value } else if i == sink { flow.value } else { 0.0 };
+        assert!((balance[i as usize] - expected).abs() < 1e-9);
+    }
+    let cut: f64 = network.arc_values()
+        .filter(|&(i, j, _, _)| flow.source_side[i as usize] && !flow.source_side[j as usize])
+        .map(|(_, _, _, capacity)| capacity)
+        .sum();
+    assert_eq!(flow.value, cut);
+}
+
+#[test]
+fn test_edmonds_karp() {
+    let network = flow_network();
+    let flow = edmonds_karp(&network, 0, 5);
+    assert_eq!(23.0, flow.value);
+    check_flow(&network, &flow, 0, 5);
+    assert_eq!(vec![true, true, true, false, true, false], flow.source_side);
+    assert_eq!(0.0, edmonds_karp(&network, 5, 0).value);
+}
+
+#[test]
+fn test_dinic() {
+    let network = flow_network();
+    let flow = dinic(&network, 0, 5);
+    assert_eq!(23.0, flow.value);
+    check_flow(&network, &flow, 0, 5);
+    assert_eq!(edmonds_karp(&network, 1, 4).value, dinic(&network, 1, 4).value);
+    assert_eq!(0.0, dinic(&network, 5, 0).value);
+}
+
+#[test]
+fn test_max_flow_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,0.0,5.0), (0,1,0.0,1.0), (1,2,0.0,100.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    for flow in vec![edmonds_karp(&network, 0, 2), dinic(&network, 0, 2)] {
+        assert_eq!(6.0, flow.value);
+        check_flow(&network, &flow, 0, 2);
+    }
+}
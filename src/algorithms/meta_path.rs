@@ -0,0 +1,105 @@
+use super::super::{ DoubleVec, Network, NodeId };
+use super::typed::{ TypeTag, TypeTags };
+
+/// The number of instances of the meta path `meta_path`, a sequence of
+/// node types like author - paper - author, from `source` to every node.
+/// An instance is a walk along the arcs whose `k`-th node has the type
+/// `meta_path[k]`; for undirected relations, give every edge as two arcs.
+/// All counts are zero if `source` is not of type `meta_path[0]`.
+///
+/// The counts are propagated one step of the meta path at a time, which
+/// takes `O(m)` time per step.
+pub fn meta_path_counts<N: Network>(network: &N, tags: &TypeTags, meta_path: &[TypeTag], source: NodeId) -> DoubleVec {
+    let n = network.num_nodes();
+    let mut counts = vec![0.0; n];
+    if meta_path.is_empty() || tags.node_type(source) != meta_path[0] {
+        return counts;
+    }
+    counts[source as usize] = 1.0;
+    let mut frontier = vec![source];
+    for node_type in &meta_path[1..] {
+        let mut next = vec![0.0; n];
+        let mut next_frontier = Vec::new();
+        for i in frontier {
+            for (j, _, _) in network.out_arcs(i) {
+                if tags.node_type(j) != *node_type {
+                    continue;
+                }
+                if next[j as usize] == 0.0 {
+                    next_frontier.push(j);
+                }
+                next[j as usize] += counts[i as usize];
+            }
+        }
+        counts = next;
+        frontier = next_frontier;
+    }
+    counts
+}
+
+/// PathSim similarity of `x` and `y` under the symmetric meta path
+/// `meta_path` (Sun et al.: "PathSim: Meta path-based top-k similarity
+/// search in heterogeneous information networks"): twice the number of
+/// meta path instances between them, divided by the number of instances
+/// from each of them back to itself. It is `1.0` for nodes with identical
+/// connections and `0.0` if they are not connected by the meta path.
+/// # Panics
+/// If `meta_path` is not symmetric, i.e. not the same read backwards.
+pub fn path_sim<N: Network>(network: &N, tags: &TypeTags, meta_path: &[TypeTag], x: NodeId, y: NodeId) -> f64 {
+    assert_symmetric(meta_path);
+    let from_x = meta_path_counts(network, tags, meta_path, x);
+    let from_y = meta_path_counts(network, tags, meta_path, y);
+    similarity(from_x[y as usize], from_x[x as usize], from_y[y as usize])
+}
+
+/// The `k` nodes most similar to `x` by `path_sim`, by descending
+/// similarity, ties broken by id. `x` itself is left out.
+/// # Panics
+/// If `meta_path` is not symmetric.
+pub fn top_path_sim<N: Network>(network: &N, tags: &TypeTags, meta_path: &[TypeTag], x: NodeId,
+                                k: usize) -> Vec<(NodeId, f64)> {
+    assert_symmetric(meta_path);
+    let from_x = meta_path_counts(network, tags, meta_path, x);
+    let mut similar: Vec<(NodeId, f64)> = network.nodes()
+        .filter(|y| *y != x && from_x[*y as usize] > 0.0)
+        .map(|y| {
+            let from_y = meta_path_counts(network, tags, meta_path, y);
+            (y, similarity(from_x[y as usize], from_x[x as usize], from_y[y as usize]))
+        })
+        .collect();
+    similar.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+    similar.truncate(k);
+    similar
+}
+
+fn similarity(between: f64, x_to_x: f64, y_to_y: f64) -> f64 {
+    if x_to_x + y_to_y == 0.0 { 0.0 } else { 2.0 * between / (x_to_x + y_to_y) }
+}
+
+fn assert_symmetric(meta_path: &[TypeTag]) {
+    assert!(meta_path.iter().eq(meta_path.iter().rev()), "PathSim needs a symmetric meta path");
+}
+
+#[test]
+fn test_meta_paths() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    const AUTHOR: TypeTag = 0;
+    const PAPER: TypeTag = 1;
+    // authors 0, 1, 2 and papers 3, 4: 0 wrote 3 and 4, 1 wrote 3 and 4,
+    // 2 wrote 4
+    let mut edges = Vec::new();
+    for &(author, paper) in &[(0, 3), (0, 4), (1, 3), (1, 4), (2, 4)] {
+        edges.push((author, paper, 1.0, 0.0));
+        edges.push((paper, author, 1.0, 0.0));
+    }
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let tags = TypeTags::new(&network, vec![AUTHOR, AUTHOR, AUTHOR, PAPER, PAPER], vec![0; 10]);
+    let apa = [AUTHOR, PAPER, AUTHOR];
+
+    assert_eq!(vec![2.0, 2.0, 1.0, 0.0, 0.0], meta_path_counts(&network, &tags, &apa, 0));
+    assert_eq!(vec![0.0; 5], meta_path_counts(&network, &tags, &apa, 3));
+    assert_eq!(1.0, path_sim(&network, &tags, &apa, 0, 1));
+    assert_eq!(2.0 / 3.0, path_sim(&network, &tags, &apa, 0, 2));
+    assert_eq!(vec![(1, 1.0), (2, 2.0 / 3.0)], top_path_sim(&network, &tags, &apa, 0, 5));
+    assert_eq!(vec![(1, 1.0)], top_path_sim(&network, &tags, &apa, 0, 1));
+}
@@ -0,0 +1,170 @@
+use std::error::Error;
+use std::f64;
+use std::fmt;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::residual::{ RESIDUAL_EPS, ResidualNetwork };
+
+/// A minimum cost flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCostFlow {
+    /// The flow on every arc, in arc order.
+    pub flows: DoubleVec,
+    pub cost:  Cost,
+}
+
+/// Reasons why `min_cost_flow` finds no flow.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MinCostFlowError {
+    /// The supplies don't add up to zero.
+    Unbalanced { total: f64 },
+    /// The capacities don't allow to ship all supplies to the demands.
+    Infeasible { unshipped: f64 },
+}
+
+impl fmt::Display for MinCostFlowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MinCostFlowError::Unbalanced { total } => write!(f, "supplies and demands differ by {}", total),
+            MinCostFlowError::Infeasible { unshipped } => write!(f, "{} units of supply can't reach any demand", unshipped),
+        }
+    }
+}
+
+impl Error for MinCostFlowError {}
+
+/// Minimum cost flow by successive shortest paths (Ahuja, Magnanti, Orlin:
+/// "Network Flows", section 9.7). `supplies[i]` is the supply of node `i`,
+/// negative for a demand; all supplies must add up to zero. Arc costs and
+/// capacities are taken from the network.
+///
+/// Ships flow from supply to demand nodes along shortest paths in the
+/// residual network, with node potentials keeping the reduced costs
+/// non-negative for Dijkstra. Arcs of negative cost are saturated up front,
+/// so they need a finite capacity. Every augmentation satisfies at least one
+/// supply, demand or arc completely, which for integral data bounds their
+/// number by the total supply.
+/// # Errors
+/// `Unbalanced` if the supplies don't add up to zero, `Infeasible` if the
+/// capacities don't allow to satisfy all demands.
+pub fn min_cost_flow<N: Network>(network: &N, supplies: &[f64]) -> Result<MinCostFlow, MinCostFlowError> {
+    let n = network.num_nodes();
+    assert_eq!(n, supplies.len());
+    let total: f64 = supplies.iter().sum();
+    if total.abs() > RESIDUAL_EPS {
+        return Err(MinCostFlowError::Unbalanced { total: total });
+    }
+
+    let mut residual = ResidualNetwork::new(network);
+    let mut excess = supplies.to_vec();
+    for arc in (0..residual.num_arcs()).step_by(2) {
+        if residual.cost(arc) < 0.0 {
+            let capacity = residual.residual_capacity(arc);
+            residual.push(arc, capacity);
+            excess[residual.tail(arc) as usize] -= capacity;
+            excess[residual.head(arc) as usize] += capacity;
+        }
+    }
+
+    let mut potential = vec![0.0; n];
+    loop {
+        let sources: Vec<usize> = (0..n).filter(|i| excess[*i] > RESIDUAL_EPS).collect();
+        if sources.is_empty() {
+            break;
+        }
+
+        // Dijkstra on reduced costs from all nodes with excess at once
+        let mut heap = BinaryHeap::new();
+        let mut d = vec![f64::INFINITY; n];
+        let mut pred_arc = vec![None; n];
+        let mut settled = vec![false; n];
+        for s in &sources {
+            d[*s] = 0.0;
+            heap.insert(*s as NodeId, 0.0);
+        }
+        let mut deficit = None;
+        while let Some(node) = heap.find_min() {
+            heap.delete_min();
+            let i = node as usize;
+            if settled[i] {
+                continue;
+            }
+            settled[i] = true;
+            if excess[i] < -RESIDUAL_EPS {
+                deficit = Some(i);
+                break;
+            }
+            for arc in residual.out_arcs(node) {
+                let j = residual.head(*arc) as usize;
+                if !residual.is_open(*arc) || settled[j] {
+                    continue;
+                }
+                let reduced = residual.cost(*arc) - potential[i] + potential[j];
+                if d[i] + reduced < d[j] {
+                    d[j] = d[i] + reduced;
+                    pred_arc[j] = Some(*arc);
+                    heap.insert(j as NodeId, d[j]);
+                }
+            }
+        }
+        let t = match deficit {
+            Some(t) => t,
+            None => {
+                let unshipped = sources.iter().map(|s| excess[*s]).sum();
+                return Err(MinCostFlowError::Infeasible { unshipped: unshipped });
+            },
+        };
+
+        // nodes settled before t are at most d[t] away, others count as d[t]
+        for i in 0..n {
+            potential[i] -= if settled[i] { d[i] } else { d[t] };
+        }
+
+        let mut path = Vec::new();
+        let mut node = t;
+        while let Some(arc) = pred_arc[node] {
+            path.push(arc);
+            node = residual.tail(arc) as usize;
+        }
+        let mut amount = excess[node].min(-excess[t]);
+        for arc in &path {
+            amount = amount.min(residual.residual_capacity(*arc));
+        }
+        for arc in &path {
+            residual.push(*arc, amount);
+        }
+        excess[node] -= amount;
+        excess[t] += amount;
+    }
+
+    let flows = residual.flows();
+    let cost = network.arc_values().zip(&flows).map(|((_, _, cost, _), f)| cost * f).sum();
+    Ok(MinCostFlow {
+        flows: flows,
+        cost:  cost,
+    })
+}
+
+#[test]
+fn test_min_cost_flow() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // two units from 0 to 3: the cheap path 0 -> 1 -> 3 takes only one
+    let mut edges = vec![(0,1,1.0,1.0), (0,2,2.0,2.0), (1,3,1.0,2.0), (2,3,2.0,2.0), (1,2,0.5,1.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let flow = min_cost_flow(&network, &[2.0, 0.0, 0.0, -2.0]).unwrap();
+    // arc order: 0->1, 0->2, 1->3, 1->2, 2->3
+    assert_eq!(vec![1.0, 1.0, 1.0, 0.0, 1.0], flow.flows);
+    assert_eq!(6.0, flow.cost);
+
+    assert_eq!(Err(MinCostFlowError::Unbalanced { total: 1.0 }), min_cost_flow(&network, &[2.0, 0.0, 0.0, -1.0]));
+    assert_eq!(Err(MinCostFlowError::Infeasible { unshipped: 1.0 }), min_cost_flow(&network, &[4.0, 0.0, 0.0, -4.0]));
+
+    // a negative arc is used as much as possible, as long as its flow
+    // can be shipped on
+    let mut edges = vec![(0,1,1.0,3.0), (1,2,-4.0,2.0), (2,3,1.0,3.0), (1,3,1.0,3.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let flow = min_cost_flow(&network, &[3.0, 0.0, 0.0, -3.0]).unwrap();
+    assert_eq!(vec![3.0, 2.0, 1.0, 2.0], flow.flows);
+    assert_eq!(3.0 - 8.0 + 1.0 + 2.0, flow.cost);
+}
@@ -0,0 +1,239 @@
+use super::super::{ Capacity, Cost, Network, NodeId, NodeVec };
+use super::super::compact_star::compact_star_from_edge_vec;
+use super::components::strongly_connected_components;
+use super::max_flow::dinic;
+use super::residual::RESIDUAL_EPS;
+
+/// A minimum cut between a source and a sink.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCut {
+    /// Whether every node is on the source side of the cut.
+    pub source_side: Vec<bool>,
+    /// The numbers of the arcs from the source side to the sink side.
+    pub arcs:        Vec<usize>,
+}
+
+/// The result of `all_min_cuts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinCuts {
+    /// The capacity of every minimum cut, the maximum flow value.
+    pub value:    f64,
+    pub cuts:     Vec<MinCut>,
+    /// Whether `cuts` holds all minimum cuts, or stopped at the limit.
+    pub complete: bool,
+}
+
+/// The result of `min_cut`: a minimum cut as a partition of the nodes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceSinkCut {
+    /// The nodes on the source side, in ascending order.
+    pub source_side: NodeVec,
+    /// The nodes on the sink side, in ascending order.
+    pub sink_side:   NodeVec,
+    /// The numbers of the arcs from the source side to the sink side, all
+    /// saturated by the maximum flow.
+    pub arcs:        Vec<usize>,
+    /// The total capacity of `arcs`, the maximum flow value.
+    pub capacity:    f64,
+}
+
+/// A minimum cut between `source` and `sink`, by a maximum flow with
+/// `dinic`: the source side are the nodes still reachable from the source
+/// in the residual network, which is the minimum cut with the smallest
+/// source side. In image segmentation, these are the pixels labelled like
+/// the source terminal. Use `all_min_cuts` for the other minimum cuts.
+pub fn min_cut<N: Network>(network: &N, source: NodeId, sink: NodeId) -> SourceSinkCut {
+    let flow = dinic(network, source, sink);
+    let (source_side, sink_side) = network.nodes().partition(|i| flow.source_side[*i as usize]);
+    let mut arcs = Vec::new();
+    let mut capacity = 0.0;
+    for (a, (i, j, _, arc_capacity)) in network.arc_values().enumerate() {
+        if flow.source_side[i as usize] && !flow.source_side[j as usize] {
+            arcs.push(a);
+            capacity += arc_capacity;
+        }
+    }
+    SourceSinkCut {
+        source_side: source_side,
+        sink_side:   sink_side,
+        arcs:        arcs,
+        capacity:    capacity,
+    }
+}
+
+/// Enumerates the minimum cuts between `source` and `sink`, at most
+/// `limit` of them (Picard, Queyranne: "On the structure of all minimum
+/// cuts in a network and applications").
+///
+/// After a maximum flow, the source sides of the minimum cuts are exactly
+/// the node sets that contain the source, not the sink, and that no
+/// residual arc leaves. These are closed sets of the strongly connected
+/// components of the residual network, which are enumerated by
+/// backtracking with no dead ends, so every cut takes `O(n + m)` time. The
+/// first cut is the one with the smallest source side.
+pub fn all_min_cuts<N: Network>(network: &N, source: NodeId, sink: NodeId, limit: usize) -> MinCuts {
+    let n = network.num_nodes();
+    let flow = dinic(network, source, sink);
+    let arcs: Vec<(NodeId, NodeId, Cost, Capacity)> = network.arc_values().collect();
+    let mut residual_arcs = Vec::new();
+    for (&(i, j, _, capacity), f) in arcs.iter().zip(&flow.flows) {
+        if capacity - f > RESIDUAL_EPS {
+            residual_arcs.push((i, j, 0.0, 0.0));
+        }
+        if *f > RESIDUAL_EPS {
+            residual_arcs.push((j, i, 0.0, 0.0));
+        }
+    }
+    let residual = compact_star_from_edge_vec(n, &mut residual_arcs);
+    let (count, labels) = strongly_connected_components(&residual);
+
+    // successors of every component in the residual condensation
+    let mut successors = vec![Vec::new(); count];
+    for i in residual.nodes() {
+        for j in residual.adjacent(i) {
+            let (a, b) = (labels[i as usize] as usize, labels[j as usize] as usize);
+            if a != b {
+                successors[a].push(b);
+            }
+        }
+    }
+    // components reachable from the source are always on the source side,
+    // those reaching the sink never; the others are free
+    let forced = reach(&successors, labels[source as usize] as usize, false);
+    let forbidden = reach(&successors, labels[sink as usize] as usize, true);
+
+    let mut cuts = MinCuts {
+        value:    flow.value,
+        cuts:     Vec::new(),
+        complete: true,
+    };
+    let free: Vec<usize> = (0..count).filter(|c| !forced[*c] && !forbidden[*c]).collect();
+    let mut chosen = forced.clone();
+    // components are numbered in reverse topological order, so deciding
+    // them by increasing number decides all successors of a component first
+    enumerate(&free, 0, &successors, &mut chosen, &mut |chosen| {
+        if cuts.cuts.len() == limit {
+            cuts.complete = false;
+            return false;
+        }
+        let source_side: Vec<bool> = labels.iter().map(|c| chosen[*c as usize]).collect();
+        let cut_arcs = arcs.iter()
+            .enumerate()
+            .filter(|&(_, &(i, j, _, _))| source_side[i as usize] && !source_side[j as usize])
+            .map(|(a, _)| a)
+            .collect();
+        cuts.cuts.push(MinCut { source_side: source_side, arcs: cut_arcs });
+        true
+    });
+    cuts
+}
+
+/// Marks the components reachable from `start`, or those that reach
+/// `start` if `backwards` is set.
+fn reach(successors: &[Vec<usize>], start: usize, backwards: bool) -> Vec<bool> {
+    let count = successors.len();
+    let mut predecessors = vec![Vec::new(); count];
+    if backwards {
+        for (a, next) in successors.iter().enumerate() {
+            for b in next {
+                predecessors[*b].push(a);
+            }
+        }
+    }
+    let edges = if backwards { &predecessors[..] } else { successors };
+    let mut reached = vec![false; count];
+    reached[start] = true;
+    let mut stack = vec![start];
+    while let Some(a) = stack.pop() {
+        for b in &edges[a] {
+            if !reached[*b] {
+                reached[*b] = true;
+                stack.push(*b);
+            }
+        }
+    }
+    reached
+}
+
+/// Decides the free components from `free[k]` on, reporting every closed
+/// choice to `report`. Returns `false` once `report` asks to stop.
+fn enumerate<F: FnMut(&[bool]) -> bool>(free: &[usize], k: usize, successors: &[Vec<usize>], chosen: &mut Vec<bool>,
+                                        report: &mut F) -> bool {
+    if k == free.len() {
+        return report(chosen);
+    }
+    let c = free[k];
+    if !enumerate(free, k + 1, successors, chosen, report) {
+        return false;
+    }
+    if successors[c].iter().all(|s| chosen[*s]) {
+        chosen[c] = true;
+        let go_on = enumerate(free, k + 1, successors, chosen, report);
+        chosen[c] = false;
+        return go_on;
+    }
+    true
+}
+
+#[test]
+fn test_all_min_cuts() {
+    // a chain 0 -> 1 -> 2 -> 3 of unit arcs: any of the three arcs is a
+    // minimum cut
+    let mut edges = vec![(0,1,0.0,1.0), (1,2,0.0,1.0), (2,3,0.0,1.0)];
+    let chain = compact_star_from_edge_vec(4, &mut edges);
+    let cuts = all_min_cuts(&chain, 0, 3, 10);
+    assert_eq!(1.0, cuts.value);
+    assert!(cuts.complete);
+    let mut cut_arcs: Vec<Vec<usize>> = cuts.cuts.iter().map(|cut| cut.arcs.clone()).collect();
+    assert_eq!(vec![0], cut_arcs[0]);
+    cut_arcs.sort();
+    assert_eq!(vec![vec![0], vec![1], vec![2]], cut_arcs);
+    assert_eq!(vec![true, false, false, false], cuts.cuts[0].source_side);
+
+    let limited = all_min_cuts(&chain, 0, 3, 2);
+    assert_eq!(2, limited.cuts.len());
+    assert!(!limited.complete);
+
+    // two parallel routes 0 -> 1 -> 3 and 0 -> 2 -> 3 give 2 * 2 cuts
+    let mut edges = vec![(0,1,0.0,1.0), (0,2,0.0,1.0), (1,3,0.0,1.0), (2,3,0.0,1.0)];
+    let diamond = compact_star_from_edge_vec(4, &mut edges);
+    let cuts = all_min_cuts(&diamond, 0, 3, 10);
+    assert_eq!(2.0, cuts.value);
+    assert_eq!(4, cuts.cuts.len());
+
+    // a unique bottleneck
+    let mut edges = vec![(0,1,0.0,5.0), (1,2,0.0,1.0), (2,3,0.0,5.0)];
+    let bottleneck = compact_star_from_edge_vec(4, &mut edges);
+    let cuts = all_min_cuts(&bottleneck, 0, 3, 10);
+    assert_eq!(1, cuts.cuts.len());
+    assert_eq!(vec![1], cuts.cuts[0].arcs);
+}
+
+#[test]
+fn test_min_cut() {
+    // two routes 0 -> 1 -> 3 and 0 -> 2 -> 3, bottlenecks 1 -> 3 and 0 -> 2
+    let mut edges = vec![(0,1,0.0,5.0), (0,2,0.0,2.0), (1,3,0.0,3.0), (2,3,0.0,4.0), (1,2,0.0,1.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let cut = min_cut(&network, 0, 3);
+    assert_eq!(vec![0, 1], cut.source_side);
+    assert_eq!(vec![2, 3], cut.sink_side);
+    // arcs in order (0,1) (0,2) (1,2) (1,3) (2,3)
+    assert_eq!(vec![1, 2, 3], cut.arcs);
+    assert_eq!(6.0, cut.capacity);
+    assert_eq!(dinic(&network, 0, 3).value, cut.capacity);
+
+    let cut = min_cut(&network, 3, 0);
+    assert_eq!((vec![3], 0.0), (cut.source_side, cut.capacity));
+    assert!(cut.arcs.is_empty());
+}
+
+#[test]
+fn test_min_cut_parallel_arcs() {
+    let mut edges = vec![(0,1,0.0,5.0), (0,1,0.0,1.0), (1,2,0.0,100.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let cut = min_cut(&network, 0, 2);
+    assert_eq!((vec![0, 1], 6.0), (cut.arcs, cut.capacity));
+    let cuts = all_min_cuts(&network, 0, 2, 10);
+    assert_eq!(6.0, cuts.value);
+    assert_eq!(vec![vec![0, 1]], cuts.cuts.iter().map(|cut| cut.arcs.clone()).collect::<Vec<_>>());
+}
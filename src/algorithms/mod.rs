@@ -1,5 +1,125 @@
 mod search_algorithms;
 mod pagerank;
+mod adjacency;
+mod path;
+mod nearest;
+mod distance_table;
+mod route;
+mod alternatives;
+mod traffic_assignment;
+mod flow_decomposition;
+mod potentials;
+mod subgraph;
+mod neighborhood;
+mod weisfeiler_lehman;
+mod heuristic;
+mod shortest_path_dag;
+mod ordering;
+mod sampling;
+mod perturbation;
+mod lexicographic;
+mod hop_limited;
+mod preferences;
+mod community;
+mod partition_metrics;
+mod block_model;
+mod core_periphery;
+mod monte_carlo_pagerank;
+mod components;
+mod hitting_times;
+mod residual;
+mod max_flow;
+mod min_cost_flow;
+mod effective_resistance;
+mod sparsification;
+mod k_shortest;
+mod contraction;
+mod cost_model;
+mod ev_routing;
+mod mst;
+mod centrality;
+mod budget;
+mod label_search;
+mod dag;
+mod oriented;
+mod gomory_hu;
+mod min_cuts;
+mod interdiction;
+mod sensitivity;
+mod parametric;
+mod zones;
+mod od_matrix;
+mod typed;
+mod meta_path;
+mod reachability;
+mod hits;
+mod two_sat;
+mod scheduling;
+mod cpm;
+mod closure;
+mod matching;
 
 pub use self::search_algorithms::*;
-pub use self::pagerank::pagerank;
+pub use self::pagerank::{ PageRankUpdate, pagerank, pagerank_by_components, pagerank_cancellable, pagerank_instrumented, pagerank_parallel, personalized_pagerank, trust_rank, update_pagerank };
+pub use self::path::{ Path, reconstruct_path };
+pub use self::nearest::{ nearest_target, nearest_target_instrumented, nearest_targets };
+pub use self::distance_table::{ DistanceTable, distance_table, distance_table_cancellable, one_to_many };
+pub use self::route::{ Route, route_via };
+pub use self::alternatives::alternative_routes;
+pub use self::traffic_assignment::{ Assignment, Bpr, Demand, EquilibriumComparison, MarginalCost, VolumeDelay,
+                                     bpr_functions, compare_equilibria, frank_wolfe, frank_wolfe_cancellable, system_optimum,
+                                     total_travel_time };
+pub use self::flow_decomposition::{ FlowComponent, decompose_flow };
+pub use self::potentials::{ is_optimal_flow, node_potentials, reduced_costs, satisfies_reduced_cost_optimality };
+pub use self::subgraph::{ ego_network, induced_subgraph, remap_node_data };
+pub use self::neighborhood::{ NeighborhoodFunction, hyper_anf, hyper_anf_cancellable };
+pub use self::weisfeiler_lehman::{ SparseFeatures, WeisfeilerLehman, weisfeiler_lehman_features, wl_kernel };
+pub use self::heuristic::{ HeuristicReport, HeuristicViolation, check_heuristic };
+pub use self::shortest_path_dag::{ ShortestPathDag, ShortestPaths, all_shortest_paths, shortest_path_dag };
+pub use self::ordering::{ NodeOrder, OrderingQuality, inverse_permutation, node_order, ordering_quality, permute,
+                             relabel };
+pub use self::sampling::{ SnowballSample, forest_fire_sample, random_edge_sample, random_node_sample, random_walk_sample,
+                             snowball_sample };
+pub use self::perturbation::perturb_costs;
+pub use self::lexicographic::{ LexicographicPath, TieBreaker, lexicographic_shortest_path };
+pub use self::hop_limited::hop_limited_shortest_path;
+pub use self::preferences::{ ArcClasses, PreferredRoute, Preferences, preferred_route };
+pub use self::community::{ CommunityLevel, edge_betweenness, girvan_newman, modularity };
+pub use self::partition_metrics::{ ClusterQuality, adjusted_rand_index, cluster_quality, coverage, normalized_mutual_information };
+pub use self::block_model::{ BlockModelFit, fit_block_model };
+pub use self::core_periphery::{ CorePeriphery, core_periphery, coreness, rich_club_coefficients };
+pub use self::monte_carlo_pagerank::{ MonteCarloRanks, monte_carlo_pagerank };
+pub use self::components::{ ConnectedComponents, components_with_direction, condensation, connected_components, sink_components,
+                            strongly_connected_components, weakly_connected_components };
+pub use self::hitting_times::{ commute_time, hitting_times };
+pub use self::residual::{ RESIDUAL_EPS, ResidualNetwork };
+pub use self::max_flow::{ MaxFlow, dinic, edmonds_karp };
+pub use self::min_cost_flow::{ MinCostFlow, MinCostFlowError, min_cost_flow };
+pub use self::effective_resistance::{ approximate_arc_resistances, effective_resistance, effective_resistances, spanning_edge_centrality };
+pub use self::sparsification::{ greedy_spanner, spectral_sparsify };
+pub use self::k_shortest::k_shortest_paths;
+pub use self::contraction::{ ContractionHierarchy, contraction_hierarchy };
+pub use self::cost_model::{ CostModel, dijkstra_with_model, shortest_path_with_model };
+pub use self::ev_routing::{ Battery, ChargingStop, EvRoute, ev_route };
+pub use self::mst::{ SpanningTree, kruskal, prim };
+pub use self::centrality::betweenness;
+pub use self::budget::{ BudgetPath, budget_constrained_path };
+pub use self::dag::{ CycleFound, dag_longest_paths, dag_shortest_paths, topological_sort };
+pub use self::oriented::{ DegreeOrderedAdjacency, count_triangles };
+pub use self::gomory_hu::{ GomoryHuTree, gomory_hu_tree };
+pub use self::min_cuts::{ MinCut, MinCuts, SourceSinkCut, all_min_cuts, min_cut };
+pub use self::interdiction::{ VitalArcs, most_vital_arcs_flow, most_vital_arcs_path };
+pub use self::sensitivity::{ ArcTolerance, arc_tolerances };
+pub use self::parametric::{ ParametricFlowSegment, ParametricSegment, parametric_min_cost_flow, parametric_shortest_path };
+pub use self::zones::{ CostAggregation, aggregate_zones };
+pub use self::od_matrix::ODMatrix;
+pub use self::typed::{ MAX_TYPES, TypeFilter, TypeTag, TypeTags, typed_breadth_first_search, typed_shortest_path };
+pub use self::meta_path::{ meta_path_counts, path_sim, top_path_sim };
+pub use self::reachability::{ ReachabilityMatrix, estimate_reachable_set_sizes, reachable_set_sizes };
+pub use self::hits::hits;
+pub use self::two_sat::{ Literal, TwoSat, Unsatisfiable };
+pub use self::scheduling::{ Schedule, list_schedule };
+pub use self::cpm::{ CpmAnalysis, PertAnalysis, ThreePointEstimate, critical_path_method, pert,
+                     simulate_completion_probability };
+pub use self::closure::{ Closure, max_weight_closure };
+pub use self::matching::{ Matching, MinCostAssignment, bipartition, hopcroft_karp, hungarian, min_cost_assignment };
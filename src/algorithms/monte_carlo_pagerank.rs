@@ -0,0 +1,135 @@
+use super::super::{ DoubleVec, Network, NodeId };
+use super::super::random::Rng;
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// PageRank estimated by `monte_carlo_pagerank`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonteCarloRanks {
+    /// The estimated rank of every node, summing up to `1.0`.
+    pub ranks:      DoubleVec,
+    /// The estimated standard error of every rank.
+    pub std_errors: DoubleVec,
+    /// The number of random walks.
+    pub walks:      usize,
+    /// The number of node visits over all walks.
+    pub visits:     usize,
+}
+
+impl MonteCarloRanks {
+    /// The interval `rank +- z * std_error` for `node`, clamped to `[0, 1]`.
+    /// `z = 1.96` gives an approximate 95% confidence interval.
+    pub fn confidence_interval(&self, node: NodeId, z: f64) -> (f64, f64) {
+        let (rank, error) = (self.ranks[node as usize], self.std_errors[node as usize]);
+        ((rank - z * error).max(0.0), (rank + z * error).min(1.0))
+    }
+
+    /// The `k` nodes with the highest estimated ranks, in descending order
+    /// of rank. Ties go to the lower node id.
+    pub fn top(&self, k: usize) -> Vec<(NodeId, f64)> {
+        let mut nodes: Vec<(NodeId, f64)> = self.ranks.iter().enumerate()
+            .map(|(i, r)| (i as NodeId, *r))
+            .collect();
+        nodes.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        nodes.truncate(k);
+        nodes
+    }
+}
+
+/// Estimates the PageRank of every node by `walks_per_node` random walks
+/// starting at every node, counting all visited nodes ("complete path"
+/// method of Avrachenkov et al.: "Monte Carlo methods in PageRank
+/// computation: When one iteration is sufficient"). A walk stops with the
+/// teleport probability `beta` before every step and at nodes without
+/// outgoing arcs, which matches the uniform redistribution of `pagerank`.
+///
+/// Ranks are visit counts over the total number of visits. The standard
+/// errors treat the walks as independent samples of this ratio. Top ranked
+/// nodes are estimated well after only a few walks per node.
+pub fn monte_carlo_pagerank<N: Network>(network: &N, beta: f64, walks_per_node: usize, seed: u64) -> MonteCarloRanks {
+    assert!(beta > 0.0 && beta <= 1.0);
+    let n = network.num_nodes();
+    let adjacency: Vec<Vec<NodeId>> = network.nodes().map(|i| network.adjacent(i)).collect();
+    let mut rng = Rng::new(seed);
+
+    let mut visits = vec![0.0; n];
+    // sums over all walks of x_i^2 and x_i * l, where x_i counts the visits
+    // of node i in the walk and l is the length of the walk
+    let mut squares = vec![0.0; n];
+    let mut products = vec![0.0; n];
+    let mut length_squares = 0.0;
+    let mut walk_counts = vec![0.0; n];
+    let mut walk = Vec::new();
+    for _ in 0..walks_per_node {
+        for start in 0..n {
+            let mut node = start;
+            walk.clear();
+            loop {
+                if walk_counts[node] == 0.0 {
+                    walk.push(node);
+                }
+                walk_counts[node] += 1.0;
+                if adjacency[node].is_empty() || rng.chance(beta) {
+                    break;
+                }
+                node = adjacency[node][rng.below(adjacency[node].len())] as usize;
+            }
+            let length: f64 = walk.iter().map(|i| walk_counts[*i]).sum();
+            length_squares += length * length;
+            for i in &walk {
+                let count = walk_counts[*i];
+                visits[*i] += count;
+                squares[*i] += count * count;
+                products[*i] += count * length;
+                walk_counts[*i] = 0.0;
+            }
+        }
+    }
+
+    let walks = walks_per_node * n;
+    let total: f64 = visits.iter().sum();
+    let mut ranks = vec![0.0; n];
+    let mut std_errors = vec![0.0; n];
+    if total > 0.0 {
+        let mean_length = total / walks as f64;
+        for i in 0..n {
+            let rank = visits[i] / total;
+            let residuals = squares[i] - 2.0 * rank * products[i] + rank * rank * length_squares;
+            ranks[i] = rank;
+            std_errors[i] = (residuals.max(0.0) / walks as f64).sqrt() / (mean_length * (walks as f64).sqrt());
+        }
+    }
+    MonteCarloRanks {
+        ranks:      ranks,
+        std_errors: std_errors,
+        walks:      walks,
+        visits:     total as usize,
+    }
+}
+
+#[test]
+fn test_monte_carlo_pagerank() {
+    use super::pagerank::pagerank;
+    let mut edges = vec![
+        (0,1,0.0,0.0),
+        (0,2,0.0,0.0),
+        (0,3,0.0,0.0),
+        (1,2,0.0,0.0),
+        (1,3,0.0,0.0),
+        (2,0,0.0,0.0),
+        (3,0,0.0,0.0),
+        (3,2,0.0,0.0),
+        (3,4,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let exact = pagerank(&network, 0.15, 1e-12);
+    let estimate = monte_carlo_pagerank(&network, 0.15, 2000, 3);
+    assert_eq!(10000, estimate.walks);
+    assert!((estimate.ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    for i in 0..5 {
+        let (low, high) = estimate.confidence_interval(i as NodeId, 4.0);
+        assert!(low <= exact[i] && exact[i] <= high, "node {}: {} not in [{}, {}]", i, exact[i], low, high);
+        assert!(estimate.std_errors[i] < 0.01);
+    }
+    assert_eq!(0, estimate.top(1)[0].0);
+    assert_eq!(estimate, monte_carlo_pagerank(&network, 0.15, 2000, 3));
+}
@@ -0,0 +1,122 @@
+use std::f64;
+
+use super::super::{ Cost, Network, NodeId, UnionFind };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::adjacency::{ forward_adjacency, reverse_adjacency };
+
+/// A minimum spanning tree, or a spanning forest if the network is not
+/// connected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanningTree {
+    /// The tree arcs as `(from, to)` pairs of the network, in the order they
+    /// were added.
+    pub arcs:   Vec<(NodeId, NodeId)>,
+    /// The sum of the costs of the tree arcs.
+    pub weight: Cost,
+}
+
+/// Kruskal's algorithm: adds the arcs in order of increasing cost unless
+/// they close a cycle. Arc directions are ignored, so a network read as
+/// undirected (two arcs per edge) gives its minimum spanning tree. For
+/// networks with several connected components, the result is a minimum
+/// spanning forest. Ties are broken by arc number, arcs with a `NaN` cost
+/// come last.
+pub fn kruskal<N: Network>(network: &N) -> SpanningTree {
+    let mut arcs: Vec<(NodeId, NodeId, Cost)> = network.arc_values().map(|(from, to, cost, _)| (from, to, cost)).collect();
+    arcs.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+    let mut components = UnionFind::new(network.num_nodes());
+    let mut tree = SpanningTree { arcs: Vec::new(), weight: 0.0 };
+    for (from, to, cost) in arcs {
+        if components.union(from, to) {
+            tree.arcs.push((from, to));
+            tree.weight += cost;
+        }
+    }
+    tree
+}
+
+/// Prim's algorithm: grows the tree from `root`, always adding the cheapest
+/// arc that connects a new node. Arc directions are ignored like in
+/// `kruskal`. Only the connected component of `root` is spanned.
+pub fn prim<N: Network>(network: &N, root: NodeId) -> SpanningTree {
+    let n = network.num_nodes();
+    let forward = forward_adjacency(network);
+    let reverse = reverse_adjacency(network);
+    let mut heap = BinaryHeap::new();
+    let mut best = vec![f64::INFINITY; n];
+    let mut best_arc = vec![(root, root); n];
+    let mut in_tree = vec![false; n];
+    let mut tree = SpanningTree { arcs: Vec::new(), weight: 0.0 };
+
+    best[root as usize] = 0.0;
+    heap.insert(root, 0.0);
+    while let Some(next_node) = heap.find_min() {
+        heap.delete_min();
+        let i = next_node as usize;
+        if in_tree[i] {
+            continue;
+        }
+        in_tree[i] = true;
+        if next_node != root {
+            tree.arcs.push(best_arc[i]);
+            tree.weight += best[i];
+        }
+
+        let outgoing = forward[i].iter().map(|&(j, cost)| (j, cost, (next_node, j)));
+        let incoming = reverse[i].iter().map(|&(j, cost)| (j, cost, (j, next_node)));
+        for (adjacent_node, cost, arc) in outgoing.chain(incoming) {
+            let j = adjacent_node as usize;
+            if !in_tree[j] && cost < best[j] {
+                best[j] = cost;
+                best_arc[j] = arc;
+                heap.insert(adjacent_node, cost);
+            }
+        }
+    }
+    tree
+}
+
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+#[test]
+fn test_kruskal() {
+    let mut edges = vec![(0,1,4.0,0.0),
+                         (0,2,1.0,0.0),
+                         (1,2,2.0,0.0),
+                         (1,3,5.0,0.0),
+                         (2,3,8.0,0.0),
+                         (3,4,3.0,0.0),
+                         (5,6,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(7, &mut edges);
+    let tree = kruskal(&compact_star);
+    assert_eq!(vec![(0,2), (5,6), (1,2), (3,4), (1,3)], tree.arcs);
+    assert_eq!(12.0, tree.weight);
+}
+
+#[test]
+fn test_prim() {
+    let mut edges = vec![(0,1,4.0,0.0),
+                         (0,2,1.0,0.0),
+                         (1,2,2.0,0.0),
+                         (1,3,5.0,0.0),
+                         (2,3,8.0,0.0),
+                         (3,4,3.0,0.0),
+                         (5,6,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(7, &mut edges);
+    let tree = prim(&compact_star, 4);
+    assert_eq!(vec![(3,4), (1,3), (1,2), (0,2)], tree.arcs);
+    assert_eq!(11.0, tree.weight);
+    assert_eq!(kruskal(&compact_star).weight, prim(&compact_star, 0).weight + prim(&compact_star, 5).weight);
+}
+
+#[test]
+fn test_spanning_tree_parallel_arcs() {
+    // the expensive arcs come first in arc order
+    let mut edges = vec![(0,1,7.0,0.0), (0,1,2.0,0.0), (1,2,3.0,0.0), (2,1,1.0,0.0), (0,2,4.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(3.0, kruskal(&compact_star).weight);
+    assert_eq!(kruskal(&compact_star).weight, prim(&compact_star, 0).weight);
+    assert_eq!(kruskal(&compact_star).weight, prim(&compact_star, 2).weight);
+}
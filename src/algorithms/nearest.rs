@@ -0,0 +1,191 @@
+use super::super::{ DoubleVec, Network, NodeId, NodeVec };
+use std::f64;
+use std::time::Instant;
+
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::super::instrument::{ Instrumentation, NoInstrumentation };
+use super::adjacency::reverse_adjacency;
+use super::path::{ Path, trace_path };
+
+/// Returns the shortest path from `source` to the closest node in `targets`.
+/// The search stops as soon as the first target is settled, so only the
+/// part of the network closer than the nearest target is explored.
+/// Returns `None` if no target can be reached from `source`.
+/// # Arguments
+/// * `network` a borrowed value that implements the Network trait.
+/// * `source` the node to search from.
+/// * `targets` the candidate nodes, e.g. all hospitals or charging stations.
+pub fn nearest_target<N: Network>(network: &N, source: NodeId, targets: &[NodeId]) -> Option<Path> {
+    nearest_target_instrumented(network, source, targets, NoInstrumentation)
+}
+
+/// Like `nearest_target`, but reports settled nodes, relaxed arcs and the
+/// running time to `instrumentation`.
+pub fn nearest_target_instrumented<N: Network, I: Instrumentation>(network: &N, source: NodeId, targets: &[NodeId],
+                                                                   instrumentation: I) -> Option<Path> {
+    bounded_nearest_target(network, source, targets, f64::INFINITY, instrumentation)
+}
+
+/// Like `nearest_target_instrumented`, but never labels a node with a
+/// distance above `bound`, so the search gives up once all nodes within
+/// `bound` are settled.
+pub(crate) fn bounded_nearest_target<N: Network, I: Instrumentation>(network: &N, source: NodeId, targets: &[NodeId], bound: f64,
+                                                                     mut instrumentation: I) -> Option<Path> {
+    let start = Instant::now();
+    let n = network.num_nodes();
+    let mut is_target = vec![false; n];
+    for target in targets {
+        is_target[*target as usize] = true;
+    }
+
+    let mut heap = BinaryHeap::new();
+    let mut pred = vec![network.invalid_id(); n];
+    // not the network's infinity, which the longest paths reach
+    let mut d = vec![f64::INFINITY; n];
+    let mut marked = vec![false; n];
+
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+
+    while !heap.is_empty() {
+        let next_node = heap.find_min().unwrap();
+        heap.delete_min();
+        let i = next_node as usize;
+
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        instrumentation.node_settled(next_node);
+
+        if is_target[i] {
+            instrumentation.finished(start.elapsed());
+            return trace_path(&pred, source, next_node).map(|nodes| Path::new(nodes, d[i]));
+        }
+
+        for (adjacent_node, cost, _) in network.out_arcs(next_node) {
+            instrumentation.arc_relaxed(next_node, adjacent_node);
+            let j = adjacent_node as usize;
+            if d[j] > d[i] + cost && d[i] + cost <= bound {
+                pred[j] = next_node;
+                d[j] = d[i] + cost;
+                heap.insert(adjacent_node, d[j]);
+            }
+        }
+    }
+    instrumentation.finished(start.elapsed());
+    None
+}
+
+/// Determines the closest node in `targets` for every node of the network
+/// with a single search. All targets are put into the heap at once and the
+/// arcs are traversed backwards, i.e. a multi-source Dijkstra on the reverse
+/// network.
+///
+/// Returns a tuple `(nearest, succ, dist)`:
+/// * `nearest` maps each node to its closest target,
+/// * `succ` maps each node to the next node on its shortest path to that
+///   target, so following `succ` from any node leads to `nearest`,
+/// * `dist` holds the distance to the closest target.
+///
+/// Nodes that cannot reach any target keep the network's invalid id in
+/// `nearest` and `succ`, and its infinity in `dist`.
+pub fn nearest_targets<N: Network>(network: &N, targets: &[NodeId]) -> (NodeVec, NodeVec, DoubleVec) {
+    let n = network.num_nodes();
+    let reverse = reverse_adjacency(network);
+
+    let mut heap = BinaryHeap::new();
+    let mut nearest = vec![network.invalid_id(); n];
+    let mut succ = vec![network.invalid_id(); n];
+    // not the network's infinity, which the longest paths reach
+    let mut d = vec![f64::INFINITY; n];
+    let mut marked = vec![false; n];
+
+    for target in targets {
+        d[*target as usize] = 0.0;
+        nearest[*target as usize] = *target;
+        heap.insert(*target, 0.0);
+    }
+
+    while !heap.is_empty() {
+        let next_node = heap.find_min().unwrap();
+        heap.delete_min();
+        let j = next_node as usize;
+
+        if marked[j] {
+            continue;
+        }
+        marked[j] = true;
+
+        for &(tail, cost) in &reverse[j] {
+            let i = tail as usize;
+            if d[i] > d[j] + cost {
+                succ[i] = next_node;
+                nearest[i] = nearest[j];
+                d[i] = d[j] + cost;
+                heap.insert(tail, d[i]);
+            }
+        }
+    }
+    let inf = network.infinity();
+    let d = d.into_iter().map(|d| if d.is_finite() { d } else { inf }).collect();
+    (nearest, succ, d)
+}
+
+#[test]
+fn test_nearest_target() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let path = nearest_target(&compact_star, 0, &[5,3]).unwrap();
+    assert_eq!(&vec![0,2,3], path.nodes());
+    assert_eq!(5.0, path.cost());
+    assert_eq!(None, nearest_target(&compact_star, 5, &[0,1]));
+}
+
+#[test]
+fn test_nearest_targets() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let (nearest, succ, dist) = nearest_targets(&compact_star, &[3,5]);
+    assert_eq!(vec![3,3,3,3,3,5], nearest);
+    assert_eq!(vec![2,3,3,6,3,6], succ);
+    assert_eq!(vec![5.0,2.0,1.0,0.0,1.0,0.0], dist);
+}
+
+#[test]
+fn test_nearest_at_cost_sum() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the distance from 0 to 2 is the sum of all costs, the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    let nearest = nearest_target(&path, 0, &[2]).unwrap();
+    assert_eq!((&vec![0,1,2], 2.0), (nearest.nodes(), nearest.cost()));
+    assert_eq!((vec![2,2,2], vec![1,2,3], vec![2.0,1.0,0.0]), nearest_targets(&path, &[2]));
+    // nodes that cannot reach a target get the invalid id and infinity
+    assert_eq!((vec![0,3,3], vec![3,3,3], vec![0.0,2.0,2.0]), nearest_targets(&path, &[0]));
+
+    let mut edges = vec![(0,1,0.0,0.0), (1,2,0.0,0.0)];
+    let free = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(Some(0.0), nearest_target(&free, 0, &[2]).map(|path| path.cost()));
+    assert_eq!(vec![2,2,2], nearest_targets(&free, &[2]).0);
+}
@@ -0,0 +1,138 @@
+use super::super::{ DoubleVec, Network, NodeId };
+use super::super::cancel::{ Cancellation, Cancelled };
+use super::super::hyperloglog::HyperLogLog;
+
+/// The (approximate) neighborhood function of a network: `pairs[t]` is the
+/// number of node pairs `(u, v)` with `v` reachable from `u` in at most `t`
+/// hops, including the pairs `(u, u)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborhoodFunction {
+    pub pairs: DoubleVec,
+}
+
+impl NeighborhoodFunction {
+    /// The number of node pairs at a distance of exactly `t` hops, for every
+    /// `t`, i.e. the distance distribution.
+    pub fn distance_distribution(&self) -> DoubleVec {
+        let mut distribution = Vec::with_capacity(self.pairs.len());
+        let mut previous = 0.0;
+        for pairs in &self.pairs {
+            distribution.push((pairs - previous).max(0.0));
+            previous = *pairs;
+        }
+        distribution
+    }
+
+    /// The smallest (interpolated) number of hops within which the given
+    /// `fraction` of all connected pairs lie. With `fraction = 0.9` this is
+    /// the usual effective diameter.
+    pub fn effective_diameter(&self, fraction: f64) -> f64 {
+        let total = match self.pairs.last() {
+            Some(total) => *total,
+            None => return 0.0
+        };
+        let threshold = fraction * total;
+        for t in 0..self.pairs.len() {
+            if self.pairs[t] >= threshold {
+                if t == 0 {
+                    return 0.0;
+                }
+                let below = self.pairs[t - 1];
+                return (t - 1) as f64 + (threshold - below) / (self.pairs[t] - below);
+            }
+        }
+        (self.pairs.len() - 1) as f64
+    }
+}
+
+/// Approximates the neighborhood function with HyperANF (Boldi, Rosa,
+/// Vigna: "HyperANF: approximating the neighbourhood function of very large
+/// graphs on a budget").
+///
+/// Every node keeps a HyperLogLog counter with `2^log2_registers` registers
+/// for the set of nodes reachable within `t` hops. In iteration `t + 1`, a
+/// node's counter is merged with the counters of its successors. The
+/// iteration stops when no counter changes any more or after `max_hops`
+/// iterations. Memory usage is `n * 2^log2_registers` bytes, twice.
+///
+/// # Panics
+/// If `log2_registers` is not within `4..17`, the range `HyperLogLog`
+/// supports.
+pub fn hyper_anf<N: Network>(network: &N, log2_registers: u8, max_hops: usize) -> NeighborhoodFunction {
+    hyper_anf_cancellable(network, log2_registers, max_hops, &Cancellation::new())
+        .expect("HyperANF cannot be cancelled without a token")
+}
+
+/// Like `hyper_anf`, but checks `cancellation` before every iteration.
+///
+/// # Panics
+/// If `log2_registers` is not within `4..17`.
+pub fn hyper_anf_cancellable<N: Network>(network: &N, log2_registers: u8, max_hops: usize,
+                                         cancellation: &Cancellation) -> Result<NeighborhoodFunction, Cancelled> {
+    assert!(log2_registers >= 4 && log2_registers <= 16, "log2_registers must be within 4..17");
+    let n = network.num_nodes();
+    let mut counters: Vec<HyperLogLog> = (0..n)
+        .map(|i| {
+            let mut counter = HyperLogLog::new(log2_registers);
+            counter.insert(i as NodeId);
+            counter
+        })
+        .collect();
+    let adj_lists: Vec<Vec<NodeId>> = (0..n).map(|i| network.adjacent(i as NodeId)).collect();
+
+    let mut pairs = vec![sum_estimates(&counters)];
+    for _ in 0..max_hops {
+        cancellation.check()?;
+        let mut next = counters.clone();
+        let mut changed = false;
+        for i in 0..n {
+            for j in &adj_lists[i] {
+                changed |= next[i].union(&counters[*j as usize]);
+            }
+        }
+        if !changed {
+            break;
+        }
+        counters = next;
+        pairs.push(sum_estimates(&counters));
+    }
+    Ok(NeighborhoodFunction { pairs: pairs })
+}
+
+fn sum_estimates(counters: &Vec<HyperLogLog>) -> f64 {
+    counters.iter().map(|counter| counter.estimate()).sum()
+}
+
+#[test]
+fn test_hyper_anf() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a directed path 0 -> 1 -> 2 -> 3
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (1,2,1.0,0.0),
+        (2,3,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let neighborhood = hyper_anf(&compact_star, 8, 10);
+    let exact = vec![4.0, 7.0, 9.0, 10.0];
+    assert_eq!(exact.len(), neighborhood.pairs.len());
+    for t in 0..exact.len() {
+        assert!((neighborhood.pairs[t] - exact[t]).abs() < 0.5);
+    }
+}
+
+#[test]
+fn test_effective_diameter() {
+    let neighborhood = NeighborhoodFunction { pairs: vec![4.0, 7.0, 9.0, 10.0] };
+    assert_eq!(vec![4.0, 3.0, 2.0, 1.0], neighborhood.distance_distribution());
+    assert_eq!(2.0, neighborhood.effective_diameter(0.9));
+    assert_eq!(1.5, neighborhood.effective_diameter(0.8));
+    assert_eq!(0.0, neighborhood.effective_diameter(0.2));
+}
+
+#[test]
+#[should_panic(expected = "log2_registers must be within 4..17")]
+fn test_hyper_anf_invalid_precision() {
+    use super::super::compact_star::empty_compact_star;
+    // checked up front, even without any counter to create
+    hyper_anf(&empty_compact_star(), 3, 10);
+}
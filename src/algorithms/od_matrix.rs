@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+
+use super::super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::distance_table::{ DistanceTable, distance_table };
+use super::traffic_assignment::Demand;
+
+/// Sparse origin-destination demand: the volume between pairs of nodes,
+/// with only the pairs of positive demand stored. The shared demand
+/// representation of traffic assignment, flow and many-to-many routing;
+/// `io::csv::read_od_matrix` loads it from a file.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ODMatrix {
+    entries: BTreeMap<(NodeId, NodeId), f64>,
+}
+
+impl ODMatrix {
+    pub fn new() -> ODMatrix {
+        ODMatrix { entries: BTreeMap::new() }
+    }
+
+    /// Adds `volume` to the demand from `origin` to `destination`. A pair
+    /// whose demand is not positive afterwards is not stored.
+    pub fn add(&mut self, origin: NodeId, destination: NodeId, volume: f64) {
+        let total = self.get(origin, destination) + volume;
+        if total > 0.0 {
+            self.entries.insert((origin, destination), total);
+        } else {
+            self.entries.remove(&(origin, destination));
+        }
+    }
+
+    /// The demand from `origin` to `destination`, `0.0` if there is none.
+    pub fn get(&self, origin: NodeId, destination: NodeId) -> f64 {
+        self.entries.get(&(origin, destination)).map_or(0.0, |v| *v)
+    }
+
+    /// The number of stored pairs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The sum of all demands.
+    pub fn total(&self) -> f64 {
+        self.entries.values().sum()
+    }
+
+    /// The pairs as `(origin, destination, volume)`, ordered by origin, then
+    /// destination.
+    pub fn iter(&self) -> impl Iterator<Item=(NodeId, NodeId, f64)> + '_ {
+        self.entries.iter().map(|(&(o, d), v)| (o, d, *v))
+    }
+
+    /// The distinct origins, in ascending order.
+    pub fn origins(&self) -> NodeVec {
+        let mut origins: NodeVec = self.entries.keys().map(|&(o, _)| o).collect();
+        origins.dedup();
+        origins
+    }
+
+    /// The distinct destinations, in ascending order.
+    pub fn destinations(&self) -> NodeVec {
+        let mut destinations: NodeVec = self.entries.keys().map(|&(_, d)| d).collect();
+        destinations.sort();
+        destinations.dedup();
+        destinations
+    }
+
+    /// The pairs as `Demand` for traffic assignment.
+    pub fn to_demand(&self) -> Demand {
+        self.iter().collect()
+    }
+
+    /// The shortest path cost of every pair, in the order of `iter`, from
+    /// one `distance_table` between the origins and destinations.
+    /// Unreachable pairs get the network's infinity.
+    pub fn travel_costs<N: Network>(&self, network: &N) -> DoubleVec {
+        let (origins, destinations) = (self.origins(), self.destinations());
+        let table = distance_table(network, &origins, &destinations);
+        self.pair_costs(&origins, &destinations, &table)
+    }
+
+    /// Picks the cost of every pair, in the order of `iter`, from a `table`
+    /// between `origins()` and `destinations()`.
+    pub(crate) fn pair_costs(&self, origins: &[NodeId], destinations: &[NodeId], table: &DistanceTable) -> DoubleVec {
+        self.iter()
+            .map(|(o, d, _)| {
+                let row = origins.binary_search(&o).unwrap();
+                let column = destinations.binary_search(&d).unwrap();
+                table[row][column]
+            })
+            .collect()
+    }
+
+    /// The demand weighted sum of the shortest path costs, i.e. the total
+    /// travel cost without congestion.
+    pub fn total_travel_cost<N: Network>(&self, network: &N) -> Cost {
+        self.iter().zip(self.travel_costs(network)).map(|((_, _, volume), cost)| volume * cost).sum()
+    }
+}
+
+impl<'a> From<&'a Demand> for ODMatrix {
+    fn from(demand: &'a Demand) -> ODMatrix {
+        let mut matrix = ODMatrix::new();
+        for &(o, d, volume) in demand {
+            matrix.add(o, d, volume);
+        }
+        matrix
+    }
+}
+
+#[test]
+fn test_od_matrix() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut matrix = ODMatrix::from(&vec![(2, 0, 1.0), (0, 2, 4.0), (0, 1, 2.0)]);
+    matrix.add(0, 2, 1.0);
+    assert_eq!(3, matrix.len());
+    assert_eq!(5.0, matrix.get(0, 2));
+    assert_eq!(0.0, matrix.get(1, 2));
+    assert_eq!(8.0, matrix.total());
+    assert_eq!(vec![0, 2], matrix.origins());
+    assert_eq!(vec![0, 1, 2], matrix.destinations());
+    assert_eq!(vec![(0, 1, 2.0), (0, 2, 5.0), (2, 0, 1.0)], matrix.to_demand());
+
+    // pairs without positive demand are not stored
+    matrix.add(1, 2, 0.0);
+    matrix.add(2, 0, -1.0);
+    assert_eq!(2, matrix.len());
+    assert_eq!(vec![0], matrix.origins());
+
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,2.0,0.0), (2,0,4.0,0.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(vec![1.0, 3.0], matrix.travel_costs(&network));
+    assert_eq!(17.0, matrix.total_travel_cost(&network));
+}
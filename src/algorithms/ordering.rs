@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+
+use super::super::{ Network, NodeId, NodeVec };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::adjacency::undirected_adjacency;
+
+/// Strategies for numbering the nodes of a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeOrder {
+    /// Breadth first search order. Arcs are followed in both directions and
+    /// every weakly connected component is started at its lowest id.
+    BreadthFirst,
+    /// Depth first search preorder, otherwise like `BreadthFirst`.
+    DepthFirst,
+    /// Nodes with many incident arcs (in and out) first, ties broken by id.
+    DegreeDescending,
+    /// Nodes with few incident arcs first, ties broken by id.
+    DegreeAscending,
+    /// Cuthill-McKee order: breadth first search from a node of minimum
+    /// degree per component, visiting neighbors by increasing degree.
+    CuthillMcKee,
+    /// The reverse of `CuthillMcKee`, which usually has the same bandwidth
+    /// but a smaller profile.
+    ReverseCuthillMcKee,
+}
+
+/// Bandwidth and profile of the symmetric adjacency matrix of a network
+/// under a node order. Both measure how far from the diagonal the non-zero
+/// entries are, smaller is better for locality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OrderingQuality {
+    /// The largest difference of the new ids of two adjacent nodes.
+    pub bandwidth: usize,
+    /// The sum over all nodes of the difference between its new id and the
+    /// smallest new id among its neighbors and itself.
+    pub profile:   usize,
+}
+
+/// Returns the nodes of `network` in the given order: `order[k]` is the
+/// node that gets the new id `k`.
+pub fn node_order<N: Network>(network: &N, order: NodeOrder) -> NodeVec {
+    match order {
+        NodeOrder::BreadthFirst => traversal_order(network, false),
+        NodeOrder::DepthFirst => traversal_order(network, true),
+        NodeOrder::DegreeDescending => {
+            let degrees = total_degrees(network);
+            let mut nodes: NodeVec = network.nodes().collect();
+            nodes.sort_by(|a, b| degrees[*b as usize].cmp(&degrees[*a as usize]));
+            nodes
+        },
+        NodeOrder::DegreeAscending => {
+            let degrees = total_degrees(network);
+            let mut nodes: NodeVec = network.nodes().collect();
+            nodes.sort_by_key(|i| degrees[*i as usize]);
+            nodes
+        },
+        NodeOrder::CuthillMcKee => cuthill_mckee(network),
+        NodeOrder::ReverseCuthillMcKee => {
+            let mut nodes = cuthill_mckee(network);
+            nodes.reverse();
+            nodes
+        },
+    }
+}
+
+/// Measures bandwidth and profile of `network` with node `order[k]` at
+/// position `k`, treating every arc as undirected.
+pub fn ordering_quality<N: Network>(network: &N, order: &NodeVec) -> OrderingQuality {
+    let new_id = inverse_permutation(order);
+    let mut first = new_id.clone();
+    let mut bandwidth = 0;
+    for (i, j) in network.arcs() {
+        let (a, b) = (new_id[i as usize], new_id[j as usize]);
+        let (low, high) = if a < b { (a, b) } else { (b, a) };
+        bandwidth = bandwidth.max((high - low) as usize);
+        let row = order[high as usize] as usize;
+        first[row] = first[row].min(low);
+    }
+    let profile = network.nodes()
+        .map(|i| (new_id[i as usize] - first[i as usize]) as usize)
+        .sum();
+    OrderingQuality {
+        bandwidth: bandwidth,
+        profile:   profile,
+    }
+}
+
+/// Inverts a node order: the result maps every old id to its new id.
+pub fn inverse_permutation(order: &NodeVec) -> NodeVec {
+    let mut new_id = vec![0; order.len()];
+    for (k, node) in order.iter().enumerate() {
+        new_id[*node as usize] = k as NodeId;
+    }
+    new_id
+}
+
+/// Builds a copy of `network` in which node `order[k]` has the id `k`.
+/// Every arc is carried over with its cost and capacity, parallel arcs
+/// included.
+/// # Panics
+/// If `order` is not a permutation of the nodes.
+pub fn permute<N: Network>(network: &N, order: &NodeVec) -> CompactStar {
+    let mut seen = vec![false; network.num_nodes()];
+    assert!(order.len() == network.num_nodes(), "order must contain every node");
+    for node in order {
+        assert!(!seen[*node as usize], "order must contain every node once");
+        seen[*node as usize] = true;
+    }
+    if order.is_empty() {
+        return empty_compact_star();
+    }
+    let new_id = inverse_permutation(order);
+    let mut edges: Vec<_> = network.arc_values()
+        .map(|(i, j, cost, capacity)| (new_id[i as usize], new_id[j as usize], cost, capacity))
+        .collect();
+    compact_star_from_edge_vec(order.len(), &mut edges)
+}
+
+/// Renumbers the nodes of `network` by `order`. Returns the renumbered
+/// network and the original id of each of its nodes.
+///
+/// Traversal orders place nodes that are close in the network at close ids,
+/// so that subsequent traversals access memory more locally. They also give
+/// canonical ids for networks that were read in arbitrary order.
+pub fn relabel<N: Network>(network: &N, order: NodeOrder) -> (CompactStar, NodeVec) {
+    let order = node_order(network, order);
+    (permute(network, &order), order)
+}
+
+/// The number of arcs incident to every node, counting both directions.
+fn total_degrees<N: Network>(network: &N) -> Vec<usize> {
+    let mut degrees = vec![0; network.num_nodes()];
+    for (i, j) in network.arcs() {
+        degrees[i as usize] += 1;
+        degrees[j as usize] += 1;
+    }
+    degrees
+}
+
+/// Cuthill-McKee order over all weakly connected components.
+fn cuthill_mckee<N: Network>(network: &N) -> NodeVec {
+    let n = network.num_nodes();
+    let neighbors = undirected_adjacency(network);
+    let degree = |i: &NodeId| neighbors[*i as usize].len();
+    let mut roots: NodeVec = network.nodes().collect();
+    roots.sort_by_key(&degree);
+
+    let mut visited = vec![false; n];
+    let mut order = NodeVec::with_capacity(n);
+    for root in roots {
+        if visited[root as usize] {
+            continue;
+        }
+        visited[root as usize] = true;
+        let mut next = order.len();
+        order.push(root);
+        while next < order.len() {
+            let i = order[next];
+            next += 1;
+            let mut unvisited: NodeVec = neighbors[i as usize].iter()
+                .filter(|j| !visited[**j as usize])
+                .cloned()
+                .collect();
+            unvisited.sort_by_key(&degree);
+            for j in unvisited {
+                visited[j as usize] = true;
+                order.push(j);
+            }
+        }
+    }
+    order
+}
+
+/// Breadth or depth first order over all weakly connected components.
+fn traversal_order<N: Network>(network: &N, depth_first: bool) -> NodeVec {
+    let n = network.num_nodes();
+    let neighbors = undirected_adjacency(network);
+    let mut visited = vec![false; n];
+    let mut order = NodeVec::with_capacity(n);
+    let mut pending = VecDeque::new();
+
+    for root in 0..n {
+        if visited[root] {
+            continue;
+        }
+        pending.push_back(root as NodeId);
+        while let Some(i) = if depth_first { pending.pop_back() } else { pending.pop_front() } {
+            if visited[i as usize] {
+                continue;
+            }
+            visited[i as usize] = true;
+            order.push(i);
+            let unvisited = neighbors[i as usize].iter().filter(|j| !visited[**j as usize]);
+            if depth_first {
+                // lowest id on top of the stack
+                pending.extend(unvisited.rev());
+            } else {
+                pending.extend(unvisited);
+            }
+        }
+    }
+    order
+}
+
+#[test]
+fn test_node_order() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    //  0 - 3 - 1      5 - 4
+    //  |   |
+    //  2 --+
+    let mut edges = vec![(0,3,1.0,0.0),
+                         (3,1,2.0,0.0),
+                         (2,0,3.0,0.0),
+                         (3,2,4.0,0.0),
+                         (4,5,5.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+
+    assert_eq!(vec![0,2,3,1,4,5], node_order(&compact_star, NodeOrder::BreadthFirst));
+    assert_eq!(vec![0,2,3,1,4,5], node_order(&compact_star, NodeOrder::DepthFirst));
+    assert_eq!(vec![3,0,2,1,4,5], node_order(&compact_star, NodeOrder::DegreeDescending));
+    assert_eq!(vec![1,4,5,0,2,3], node_order(&compact_star, NodeOrder::DegreeAscending));
+}
+
+#[test]
+fn test_relabel() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a path 3 -> 1 -> 0 -> 2
+    let mut edges = vec![(3,1,1.0,10.0), (1,0,2.0,20.0), (0,2,3.0,30.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let (relabeled, order) = relabel(&compact_star, NodeOrder::DepthFirst);
+    assert_eq!(vec![0,1,3,2], order);
+    assert_eq!(vec![0,1,3,2], inverse_permutation(&order));
+    assert_eq!(Some(3.0), relabeled.cost(0, 3));
+    assert_eq!(Some(10.0), relabeled.capacity(2, 1));
+    assert_eq!(compact_star.num_arcs(), relabeled.num_arcs());
+}
+
+#[test]
+fn test_permute_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.0,10.0), (0,1,2.0,20.0), (1,0,3.0,30.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    let permuted = permute(&compact_star, &vec![1,0]);
+    assert_eq!(3, permuted.num_arcs());
+    assert_eq!(vec![(0,1.0,10.0), (0,2.0,20.0), (1,3.0,30.0)],
+               permuted.out_arcs(1).chain(permuted.out_arcs(0)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_reverse_cuthill_mckee() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a path 0 - 1 - 2 - 3 - 4 with scrambled ids
+    let ids = [3, 0, 4, 1, 2];
+    let mut edges: Vec<_> = (0..4).map(|k| (ids[k], ids[k + 1], 1.0, 0.0)).collect();
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+
+    let identity: NodeVec = compact_star.nodes().collect();
+    assert_eq!(OrderingQuality { bandwidth: 4, profile: 8 }, ordering_quality(&compact_star, &identity));
+
+    let cuthill_mckee = node_order(&compact_star, NodeOrder::CuthillMcKee);
+    assert_eq!(vec![2,1,4,0,3], cuthill_mckee);
+    let reverse = node_order(&compact_star, NodeOrder::ReverseCuthillMcKee);
+    assert_eq!(vec![3,0,4,1,2], reverse);
+    assert_eq!(OrderingQuality { bandwidth: 1, profile: 4 }, ordering_quality(&compact_star, &reverse));
+
+    let (relabeled, _) = relabel(&compact_star, NodeOrder::ReverseCuthillMcKee);
+    let identity: NodeVec = relabeled.nodes().collect();
+    assert_eq!(1, ordering_quality(&relabeled, &identity).bandwidth);
+}
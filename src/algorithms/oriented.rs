@@ -0,0 +1,135 @@
+use super::super::{ Network, NodeId, NodeVec };
+use super::adjacency::undirected_adjacency;
+
+/// The network as undirected graph, with every edge oriented towards the
+/// endpoint of higher degree. Nodes are ranked by descending degree, ties
+/// broken by id, and every node keeps only its neighbors of better (lower)
+/// rank. This is the standard preprocessing for triangle counting and
+/// clique listing: every edge is stored once and no node keeps more than
+/// `O(sqrt(m))` neighbors, so triangles and cliques can be enumerated
+/// from their worst ranked node without duplicates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DegreeOrderedAdjacency {
+    order:  NodeVec,
+    rank:   NodeVec,
+    higher: Vec<NodeVec>,
+}
+
+impl DegreeOrderedAdjacency {
+    /// Builds the view, ignoring arc directions, parallel arcs and
+    /// self-loops.
+    pub fn new<N: Network>(network: &N) -> DegreeOrderedAdjacency {
+        let neighbors = undirected_adjacency(network);
+        let mut order: NodeVec = network.nodes().collect();
+        order.sort_by(|a, b| neighbors[*b as usize].len().cmp(&neighbors[*a as usize].len()).then(a.cmp(b)));
+        let mut rank = vec![0; order.len()];
+        for (k, node) in order.iter().enumerate() {
+            rank[*node as usize] = k as NodeId;
+        }
+        let higher = neighbors.into_iter()
+            .enumerate()
+            .map(|(i, adjacent)| {
+                let mut higher: NodeVec = adjacent.into_iter().filter(|j| rank[*j as usize] < rank[i]).collect();
+                higher.sort_by_key(|j| rank[*j as usize]);
+                higher
+            })
+            .collect();
+        DegreeOrderedAdjacency {
+            order:  order,
+            rank:   rank,
+            higher: higher,
+        }
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.order.len()
+    }
+
+    /// The nodes by descending degree: `order()[k]` has rank `k`.
+    pub fn order(&self) -> &NodeVec {
+        &self.order
+    }
+
+    /// The position of `node` in `order()`, `0` for the node of highest
+    /// degree.
+    pub fn rank(&self, node: NodeId) -> NodeId {
+        self.rank[node as usize]
+    }
+
+    /// The neighbors of `node` that rank before it, sorted by rank, so
+    /// that two of these lists can be intersected by merging.
+    pub fn higher_neighbors(&self, node: NodeId) -> &NodeVec {
+        &self.higher[node as usize]
+    }
+
+    /// The length of the longest `higher_neighbors` list.
+    pub fn max_higher_degree(&self) -> usize {
+        self.higher.iter().map(|h| h.len()).max().unwrap_or(0)
+    }
+}
+
+/// Counts the triangles of `network` as undirected graph, in
+/// `O(m sqrt(m))` time on the degree ordered adjacency.
+pub fn count_triangles<N: Network>(network: &N) -> usize {
+    let view = DegreeOrderedAdjacency::new(network);
+    let mut triangles = 0;
+    for v in network.nodes() {
+        let higher_v = view.higher_neighbors(v);
+        for u in higher_v {
+            let higher_u = view.higher_neighbors(*u);
+            let (mut a, mut b) = (0, 0);
+            while a < higher_v.len() && b < higher_u.len() {
+                let (ra, rb) = (view.rank(higher_v[a]), view.rank(higher_u[b]));
+                if ra == rb {
+                    triangles += 1;
+                }
+                if ra <= rb {
+                    a += 1;
+                }
+                if rb <= ra {
+                    b += 1;
+                }
+            }
+        }
+    }
+    triangles
+}
+
+#[test]
+fn test_degree_ordered_adjacency() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a star around 2 plus the edge 0 - 1, given with one arc per edge
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (2,0,1.0,0.0),
+                         (2,1,1.0,0.0),
+                         (3,2,1.0,0.0),
+                         (4,2,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+    let view = DegreeOrderedAdjacency::new(&compact_star);
+    assert_eq!(&vec![2,0,1,3,4], view.order());
+    assert_eq!(0, view.rank(2));
+    assert!(view.higher_neighbors(2).is_empty());
+    assert_eq!(&vec![2,0], view.higher_neighbors(1));
+    assert_eq!(&vec![2], view.higher_neighbors(4));
+    assert_eq!(2, view.max_higher_degree());
+    assert_eq!(1, count_triangles(&compact_star));
+}
+
+#[test]
+fn test_count_triangles() {
+    use super::super::generators::{ ArcWeights, gnp };
+    let network = gnp(30, 0.3, &ArcWeights::default(), 3);
+    let mut brute_force = 0;
+    for a in 0..30 {
+        for b in a + 1..30 {
+            for c in b + 1..30 {
+                let edge = |i: u32, j: u32| network.has_arc(i, j) || network.has_arc(j, i);
+                if edge(a, b) && edge(b, c) && edge(a, c) {
+                    brute_force += 1;
+                }
+            }
+        }
+    }
+    assert!(brute_force > 0);
+    assert_eq!(brute_force, count_triangles(&network));
+}
@@ -1,4 +1,11 @@
 use super::super::{ Network, NodeId };
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Instant;
+
+use super::super::cancel::{ Cancellation, Cancelled };
+use super::super::instrument::{ Instrumentation, NoInstrumentation };
+use super::components::strongly_connected_components;
 
 /// Runs pagerank algorithm on a graph until convergence.
 /// Convergence is reached, when the last ranks vector and the new one
@@ -9,6 +16,20 @@ use super::super::{ Network, NodeId };
 /// will be caught by an assertion and the algorithm will panic.  
 /// The result will be the pagerank for each node in the network.
 pub fn pagerank<N: Network>(network: &N, beta: f64, eps: f64) -> Vec<f64> {
+    pagerank_cancellable(network, beta, eps, &Cancellation::new())
+        .expect("pagerank cannot be cancelled without a token")
+}
+
+/// Like `pagerank`, but checks `cancellation` before every iteration.
+pub fn pagerank_cancellable<N: Network>(network: &N, beta: f64, eps: f64, cancellation: &Cancellation) -> Result<Vec<f64>, Cancelled> {
+    pagerank_instrumented(network, beta, eps, cancellation, NoInstrumentation)
+}
+
+/// Like `pagerank_cancellable`, but reports every iteration and the running
+/// time to `instrumentation`.
+pub fn pagerank_instrumented<N: Network, I: Instrumentation>(network: &N, beta: f64, eps: f64, cancellation: &Cancellation,
+                                                             mut instrumentation: I) -> Result<Vec<f64>, Cancelled> {
+    let start = Instant::now();
     let init_value = 1.0 / (network.num_nodes() as f64);
     let mut ranks = vec![0.0; network.num_nodes()];
     let mut new_ranks = vec![init_value; network.num_nodes()];
@@ -16,21 +37,271 @@ pub fn pagerank<N: Network>(network: &N, beta: f64, eps: f64) -> Vec<f64> {
     let inv_out_deg = inv_out_deg(network);
     let mut i = 0;
     while !is_converged(&ranks, &new_ranks, eps) {
+        cancellation.check()?;
         print!("iteration {}: ", i);
         ranks = new_ranks;
         new_ranks = mult_matrix_vec(&adj_lists, &inv_out_deg, beta, &ranks);
         normalize(&mut new_ranks);
+        instrumentation.iteration(i);
         i+=1;
     } 
+    instrumentation.finished(start.elapsed());
+    Ok(ranks)
+}
+
+/// Computes the same ranks as `pagerank` with `num_threads` threads. The
+/// nodes are split into contiguous blocks of about equal numbers of
+/// incoming arcs, one per thread, and every thread sums up the ranks
+/// flowing into its own nodes, so the threads never write to shared
+/// entries. Only the matrix-vector product runs in parallel; it dominates
+/// the running time on large networks.
+/// # Panics
+/// If `num_threads` is `0`.
+pub fn pagerank_parallel<N: Network>(network: &N, beta: f64, eps: f64, num_threads: usize) -> Vec<f64> {
+    assert!(num_threads > 0, "at least one thread is required");
+    let n = network.num_nodes();
+    let in_adj_lists: Vec<Vec<usize>> = network.nodes()
+        .map(|j| network.incoming(j).map(|(i, _, _)| i as usize).collect())
+        .collect();
+    let inv_out_deg = inv_out_deg(network);
+
+    // block boundaries by incoming arcs, so that hubs don't stall a thread
+    let per_thread = (network.num_arcs() + n) / num_threads + 1;
+    let mut block_sizes = Vec::with_capacity(num_threads);
+    let (mut size, mut work) = (0, 0);
+    for in_adj in &in_adj_lists {
+        size += 1;
+        work += in_adj.len() + 1;
+        if work >= per_thread {
+            block_sizes.push(size);
+            size = 0;
+            work = 0;
+        }
+    }
+    block_sizes.push(size);
+
+    let init_value = 1.0 / (n as f64);
+    let mut ranks = vec![0.0; n];
+    let mut new_ranks = vec![init_value; n];
+    while !is_converged(&ranks, &new_ranks, eps) {
+        ranks = new_ranks;
+        new_ranks = vec![0.0; n];
+        thread::scope(|scope| {
+            let mut rest = &mut new_ranks[..];
+            let mut first = 0;
+            for size in &block_sizes {
+                let (block, tail) = rest.split_at_mut(*size);
+                rest = tail;
+                let (in_adj_lists, inv_out_deg, ranks) = (&in_adj_lists, &inv_out_deg, &ranks);
+                scope.spawn(move || {
+                    for (k, rank) in block.iter_mut().enumerate() {
+                        for i in &in_adj_lists[first + k] {
+                            *rank += (1.0 - beta) * inv_out_deg[*i] * ranks[*i];
+                        }
+                    }
+                });
+                first += size;
+            }
+        });
+        normalize(&mut new_ranks);
+    }
+    ranks
+}
+/// Computes the same ranks as `pagerank`, but solves one strongly
+/// connected component after the other in topological order of the
+/// condensation. The ranks flowing into a component are final once all
+/// components before it are solved, so every component is iterated only
+/// until its own ranks change by less than `eps` in the maximum norm.
+/// Sink components and nodes without outgoing arcs, which slow down or
+/// distort the global iteration on crawled networks, are handled exactly:
+/// the ranks of the linear system without teleport from dangling nodes are
+/// proportional to the PageRank and only normalized at the end.
+pub fn pagerank_by_components<N: Network>(network: &N, beta: f64, eps: f64) -> Vec<f64> {
+    let n = network.num_nodes();
+    let (count, labels) = strongly_connected_components(network);
+    let inv_out_deg = inv_out_deg(network);
+    let mut incoming = vec![Vec::new(); n];
+    for (i, j) in network.arcs() {
+        incoming[j as usize].push(i as usize);
+    }
+    let mut members = vec![Vec::new(); count];
+    for i in 0..n {
+        members[labels[i] as usize].push(i);
+    }
+
+    let teleport = 1.0 / n as f64;
+    let mut ranks = vec![0.0; n];
+    for component in members.iter().rev() {
+        loop {
+            let mut change: f64 = 0.0;
+            for i in component {
+                let mut rank = teleport;
+                for j in &incoming[*i] {
+                    rank += (1.0 - beta) * inv_out_deg[*j] * ranks[*j];
+                }
+                change = change.max((rank - ranks[*i]).abs());
+                ranks[*i] = rank;
+            }
+            if change <= eps {
+                break;
+            }
+        }
+    }
+
+    let sum: f64 = ranks.iter().sum();
+    for rank in ranks.iter_mut() {
+        *rank /= sum;
+    }
     ranks
 }
+
+/// Personalized PageRank: teleports, including those from nodes without
+/// outgoing arcs, lead to a node of `teleport_set` chosen uniformly at
+/// random instead of to any node. The ranks then measure how close nodes
+/// are to the teleport set, which makes them useful for recommendations
+/// around a few seed nodes. With all nodes in `teleport_set`, the ranks are
+/// those of `pagerank`. Duplicates in `teleport_set` count once.
+/// Iterates until the ranks change by less than `eps` in their L1-norm.
+/// # Panics
+/// If `teleport_set` is empty.
+pub fn personalized_pagerank<N: Network>(network: &N, beta: f64, eps: f64, teleport_set: &[NodeId]) -> Vec<f64> {
+    assert!(!teleport_set.is_empty(), "personalized PageRank needs at least one teleport node");
+    let mut teleport = vec![0.0; network.num_nodes()];
+    for node in teleport_set {
+        teleport[*node as usize] = 1.0;
+    }
+    let sum: f64 = teleport.iter().sum();
+    for value in teleport.iter_mut() {
+        *value /= sum;
+    }
+    teleport_pagerank(network, beta, &teleport, eps)
+}
+
+/// TrustRank (Gyöngyi, Garcia-Molina, Pedersen: "Combating web spam with
+/// TrustRank"): PageRank whose teleport only leads back to the trusted
+/// `seeds`, so trust flows from the seeds along the arcs and decays by the
+/// factor `1.0 - beta` with every arc. Nodes that are reached from the seeds
+/// only over long paths, like most spam pages, get low scores. This is
+/// `personalized_pagerank` with the seeds as teleport set.
+/// Iterates until the scores change by less than `eps` in their L1-norm.
+/// # Panics
+/// If `seeds` is empty.
+pub fn trust_rank<N: Network>(network: &N, seeds: &[NodeId], beta: f64, eps: f64) -> Vec<f64> {
+    assert!(!seeds.is_empty(), "TrustRank needs at least one seed");
+    personalized_pagerank(network, beta, eps, seeds)
+}
+
+/// PageRank with teleports according to the distribution `teleport`
+/// instead of the uniform one. The ranks of nodes without outgoing arcs are
+/// teleported as well. Iterates until the ranks change by less than `eps`
+/// in their L1-norm.
+pub(crate) fn teleport_pagerank<N: Network>(network: &N, beta: f64, teleport: &[f64], eps: f64) -> Vec<f64> {
+    let adj_lists = build_adj_list(network);
+    let inv_out_deg = inv_out_deg(network);
+    let mut ranks = teleport.to_vec();
+    loop {
+        let mut new_ranks = mult_matrix_vec(&adj_lists, &inv_out_deg, beta, &ranks);
+        let missing = 1.0 - new_ranks.iter().sum::<f64>();
+        for (rank, share) in new_ranks.iter_mut().zip(teleport) {
+            *rank += missing * share;
+        }
+        let change: f64 = ranks.iter().zip(&new_ranks).map(|(a, b)| (a - b).abs()).sum();
+        ranks = new_ranks;
+        if change <= eps {
+            return ranks;
+        }
+    }
+}
+
+/// Result of `update_pagerank`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageRankUpdate {
+    /// The updated rank of every node.
+    pub ranks:  Vec<f64>,
+    /// The number of local push operations that were necessary.
+    pub pushes: usize,
+}
+
+/// Updates converged `previous` ranks after the arcs `added` and `removed`
+/// changed the network into `network`, which must have the same nodes.
+/// Instead of iterating over the whole network again, only the error the
+/// changes introduced is pushed along the arcs from node to node (a
+/// Gauss-Southwell iteration) until no node carries an error above `eps`,
+/// so the work depends on how far the changes reach.
+///
+/// Mass that reaches nodes without outgoing arcs is spread evenly over all
+/// nodes like in `pagerank`; that part is exactly accounted for by the
+/// final normalization.
+///
+/// # Panics
+/// If `previous` doesn't have one rank per node, or if an arc of `added`
+/// is not in `network`.
+pub fn update_pagerank<N: Network>(network: &N, previous: &Vec<f64>, added: &[(NodeId, NodeId)], removed: &[(NodeId, NodeId)],
+                                   beta: f64, eps: f64) -> PageRankUpdate {
+    let n = network.num_nodes();
+    assert_eq!(n, previous.len());
+    let adj_lists = build_adj_list(network);
+    let mut ranks = previous.clone();
+    let mut residuals = vec![0.0; n];
+
+    let mut tails: Vec<usize> = added.iter().chain(removed).map(|&(i, _)| i as usize).collect();
+    tails.sort();
+    tails.dedup();
+    for i in tails {
+        let mut old_adj = adj_lists[i].clone();
+        for &(_, j) in added.iter().filter(|&&(from, _)| from as usize == i) {
+            let position = old_adj.iter().position(|k| *k == j as usize).expect("added arc is missing in the network");
+            old_adj.swap_remove(position);
+        }
+        old_adj.extend(removed.iter().filter(|&&(from, _)| from as usize == i).map(|&(_, j)| j as usize));
+        let share = (1.0 - beta) * ranks[i];
+        for j in &old_adj {
+            residuals[*j] -= share / old_adj.len() as f64;
+        }
+        for j in &adj_lists[i] {
+            residuals[*j] += share / adj_lists[i].len() as f64;
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..n).filter(|i| residuals[*i].abs() > eps).collect();
+    let mut queued = vec![false; n];
+    for i in &queue {
+        queued[*i] = true;
+    }
+    let mut pushes = 0;
+    while let Some(i) = queue.pop_front() {
+        queued[i] = false;
+        let residual = residuals[i];
+        residuals[i] = 0.0;
+        ranks[i] += residual;
+        pushes += 1;
+        let share = (1.0 - beta) * residual / adj_lists[i].len() as f64;
+        for j in &adj_lists[i] {
+            residuals[*j] += share;
+            if !queued[*j] && residuals[*j].abs() > eps {
+                queued[*j] = true;
+                queue.push_back(*j);
+            }
+        }
+    }
+
+    let sum: f64 = ranks.iter().sum();
+    for rank in ranks.iter_mut() {
+        *rank /= sum;
+    }
+    PageRankUpdate {
+        ranks:  ranks,
+        pushes: pushes,
+    }
+}
+
 /// Calculates the inverse of the out degree for each node in the network.
 /// For out degree `0`, the inverse will also be `0`, guaranteeing that we 
 /// add `0.0` to the pagerank of the respective node.
 fn inv_out_deg<N: Network>(network: &N) -> Vec<f64> {
     let mut inv_out_deg = Vec::with_capacity(network.num_nodes());
     for i in 0..network.num_nodes() {
-        let out_deg = network.adjacent(i as NodeId).len() as f64;
+        let out_deg = network.out_degree(i as NodeId) as f64;
         if out_deg > 0.0 {
             inv_out_deg.push(1.0 / out_deg);
         } else {
@@ -41,15 +312,10 @@ fn inv_out_deg<N: Network>(network: &N) -> Vec<f64> {
 }
 
 /// Converts the network in a slightly faster traversable adjacency list.
-fn build_adj_list<N: Network>(network: &N) -> Vec<Vec<usize>> {
+pub(crate) fn build_adj_list<N: Network>(network: &N) -> Vec<Vec<usize>> {
     let mut adj_list = Vec::with_capacity(network.num_nodes());
     for i in 0..network.num_nodes() {
-        let adj_nodes = network.adjacent(i as NodeId);
-        let mut i_th_adj_nodes = Vec::with_capacity(adj_nodes.len());
-        for j in adj_nodes {
-            i_th_adj_nodes.push(j as usize);
-        }
-        adj_list.push(i_th_adj_nodes);
+        adj_list.push(network.out_arcs(i as NodeId).map(|(j, _, _)| j as usize).collect());
     }
     adj_list
 }
@@ -161,3 +427,94 @@ fn test_pagerank() {
     let ranks = pagerank(&compact_star, 1e-10,1e-3);
     assert_eq!(vec![0.38,0.12,0.29,0.19], ranks);
 }
+
+#[test]
+fn test_pagerank_parallel() {
+    use super::super::generators::{ ArcWeights, gnp };
+    let network = gnp(200, 0.05, &ArcWeights::default(), 9);
+    let sequential = pagerank(&network, 0.15, 1e-9);
+    for threads in 1..5 {
+        let parallel = pagerank_parallel(&network, 0.15, 1e-9, threads);
+        assert!(sequential.iter().zip(&parallel).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+}
+
+#[test]
+fn test_update_pagerank() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges: Vec<_> = (0..20).map(|i| (i, (i + 1) % 20, 0.0, 0.0)).collect();
+    edges.push((5, 15, 0.0, 0.0));
+    edges.push((7, 3, 0.0, 0.0));
+    let before = compact_star_from_edge_vec(20, &mut edges.clone());
+    let previous = pagerank(&before, 0.15, 1e-12);
+
+    // add 12 -> 2, remove 7 -> 3 and make 19 a dangling node
+    edges.push((12, 2, 0.0, 0.0));
+    edges.retain(|e| (e.0, e.1) != (7, 3) && e.0 != 19);
+    let after = compact_star_from_edge_vec(20, &mut edges);
+    let update = update_pagerank(&after, &previous, &[(12, 2)], &[(7, 3), (19, 0)], 0.15, 1e-12);
+    let expected = pagerank(&after, 0.15, 1e-12);
+    for i in 0..20 {
+        assert!((update.ranks[i] - expected[i]).abs() < 1e-9);
+    }
+    assert!(update.pushes > 0);
+
+    let unchanged = update_pagerank(&before, &previous, &[], &[], 0.15, 1e-12);
+    assert_eq!(0, unchanged.pushes);
+}
+
+#[test]
+fn test_pagerank_by_components() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a source cycle 0 -> 1 -> 2 -> 0 draining into the sink cycle
+    // 3 -> 4 -> 5 -> 3 and the dangling node 6
+    let mut edges = vec![(0,1,0.0,0.0), (1,2,0.0,0.0), (2,0,0.0,0.0), (2,3,0.0,0.0),
+                         (3,4,0.0,0.0), (4,5,0.0,0.0), (5,3,0.0,0.0), (1,6,0.0,0.0)];
+    let network = compact_star_from_edge_vec(7, &mut edges);
+    let expected = pagerank(&network, 0.15, 1e-14);
+    let ranks = pagerank_by_components(&network, 0.15, 1e-14);
+    assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    for i in 0..7 {
+        assert!((ranks[i] - expected[i]).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn test_trust_rank() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 and 1 are trusted and link to 2; the spam farm 3 <-> 4 is only
+    // linked from 2, 5 is only linked from the spam farm
+    let mut edges = vec![(0,1,0.0,0.0), (1,0,0.0,0.0), (0,2,0.0,0.0), (1,2,0.0,0.0),
+                         (2,3,0.0,0.0), (3,4,0.0,0.0), (4,3,0.0,0.0), (4,5,0.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let trust = trust_rank(&network, &[0, 1], 0.15, 1e-12);
+    assert!((trust.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(trust[0] > trust[2] && trust[2] > trust[5]);
+    assert_eq!(trust[0], trust[1]);
+
+    // uniform teleports give the usual ranks
+    let uniform = teleport_pagerank(&network, 0.15, &vec![1.0 / 6.0; 6], 1e-12);
+    let expected = pagerank(&network, 0.15, 1e-12);
+    for i in 0..6 {
+        assert!((uniform[i] - expected[i]).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn test_personalized_pagerank() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 2 -> 0 and 3 -> 2, 4 without arcs
+    let mut edges = vec![(0,1,0.0,0.0), (1,2,0.0,0.0), (2,0,0.0,0.0), (3,2,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let ranks = personalized_pagerank(&network, 0.2, 1e-12, &[3, 3]);
+    assert!((ranks.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert_eq!(0.0, ranks[4]);
+    assert!(ranks[3] > 0.0 && ranks[2] > ranks[0] && ranks[0] > ranks[1]);
+
+    let all: Vec<NodeId> = network.nodes().collect();
+    let uniform = personalized_pagerank(&network, 0.2, 1e-12, &all);
+    let expected = pagerank(&network, 0.2, 1e-12);
+    for i in 0..5 {
+        assert!((uniform[i] - expected[i]).abs() < 1e-9);
+    }
+}
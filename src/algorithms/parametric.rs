@@ -0,0 +1,238 @@
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::budget::{ lagrangian_distances, lagrangian_path };
+use super::min_cost_flow::{ MinCostFlowError, min_cost_flow };
+use super::path::Path;
+
+/// Tolerance for comparing parametric path costs.
+const PARAMETRIC_EPS: f64 = 1e-9;
+
+/// A range of the parameter `lambda` over which one path is shortest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParametricSegment {
+    pub from:  f64,
+    pub to:    f64,
+    /// The path with the sum of the base costs `c` as cost.
+    pub path:  Path,
+    /// The sum of the extra costs `d` along the path.
+    pub extra: Cost,
+}
+
+impl ParametricSegment {
+    /// The cost `c + lambda * d` of the path.
+    pub fn cost_at(&self, lambda: f64) -> Cost {
+        self.path.cost() + lambda * self.extra
+    }
+}
+
+/// Shortest paths from `source` to `target` under the arc costs
+/// `c + lambda * d` for every `lambda` in `[lambda_min, lambda_max]`, e.g.
+/// travel time plus money weighted by the value of time. `c` are the
+/// network's costs, `extra` holds `d` for every arc in arc order. Both must
+/// not be negative, nor must `lambda_min`.
+///
+/// The cost of the shortest path is a concave, piecewise linear function of
+/// `lambda`, the lower envelope of the lines of all paths. Its breakpoints
+/// are found by recursive bisection: the lines of the shortest paths at
+/// both ends of a range cross at some `lambda`, and either the shortest path
+/// there is on one of the lines, which makes the crossing a breakpoint, or
+/// it is a new line that splits the range. This takes two shortest path
+/// searches per breakpoint.
+///
+/// Returns the segments by increasing `lambda`, with the extra cost of the
+/// paths decreasing from one segment to the next, or no segment if `target`
+/// can't be reached.
+pub fn parametric_shortest_path<N: Network>(network: &N, extra: &[Cost], source: NodeId, target: NodeId,
+                                            lambda_min: f64, lambda_max: f64) -> Vec<ParametricSegment> {
+    assert_eq!(network.num_arcs(), extra.len(), "one extra cost per arc is required");
+    assert!(0.0 <= lambda_min && lambda_min <= lambda_max, "0 <= lambda_min <= lambda_max is required");
+    let n = network.num_nodes();
+    let mut forward: Vec<Vec<(NodeId, Cost, Cost)>> = vec![Vec::new(); n];
+    let mut reverse: Vec<Vec<(NodeId, Cost, Cost)>> = vec![Vec::new(); n];
+    let mut extras = extra.iter();
+    for i in network.nodes() {
+        for (j, cost, _) in network.out_arcs(i) {
+            let d = *extras.next().unwrap();
+            forward[i as usize].push((j, cost, d));
+            reverse[j as usize].push((i, cost, d));
+        }
+    }
+    if !lagrangian_distances(&reverse, target, 1.0, 0.0)[source as usize].is_finite() {
+        return Vec::new();
+    }
+
+    let solve = |lambda: f64| lagrangian_path(&forward, source, target, 1.0, lambda);
+    let first = solve(lambda_min);
+    let last = solve(lambda_max);
+    let mut breakpoints = vec![(lambda_min, first.clone())];
+    split(&solve, (lambda_min, &first), (lambda_max, &last), &mut breakpoints);
+
+    let mut segments: Vec<ParametricSegment> = Vec::with_capacity(breakpoints.len());
+    for (k, &(from, ref line)) in breakpoints.iter().enumerate() {
+        let to = breakpoints.get(k + 1).map_or(lambda_max, |b| b.0);
+        if to > from || segments.is_empty() && k + 1 == breakpoints.len() {
+            segments.push(ParametricSegment {
+                from:  from,
+                to:    to,
+                path:  Path::new(line.0.clone(), line.1),
+                extra: line.2,
+            });
+        }
+    }
+    segments
+}
+
+/// A range of the parameter `lambda` over which one flow is a minimum cost
+/// flow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParametricFlowSegment {
+    pub from:  f64,
+    pub to:    f64,
+    /// The flow on every arc, in arc order.
+    pub flows: DoubleVec,
+    /// The base cost `c` of the flow.
+    pub cost:  Cost,
+    /// The extra cost `d` of the flow.
+    pub extra: Cost,
+}
+
+impl ParametricFlowSegment {
+    /// The cost `c + lambda * d` of the flow.
+    pub fn cost_at(&self, lambda: f64) -> Cost {
+        self.cost + lambda * self.extra
+    }
+}
+
+/// Minimum cost flows for the `supplies` under the arc costs
+/// `c + lambda * d` for every `lambda` in `[lambda_min, lambda_max]`, the
+/// flow counterpart of `parametric_shortest_path` with the same arguments.
+///
+/// The minimum cost is again a concave, piecewise linear function of
+/// `lambda`, and its breakpoints are found by the same bisection, with one
+/// `min_cost_flow` per probe on a copy of the network with the combined
+/// costs. Returns the segments by increasing `lambda`, with the extra cost
+/// of the flows decreasing from one segment to the next.
+/// # Errors
+/// Those of `min_cost_flow`, which don't depend on the costs.
+pub fn parametric_min_cost_flow<N: Network>(network: &N, extra: &[Cost], supplies: &[f64],
+                                            lambda_min: f64, lambda_max: f64) -> Result<Vec<ParametricFlowSegment>, MinCostFlowError> {
+    assert_eq!(network.num_arcs(), extra.len(), "one extra cost per arc is required");
+    assert!(0.0 <= lambda_min && lambda_min <= lambda_max, "0 <= lambda_min <= lambda_max is required");
+    let base: DoubleVec = network.arc_values().map(|(_, _, cost, _)| cost).collect();
+    let flow_at = |lambda: f64| min_cost_flow(&combined_costs(network, extra, lambda), supplies);
+    let line = |flows: DoubleVec| {
+        let cost = flows.iter().zip(&base).map(|(f, c)| f * c).sum();
+        let extra_cost = flows.iter().zip(extra).map(|(f, d)| f * d).sum();
+        (flows, cost, extra_cost)
+    };
+
+    let first = line(flow_at(lambda_min)?.flows);
+    let last = line(flow_at(lambda_max)?.flows);
+    let solve = |lambda: f64| line(flow_at(lambda).expect("feasibility doesn't depend on the costs").flows);
+    let mut breakpoints = vec![(lambda_min, first.clone())];
+    split(&solve, (lambda_min, &first), (lambda_max, &last), &mut breakpoints);
+
+    let mut segments: Vec<ParametricFlowSegment> = Vec::with_capacity(breakpoints.len());
+    for (k, &(from, ref line)) in breakpoints.iter().enumerate() {
+        let to = breakpoints.get(k + 1).map_or(lambda_max, |b| b.0);
+        if to > from || segments.is_empty() && k + 1 == breakpoints.len() {
+            segments.push(ParametricFlowSegment {
+                from:  from,
+                to:    to,
+                flows: line.0.clone(),
+                cost:  line.1,
+                extra: line.2,
+            });
+        }
+    }
+    Ok(segments)
+}
+
+/// A copy of `network` with the costs `c + lambda * d`, arcs in the same
+/// order.
+fn combined_costs<N: Network>(network: &N, extra: &[Cost], lambda: f64) -> CompactStar {
+    if network.num_nodes() == 0 {
+        return empty_compact_star();
+    }
+    let mut edges: Vec<_> = network.arc_values().zip(extra)
+        .map(|((i, j, cost, capacity), d)| (i, j, cost + lambda * d, capacity))
+        .collect();
+    compact_star_from_edge_vec(network.num_nodes(), &mut edges)
+}
+
+/// Adds the breakpoints strictly between the optimal solutions `lo` and
+/// `hi`, found at the ends of a range of `lambda`, each with the solution
+/// that is optimal from there on. Solutions are given with their base and
+/// extra cost.
+fn split<T: Clone, F: Fn(f64) -> (T, Cost, Cost)>(solve: &F, lo: (f64, &(T, Cost, Cost)), hi: (f64, &(T, Cost, Cost)),
+                                                  breakpoints: &mut Vec<(f64, (T, Cost, Cost))>) {
+    let (a, b) = (lo.1, hi.1);
+    if a.2 - b.2 <= PARAMETRIC_EPS {
+        // the same line, no breakpoint in between
+        return;
+    }
+    let crossing = ((b.1 - a.1) / (a.2 - b.2)).max(lo.0).min(hi.0);
+    let middle = solve(crossing);
+    if middle.1 + crossing * middle.2 >= a.1 + crossing * a.2 - PARAMETRIC_EPS {
+        breakpoints.push((crossing, b.clone()));
+        return;
+    }
+    split(solve, lo, (crossing, &middle), breakpoints);
+    split(solve, (crossing, &middle), hi, breakpoints);
+}
+
+#[test]
+fn test_parametric_shortest_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // three routes from 0 to 4 as (time, money): the toll road 0 -> 1 -> 4
+    // (2, 10), 0 -> 2 -> 4 (4, 4) and the free road 0 -> 3 -> 4 (10, 0)
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,2.0,0.0),
+                         (0,3,5.0,0.0),
+                         (1,4,1.0,0.0),
+                         (2,4,2.0,0.0),
+                         (3,4,5.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let money = vec![5.0, 2.0, 0.0, 5.0, 2.0, 0.0];
+    let segments = parametric_shortest_path(&network, &money, 0, 4, 0.0, 10.0);
+    assert_eq!(3, segments.len());
+    assert_eq!((0.0, 1.0 / 3.0), (segments[0].from, segments[0].to));
+    assert_eq!(&vec![0,1,4], segments[0].path.nodes());
+    assert_eq!((1.0 / 3.0, 1.5), (segments[1].from, segments[1].to));
+    assert_eq!(&vec![0,2,4], segments[1].path.nodes());
+    assert_eq!((4.0, 4.0), (segments[1].path.cost(), segments[1].extra));
+    assert_eq!((1.5, 10.0), (segments[2].from, segments[2].to));
+    assert_eq!(&vec![0,3,4], segments[2].path.nodes());
+    assert_eq!(10.0, segments[2].cost_at(7.0));
+
+    let segments = parametric_shortest_path(&network, &money, 0, 4, 2.0, 3.0);
+    assert_eq!(1, segments.len());
+    assert_eq!(&vec![0,3,4], segments[0].path.nodes());
+    assert!(parametric_shortest_path(&network, &money, 0, 5, 0.0, 1.0).is_empty());
+}
+
+#[test]
+fn test_parametric_min_cost_flow() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 3 units from 0 to 3 over a fast toll road 0 -> 1 -> 3 with capacity
+    // 2 and time 2, money 4, and a slow free road 0 -> 2 -> 3 with time 6
+    let mut edges = vec![(0,1,1.0,2.0),
+                         (0,2,3.0,10.0),
+                         (1,3,1.0,2.0),
+                         (2,3,3.0,10.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let money = vec![2.0, 0.0, 2.0, 0.0];
+    let supplies = vec![3.0, 0.0, 0.0, -3.0];
+    let segments = parametric_min_cost_flow(&network, &money, &supplies, 0.0, 5.0).unwrap();
+    assert_eq!(2, segments.len());
+    // the toll road pays off while 2 + 4 lambda < 6
+    assert_eq!((0.0, 1.0), (segments[0].from, segments[0].to));
+    assert_eq!(vec![2.0, 1.0, 2.0, 1.0], segments[0].flows);
+    assert_eq!((10.0, 8.0), (segments[0].cost, segments[0].extra));
+    assert_eq!((1.0, 5.0), (segments[1].from, segments[1].to));
+    assert_eq!(vec![0.0, 3.0, 0.0, 3.0], segments[1].flows);
+    assert_eq!(18.0, segments[1].cost_at(2.0));
+
+    let unbalanced = parametric_min_cost_flow(&network, &money, &[1.0, 0.0, 0.0, 0.0], 0.0, 1.0);
+    assert_eq!(Err(MinCostFlowError::Unbalanced { total: 1.0 }), unbalanced);
+}
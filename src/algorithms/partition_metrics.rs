@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+
+use super::super::{ Network, NodeVec };
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// Quality measures of a single cluster of a partition. Arcs are counted
+/// regardless of their direction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterQuality {
+    /// The number of nodes in the cluster.
+    pub size:           usize,
+    /// Arcs with both end nodes in the cluster.
+    pub internal_arcs:  usize,
+    /// Arcs with exactly one end node in the cluster.
+    pub cut_arcs:       usize,
+    /// Sum of the in- and out-degrees of the nodes in the cluster.
+    pub volume:         usize,
+    /// `cut_arcs / min(volume, total volume - volume)`, or 0 if that
+    /// minimum is 0.
+    pub conductance:    f64,
+}
+
+fn num_labels(labels: &NodeVec) -> usize {
+    labels.iter().max().map_or(0, |l| *l as usize + 1)
+}
+
+/// Computes the quality of every cluster of the partition given by
+/// `labels`, indexed by label. Labels without nodes yield empty clusters.
+pub fn cluster_quality<N: Network>(network: &N, labels: &NodeVec) -> Vec<ClusterQuality> {
+    assert_eq!(network.num_nodes(), labels.len());
+    let mut clusters = vec![ClusterQuality {
+        size: 0, internal_arcs: 0, cut_arcs: 0, volume: 0, conductance: 0.0,
+    }; num_labels(labels)];
+    for label in labels {
+        clusters[*label as usize].size += 1;
+    }
+    for (i, j) in network.arcs() {
+        let (ci, cj) = (labels[i as usize] as usize, labels[j as usize] as usize);
+        clusters[ci].volume += 1;
+        clusters[cj].volume += 1;
+        if ci == cj {
+            clusters[ci].internal_arcs += 1;
+        } else {
+            clusters[ci].cut_arcs += 1;
+            clusters[cj].cut_arcs += 1;
+        }
+    }
+    let total_volume = 2 * network.num_arcs();
+    for cluster in &mut clusters {
+        let denominator = ::std::cmp::min(cluster.volume, total_volume - cluster.volume);
+        if denominator > 0 {
+            cluster.conductance = cluster.cut_arcs as f64 / denominator as f64;
+        }
+    }
+    clusters
+}
+
+/// The fraction of arcs that have both end nodes in the same cluster, or 1
+/// for a network without arcs.
+pub fn coverage<N: Network>(network: &N, labels: &NodeVec) -> f64 {
+    assert_eq!(network.num_nodes(), labels.len());
+    if network.num_arcs() == 0 {
+        return 1.0;
+    }
+    let internal = network.arcs()
+        .filter(|&(i, j)| labels[i as usize] == labels[j as usize])
+        .count();
+    internal as f64 / network.num_arcs() as f64
+}
+
+/// Cluster sizes of both partitions and the sizes of their pairwise
+/// intersections.
+struct Contingency {
+    a:      Vec<usize>,
+    b:      Vec<usize>,
+    joint:  HashMap<(u32, u32), usize>,
+}
+
+fn contingency(a: &NodeVec, b: &NodeVec) -> Contingency {
+    assert_eq!(a.len(), b.len());
+    let mut table = Contingency {
+        a: vec![0; num_labels(a)],
+        b: vec![0; num_labels(b)],
+        joint: HashMap::new(),
+    };
+    for (la, lb) in a.iter().zip(b) {
+        table.a[*la as usize] += 1;
+        table.b[*lb as usize] += 1;
+        *table.joint.entry((*la, *lb)).or_insert(0) += 1;
+    }
+    table
+}
+
+fn entropy(sizes: &[usize], n: f64) -> f64 {
+    sizes.iter()
+        .filter(|s| **s > 0)
+        .map(|s| { let p = *s as f64 / n; -p * p.ln() })
+        .sum()
+}
+
+/// Normalized mutual information `2 I(A; B) / (H(A) + H(B))` of two
+/// partitions of the same nodes, between 0 for independent and 1 for
+/// identical partitions. Two trivial partitions (a single cluster each)
+/// are considered identical.
+pub fn normalized_mutual_information(a: &NodeVec, b: &NodeVec) -> f64 {
+    let n = a.len() as f64;
+    let table = contingency(a, b);
+    let (ha, hb) = (entropy(&table.a, n), entropy(&table.b, n));
+    if ha + hb == 0.0 {
+        return 1.0;
+    }
+    let mutual: f64 = table.joint.iter()
+        .map(|(&(la, lb), &count)| {
+            let count = count as f64;
+            let expected = table.a[la as usize] as f64 * table.b[lb as usize] as f64;
+            count / n * (n * count / expected).ln()
+        })
+        .sum();
+    2.0 * mutual / (ha + hb)
+}
+
+fn pairs(k: usize) -> f64 {
+    let k = k as f64;
+    k * (k - 1.0) / 2.0
+}
+
+/// Adjusted Rand index of two partitions of the same nodes (Hubert, Arabie:
+/// "Comparing partitions"): the fraction of node pairs both partitions
+/// agree on, corrected for chance. It is 1 for identical partitions, around
+/// 0 for random ones and may become negative. Partitions of fewer than
+/// two nodes have no pairs to disagree on and are considered identical.
+pub fn adjusted_rand_index(a: &NodeVec, b: &NodeVec) -> f64 {
+    let table = contingency(a, b);
+    if a.len() < 2 {
+        return 1.0;
+    }
+    let index: f64 = table.joint.values().map(|c| pairs(*c)).sum();
+    let sum_a: f64 = table.a.iter().map(|c| pairs(*c)).sum();
+    let sum_b: f64 = table.b.iter().map(|c| pairs(*c)).sum();
+    let expected = sum_a * sum_b / pairs(a.len());
+    let maximum = (sum_a + sum_b) / 2.0;
+    if maximum == expected {
+        return 1.0;
+    }
+    (index - expected) / (maximum - expected)
+}
+
+#[test]
+fn test_cluster_quality() {
+    // two directed triangles joined by the bridge 2 -> 3
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,0,1.0,0.0),
+                         (3,4,1.0,0.0), (4,5,1.0,0.0), (5,3,1.0,0.0),
+                         (2,3,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let labels = vec![0,0,0,1,1,1];
+    let clusters = cluster_quality(&network, &labels);
+    assert_eq!(2, clusters.len());
+    assert_eq!(ClusterQuality { size: 3, internal_arcs: 3, cut_arcs: 1, volume: 7, conductance: 1.0 / 7.0 }, clusters[0]);
+    assert_eq!(1.0 / 7.0, clusters[1].conductance);
+    assert_eq!(6.0 / 7.0, coverage(&network, &labels));
+    assert_eq!(1.0, coverage(&network, &vec![0; 6]));
+    assert_eq!(0.0, cluster_quality(&network, &vec![0; 6])[0].conductance);
+}
+
+#[test]
+fn test_partition_similarity() {
+    let a = vec![0,0,0,1,1,1];
+    let relabeled = vec![1,1,1,0,0,0];
+    assert!((normalized_mutual_information(&a, &relabeled) - 1.0).abs() < 1e-12);
+    assert!((adjusted_rand_index(&a, &relabeled) - 1.0).abs() < 1e-12);
+
+    // every cluster of b meets both clusters of a equally
+    let b = vec![0,1,2,0,1,2];
+    assert!(normalized_mutual_information(&a, &b).abs() < 1e-12);
+    assert!(adjusted_rand_index(&a, &b) < 0.0);
+
+    // 2 agreeing pairs, 1.2 expected by chance, 4.5 at most
+    let c = vec![0,0,0,1,1,1];
+    let d = vec![0,0,1,1,2,2];
+    assert!((adjusted_rand_index(&c, &d) - 8.0 / 33.0).abs() < 1e-12);
+    assert_eq!(1.0, normalized_mutual_information(&vec![0; 4], &vec![0; 4]));
+    assert_eq!(1.0, adjusted_rand_index(&vec![0], &vec![3]));
+    assert_eq!(1.0, adjusted_rand_index(&vec![], &vec![]));
+}
@@ -0,0 +1,119 @@
+use std::fmt;
+
+use super::super::{ Cost, DoubleVec, NodeId, NodeVec };
+use super::super::pretty::{ LabeledDisplay, node_label };
+
+/// A route through a network, given as the sequence of visited nodes and
+/// the accumulated cost along the way.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path {
+    nodes: NodeVec,
+    cost:  Cost,
+}
+
+impl Path {
+    pub fn new(nodes: NodeVec, cost: Cost) -> Path {
+        Path {
+            nodes: nodes,
+            cost:  cost,
+        }
+    }
+
+    /// The visited nodes, starting with the source and ending with the target.
+    pub fn nodes(&self) -> &NodeVec {
+        &self.nodes
+    }
+
+    pub fn cost(&self) -> Cost {
+        self.cost
+    }
+
+    pub fn source(&self) -> Option<NodeId> {
+        self.nodes.first().map(|p| *p)
+    }
+
+    pub fn target(&self) -> Option<NodeId> {
+        self.nodes.last().map(|p| *p)
+    }
+
+    /// The number of arcs on the path.
+    pub fn hops(&self) -> usize {
+        self.nodes.len().saturating_sub(1)
+    }
+
+    /// The arcs on the path as `(from, to)` pairs, in order.
+    pub fn arcs(&self) -> Vec<(NodeId, NodeId)> {
+        self.nodes.windows(2).map(|arc| (arc[0], arc[1])).collect()
+    }
+}
+
+/// The path from `source` to `target` in the shortest path tree given by
+/// predecessors `pred` and distances `dist`, as returned by `dijkstra` and
+/// `bellman_ford`, with the distance of `target` as cost. Returns `None` if
+/// `target` is unreachable.
+pub fn reconstruct_path(pred: &NodeVec, dist: &DoubleVec, source: NodeId, target: NodeId) -> Option<Path> {
+    trace_path(pred, source, target).map(|nodes| Path::new(nodes, dist[target as usize]))
+}
+
+/// Prints the path as `0 -> 2 -> 3 (cost: 5)`.
+impl LabeledDisplay for Path {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        let names: Vec<String> = self.nodes.iter().map(|i| node_label(labels, *i)).collect();
+        write!(f, "{} (cost: {})", names.join(" -> "), self.cost)
+    }
+}
+
+impl fmt::Display for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
+}
+
+/// Walks a predecessor list back from `target` to `source` and returns the
+/// nodes on the way in forward order. A predecessor outside of the node
+/// range (i.e. the network's invalid id) marks an unreachable node.
+pub(crate) fn trace_path(pred: &NodeVec, source: NodeId, target: NodeId) -> Option<NodeVec> {
+    let n = pred.len();
+    let mut nodes = vec![target];
+    let mut current = target;
+    while current != source {
+        let p = pred[current as usize];
+        if p as usize >= n || nodes.len() > n {
+            return None;
+        }
+        nodes.push(p);
+        current = p;
+    }
+    nodes.reverse();
+    Some(nodes)
+}
+
+#[test]
+fn test_trace_path() {
+    let pred = vec![6,0,0,2,2,4];
+    assert_eq!(Some(vec![0,2,4,5]), trace_path(&pred, 0, 5));
+    assert_eq!(Some(vec![0]), trace_path(&pred, 0, 0));
+    let pred = vec![3,0,3];
+    assert_eq!(None, trace_path(&pred, 0, 2));
+}
+
+#[test]
+fn test_reconstruct_path() {
+    let pred = vec![6,0,0,2,2,4];
+    let dist = vec![0.0,6.0,4.0,5.0,6.0,9.0];
+    let path = reconstruct_path(&pred, &dist, 0, 5).unwrap();
+    assert_eq!(&vec![0,2,4,5], path.nodes());
+    assert_eq!(9.0, path.cost());
+    assert_eq!(3, path.hops());
+    assert_eq!(vec![(0,2), (2,4), (4,5)], path.arcs());
+    assert_eq!(0, reconstruct_path(&pred, &dist, 0, 0).unwrap().hops());
+    assert_eq!(None, reconstruct_path(&vec![3,0,3], &dist, 0, 2));
+}
+
+#[test]
+fn test_display_path() {
+    let path = Path::new(vec![0,2,3], 5.0);
+    assert_eq!("0 -> 2 -> 3 (cost: 5)", format!("{}", path));
+    let labels = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    assert_eq!("a -> c -> 3 (cost: 5)", format!("{}", path.with_labels(&labels)));
+}
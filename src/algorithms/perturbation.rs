@@ -0,0 +1,60 @@
+use super::super::{ Cost, Network };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::super::random::Rng;
+
+/// Returns a copy of `network` in which every arc cost is increased by a
+/// random amount in `[0, magnitude)`. The perturbations are drawn from a
+/// generator seeded with `seed`, in arc order, so the same network and seed
+/// always give the same result.
+///
+/// With a `magnitude` well below the smallest difference between the costs
+/// of two distinct paths, shortest paths in the perturbed network are
+/// shortest paths in the original one, and almost surely unique.
+pub fn perturb_costs<N: Network>(network: &N, magnitude: Cost, seed: u64) -> CompactStar {
+    if network.num_nodes() == 0 {
+        return empty_compact_star();
+    }
+    let mut rng = Rng::new(seed);
+    let mut edges: Vec<_> = network.arc_values()
+        .map(|(i, j, cost, capacity)| (i, j, cost + magnitude * rng.next_f64(), capacity))
+        .collect();
+    compact_star_from_edge_vec(network.num_nodes(), &mut edges)
+}
+
+#[test]
+fn test_perturb_costs() {
+    use super::shortest_path_dag::shortest_path_dag;
+    // a 3x3 grid with unit costs, 6 shortest paths from corner to corner
+    let mut edges = Vec::new();
+    for row in 0..3 {
+        for col in 0..3 {
+            let node = row * 3 + col;
+            if col < 2 { edges.push((node, node + 1, 1.0, 0.0)); }
+            if row < 2 { edges.push((node, node + 3, 1.0, 0.0)); }
+        }
+    }
+    let compact_star = compact_star_from_edge_vec(9, &mut edges);
+    let perturbed = perturb_costs(&compact_star, 1e-6, 42);
+    assert_eq!(perturbed, perturb_costs(&compact_star, 1e-6, 42));
+    for ((_, _, cost, _), (_, _, perturbed_cost, _)) in compact_star.arc_values().zip(perturbed.arc_values()) {
+        let delta = perturbed_cost - cost;
+        assert!(delta >= 0.0 && delta < 1e-6);
+    }
+    assert_eq!(6.0, shortest_path_dag(&compact_star, 0, 0.0).path_counts()[8]);
+    assert_eq!(1.0, shortest_path_dag(&perturbed, 0, 0.0).path_counts()[8]);
+}
+
+#[test]
+fn test_perturb_parallel_arcs() {
+    let mut edges = vec![(0,1,1.0,10.0), (0,1,5.0,20.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    let perturbed = perturb_costs(&compact_star, 1e-6, 7);
+    let arcs: Vec<_> = perturbed.out_arcs(0).collect();
+    assert!(arcs[0].1 < 1.0 + 1e-6 && arcs[1].1 >= 5.0);
+    assert_eq!((10.0, 20.0), (arcs[0].2, arcs[1].2));
+}
+
+#[test]
+fn test_perturb_empty() {
+    assert_eq!(empty_compact_star(), perturb_costs(&empty_compact_star(), 1e-6, 7));
+}
@@ -0,0 +1,144 @@
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::search_algorithms::heap_dijkstra;
+
+/// Costs and flows within this tolerance are treated as equal.
+const OPTIMALITY_EPS: f64 = 1e-9;
+
+/// Returns node potentials derived from the shortest path tree rooted at
+/// `source`. Following Ahuja, Magnati, Orlin: "Network Flows", the potential
+/// of a node is its negative distance from the source, `pi(i) = -d(i)`, so
+/// that all reduced costs are non-negative and the arcs of the shortest
+/// path tree have reduced cost zero. Unreachable nodes get the negative
+/// infinity of the network.
+pub fn node_potentials<N: Network>(network: &N, source: NodeId) -> DoubleVec {
+    let (_, dist) = heap_dijkstra(network, source);
+    dist.iter().map(|d| -d).collect()
+}
+
+/// Returns the reduced cost `c(i,j) - pi(i) + pi(j)` of every arc, in arc
+/// order (see `frank_wolfe` for the numbering).
+pub fn reduced_costs<N: Network>(network: &N, potentials: &DoubleVec) -> DoubleVec {
+    network.arc_values()
+        .map(|(i, j, cost, _)| reduced_cost(potentials, i, j, cost))
+        .collect()
+}
+
+fn reduced_cost(potentials: &DoubleVec, i: NodeId, j: NodeId, cost: Cost) -> Cost {
+    cost - potentials[i as usize] + potentials[j as usize]
+}
+
+/// Checks the reduced cost optimality conditions of a flow for the given
+/// potentials: arcs with residual capacity (`flow < capacity`) must have
+/// non-negative reduced cost and arcs carrying flow must have non-positive
+/// reduced cost. `flow` holds one value per arc, in arc order.
+pub fn satisfies_reduced_cost_optimality<N: Network>(network: &N, flow: &DoubleVec, potentials: &DoubleVec) -> bool {
+    assert!(network.num_arcs() == flow.len());
+    for (a, (i, j, cost, capacity)) in network.arc_values().enumerate() {
+        let reduced = reduced_cost(potentials, i, j, cost);
+        if flow[a] < capacity - OPTIMALITY_EPS && reduced < -OPTIMALITY_EPS {
+            return false;
+        }
+        if flow[a] > OPTIMALITY_EPS && reduced > OPTIMALITY_EPS {
+            return false;
+        }
+    }
+    true
+}
+
+/// Checks whether a flow is a minimum cost flow for its own node
+/// imbalances. The flow must respect the arc capacities; conservation is
+/// not checked, since the supplies are implied by the flow itself.
+///
+/// Potentials are computed by a label correcting search on the residual
+/// network, started from all nodes at once. If the residual network has a
+/// negative cycle, no such potentials exist and the flow is not optimal;
+/// otherwise the reduced cost optimality conditions are verified with them.
+pub fn is_optimal_flow<N: Network>(network: &N, flow: &DoubleVec) -> bool {
+    assert!(network.num_arcs() == flow.len());
+    let n = network.num_nodes();
+
+    let mut residual = Vec::with_capacity(2 * flow.len());
+    for (a, (i, j, cost, capacity)) in network.arc_values().enumerate() {
+        if flow[a] < -OPTIMALITY_EPS || flow[a] > capacity + OPTIMALITY_EPS {
+            return false;
+        }
+        if flow[a] < capacity - OPTIMALITY_EPS {
+            residual.push((i, j, cost));
+        }
+        if flow[a] > OPTIMALITY_EPS {
+            residual.push((j, i, -cost));
+        }
+    }
+
+    // Bellman-Ford from a virtual root connected to every node at cost zero
+    let mut d = vec![0.0; n];
+    for round in 0..(n + 1) {
+        let mut changed = false;
+        for &(i, j, cost) in &residual {
+            if d[j as usize] > d[i as usize] + cost + OPTIMALITY_EPS {
+                d[j as usize] = d[i as usize] + cost;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+        if round == n {
+            return false;
+        }
+    }
+
+    let potentials: DoubleVec = d.iter().map(|d| -d).collect();
+    satisfies_reduced_cost_optimality(network, flow, &potentials)
+}
+
+#[test]
+fn test_potentials_and_reduced_costs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let potentials = node_potentials(&compact_star, 0);
+    assert_eq!(vec![0.0,-6.0,-4.0,-5.0,-6.0,-9.0], potentials);
+    let reduced = reduced_costs(&compact_star, &potentials);
+    assert_eq!(vec![0.0,0.0,4.0,3.0,0.0,0.0,3.0,2.0,0.0], reduced);
+}
+
+#[test]
+fn test_is_optimal_flow() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // two routes from 0 to 3: 0-1-3 costs 2, 0-2-3 costs 4
+    let mut edges = vec![
+        (0,1,1.0,2.0),
+        (0,2,2.0,5.0),
+        (1,3,1.0,2.0),
+        (2,3,2.0,5.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    // 3 units: the cheap route is saturated first
+    assert!(is_optimal_flow(&compact_star, &vec![2.0, 1.0, 2.0, 1.0]));
+    assert!(!is_optimal_flow(&compact_star, &vec![1.0, 2.0, 1.0, 2.0]));
+    // exceeding a capacity
+    assert!(!is_optimal_flow(&compact_star, &vec![3.0, 0.0, 3.0, 0.0]));
+
+    let potentials = vec![0.0, -1.0, -2.0, -4.0];
+    assert!(satisfies_reduced_cost_optimality(&compact_star, &vec![2.0, 1.0, 2.0, 1.0], &potentials));
+}
+
+#[test]
+fn test_is_optimal_flow_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // two parallel arcs 0-1, the cheap one with capacity 1
+    let mut edges = vec![(0,1,1.0,1.0), (0,1,3.0,5.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    assert_eq!(vec![1.0, 3.0], reduced_costs(&compact_star, &vec![0.0, 0.0]));
+    assert!(is_optimal_flow(&compact_star, &vec![1.0, 1.0]));
+    assert!(!is_optimal_flow(&compact_star, &vec![0.0, 2.0]));
+}
@@ -0,0 +1,128 @@
+use std::f64;
+
+use super::super::{ Cost, Network, NodeId };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::path::{ Path, trace_path };
+
+/// The classes an arc belongs to as a bit set, e.g. `TOLL | SCENIC` for
+/// constants `TOLL = 1` and `SCENIC = 2` defined by the application.
+pub type ArcClasses = u32;
+
+/// Per query routing preferences: cost multipliers for arc classes. A
+/// multiplier above `1.0` avoids a class, one below `1.0` prefers it. An
+/// arc in several classes gets the product of their multipliers.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Preferences {
+    multipliers: Vec<(ArcClasses, f64)>,
+}
+
+impl Preferences {
+    pub fn new() -> Preferences {
+        Preferences::default()
+    }
+
+    /// Multiplies the cost of all arcs in any of `classes` by `multiplier`.
+    /// # Panics
+    /// If `multiplier` is not positive.
+    pub fn with(mut self, classes: ArcClasses, multiplier: f64) -> Preferences {
+        assert!(multiplier > 0.0, "multipliers must be positive");
+        self.multipliers.push((classes, multiplier));
+        self
+    }
+
+    /// The factor applied to the cost of an arc in `classes`.
+    pub fn factor(&self, classes: ArcClasses) -> f64 {
+        self.multipliers.iter()
+            .filter(|&&(mask, _)| classes & mask != 0)
+            .fold(1.0, |factor, &(_, multiplier)| factor * multiplier)
+    }
+}
+
+/// A route found under routing preferences.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreferredRoute {
+    /// The route with its actual cost.
+    pub path:           Path,
+    /// The cost of the route with the preference multipliers applied, which
+    /// is what the route minimizes.
+    pub perceived_cost: Cost,
+}
+
+/// Finds the route from `source` to `target` that minimizes the arc costs
+/// weighted by `preferences`. `arc_classes` holds the classes of every arc
+/// in arc order. The multipliers are applied during the relaxation of the
+/// arcs, so different preference profiles share the same network.
+/// Returns `None` if `target` can't be reached.
+pub fn preferred_route<N: Network>(network: &N, arc_classes: &[ArcClasses], source: NodeId, target: NodeId,
+                                   preferences: &Preferences) -> Option<PreferredRoute> {
+    let n = network.num_nodes();
+    let mut heap = BinaryHeap::new();
+    let mut pred = vec![network.invalid_id(); n];
+    let mut d = vec![f64::INFINITY; n];
+    let mut actual = vec![0.0; n];
+    let mut marked = vec![false; n];
+
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+
+    let first_arcs = network.first_arcs();
+
+    while let Some(next_node) = heap.find_min() {
+        heap.delete_min();
+        let i = next_node as usize;
+        if marked[i] {
+            continue;
+        }
+        marked[i] = true;
+        if next_node == target {
+            let nodes = trace_path(&pred, source, target)?;
+            return Some(PreferredRoute {
+                path:           Path::new(nodes, actual[i]),
+                perceived_cost: d[i],
+            });
+        }
+
+        for (k, (adjacent_node, cost, _)) in network.out_arcs(next_node).enumerate() {
+            let j = adjacent_node as usize;
+            let perceived = cost * preferences.factor(arc_classes[first_arcs[i] + k]);
+            if d[j] > d[i] + perceived {
+                pred[j] = next_node;
+                d[j] = d[i] + perceived;
+                actual[j] = actual[i] + cost;
+                heap.insert(adjacent_node, d[j]);
+            }
+        }
+    }
+    None
+}
+
+#[test]
+fn test_preferred_route() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    const TOLL: ArcClasses = 1;
+    const SCENIC: ArcClasses = 2;
+    // 0 -> 1 -> 3 over a toll road (2 + 2), 0 -> 2 -> 3 on a scenic road (3 + 3),
+    // 0 -> 3 on a plain road (7)
+    let mut edges = vec![(0,1,2.0,0.0),
+                         (0,2,3.0,0.0),
+                         (0,3,7.0,0.0),
+                         (1,3,2.0,0.0),
+                         (2,3,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let classes = vec![0, SCENIC, 0, TOLL, SCENIC];
+
+    let fastest = preferred_route(&compact_star, &classes, 0, 3, &Preferences::new()).unwrap();
+    assert_eq!(&vec![0,1,3], fastest.path.nodes());
+    assert_eq!(4.0, fastest.perceived_cost);
+
+    let no_tolls = Preferences::new().with(TOLL, 5.0);
+    let route = preferred_route(&compact_star, &classes, 0, 3, &no_tolls).unwrap();
+    assert_eq!(&vec![0,2,3], route.path.nodes());
+    assert_eq!(6.0, route.path.cost());
+
+    let scenic = Preferences::new().with(TOLL, 5.0).with(SCENIC, 0.5);
+    let route = preferred_route(&compact_star, &classes, 0, 3, &scenic).unwrap();
+    assert_eq!(3.0, route.perceived_cost);
+    assert_eq!(2.5, Preferences::new().with(TOLL, 5.0).with(SCENIC | TOLL, 0.5).factor(TOLL));
+    assert_eq!(None, preferred_route(&compact_star, &classes, 3, 0, &scenic));
+}
@@ -0,0 +1,210 @@
+use super::super::{ DoubleVec, Network, NodeId };
+use super::super::hyperloglog::HyperLogLog;
+use super::components::strongly_connected_components;
+
+/// The number of nodes reachable from every node, including the node
+/// itself. In a dependency graph with arcs from a component to what depends
+/// on it, this is the blast radius of a failure.
+///
+/// All nodes of a strongly connected component reach the same set, so the
+/// sets are computed once per component, as bitsets of `n` bits that are
+/// merged along the condensation in reverse topological order. This is
+/// exact, but needs `n / 8` bytes per component and `O(n / 64)` time per
+/// arc between components; use `estimate_reachable_set_sizes` for large
+/// networks.
+pub fn reachable_set_sizes<N: Network>(network: &N) -> Vec<usize> {
+    let n = network.num_nodes();
+    let words = (n + 63) / 64;
+    let (count, labels) = strongly_connected_components(network);
+    let successors = component_successors(network, count, &labels);
+    let mut reachable = vec![vec![0u64; words]; count];
+    for i in 0..n {
+        reachable[labels[i] as usize][i / 64] |= 1 << (i % 64);
+    }
+    // successors always have lower numbers, so they are complete already
+    for c in 0..count {
+        for d in &successors[c] {
+            let (done, current) = reachable.split_at_mut(c);
+            for (word, theirs) in current[0].iter_mut().zip(&done[*d]) {
+                *word |= *theirs;
+            }
+        }
+    }
+    let sizes: Vec<usize> = reachable.iter()
+        .map(|set| set.iter().map(|word| word.count_ones() as usize).sum())
+        .collect();
+    labels.iter().map(|c| sizes[*c as usize]).collect()
+}
+
+/// Estimates `reachable_set_sizes` with a HyperLogLog counter of
+/// `2^log2_registers` registers per strongly connected component, merged
+/// along the condensation like the exact bitsets. The relative error is
+/// about `1.04 / sqrt(2^log2_registers)`, the memory `2^log2_registers`
+/// bytes per component, independent of the number of nodes.
+/// # Panics
+/// If `log2_registers` is not within `4..17`.
+pub fn estimate_reachable_set_sizes<N: Network>(network: &N, log2_registers: u8) -> DoubleVec {
+    let n = network.num_nodes();
+    let (count, labels) = strongly_connected_components(network);
+    let successors = component_successors(network, count, &labels);
+    let mut counters = vec![HyperLogLog::new(log2_registers); count];
+    for i in 0..n {
+        counters[labels[i] as usize].insert(i as NodeId);
+    }
+    for c in 0..count {
+        for d in &successors[c] {
+            let (done, current) = counters.split_at_mut(c);
+            current[0].union(&done[*d]);
+        }
+    }
+    let estimates: DoubleVec = counters.iter().map(|counter| counter.estimate()).collect();
+    labels.iter().map(|c| estimates[*c as usize]).collect()
+}
+
+/// The exact reachability relation of a network as a dense bit matrix:
+/// row `i` has bit `j` set if `j` is reachable from `i`. Every node reaches
+/// itself. The rows are packed into 64-bit words, so the matrix takes
+/// `n * n / 8` bytes, about 12 MB for 10,000 nodes; meant for small
+/// networks whose reachability is queried many times.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReachabilityMatrix {
+    num_nodes: usize,
+    words:     usize,
+    bits:      Vec<u64>,
+}
+
+impl ReachabilityMatrix {
+    /// The transitive closure of `network`, by a breadth first search from
+    /// every node in `O(n * m)` time.
+    pub fn new<N: Network>(network: &N) -> ReachabilityMatrix {
+        let n = network.num_nodes();
+        let words = (n + 63) / 64;
+        let adj_lists: Vec<Vec<NodeId>> = network.nodes().map(|i| network.adjacent(i)).collect();
+        let mut bits = vec![0u64; n * words];
+        let mut queue = Vec::with_capacity(n);
+        for source in 0..n {
+            let row = &mut bits[source * words..(source + 1) * words];
+            row[source / 64] |= 1 << (source % 64);
+            queue.clear();
+            queue.push(source as NodeId);
+            let mut next = 0;
+            while next < queue.len() {
+                let i = queue[next];
+                next += 1;
+                for j in &adj_lists[i as usize] {
+                    let (word, bit) = (*j as usize / 64, 1 << (*j % 64));
+                    if row[word] & bit == 0 {
+                        row[word] |= bit;
+                        queue.push(*j);
+                    }
+                }
+            }
+        }
+        ReachabilityMatrix {
+            num_nodes: n,
+            words:     words,
+            bits:      bits,
+        }
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    /// Whether `to` is reachable from `from`.
+    pub fn reaches(&self, from: NodeId, to: NodeId) -> bool {
+        self.row(from)[to as usize / 64] & (1 << (to % 64)) != 0
+    }
+
+    /// The number of nodes reachable from `from`, including itself.
+    pub fn count_reachable(&self, from: NodeId) -> usize {
+        self.row(from).iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    /// Whether every node of `targets` is reachable from every node of
+    /// `sources`, checked a word at a time.
+    pub fn all_reachable(&self, sources: &[NodeId], targets: &[NodeId]) -> bool {
+        let mut mask = vec![0u64; self.words];
+        for j in targets {
+            mask[*j as usize / 64] |= 1 << (*j % 64);
+        }
+        sources.iter().all(|i| self.row(*i).iter().zip(&mask).all(|(word, m)| word & m == *m))
+    }
+
+    /// Whether every node reaches every other node, i.e. whether the
+    /// network is strongly connected.
+    pub fn all_pairs_reachable(&self) -> bool {
+        (0..self.num_nodes).all(|i| self.count_reachable(i as NodeId) == self.num_nodes)
+    }
+
+    /// The pairs `(from, to)` with `to` not reachable from `from`, in
+    /// ascending order.
+    pub fn unreachable_pairs(&self) -> Vec<(NodeId, NodeId)> {
+        let n = self.num_nodes as NodeId;
+        (0..n).flat_map(|i| (0..n).filter(move |j| !self.reaches(i, *j)).map(move |j| (i, j))).collect()
+    }
+
+    fn row(&self, from: NodeId) -> &[u64] {
+        let start = from as usize * self.words;
+        &self.bits[start..start + self.words]
+    }
+}
+
+/// The distinct components every component of `labels` has arcs into.
+fn component_successors<N: Network>(network: &N, count: usize, labels: &[NodeId]) -> Vec<Vec<usize>> {
+    let mut successors = vec![Vec::new(); count];
+    for (i, j) in network.arcs() {
+        let (a, b) = (labels[i as usize] as usize, labels[j as usize] as usize);
+        if a != b {
+            successors[a].push(b);
+        }
+    }
+    for list in successors.iter_mut() {
+        list.sort();
+        list.dedup();
+    }
+    successors
+}
+
+#[test]
+fn test_reachable_set_sizes() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the cycle 0 -> 1 -> 0 leads to 2 and 3, both lead to 4; 5 is alone
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0), (1,2,1.0,0.0), (0,3,1.0,0.0),
+                         (2,4,1.0,0.0), (3,4,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    assert_eq!(vec![5, 5, 2, 2, 1, 1], reachable_set_sizes(&network));
+    let estimates = estimate_reachable_set_sizes(&network, 8);
+    for (estimate, exact) in estimates.iter().zip(reachable_set_sizes(&network)) {
+        assert!((estimate - exact as f64).abs() < 0.5);
+    }
+
+    // more nodes than fit a single word
+    let mut path: Vec<_> = (0..99).map(|i| (i, i + 1, 1.0, 0.0)).collect();
+    let network = compact_star_from_edge_vec(100, &mut path);
+    let sizes = reachable_set_sizes(&network);
+    assert_eq!(100, sizes[0]);
+    assert_eq!(36, sizes[64]);
+}
+
+#[test]
+fn test_reachability_matrix() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 <-> 1 -> 2, 3 alone
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0), (1,2,1.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let matrix = ReachabilityMatrix::new(&network);
+    assert!(matrix.reaches(0, 2) && matrix.reaches(1, 0) && matrix.reaches(3, 3));
+    assert!(!matrix.reaches(2, 0) && !matrix.reaches(0, 3));
+    assert_eq!(vec![3, 3, 1, 1], (0..4).map(|i| matrix.count_reachable(i)).collect::<Vec<_>>());
+    assert!(matrix.all_reachable(&[0, 1], &[0, 1, 2]));
+    assert!(matrix.all_reachable(&[0, 2], &[2]));
+    assert!(!matrix.all_reachable(&[0, 2], &[1, 2]));
+    assert!(!matrix.all_pairs_reachable());
+    assert_eq!(vec![(0,3), (1,3), (2,0), (2,1), (2,3), (3,0), (3,1), (3,2)], matrix.unreachable_pairs());
+
+    let mut cycle: Vec<_> = (0..70).map(|i| (i, (i + 1) % 70, 1.0, 0.0)).collect();
+    let matrix = ReachabilityMatrix::new(&compact_star_from_edge_vec(70, &mut cycle));
+    assert!(matrix.all_pairs_reachable());
+    assert!(matrix.reaches(69, 64));
+}
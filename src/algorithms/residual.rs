@@ -0,0 +1,142 @@
+use super::super::{ Capacity, Cost, DoubleVec, Network, NodeId };
+
+/// Residual capacities below this value count as saturated, so rounding
+/// errors in floating point capacities don't leave arcs usable.
+pub const RESIDUAL_EPS: f64 = 1e-9;
+
+/// The residual network of a flow on a network. Every arc `a` of the
+/// network (in arc order) has the forward residual arc `2a` and the
+/// backward residual arc `2a + 1`, with the negated cost. Initially the
+/// flow is zero: forward arcs have the full capacity, backward arcs none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResidualNetwork {
+    /// residual arcs sorted by tail, `first[i]..first[i + 1]` leave node `i`
+    first:    Vec<usize>,
+    out_arcs: Vec<usize>,
+    tail:     Vec<NodeId>,
+    head:     Vec<NodeId>,
+    residual: Vec<Capacity>,
+    cost:     Vec<Cost>,
+}
+
+impl ResidualNetwork {
+    pub fn new<N: Network>(network: &N) -> ResidualNetwork {
+        let n = network.num_nodes();
+        let m = network.num_arcs();
+        let mut residual_network = ResidualNetwork {
+            first:    vec![0; n + 1],
+            out_arcs: vec![0; 2 * m],
+            tail:     Vec::with_capacity(2 * m),
+            head:     Vec::with_capacity(2 * m),
+            residual: Vec::with_capacity(2 * m),
+            cost:     Vec::with_capacity(2 * m),
+        };
+        for (i, j, cost, capacity) in network.arc_values() {
+            residual_network.tail.extend(&[i, j]);
+            residual_network.head.extend(&[j, i]);
+            residual_network.residual.extend(&[capacity, 0.0]);
+            residual_network.cost.extend(&[cost, -cost]);
+        }
+        for tail in &residual_network.tail {
+            residual_network.first[*tail as usize + 1] += 1;
+        }
+        for i in 0..n {
+            residual_network.first[i + 1] += residual_network.first[i];
+        }
+        let mut next = residual_network.first.clone();
+        for (arc, tail) in residual_network.tail.iter().enumerate() {
+            residual_network.out_arcs[next[*tail as usize]] = arc;
+            next[*tail as usize] += 1;
+        }
+        residual_network
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.first.len() - 1
+    }
+
+    /// The number of residual arcs, twice the number of network arcs.
+    pub fn num_arcs(&self) -> usize {
+        self.tail.len()
+    }
+
+    /// The residual arcs leaving `node`, with or without residual capacity.
+    pub fn out_arcs(&self, node: NodeId) -> &[usize] {
+        &self.out_arcs[self.first[node as usize]..self.first[node as usize + 1]]
+    }
+
+    pub fn tail(&self, arc: usize) -> NodeId {
+        self.tail[arc]
+    }
+
+    pub fn head(&self, arc: usize) -> NodeId {
+        self.head[arc]
+    }
+
+    pub fn cost(&self, arc: usize) -> Cost {
+        self.cost[arc]
+    }
+
+    pub fn residual_capacity(&self, arc: usize) -> Capacity {
+        self.residual[arc]
+    }
+
+    /// Whether `arc` has residual capacity above `RESIDUAL_EPS`.
+    pub fn is_open(&self, arc: usize) -> bool {
+        self.residual[arc] > RESIDUAL_EPS
+    }
+
+    /// The residual arc in the opposite direction.
+    pub fn reverse(arc: usize) -> usize {
+        arc ^ 1
+    }
+
+    /// Sends `amount` units of flow along `arc`.
+    pub fn push(&mut self, arc: usize, amount: f64) {
+        self.residual[arc] -= amount;
+        self.residual[arc ^ 1] += amount;
+    }
+
+    /// The flow on every network arc, in arc order.
+    pub fn flows(&self) -> DoubleVec {
+        (0..self.num_arcs() / 2).map(|a| self.residual[2 * a + 1]).collect()
+    }
+
+    /// The nodes reachable from `source` over open residual arcs.
+    pub fn reachable(&self, source: NodeId) -> Vec<bool> {
+        let mut reached = vec![false; self.num_nodes()];
+        reached[source as usize] = true;
+        let mut stack = vec![source];
+        while let Some(i) = stack.pop() {
+            for arc in self.out_arcs(i) {
+                let j = self.head[*arc];
+                if self.is_open(*arc) && !reached[j as usize] {
+                    reached[j as usize] = true;
+                    stack.push(j);
+                }
+            }
+        }
+        reached
+    }
+}
+
+#[test]
+fn test_residual_network() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,2.0,5.0), (1,2,3.0,4.0), (0,2,1.0,1.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let mut residual = ResidualNetwork::new(&network);
+    // arc order: 0->1, 0->2, 1->2
+    assert_eq!(6, residual.num_arcs());
+    assert_eq!(&[0, 2], residual.out_arcs(0));
+    assert_eq!(&[1, 4], residual.out_arcs(1));
+    assert_eq!(-3.0, residual.cost(5));
+    residual.push(4, 4.0);
+    residual.push(0, 4.0);
+    assert!(!residual.is_open(4));
+    assert_eq!(4.0, residual.residual_capacity(5));
+    assert_eq!(vec![4.0, 0.0, 4.0], residual.flows());
+    assert_eq!(vec![true, true, true], residual.reachable(0));
+    residual.push(2, 1.0);
+    assert_eq!(vec![true, true, false], residual.reachable(0));
+}
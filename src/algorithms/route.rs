@@ -0,0 +1,107 @@
+use super::super::{ Cost, DoubleVec, Network, NodeId };
+use super::path::Path;
+use super::search_algorithms::shortest_path;
+
+/// A route through an ordered list of via nodes. It consists of one leg per
+/// pair of consecutive via nodes and the concatenation of all legs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Route {
+    path: Path,
+    legs: Vec<Path>,
+}
+
+impl Route {
+    /// The complete route from the first to the last via node.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The shortest paths between consecutive via nodes.
+    pub fn legs(&self) -> &Vec<Path> {
+        &self.legs
+    }
+
+    pub fn leg_costs(&self) -> DoubleVec {
+        self.legs.iter().map(|leg| leg.cost()).collect()
+    }
+
+    pub fn cost(&self) -> Cost {
+        self.path.cost()
+    }
+}
+
+/// Returns the shortest route that visits the nodes in `via` in the given
+/// order. Each leg is solved as an independent point-to-point query, so a
+/// route may pass through the same node several times.
+/// Returns `None` if `via` is empty or one of the legs has no path.
+/// # Example
+/// ```
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,1.0,0.0),
+///                      (1,2,1.0,0.0),
+///                      (2,0,1.0,0.0)];
+/// let compact_star = compact_star_from_edge_vec(3, &mut edges);
+/// let route = route_via(&compact_star, &[0,2,1]).unwrap();
+/// assert_eq!(&vec![0,1,2,0,1], route.path().nodes());
+/// assert_eq!(vec![2.0, 2.0], route.leg_costs());
+/// ```
+pub fn route_via<N: Network>(network: &N, via: &[NodeId]) -> Option<Route> {
+    let first = match via.first() {
+        Some(node) => *node,
+        None => return None
+    };
+
+    let mut nodes = vec![first];
+    let mut cost = 0.0;
+    let mut legs = Vec::with_capacity(via.len());
+    for pair in via.windows(2) {
+        let leg = match shortest_path(network, pair[0], pair[1]) {
+            Some(path) => path,
+            None => return None
+        };
+        nodes.extend(leg.nodes().iter().skip(1));
+        cost += leg.cost();
+        legs.push(leg);
+    }
+
+    Some(Route {
+        path: Path::new(nodes, cost),
+        legs: legs,
+    })
+}
+
+#[test]
+fn test_route_via() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (1,2,2.0,0.0),
+        (1,3,2.0,0.0),
+        (2,3,1.0,0.0),
+        (2,4,2.0,0.0),
+        (3,5,7.0,0.0),
+        (4,3,1.0,0.0),
+        (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let route = route_via(&compact_star, &[0,1,5]).unwrap();
+    assert_eq!(&vec![0,1,2,4,5], route.path().nodes());
+    assert_eq!(vec![6.0, 7.0], route.leg_costs());
+    assert_eq!(13.0, route.cost());
+
+    let single = route_via(&compact_star, &[3]).unwrap();
+    assert_eq!(&vec![3], single.path().nodes());
+    assert!(single.legs().is_empty());
+
+    assert_eq!(None, route_via(&compact_star, &[0,5,1]));
+    assert_eq!(None, route_via(&compact_star, &[]));
+
+    // a leg as long as the sum of all costs, the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,1,0.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(Some(2.0), shortest_path(&path, 0, 2).map(|p| p.cost()));
+    let route = route_via(&path, &[0,2,1]).unwrap();
+    assert_eq!(&vec![0,1,2,1], route.path().nodes());
+    assert_eq!(vec![2.0, 0.0], route.leg_costs());
+}
@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+
+use super::super::{ Network, NodeId, NodeVec };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::super::random::Rng;
+use super::subgraph::{ induced_subgraph, remap_node_data };
+
+// All samplers return the sampled network together with the original id of
+// each of its nodes, like `ego_network`.
+
+/// Samples `size` nodes uniformly at random and returns the subgraph they
+/// induce. The sampled nodes keep their relative order.
+pub fn random_node_sample<N: Network>(network: &N, size: usize, seed: u64) -> (CompactStar, NodeVec) {
+    let mut rng = Rng::new(seed);
+    let mut nodes: NodeVec = rng.sample(network.num_nodes(), size).into_iter().map(|i| i as NodeId).collect();
+    nodes.sort();
+    (induced_subgraph(network, &nodes), nodes)
+}
+
+/// Samples `size` arcs uniformly at random. The sample consists of these
+/// arcs and their end nodes only, in order of the original node ids. The
+/// arc numbers are drawn first and the arcs picked up in one pass over the
+/// network, so only the sampled arcs are held in memory.
+pub fn random_edge_sample<N: Network>(network: &N, size: usize, seed: u64) -> (CompactStar, NodeVec) {
+    let mut rng = Rng::new(seed);
+    let mut sampled = rng.sample(network.num_arcs(), size);
+    sampled.sort();
+
+    let mut arcs = Vec::with_capacity(sampled.len());
+    let mut next = sampled.iter().peekable();
+    for (a, arc) in network.arc_values().enumerate() {
+        match next.peek() {
+            Some(&&wanted) if wanted == a => {
+                arcs.push(arc);
+                next.next();
+            },
+            Some(_) => {},
+            None => break,
+        }
+    }
+    if arcs.is_empty() {
+        return (empty_compact_star(), NodeVec::new());
+    }
+
+    let mut is_sampled = vec![false; network.num_nodes()];
+    for &(i, j, _, _) in &arcs {
+        is_sampled[i as usize] = true;
+        is_sampled[j as usize] = true;
+    }
+    let nodes: NodeVec = network.nodes().filter(|i| is_sampled[*i as usize]).collect();
+    let mut new_id = vec![network.invalid_id(); network.num_nodes()];
+    for (k, node) in nodes.iter().enumerate() {
+        new_id[*node as usize] = k as NodeId;
+    }
+
+    let mut edges: Vec<_> = arcs.into_iter()
+        .map(|(i, j, cost, capacity)| (new_id[i as usize], new_id[j as usize], cost, capacity))
+        .collect();
+    (compact_star_from_edge_vec(nodes.len(), &mut edges), nodes)
+}
+
+/// Random walk sampling: a walk starts at a random node and follows random
+/// outgoing arcs, returning to its start node with probability
+/// `escape_probability` in every step. If the walk gets stuck, or doesn't
+/// find new nodes for `10 * n` steps, it restarts at another random node.
+/// The subgraph induced by the first `size` visited nodes is returned, with
+/// the nodes in order of their first visit.
+pub fn random_walk_sample<N: Network>(network: &N, size: usize, escape_probability: f64, seed: u64) -> (CompactStar, NodeVec) {
+    let n = network.num_nodes();
+    let size = size.min(n);
+    let mut rng = Rng::new(seed);
+    let mut visited = vec![false; n];
+    let mut nodes = NodeVec::with_capacity(size);
+
+    while nodes.len() < size {
+        let start = rng.below(n) as NodeId;
+        let mut current = start;
+        let mut steps_without_news = 0;
+        while nodes.len() < size && steps_without_news < 10 * n {
+            if !visited[current as usize] {
+                visited[current as usize] = true;
+                nodes.push(current);
+                steps_without_news = 0;
+            } else {
+                steps_without_news += 1;
+            }
+            let adjacent = network.adjacent(current);
+            if adjacent.is_empty() {
+                break;
+            }
+            current = if rng.chance(escape_probability) {
+                start
+            } else {
+                adjacent[rng.below(adjacent.len())]
+            };
+        }
+    }
+    (induced_subgraph(network, &nodes), nodes)
+}
+
+/// Forest fire sampling (Leskovec, Faloutsos: "Sampling from large graphs").
+/// A fire starts at a random node. Every burning node sets a geometrically
+/// distributed number of its not yet burnt successors on fire, with mean
+/// `p / (1 - p)` for the forward burning probability `p`, capped at the
+/// number of unburnt successors, so that a `p` of `1` or more burns all of
+/// them. When the fire dies out, a new one starts at a random unburnt
+/// node. The subgraph
+/// induced by the first `size` burnt nodes is returned, with the nodes in
+/// the order they caught fire.
+pub fn forest_fire_sample<N: Network>(network: &N, size: usize, p: f64, seed: u64) -> (CompactStar, NodeVec) {
+    let n = network.num_nodes();
+    let size = size.min(n);
+    let mut rng = Rng::new(seed);
+    let mut burnt = vec![false; n];
+    let mut nodes = NodeVec::with_capacity(size);
+    let mut burning = VecDeque::new();
+
+    while nodes.len() < size {
+        let start = rng.below(n) as NodeId;
+        if burnt[start as usize] {
+            continue;
+        }
+        burnt[start as usize] = true;
+        nodes.push(start);
+        burning.push_back(start);
+
+        while let Some(i) = burning.pop_front() {
+            let mut unburnt: NodeVec = network.adjacent(i).into_iter().filter(|j| !burnt[*j as usize]).collect();
+            unburnt.dedup();
+            let mut spread = 0;
+            while spread < unburnt.len() && rng.chance(p) {
+                spread += 1;
+            }
+            for k in rng.sample(unburnt.len(), spread) {
+                let j = unburnt[k];
+                if nodes.len() == size || burnt[j as usize] {
+                    continue;
+                }
+                burnt[j as usize] = true;
+                nodes.push(j);
+                burning.push_back(j);
+            }
+        }
+    }
+    (induced_subgraph(network, &nodes), nodes)
+}
+
+/// The result of `snowball_sample`.
+#[derive(Debug, PartialEq)]
+pub struct SnowballSample {
+    /// The subgraph induced by the sampled nodes.
+    pub network: CompactStar,
+    /// The original id of each node of `network`.
+    pub nodes:   NodeVec,
+    /// The wave in which each node of `network` was reached, `0` for seeds.
+    pub waves:   Vec<usize>,
+}
+
+impl SnowballSample {
+    /// Carries per node labels or attributes of the original network over
+    /// to the sample.
+    pub fn remap<T: Clone>(&self, data: &[T]) -> Vec<T> {
+        remap_node_data(data, &self.nodes)
+    }
+}
+
+/// k-wave snowball sampling: the `seeds` form wave `0`, and every node of
+/// wave `w` nominates its successors that are not yet in the sample, which
+/// form wave `w + 1`, for `waves` waves. With `nominations`, every node
+/// nominates at most that many successors, chosen at random, like
+/// respondents who are asked to name a limited number of contacts.
+/// Nodes are numbered in order of their wave and, within a wave, in order
+/// of nomination.
+pub fn snowball_sample<N: Network>(network: &N, seeds: &[NodeId], waves: usize, nominations: Option<usize>, seed: u64) -> SnowballSample {
+    let mut rng = Rng::new(seed);
+    let mut wave_of = vec![usize::MAX; network.num_nodes()];
+    let mut nodes = NodeVec::new();
+    for s in seeds {
+        if wave_of[*s as usize] == usize::MAX {
+            wave_of[*s as usize] = 0;
+            nodes.push(*s);
+        }
+    }
+
+    let mut wave_start = 0;
+    for wave in 1..waves + 1 {
+        let wave_end = nodes.len();
+        for k in wave_start..wave_end {
+            let mut contacts = network.adjacent(nodes[k]);
+            if let Some(limit) = nominations {
+                let chosen = rng.sample(contacts.len(), limit);
+                contacts = chosen.into_iter().map(|c| contacts[c]).collect();
+            }
+            for j in contacts {
+                if wave_of[j as usize] == usize::MAX {
+                    wave_of[j as usize] = wave;
+                    nodes.push(j);
+                }
+            }
+        }
+        wave_start = wave_end;
+    }
+
+    SnowballSample {
+        network: induced_subgraph(network, &nodes),
+        waves:   nodes.iter().map(|i| wave_of[*i as usize]).collect(),
+        nodes:   nodes,
+    }
+}
+
+#[cfg(test)]
+fn check_sample<N: Network>(network: &N, sample: &CompactStar, nodes: &NodeVec) {
+    for (i, j) in sample.arcs() {
+        let (from, to) = (nodes[i as usize], nodes[j as usize]);
+        assert_eq!(network.cost(from, to), sample.cost(i, j));
+    }
+    let mut sorted = nodes.clone();
+    sorted.sort();
+    sorted.dedup();
+    assert_eq!(nodes.len(), sorted.len());
+}
+
+#[test]
+fn test_sampling() {
+    use super::super::generators::{ ArcWeights, Distribution, gnp };
+    let weights = ArcWeights { cost: Distribution::Uniform(1.0, 2.0), capacity: Distribution::Constant(1.0) };
+    let network = gnp(200, 0.03, &weights, 3);
+
+    let (sample, nodes) = random_node_sample(&network, 50, 1);
+    assert_eq!(50, sample.num_nodes());
+    check_sample(&network, &sample, &nodes);
+
+    let (sample, nodes) = random_edge_sample(&network, 100, 1);
+    assert_eq!(100, sample.num_arcs());
+    check_sample(&network, &sample, &nodes);
+    assert!(sample.nodes().all(|i| sample.out_degree(i) > 0 || sample.arcs().any(|(_, j)| j == i)));
+
+    let (sample, nodes) = random_walk_sample(&network, 60, 0.15, 1);
+    assert_eq!(60, sample.num_nodes());
+    check_sample(&network, &sample, &nodes);
+
+    let (sample, nodes) = forest_fire_sample(&network, 60, 0.7, 1);
+    assert_eq!(60, sample.num_nodes());
+    check_sample(&network, &sample, &nodes);
+
+    assert_eq!(200, forest_fire_sample(&network, 500, 0.7, 1).1.len());
+    assert_eq!(random_walk_sample(&network, 60, 0.15, 9), random_walk_sample(&network, 60, 0.15, 9));
+
+    // a fire that always spreads burns every reachable node
+    let (sample, _) = forest_fire_sample(&network, 60, 1.0, 1);
+    assert_eq!(60, sample.num_nodes());
+
+    // empty samples
+    for (sample, nodes) in vec![random_node_sample(&network, 0, 1), random_edge_sample(&network, 0, 1),
+                                random_walk_sample(&network, 0, 0.15, 1), forest_fire_sample(&network, 0, 0.7, 1)] {
+        assert_eq!((0, 0), (sample.num_nodes(), nodes.len()));
+    }
+}
+
+#[test]
+fn test_snowball_sample() {
+    // 0 -> 1, 2;  1 -> 3;  2 -> 3, 4;  4 -> 5
+    let mut edges = vec![(0,1,1.0,0.0), (0,2,1.0,0.0), (1,3,1.0,0.0), (2,3,1.0,0.0), (2,4,1.0,0.0), (4,5,1.0,0.0)];
+    let network = compact_star_from_edge_vec(6, &mut edges);
+    let labels: Vec<String> = ["a", "b", "c", "d", "e", "f"].iter().map(|s| s.to_string()).collect();
+
+    let sample = snowball_sample(&network, &[0], 2, None, 1);
+    assert_eq!(vec![0,1,2,3,4], sample.nodes);
+    assert_eq!(vec![0,1,1,2,2], sample.waves);
+    assert_eq!(vec!["a", "b", "c", "d", "e"], sample.remap(&labels));
+    assert_eq!(5, sample.network.num_arcs());
+
+    let limited = snowball_sample(&network, &[0, 4], 3, Some(1), 1);
+    assert_eq!(vec![0, 0], limited.waves[..2].to_vec());
+    // every node nominates at most one new node
+    for wave in 1..4 {
+        let size = |w| limited.waves.iter().filter(|v| **v == w).count();
+        assert!(size(wave) <= size(wave - 1));
+    }
+    assert_eq!(vec![1.0, 0.5], remap_node_data(&[0.5, 1.0, 2.0], &vec![1, 0]));
+}
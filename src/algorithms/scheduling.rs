@@ -0,0 +1,104 @@
+use super::super::{ DoubleVec, Network, NodeId };
+use super::dag::{ CycleFound, topological_sort };
+
+/// Result of `list_schedule`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schedule {
+    /// The start time of every task.
+    pub start:    DoubleVec,
+    /// The time at which the last task finishes.
+    pub makespan: f64,
+}
+
+/// Schedules the tasks of a precedence network, one node per task and an
+/// arc from every task to each task that can only start after it has
+/// finished, on `resources` identical resources: at most `resources` tasks
+/// run at the same time and task `i` runs for `durations[i]` without
+/// interruption.
+///
+/// Whenever a resource is free, it starts the ready task with the longest
+/// remaining chain of durations up to the end of the project (the highest
+/// bottom level), ties broken by lowest id. This list scheduling heuristic
+/// is at most `2 - 1 / resources` times longer than the optimal schedule
+/// (Graham: "Bounds on multiprocessing timing anomalies"). With enough
+/// resources the makespan is the length of the critical path.
+/// # Errors
+/// `CycleFound` if the precedences are cyclic.
+/// # Panics
+/// If `resources` is `0` or there isn't one duration per node.
+pub fn list_schedule<N: Network>(network: &N, durations: &[f64], resources: usize) -> Result<Schedule, CycleFound> {
+    assert!(resources > 0, "at least one resource is required");
+    let n = network.num_nodes();
+    assert_eq!(n, durations.len(), "one duration per task is required");
+    let order = topological_sort(network)?;
+
+    let mut bottom_level = durations.to_vec();
+    for i in order.iter().rev() {
+        let i = *i as usize;
+        let longest = network.out_arcs(i as NodeId).map(|(j, _, _)| bottom_level[j as usize]).fold(0.0, f64::max);
+        bottom_level[i] = durations[i] + longest;
+    }
+    let mut waiting_for: Vec<usize> = network.nodes().map(|i| network.in_degree(i)).collect();
+
+    let mut start = vec![0.0; n];
+    let mut ready: Vec<NodeId> = network.nodes().filter(|i| waiting_for[*i as usize] == 0).collect();
+    // running tasks as (finish time, task)
+    let mut running: Vec<(f64, NodeId)> = Vec::with_capacity(resources);
+    let mut now = 0.0;
+    let mut makespan: f64 = 0.0;
+    while !ready.is_empty() || !running.is_empty() {
+        ready.sort_by(|a, b| bottom_level[*b as usize].partial_cmp(&bottom_level[*a as usize]).unwrap().then(a.cmp(b)));
+        let free = resources - running.len();
+        for task in ready.drain(..free.min(ready.len())) {
+            start[task as usize] = now;
+            running.push((now + durations[task as usize], task));
+        }
+
+        // advance to the next finishing task, and release all tasks
+        // finishing at the same time
+        now = running.iter().map(|&(finish, _)| finish).fold(f64::INFINITY, f64::min);
+        makespan = makespan.max(now);
+        let mut k = 0;
+        while k < running.len() {
+            if running[k].0 > now {
+                k += 1;
+                continue;
+            }
+            let (_, task) = running.swap_remove(k);
+            for (j, _, _) in network.out_arcs(task) {
+                waiting_for[j as usize] -= 1;
+                if waiting_for[j as usize] == 0 {
+                    ready.push(j);
+                }
+            }
+        }
+    }
+    Ok(Schedule {
+        start:    start,
+        makespan: makespan,
+    })
+}
+
+#[test]
+fn test_list_schedule() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 2 -> 4 is the critical chain of length 7; 1 and 3 are short
+    // independent tasks, 1 -> 3
+    let mut edges = vec![(0,2,0.0,0.0), (2,4,0.0,0.0), (1,3,0.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let durations = [3.0, 2.0, 2.0, 2.0, 2.0];
+
+    let schedule = list_schedule(&network, &durations, 1).unwrap();
+    assert_eq!(11.0, schedule.makespan);
+    assert_eq!(vec![0.0, 3.0, 5.0, 7.0, 9.0], schedule.start);
+
+    let schedule = list_schedule(&network, &durations, 2).unwrap();
+    assert_eq!(7.0, schedule.makespan);
+    assert_eq!(vec![0.0, 0.0, 3.0, 2.0, 5.0], schedule.start);
+    for (i, j) in network.arcs() {
+        assert!(schedule.start[j as usize] >= schedule.start[i as usize] + durations[i as usize]);
+    }
+
+    let mut edges = vec![(0,1,0.0,0.0), (1,0,0.0,0.0)];
+    assert!(list_schedule(&compact_star_from_edge_vec(2, &mut edges), &[1.0, 1.0], 1).is_err());
+}
@@ -1,6 +1,13 @@
-use super::super::{Cost, DoubleVec, Network, NodeId, NodeVec};
+use super::super::{Cost, Direction, DoubleVec, Network, NodeId, NodeVec, Weight};
 use super::super::collections::{Collection, Queue, Stack};
-use super::super::heaps::{ BinaryHeap, Heap };
+use std::error::Error;
+use std::fmt;
+use std::time::Instant;
+
+use super::super::heaps::{ BinaryHeap, FibonacciHeap, Heap, IndexedHeap, PairingHeap };
+use super::super::instrument::{ Instrumentation, NoInstrumentation };
+use super::nearest::{ bounded_nearest_target, nearest_target, nearest_target_instrumented };
+use super::path::Path;
 
 /// Returns a tuple of node id lists as result of a Breadth-First search from node `start`. 
 /// The first list is the predecessor list, that matches each node to it's predecessor in the
@@ -11,22 +18,24 @@ use super::super::heaps::{ BinaryHeap, Heap };
 /// * `start` a start node from where to search.
 /// # Example
 /// ```
-/// let edges = vec![(0,1,25.0,30.0),
-///                  (0,2,35.0,50.0),
-///                  (1,3,15.0,40.0),
-///                  (2,1,45.0,10.0),
-///                  (3,2,15.0,30.0),
-///                  (3,4,45.0,60.0),
-///                  (4,2,25.0,20.0),
-///                  (4,3,35.0,50.0)];
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,25.0,30.0),
+///                      (0,2,35.0,50.0),
+///                      (1,3,15.0,40.0),
+///                      (2,1,45.0,10.0),
+///                      (3,2,15.0,30.0),
+///                      (3,4,45.0,60.0),
+///                      (4,2,25.0,20.0),
+///                      (4,3,35.0,50.0)];
 /// let compact_star = compact_star_from_edge_vec(5, &mut edges);
-/// assert_eq!((vec![5,0,0,1,3],vec![0,1,2,3,4]));
+/// assert_eq!((vec![5,0,0,1,3], vec![0,1,2,3,4]), breadth_first_search(&compact_star, 0));
 /// ```
 ///
 pub fn breadth_first_search<N: Network>(network: &N, start: NodeId) -> (NodeVec, NodeVec) {
     let n = network.num_nodes();
     let mut queue = Queue::with_capacity(n);
-    search(network, &mut queue, start)
+    search(network, &mut queue, start, Direction::Forward)
 }
 
 /// Returns a tuple of node id lists as result of a Depth-First search from node `start`. 
@@ -36,14 +45,45 @@ pub fn breadth_first_search<N: Network>(network: &N, start: NodeId) -> (NodeVec,
 /// # Arguments
 /// * `network` a borrowed value that implements the Network trait.
 /// * `start` a start node from where to search.
+/// # Example
+/// ```
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,25.0,30.0),
+///                      (0,2,35.0,50.0),
+///                      (1,3,15.0,40.0),
+///                      (2,1,45.0,10.0),
+///                      (3,2,15.0,30.0),
+///                      (3,4,45.0,60.0),
+///                      (4,2,25.0,20.0),
+///                      (4,3,35.0,50.0)];
+/// let compact_star = compact_star_from_edge_vec(5, &mut edges);
+/// assert_eq!((vec![5,0,3,1,3], vec![0,1,3,2,4]), depth_first_search(&compact_star, 0));
+/// ```
 pub fn depth_first_search<N: Network>(network: &N, start: NodeId) -> (NodeVec, NodeVec) {
     let n = network.num_nodes();
     let mut stack = Stack::with_capacity(n);
-    search(network, &mut stack, start)
+    search(network, &mut stack, start, Direction::Forward)
+}
+
+/// Like `breadth_first_search`, but follows the arcs in `direction`. With
+/// `Direction::Reverse` the search finds the nodes that can reach `start`,
+/// with `Direction::Both` it treats the network as undirected and visits
+/// the weakly connected component of `start`.
+pub fn breadth_first_search_with_direction<N: Network>(network: &N, start: NodeId, direction: Direction) -> (NodeVec, NodeVec) {
+    let mut queue = Queue::with_capacity(network.num_nodes());
+    search(network, &mut queue, start, direction)
+}
+
+/// Like `depth_first_search`, but follows the arcs in `direction`, see
+/// `breadth_first_search_with_direction`.
+pub fn depth_first_search_with_direction<N: Network>(network: &N, start: NodeId, direction: Direction) -> (NodeVec, NodeVec) {
+    let mut stack = Stack::with_capacity(network.num_nodes());
+    search(network, &mut stack, start, direction)
 }
 
 
-fn search<C: Collection, N: Network>(network: &N, to_process: &mut C, start: NodeId) -> (NodeVec, NodeVec) {
+fn search<C: Collection, N: Network>(network: &N, to_process: &mut C, start: NodeId, direction: Direction) -> (NodeVec, NodeVec) {
     let n = network.num_nodes();
     let no_pred = network.invalid_id();
     let mut pred_slice = &mut (vec![no_pred; n])[..];
@@ -57,13 +97,10 @@ fn search<C: Collection, N: Network>(network: &N, to_process: &mut C, start: Nod
     to_process.push(start);
     while !to_process.is_empty() {
         let i = *to_process.peek().unwrap();
-        let adj = network.adjacent(i); let mut j = no_pred;
-        for candidate in adj {
-            if ! marks[candidate as usize] {
-                j = candidate;
-                break;
-            }
-        }
+        let j = network.followed_arcs(i, direction)
+            .map(|(candidate, _, _)| candidate)
+            .find(|candidate| !marks[*candidate as usize])
+            .unwrap_or(no_pred);
         if j != no_pred {
             marks[j as usize] = true;
             pred_slice[j as usize] = i;
@@ -83,6 +120,28 @@ fn search<C: Collection, N: Network>(network: &N, to_process: &mut C, start: Nod
     (pred, order)
 }
 
+/// Returns the predecessor and distance lists of the shortest path tree rooted at
+/// `source`. With `use_heap`, nodes are selected from a binary heap, otherwise by a
+/// linear scan. Unreachable nodes keep the network's invalid id as predecessor and
+/// its infinity as distance.
+/// # Example
+/// ```
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,6.0,0.0),
+///                      (0,2,4.0,0.0),
+///                      (1,2,2.0,0.0),
+///                      (1,3,2.0,0.0),
+///                      (2,3,1.0,0.0),
+///                      (2,4,2.0,0.0),
+///                      (3,5,7.0,0.0),
+///                      (4,3,1.0,0.0),
+///                      (4,5,3.0,0.0)];
+/// let compact_star = compact_star_from_edge_vec(6, &mut edges);
+/// let (pred, dist) = dijkstra(&compact_star, 0, true);
+/// assert_eq!(vec![6,0,0,2,2,4], pred);
+/// assert_eq!(vec![0.0,6.0,4.0,5.0,6.0,9.0], dist);
+/// ```
 pub fn dijkstra<N: Network>(network: &N, source: NodeId, use_heap: bool) -> (NodeVec, DoubleVec) {
     if use_heap {
         heap_dijkstra(network, source)
@@ -107,10 +166,9 @@ pub fn vanilla_dijkstra<N: Network>(network: &N, source: NodeId) -> (NodeVec, Do
         let next_node = find_min(&temporary, d, network.infinity());
         let index_in_temporary = find_min_index(&temporary, next_node);
         permanent.push(temporary.remove(index_in_temporary));
-        for adjacent_node in network.adjacent(next_node) {
+        for (adjacent_node, cost, _) in network.out_arcs(next_node) {
             let i = next_node as usize;
             let j = adjacent_node as usize;
-            let cost = network.cost(next_node, adjacent_node).unwrap();
             if d[j] > d[i] + cost {
                 d[j] = d[i] + cost;
                 pred[j] = next_node;
@@ -152,11 +210,79 @@ fn find_min_index(list: &NodeVec, node: NodeId) -> usize {
 }
 
 pub fn heap_dijkstra<N: Network> (network: &N, source: NodeId) -> (NodeVec, DoubleVec) {
+    heap_dijkstra_instrumented(network, source, NoInstrumentation)
+}
+
+/// Like `heap_dijkstra`, but reports settled nodes, relaxed arcs and the
+/// running time to `instrumentation`.
+pub fn heap_dijkstra_instrumented<N: Network, I: Instrumentation>(network: &N, source: NodeId, instrumentation: I) -> (NodeVec, DoubleVec) {
+    tie_breaking_dijkstra(network, source, Direction::Forward, TieBreaking::FirstFound, BinaryHeap::new(), instrumentation)
+}
+
+/// The priority queue behind `dijkstra_with_heap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeapKind {
+    /// Binary heap with lazy deletion: a node is queued again whenever its
+    /// distance drops and stale entries are skipped. Usually the fastest.
+    Binary,
+    /// Binary heap with a position map and decrease-key. Holds at most one
+    /// entry per node, which saves memory on dense networks.
+    Indexed,
+    /// Fibonacci heap with decrease-key, `O(m + n log n)` in total.
+    Fibonacci,
+    /// Pairing heap with decrease-key.
+    Pairing,
+}
+
+/// Like `heap_dijkstra`, but runs on the priority queue chosen by `heap`.
+/// The addressable heaps hold every node at most once and lower its
+/// distance in place instead of queueing it again.
+pub fn dijkstra_with_heap<N: Network>(network: &N, source: NodeId, heap: HeapKind) -> (NodeVec, DoubleVec) {
+    let n = network.num_nodes();
+    let (direction, ties) = (Direction::Forward, TieBreaking::FirstFound);
+    match heap {
+        HeapKind::Binary => tie_breaking_dijkstra(network, source, direction, ties, BinaryHeap::with_capacity(n), NoInstrumentation),
+        HeapKind::Indexed => tie_breaking_dijkstra(network, source, direction, ties, IndexedHeap::with_capacity(n), NoInstrumentation),
+        HeapKind::Fibonacci => tie_breaking_dijkstra(network, source, direction, ties, FibonacciHeap::with_capacity(n), NoInstrumentation),
+        HeapKind::Pairing => tie_breaking_dijkstra(network, source, direction, ties, PairingHeap::with_capacity(n), NoInstrumentation),
+    }
+}
+
+/// How Dijkstra's algorithm chooses among several shortest paths to a node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreaking {
+    /// Keep the predecessor that was found first, which depends on the
+    /// order of the arcs.
+    FirstFound,
+    /// Prefer the predecessor with the lowest id. For positive arc costs,
+    /// the path to every node is then the one whose node sequence, read
+    /// backwards from the node, is lexicographically smallest. It doesn't
+    /// depend on the order of the arcs.
+    LowestId,
+}
+
+/// Like `heap_dijkstra`, but chooses among equally short paths by `ties`.
+pub fn dijkstra_with_tie_breaking<N: Network>(network: &N, source: NodeId, ties: TieBreaking) -> (NodeVec, DoubleVec) {
+    tie_breaking_dijkstra(network, source, Direction::Forward, ties, BinaryHeap::new(), NoInstrumentation)
+}
+
+/// Like `heap_dijkstra`, but follows the arcs in `direction`. With
+/// `Direction::Reverse`, `dist[i]` is the distance from `i` to `source`
+/// and `pred[i]` the next node on the way there; with `Direction::Both`,
+/// every arc may be used either way, as in an undirected network.
+pub fn dijkstra_with_direction<N: Network>(network: &N, source: NodeId, direction: Direction) -> (NodeVec, DoubleVec) {
+    tie_breaking_dijkstra(network, source, direction, TieBreaking::FirstFound, BinaryHeap::new(), NoInstrumentation)
+}
+
+fn tie_breaking_dijkstra<N: Network, H: Heap, I: Instrumentation>(network: &N, source: NodeId, direction: Direction, ties: TieBreaking,
+                                                                  mut heap: H, mut instrumentation: I) -> (NodeVec, DoubleVec) {
+    let start = Instant::now();
     let n = network.num_nodes();
 
-    let mut heap = BinaryHeap::new();
     let pred = &mut (vec![network.invalid_id(); n])[..];
-    let d = &mut (vec![network.infinity(); n])[..];
+    // the network's infinity is the sum of all costs, which the longest
+    // paths reach, so unreached nodes only get it at the end
+    let d = &mut (vec![Cost::INFINITY; n])[..];
     let marked = &mut(vec![false; n])[..];
 
     d[source as usize] = 0.0;
@@ -173,14 +299,17 @@ pub fn heap_dijkstra<N: Network> (network: &N, source: NodeId) -> (NodeVec, Doub
         }
 
         marked[i] = true;
+        instrumentation.node_settled(next_node);
 
-        for adjacent_node in network.adjacent(next_node) {
-            let cost = network.cost(next_node, adjacent_node).unwrap();
+        for (adjacent_node, cost, _) in network.followed_arcs(next_node, direction) {
+            instrumentation.arc_relaxed(next_node, adjacent_node);
             let j = adjacent_node as usize;
             if d[j] > d[i] + cost {
                 pred[j] = next_node;
                 d[j] = d[i] + cost;
                 heap.insert(adjacent_node, d[j]);
+            } else if ties == TieBreaking::LowestId && d[j] == d[i] + cost && next_node < pred[j] && j != source as usize {
+                pred[j] = next_node;
             }
         }
     }
@@ -190,11 +319,178 @@ pub fn heap_dijkstra<N: Network> (network: &N, source: NodeId) -> (NodeVec, Doub
     let mut dist_vec = DoubleVec::with_capacity(n);
     for i in 0..n {
         pred_vec.push(pred[i]);
-        dist_vec.push(d[i]);
+        dist_vec.push(if d[i].is_finite() { d[i] } else { network.infinity() });
     }
+    instrumentation.finished(start.elapsed());
     (pred_vec, dist_vec)
 }
 
+/// Dijkstra's algorithm on the arc weights `weights`, one per arc in arc
+/// order (see `Network::arcs`), instead of the costs. With integer weights
+/// the distances are exact, so equally long paths are recognized as such.
+/// Returns predecessors like `heap_dijkstra` and the distance of every
+/// node, `None` for unreachable nodes. Uses an `IndexedHeap` keyed by the
+/// weights.
+/// # Panics
+/// If there isn't one weight per arc, or a weight is negative or can't be
+/// compared to zero, like a `NaN` float. Use `OrderedFloat` for floats that
+/// may be `NaN`.
+/// # Example
+/// ```
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,0.1,0.0), (1,2,0.2,0.0), (0,2,0.3,0.0)];
+/// let compact_star = compact_star_from_edge_vec(3, &mut edges);
+/// let cents: Vec<u32> = compact_star.edges().iter().map(|e| (e.2 * 100.0).round() as u32).collect();
+/// let (_, dist) = weighted_dijkstra(&compact_star, &cents, 0);
+/// assert_eq!(vec![Some(0), Some(10), Some(30)], dist);
+/// ```
+pub fn weighted_dijkstra<N: Network, W: Weight>(network: &N, weights: &[W], source: NodeId) -> (NodeVec, Vec<Option<W>>) {
+    assert_eq!(network.num_arcs(), weights.len(), "one weight per arc is required");
+    assert!(weights.iter().all(|w| *w >= W::zero()), "weights must be non-negative and comparable");
+    let n = network.num_nodes();
+    let first_arcs = network.first_arcs();
+
+    let mut pred = vec![network.invalid_id(); n];
+    let mut d: Vec<Option<W>> = vec![None; n];
+    let mut marked = vec![false; n];
+    let mut heap = IndexedHeap::with_capacity(n);
+    d[source as usize] = Some(W::zero());
+    heap.insert(source, W::zero());
+    while let Some(i) = heap.find_min() {
+        heap.delete_min();
+        marked[i as usize] = true;
+        let distance = d[i as usize].unwrap();
+        for (k, (j, _, _)) in network.out_arcs(i).enumerate() {
+            if marked[j as usize] {
+                continue;
+            }
+            let candidate = distance + weights[first_arcs[i as usize] + k];
+            let shorter = match d[j as usize] {
+                Some(current) => candidate < current,
+                None => true,
+            };
+            if shorter {
+                d[j as usize] = Some(candidate);
+                pred[j as usize] = i;
+                heap.insert(j, candidate);
+            }
+        }
+    }
+    (pred, d)
+}
+
+/// Returns the successor and distance lists of the shortest path tree of all
+/// paths leading *into* `target`, i.e. Dijkstra's algorithm along the
+/// reversed arcs. `dist[i]` is the distance from node `i` to `target` and
+/// `succ[i]` the next node on a shortest path from `i` to `target`. Nodes
+/// that can't reach `target` keep the network's invalid id as successor and
+/// its infinity as distance.
+///
+/// The distances are a consistent lower bound on the remaining cost to
+/// `target`, which makes them the ideal potential for goal directed searches.
+pub fn reverse_dijkstra<N: Network>(network: &N, target: NodeId) -> (NodeVec, DoubleVec) {
+    dijkstra_with_direction(network, target, Direction::Reverse)
+}
+
+/// A cycle of negative total cost, found by `bellman_ford`. The cycle
+/// lists its nodes in the order of its arcs; the last node leads back to
+/// the first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegativeCycle {
+    pub cycle: NodeVec,
+}
+
+impl fmt::Display for NegativeCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "negative cycle through nodes {:?}", self.cycle)
+    }
+}
+
+impl Error for NegativeCycle {}
+
+/// Bellman-Ford shortest paths from `source`, which other than Dijkstra
+/// allow negative arc costs. Runs in `O(nm)` passes over all arcs, but
+/// stops as soon as a pass changes nothing. Nodes that can't be reached keep
+/// the network's invalid id as predecessor and the network's infinity as
+/// distance.
+/// # Errors
+/// `NegativeCycle` if a cycle of negative cost can be reached from
+/// `source`, which leaves the shortest paths undefined.
+pub fn bellman_ford<N: Network>(network: &N, source: NodeId) -> Result<(NodeVec, DoubleVec), NegativeCycle> {
+    let n = network.num_nodes();
+    let arcs: Vec<(NodeId, NodeId, Cost)> = network.arc_values().map(|(i, j, cost, _)| (i, j, cost)).collect();
+    let mut pred = vec![network.invalid_id(); n];
+    let mut d = vec![network.infinity(); n];
+    // with negative costs the network's infinity may be below some distances
+    let mut reached = vec![false; n];
+    d[source as usize] = 0.0;
+    reached[source as usize] = true;
+
+    for pass in 0..n {
+        let mut last_changed = None;
+        for &(i, j, cost) in &arcs {
+            if reached[i as usize] && (!reached[j as usize] || d[i as usize] + cost < d[j as usize]) {
+                d[j as usize] = d[i as usize] + cost;
+                pred[j as usize] = i;
+                reached[j as usize] = true;
+                last_changed = Some(j);
+            }
+        }
+        match last_changed {
+            None => break,
+            // still changing in the n-th pass: walking back n predecessors
+            // ends on a negative cycle
+            Some(changed) if pass == n - 1 => {
+                let mut node = changed;
+                for _ in 0..n {
+                    node = pred[node as usize];
+                }
+                let mut cycle = vec![node];
+                let mut next = pred[node as usize];
+                while next != node {
+                    cycle.push(next);
+                    next = pred[next as usize];
+                }
+                cycle.reverse();
+                return Err(NegativeCycle { cycle: cycle });
+            },
+            Some(_) => {},
+        }
+    }
+    Ok((pred, d))
+}
+
+/// Returns the shortest path from `source` to `target`, or `None` if the
+/// target can't be reached. Other than `dijkstra`, the search stops as
+/// soon as the target is settled.
+pub fn shortest_path<N: Network>(network: &N, source: NodeId, target: NodeId) -> Option<Path> {
+    nearest_target(network, source, &[target])
+}
+
+/// Like `shortest_path`, but reports settled nodes, relaxed arcs and the
+/// running time to `instrumentation`.
+pub fn shortest_path_instrumented<N: Network, I: Instrumentation>(network: &N, source: NodeId, target: NodeId,
+                                                                  instrumentation: I) -> Option<Path> {
+    nearest_target_instrumented(network, source, &[target], instrumentation)
+}
+
+/// Returns the shortest path from `source` to `target` if its cost is at
+/// most `upper_bound`, and `None` otherwise. Labels above the bound are
+/// pruned, so a good bound, e.g. the cost of the answer to a similar
+/// earlier query or a landmark estimate, keeps the search small. A bound
+/// below the actual distance makes the search fail fast.
+pub fn bounded_shortest_path<N: Network>(network: &N, source: NodeId, target: NodeId, upper_bound: Cost) -> Option<Path> {
+    bounded_nearest_target(network, source, &[target], upper_bound, NoInstrumentation)
+}
+
+/// Like `bounded_shortest_path`, but reports settled nodes, relaxed arcs
+/// and the running time to `instrumentation`.
+pub fn bounded_shortest_path_instrumented<N: Network, I: Instrumentation>(network: &N, source: NodeId, target: NodeId,
+                                                                          upper_bound: Cost, instrumentation: I) -> Option<Path> {
+    bounded_nearest_target(network, source, &[target], upper_bound, instrumentation)
+}
+
 #[test]
 fn test_dijkstra() {
     use super::super::compact_star::compact_star_from_edge_vec;
@@ -236,3 +532,197 @@ fn test_heap_dijkstra() {
     assert_eq!(vec![6,0,0,2,2,4], pred);
     assert_eq!(vec![0.0,6.0,4.0,5.0,6.0,9.0], dist);
 }
+
+#[test]
+fn test_dijkstra_with_heap() {
+    use super::super::generators::{ ArcWeights, gnp };
+    let network = gnp(60, 0.1, &ArcWeights::default(), 5);
+    let (_, expected) = heap_dijkstra(&network, 0);
+    for heap in &[HeapKind::Binary, HeapKind::Indexed, HeapKind::Fibonacci, HeapKind::Pairing] {
+        let (pred, dist) = dijkstra_with_heap(&network, 0, *heap);
+        assert_eq!(expected, dist);
+        for j in network.nodes().filter(|j| pred[*j as usize] != network.invalid_id()) {
+            let i = pred[j as usize];
+            assert_eq!(dist[i as usize] + network.cost(i, j).unwrap(), dist[j as usize]);
+        }
+    }
+}
+
+#[test]
+fn test_instrumented_searches() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    use super::super::instrument::Counters;
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (0,2,5.0,0.0), (2,3,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+
+    let mut counters = Counters::default();
+    assert_eq!(heap_dijkstra(&compact_star, 0), heap_dijkstra_instrumented(&compact_star, 0, &mut counters));
+    assert_eq!(4, counters.nodes_settled);
+    assert_eq!(4, counters.arcs_relaxed);
+
+    let mut counters = Counters::default();
+    assert_eq!(Some(2.0), shortest_path_instrumented(&compact_star, 0, 2, &mut counters).map(|p| p.cost()));
+    assert_eq!(3, counters.nodes_settled);
+    assert_eq!(3, counters.arcs_relaxed);
+
+    // the target is as far as the sum of all costs, the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    let mut counters = Counters::default();
+    let instrumented = shortest_path_instrumented(&path, 0, 2, &mut counters);
+    assert_eq!(shortest_path(&path, 0, 2), instrumented);
+    assert_eq!(Some(&vec![0,1,2]), instrumented.as_ref().map(|p| p.nodes()));
+    assert_eq!(3, counters.nodes_settled);
+}
+
+#[test]
+fn test_reverse_dijkstra() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut test_edges = vec![(0,1,6.0,0.0),
+                              (0,2,4.0,0.0),
+                              (1,2,2.0,0.0),
+                              (1,3,2.0,0.0),
+                              (2,3,1.0,0.0),
+                              (2,4,2.0,0.0),
+                              (3,5,7.0,0.0),
+                              (4,3,1.0,0.0),
+                              (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut test_edges);
+    let (succ, dist) = reverse_dijkstra(&compact_star, 3);
+    assert_eq!(vec![2,3,3,6,3,6], succ);
+    assert_eq!(vec![5.0,2.0,1.0,0.0,1.0,compact_star.infinity()], dist);
+    for source in 0..5 {
+        assert_eq!(heap_dijkstra(&compact_star, source).1[3], dist[source as usize]);
+    }
+
+    // the path over all arcs is as long as the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!((vec![1,2,3], vec![2.0,1.0,0.0]), reverse_dijkstra(&path, 2));
+}
+
+#[test]
+fn test_tie_breaking() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // three equally short paths 0 -> {3, 1, 2} -> 4
+    let mut edges = vec![(0,3,1.0,0.0), (0,1,1.0,0.0), (0,2,1.0,0.0),
+                         (3,4,1.0,0.0), (2,4,1.0,0.0), (1,4,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+    assert_eq!(3, dijkstra_with_tie_breaking(&compact_star, 0, TieBreaking::FirstFound).0[4]);
+    assert_eq!(heap_dijkstra(&compact_star, 0), dijkstra_with_tie_breaking(&compact_star, 0, TieBreaking::FirstFound));
+    let (pred, dist) = dijkstra_with_tie_breaking(&compact_star, 0, TieBreaking::LowestId);
+    assert_eq!(vec![5,0,0,0,1], pred);
+    assert_eq!(vec![0.0,1.0,1.0,1.0,2.0], dist);
+}
+
+#[test]
+fn test_bellman_ford() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,4.0,0.0),
+        (0,2,5.0,0.0),
+        (1,3,3.0,0.0),
+        (2,1,-3.0,0.0),
+        (3,4,-1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(6, &mut edges);
+    let (pred, dist) = bellman_ford(&compact_star, 0).unwrap();
+    assert_eq!(vec![6,2,0,1,3,6], pred);
+    assert_eq!(vec![0.0,2.0,5.0,5.0,4.0], dist[..5].to_vec());
+    assert_eq!(compact_star.infinity(), dist[5]);
+
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (1,2,-2.0,0.0),
+        (2,3,1.0,0.0),
+        (3,1,-1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let cycle = bellman_ford(&compact_star, 0).unwrap_err().cycle;
+    assert_eq!(3, cycle.len());
+    let first = cycle.iter().position(|i| *i == 1).unwrap();
+    assert_eq!(vec![1,2,3], cycle[first..].iter().chain(&cycle[..first]).cloned().collect::<Vec<_>>());
+    assert!(bellman_ford(&compact_star, 3).is_err());
+    // a negative cycle that can't be reached doesn't matter
+    let (_, dist) = bellman_ford(&compact_star_from_edge_vec(3, &mut vec![(1,2,-1.0,0.0), (2,1,-1.0,0.0)]), 0).unwrap();
+    assert_eq!(0.0, dist[0]);
+    // the negative cycle runs through the cheaper of two parallel arcs
+    let mut edges = vec![(0,1,5.0,0.0), (0,1,-3.0,0.0), (1,0,1.0,0.0)];
+    assert!(bellman_ford(&compact_star_from_edge_vec(2, &mut edges), 0).is_err());
+}
+
+#[test]
+fn test_bounded_shortest_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    use super::super::instrument::Counters;
+    // a path 0 -> 1 -> 2 -> 3 and a long arm 0 -> 4 -> 5 -> 6 of cheap arcs
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (2,3,1.0,0.0),
+                         (0,4,0.5,0.0), (4,5,0.5,0.0), (5,6,0.5,0.0), (6,3,5.0,0.0)];
+    let network = compact_star_from_edge_vec(7, &mut edges);
+    assert_eq!(shortest_path(&network, 0, 3), bounded_shortest_path(&network, 0, 3, 3.0));
+    assert_eq!(None, bounded_shortest_path(&network, 0, 3, 2.5));
+
+    // with a bound too low for the target, nodes beyond the bound are never settled
+    let mut counters = Counters::default();
+    bounded_shortest_path_instrumented(&network, 0, 3, 1.0, &mut counters);
+    assert_eq!(4, counters.nodes_settled);
+
+    // the target is as far as the sum of all costs, the network's infinity
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let path = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!(Some(2.0), bounded_shortest_path(&path, 0, 2, 2.0).map(|p| p.cost()));
+    assert_eq!(None, bounded_shortest_path(&path, 0, 2, 1.5));
+}
+
+#[test]
+fn test_search_with_direction() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 2 and 3 -> 1
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,2.0,0.0), (3,1,4.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(breadth_first_search(&compact_star, 0), breadth_first_search_with_direction(&compact_star, 0, Direction::Forward));
+    assert_eq!(depth_first_search(&compact_star, 0), depth_first_search_with_direction(&compact_star, 0, Direction::Forward));
+    assert_eq!(vec![1,2,4,1], breadth_first_search_with_direction(&compact_star, 2, Direction::Reverse).0);
+    assert_eq!(vec![4,0,1,1], breadth_first_search_with_direction(&compact_star, 0, Direction::Both).0);
+
+    assert_eq!(heap_dijkstra(&compact_star, 0), dijkstra_with_direction(&compact_star, 0, Direction::Forward));
+    let inf = compact_star.infinity();
+    assert_eq!((vec![1,2,4,1], vec![3.0,2.0,0.0,6.0]), dijkstra_with_direction(&compact_star, 2, Direction::Reverse));
+    assert_eq!((vec![1,4,1,1], vec![1.0,0.0,2.0,4.0]), dijkstra_with_direction(&compact_star, 1, Direction::Both));
+    assert_eq!(vec![0.0,1.0,3.0,inf], dijkstra_with_direction(&compact_star, 0, Direction::Forward).1);
+}
+
+#[test]
+fn test_weighted_dijkstra() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,6.0,0.0),
+                         (0,2,4.0,0.0),
+                         (1,2,2.0,0.0),
+                         (1,3,2.0,0.0),
+                         (2,3,1.0,0.0),
+                         (2,4,2.0,0.0),
+                         (3,5,7.0,0.0),
+                         (4,3,1.0,0.0),
+                         (4,5,3.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(7, &mut edges);
+    let weights: Vec<i64> = compact_star.edges().iter().map(|e| e.2 as i64).collect();
+    let (pred, dist) = weighted_dijkstra(&compact_star, &weights, 0);
+    let (heap_pred, heap_dist) = heap_dijkstra(&compact_star, 0);
+    assert_eq!(heap_pred, pred);
+    assert_eq!(vec![Some(0), Some(6), Some(4), Some(5), Some(6), Some(9), None], dist);
+    assert_eq!(heap_dist[..6].to_vec(), dist[..6].iter().map(|d| d.unwrap() as f64).collect::<Vec<_>>());
+
+    // NaN weights order after every number instead of breaking the heap
+    use super::super::OrderedFloat;
+    let mut weights: Vec<OrderedFloat> = compact_star.edges().iter().map(|e| OrderedFloat(e.2)).collect();
+    weights[1] = OrderedFloat(f64::NAN);
+    let (_, dist) = weighted_dijkstra(&compact_star, &weights, 0);
+    assert_eq!(Some(OrderedFloat(8.0)), dist[2]);
+}
+
+#[test]
+#[should_panic(expected = "weights must be non-negative")]
+fn test_weighted_dijkstra_negative_weight() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    weighted_dijkstra(&compact_star, &[-1i64], 0);
+}
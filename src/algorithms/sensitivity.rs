@@ -0,0 +1,134 @@
+use std::f64;
+
+use super::super::{ Cost, Network, NodeId, NodeVec };
+use super::search_algorithms::heap_dijkstra;
+
+/// How far the cost of one arc may change while the shortest path tree
+/// stays optimal. Changes strictly smaller than the tolerances keep the
+/// tree; at a tolerance another tree becomes equally short.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArcTolerance {
+    /// Whether the arc is part of the shortest path tree.
+    pub in_tree:  bool,
+    /// How much the cost may decrease.
+    pub decrease: Cost,
+    /// How much the cost may increase.
+    pub increase: Cost,
+}
+
+/// Cost tolerances of every arc, in arc order, for the shortest path tree
+/// from `source` (Ahuja, Magnanti, Orlin: "Network Flows", sensitivity
+/// analysis). With `d` the distances and `r(i,j) = c(i,j) + d(i) - d(j)`
+/// the reduced costs:
+///
+/// * an arc outside the tree may become cheaper by `r(i,j)` and arbitrarily
+///   more expensive;
+/// * a tree arc into `j` shifts all distances in the subtree of `j`, so it
+///   may become more expensive by the least reduced cost of the other arcs
+///   entering that subtree, and cheaper by the least reduced cost of the
+///   arcs leaving it.
+///
+/// Arcs that can't be reached from `source` have infinite tolerances. Takes
+/// `O(m h)` time after Dijkstra, `h` being the depth of the tree.
+pub fn arc_tolerances<N: Network>(network: &N, source: NodeId) -> Vec<ArcTolerance> {
+    let n = network.num_nodes();
+    let (pred, d) = heap_dijkstra(network, source);
+    let reachable = |i: NodeId| i == source || pred[i as usize] != network.invalid_id();
+
+    let arcs: Vec<(NodeId, NodeId, Cost)> = network.nodes()
+        .flat_map(|i| network.out_arcs(i).map(move |(j, cost, _)| (i, j, cost)))
+        .collect();
+    // the tree arc into every node: the first cheapest arc from its
+    // predecessor
+    let mut tree_arc = vec![None; n];
+    for (a, &(i, j, cost)) in arcs.iter().enumerate() {
+        let t = j as usize;
+        if j != source && pred[t] == i && tree_arc[t].map_or(true, |b: usize| cost < arcs[b].2) {
+            tree_arc[t] = Some(a);
+        }
+    }
+
+    // preorder intervals: x is an ancestor of y iff enter[x] <= enter[y] < leave[x]
+    let mut children = vec![NodeVec::new(); n];
+    for j in network.nodes().filter(|j| *j != source && reachable(*j)) {
+        children[pred[j as usize] as usize].push(j);
+    }
+    let (mut enter, mut leave) = (vec![0; n], vec![0; n]);
+    let mut clock = 0;
+    let mut stack = vec![(source, 0)];
+    while let Some((x, k)) = stack.pop() {
+        let i = x as usize;
+        if k == 0 {
+            enter[i] = clock;
+            clock += 1;
+        }
+        if k < children[i].len() {
+            stack.push((x, k + 1));
+            stack.push((children[i][k], 0));
+        } else {
+            leave[i] = clock;
+        }
+    }
+    let is_ancestor = |x: NodeId, y: NodeId| enter[x as usize] <= enter[y as usize] && enter[y as usize] < leave[x as usize];
+
+    // the least reduced cost of the arcs entering and leaving every subtree
+    let mut entering = vec![f64::INFINITY; n];
+    let mut leaving = vec![f64::INFINITY; n];
+    for (a, &(i, j, cost)) in arcs.iter().enumerate() {
+        if !reachable(i) || tree_arc[j as usize] == Some(a) {
+            continue;
+        }
+        let reduced = (cost + d[i as usize] - d[j as usize]).max(0.0);
+        let mut x = j;
+        while !is_ancestor(x, i) {
+            entering[x as usize] = entering[x as usize].min(reduced);
+            x = pred[x as usize];
+        }
+        let mut x = i;
+        while !is_ancestor(x, j) {
+            leaving[x as usize] = leaving[x as usize].min(reduced);
+            x = pred[x as usize];
+        }
+    }
+
+    arcs.iter()
+        .enumerate()
+        .map(|(a, &(i, j, cost))| {
+            if !reachable(i) {
+                ArcTolerance { in_tree: false, decrease: f64::INFINITY, increase: f64::INFINITY }
+            } else if tree_arc[j as usize] == Some(a) {
+                ArcTolerance { in_tree: true, decrease: leaving[j as usize], increase: entering[j as usize] }
+            } else {
+                let reduced = (cost + d[i as usize] - d[j as usize]).max(0.0);
+                ArcTolerance { in_tree: false, decrease: reduced, increase: f64::INFINITY }
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_arc_tolerances() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // tree 0 -> 1 -> 2 -> 3, alternatives 0 -> 2 and 1 -> 3, and an arc
+    // 3 -> 1 back into the tree
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,4.0,0.0),
+                         (1,2,1.0,0.0),
+                         (1,3,5.0,0.0),
+                         (2,3,1.0,0.0),
+                         (3,1,1.0,0.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let tolerances = arc_tolerances(&network, 0);
+    let as_tuples: Vec<(bool, f64, f64)> = tolerances.iter().map(|t| (t.in_tree, t.decrease, t.increase)).collect();
+    assert_eq!(vec![(true, f64::INFINITY, 2.0),
+                    (false, 2.0, f64::INFINITY),
+                    (true, 3.0, 2.0),
+                    (false, 3.0, f64::INFINITY),
+                    (true, 3.0, 3.0),
+                    (false, 3.0, f64::INFINITY)], as_tuples);
+
+    // the tolerances are tight: raising 1 -> 2 by 2 makes 0 -> 2 just as short
+    let mut changed = vec![(0,1,1.0,0.0), (0,2,4.0,0.0), (1,2,3.0,0.0), (1,3,5.0,0.0), (2,3,1.0,0.0), (3,1,1.0,0.0)];
+    let changed = compact_star_from_edge_vec(5, &mut changed);
+    assert_eq!(4.0, heap_dijkstra(&changed, 0).1[2]);
+}
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+
+use super::super::{ DoubleVec, Network, NodeId, NodeVec };
+use super::path::Path;
+use super::search_algorithms::heap_dijkstra;
+
+/// All shortest paths from a source at once: the subgraph of tight arcs,
+/// i.e. arcs `(i, j)` with `dist[i] + cost(i, j) == dist[j]`. Every path
+/// from the source along tight arcs is a shortest path and every shortest
+/// path consists of tight arcs only. For positive arc costs the subgraph is
+/// acyclic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShortestPathDag {
+    source:       NodeId,
+    dist:         DoubleVec,
+    predecessors: Vec<NodeVec>,
+    order:        NodeVec,
+}
+
+impl ShortestPathDag {
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// The distances from the source. Unreachable nodes have the network's
+    /// infinity.
+    pub fn distances(&self) -> &DoubleVec {
+        &self.dist
+    }
+
+    /// The tails of all tight arcs into `node`, in arc order.
+    pub fn predecessors(&self, node: NodeId) -> &NodeVec {
+        &self.predecessors[node as usize]
+    }
+
+    /// The nodes reachable from the source, in order of non-decreasing
+    /// distance, starting with the source. Every node comes after all its
+    /// predecessors in the DAG.
+    pub fn order(&self) -> &NodeVec {
+        &self.order
+    }
+
+    pub fn is_reachable(&self, node: NodeId) -> bool {
+        node == self.source || !self.predecessors[node as usize].is_empty()
+    }
+
+    /// All tight arcs as `(tail, head)` pairs.
+    pub fn arcs(&self) -> Vec<(NodeId, NodeId)> {
+        let mut arcs = Vec::new();
+        for j in 0..self.predecessors.len() {
+            for i in &self.predecessors[j] {
+                arcs.push((*i, j as NodeId));
+            }
+        }
+        arcs
+    }
+
+    /// The number of distinct shortest paths from the source to every node.
+    /// Counted in floating point, since the number can grow exponentially
+    /// with the size of the network.
+    pub fn path_counts(&self) -> DoubleVec {
+        let mut counts = vec![0.0; self.dist.len()];
+        counts[self.source as usize] = 1.0;
+        for j in &self.order {
+            for i in &self.predecessors[*j as usize] {
+                counts[*j as usize] += counts[*i as usize];
+            }
+        }
+        counts
+    }
+
+    /// Iterates over all shortest paths from the source to `target`, none
+    /// if `target` is unreachable. Paths are enumerated by a depth first
+    /// search backwards along the tight arcs, which needs memory linear in
+    /// the path length only. Their number can be exponential in the size of
+    /// the network, so limit it with `take` where that matters.
+    pub fn paths_to(&self, target: NodeId) -> ShortestPaths<'_> {
+        let stack = if self.is_reachable(target) { vec![(target, 0)] } else { Vec::new() };
+        ShortestPaths {
+            dag:    self,
+            target: target,
+            stack:  stack,
+        }
+    }
+}
+
+/// Iterator over all shortest paths to a target, see
+/// `ShortestPathDag::paths_to`.
+pub struct ShortestPaths<'a> {
+    dag:    &'a ShortestPathDag,
+    target: NodeId,
+    /// The current partial path from the target backwards, with the index
+    /// of the next predecessor to try for every node.
+    stack:  Vec<(NodeId, usize)>,
+}
+
+impl<'a> Iterator for ShortestPaths<'a> {
+    type Item = Path;
+
+    fn next(&mut self) -> Option<Path> {
+        while let Some(&(node, k)) = self.stack.last() {
+            if node == self.dag.source {
+                let nodes = self.stack.iter().rev().map(|&(i, _)| i).collect();
+                self.stack.pop();
+                return Some(Path::new(nodes, self.dag.dist[self.target as usize]));
+            }
+            let predecessors = self.dag.predecessors(node);
+            if k < predecessors.len() {
+                self.stack.last_mut().unwrap().1 += 1;
+                self.stack.push((predecessors[k], 0));
+            } else {
+                self.stack.pop();
+            }
+        }
+        None
+    }
+}
+
+/// Returns up to `max_paths` distinct shortest paths from `source` to
+/// `target`. See `shortest_path_dag` for `eps`.
+pub fn all_shortest_paths<N: Network>(network: &N, source: NodeId, target: NodeId, eps: f64, max_paths: usize) -> Vec<Path> {
+    shortest_path_dag(network, source, eps).paths_to(target).take(max_paths).collect()
+}
+
+/// Runs Dijkstra's algorithm from `source` and keeps every tight arc instead
+/// of a single predecessor per node. Arcs whose slack
+/// `dist[i] + cost(i, j) - dist[j]` is at most `eps` count as tight, which
+/// absorbs rounding errors on real valued costs; use `0.0` for integral
+/// costs. Arc costs must be positive. A node is a predecessor once, even if
+/// several parallel arcs from it are tight.
+pub fn shortest_path_dag<N: Network>(network: &N, source: NodeId, eps: f64) -> ShortestPathDag {
+    let n = network.num_nodes();
+    let (pred, dist) = heap_dijkstra(network, source);
+    let reachable = |i: NodeId| i == source || pred[i as usize] != network.invalid_id();
+
+    let mut predecessors = vec![NodeVec::new(); n];
+    for (i, j, cost, _) in network.arc_values() {
+        if j == source || !reachable(i) {
+            continue;
+        }
+        // arcs are ordered by tail, so parallel arcs from i come together
+        if dist[i as usize] + cost - dist[j as usize] <= eps && predecessors[j as usize].last() != Some(&i) {
+            predecessors[j as usize].push(i);
+        }
+    }
+
+    let mut order: NodeVec = network.nodes().filter(|i| reachable(*i)).collect();
+    order.sort_by(|a, b| dist[*a as usize].partial_cmp(&dist[*b as usize]).unwrap_or(Ordering::Equal));
+
+    ShortestPathDag {
+        source:       source,
+        dist:         dist,
+        predecessors: predecessors,
+        order:        order,
+    }
+}
+
+#[test]
+fn test_shortest_path_dag() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // two shortest paths 0 -> 1 -> 3 and 0 -> 2 -> 3, doubled again by the
+    // parallel routes 3 -> 4 -> 6 and 3 -> 5 -> 6
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,1.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,1.0,0.0),
+                         (0,3,3.0,0.0),
+                         (3,4,1.0,0.0),
+                         (3,5,1.0,0.0),
+                         (4,6,1.0,0.0),
+                         (5,6,1.0,0.0),
+                         (6,0,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(8, &mut edges);
+    let dag = shortest_path_dag(&compact_star, 0, 0.0);
+
+    assert_eq!(&vec![1,2], dag.predecessors(3));
+    assert!(dag.predecessors(0).is_empty());
+    assert_eq!(vec![(0,1), (0,2), (1,3), (2,3), (3,4), (3,5), (4,6), (5,6)], dag.arcs());
+    assert_eq!(vec![1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 4.0, 0.0], dag.path_counts());
+    assert_eq!(0, dag.order()[0]);
+    assert_eq!(6, *dag.order().last().unwrap());
+    assert!(!dag.is_reachable(7));
+
+    // only the cheaper of the parallel arcs 0 -> 1 is tight
+    let mut edges = vec![(0,1,5.0,0.0), (0,1,1.0,0.0), (0,1,1.0,0.0), (1,2,1.0,0.0)];
+    let dag = shortest_path_dag(&compact_star_from_edge_vec(3, &mut edges), 0, 0.0);
+    assert_eq!(&vec![0], dag.predecessors(1));
+    assert_eq!(vec![1.0, 1.0, 1.0], dag.path_counts());
+}
+
+#[test]
+fn test_all_shortest_paths() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a 3x3 grid with unit costs, 6 shortest paths from corner to corner
+    let mut edges = Vec::new();
+    for row in 0..3 {
+        for col in 0..3 {
+            let node = row * 3 + col;
+            if col < 2 { edges.push((node, node + 1, 1.0, 0.0)); }
+            if row < 2 { edges.push((node, node + 3, 1.0, 0.0)); }
+        }
+    }
+    let compact_star = compact_star_from_edge_vec(9, &mut edges);
+    let dag = shortest_path_dag(&compact_star, 0, 0.0);
+    let paths: Vec<Path> = dag.paths_to(8).collect();
+    assert_eq!(6, paths.len());
+    assert_eq!(6.0, dag.path_counts()[8]);
+    for path in &paths {
+        assert_eq!(4.0, path.cost());
+        assert_eq!(5, path.nodes().len());
+        assert_eq!((Some(0), Some(8)), (path.source(), path.target()));
+    }
+    assert_eq!(&vec![0,1,2,5,8], paths[0].nodes());
+    assert_eq!(2, all_shortest_paths(&compact_star, 0, 8, 0.0, 2).len());
+    assert_eq!(vec![Path::new(vec![0], 0.0)], all_shortest_paths(&compact_star, 0, 0, 0.0, 10));
+    assert!(all_shortest_paths(&compact_star, 8, 0, 0.0, 10).is_empty());
+}
@@ -0,0 +1,163 @@
+use std::f64;
+
+use super::super::{ Capacity, Cost, Network, NodeId };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec };
+use super::super::heaps::{ BinaryHeap, Heap };
+use super::super::linalg::Entries;
+use super::super::random::Rng;
+use super::effective_resistance::approximate_arc_resistances;
+
+/// Greedy `stretch`-spanner (Althöfer et al.: "On sparse spanners of
+/// weighted graphs"): goes through the arcs by ascending cost and keeps an
+/// arc only if the kept arcs don't already connect its end nodes within
+/// `stretch` times its cost. In the result, every shortest path distance is
+/// at most `stretch` times the original one. Arcs keep their costs,
+/// capacities and relative order; ties in cost go to the lower arc number.
+pub fn greedy_spanner<N: Network>(network: &N, stretch: f64) -> CompactStar {
+    assert!(stretch >= 1.0);
+    let n = network.num_nodes();
+    let edges: Vec<(NodeId, NodeId, Cost, Capacity)> = network.arc_values().collect();
+    let mut arcs: Vec<(usize, NodeId, NodeId, Cost)> = edges.iter().enumerate()
+        .map(|(a, &(i, j, cost, _))| (a, i, j, cost))
+        .collect();
+    arcs.sort_by(|x, y| x.3.total_cmp(&y.3).then(x.0.cmp(&y.0)));
+
+    let mut adjacency: Vec<Vec<(NodeId, Cost)>> = vec![Vec::new(); n];
+    let mut kept = Vec::new();
+    let mut d = vec![f64::INFINITY; n];
+    for (a, i, j, cost) in arcs {
+        if bounded_distance(&adjacency, &mut d, i, j, stretch * cost) > stretch * cost {
+            adjacency[i as usize].push((j, cost));
+            kept.push(a);
+        }
+    }
+    kept.sort();
+
+    let mut kept_edges = kept.into_iter().map(|a| edges[a]).collect();
+    compact_star_from_edge_vec(n, &mut kept_edges)
+}
+
+/// The distance from `source` to `target`, or infinity if it exceeds
+/// `bound`. `d` has to be all infinite and is left that way.
+fn bounded_distance(adjacency: &Vec<Vec<(NodeId, Cost)>>, d: &mut Vec<f64>, source: NodeId, target: NodeId, bound: f64) -> f64 {
+    let mut heap = BinaryHeap::new();
+    let mut touched = vec![source as usize];
+    d[source as usize] = 0.0;
+    heap.insert(source, 0.0);
+    let mut result = f64::INFINITY;
+    while let Some(node) = heap.find_min() {
+        heap.delete_min();
+        let i = node as usize;
+        if node == target {
+            result = d[i];
+            break;
+        }
+        for &(j, cost) in &adjacency[i] {
+            let candidate = d[i] + cost;
+            if candidate <= bound && candidate < d[j as usize] {
+                if d[j as usize].is_infinite() {
+                    touched.push(j as usize);
+                }
+                d[j as usize] = candidate;
+                heap.insert(j, candidate);
+            }
+        }
+    }
+    for i in touched {
+        d[i] = f64::INFINITY;
+    }
+    result
+}
+
+/// Spectral sparsification by effective resistances (Spielman, Srivastava:
+/// "Graph sparsification by effective resistances"). The capacities are
+/// the edge weights. Draws `samples` arcs with probabilities proportional
+/// to capacity times effective resistance, approximated with `projections`
+/// Laplacian solves (see `approximate_arc_resistances`), and gives every
+/// drawn arc the capacity `capacity / (samples * probability)`, summed over
+/// repeated draws. With enough samples the Laplacian of the result, and so
+/// every cut, stays close to the original. Drawn arcs keep their costs and
+/// relative order.
+pub fn spectral_sparsify(network: &CompactStar, samples: usize, projections: usize, eps: f64, seed: u64) -> CompactStar {
+    let resistances = approximate_arc_resistances(network, Entries::Capacities, projections, eps, seed);
+    let edges = network.edges();
+    let importance: Vec<f64> = edges.iter().zip(&resistances)
+        .map(|(&(_, _, _, capacity), r)| if r.is_finite() { capacity * r } else { 0.0 })
+        .collect();
+    let mut cumulative = Vec::with_capacity(importance.len());
+    let mut total = 0.0;
+    for value in &importance {
+        total += *value;
+        cumulative.push(total);
+    }
+
+    let mut weights = vec![0.0; edges.len()];
+    if total > 0.0 {
+        let mut rng = Rng::new(seed ^ 0x5DEECE66D);
+        for _ in 0..samples {
+            let x = rng.next_f64() * total;
+            let arc = cumulative.partition_point(|c| *c <= x).min(edges.len() - 1);
+            let probability = importance[arc] / total;
+            weights[arc] += edges[arc].3 / (samples as f64 * probability);
+        }
+    }
+    let mut sampled = edges.into_iter().zip(weights)
+        .filter(|&(_, w)| w > 0.0)
+        .map(|((i, j, cost, _), w)| (i, j, cost, w))
+        .collect();
+    compact_star_from_edge_vec(network.num_nodes(), &mut sampled)
+}
+
+#[test]
+fn test_greedy_spanner() {
+    use super::search_algorithms::heap_dijkstra;
+    let network = super::super::generators::gnp(30, 0.5, &super::super::generators::ArcWeights {
+        cost: super::super::generators::Distribution::Uniform(1.0, 10.0),
+        ..Default::default()
+    }, 3);
+    let spanner = greedy_spanner(&network, 2.0);
+    assert!(spanner.num_arcs() < network.num_arcs() / 2);
+    for source in 0..5 {
+        let (_, original) = heap_dijkstra(&network, source);
+        let (_, stretched) = heap_dijkstra(&spanner, source);
+        for i in 0..30 {
+            assert!(stretched[i] <= 2.0 * original[i] + 1e-9);
+        }
+    }
+}
+
+#[test]
+fn test_greedy_spanner_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // the cheaper of the two parallel arcs is kept, with its capacity
+    let mut edges = vec![(0,1,5.0,50.0), (0,1,2.0,20.0)];
+    let network = compact_star_from_edge_vec(2, &mut edges);
+    let spanner = greedy_spanner(&network, 1.5);
+    assert_eq!(vec![(0, 1, 2.0, 20.0)], spanner.arc_values().collect::<Vec<_>>());
+}
+
+#[test]
+fn test_spectral_sparsify() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // complete undirected network on 20 nodes, unit capacities
+    let mut edges = Vec::new();
+    for i in 0..20 {
+        for j in 0..20 {
+            if i != j {
+                edges.push((i, j, 1.0, 1.0));
+            }
+        }
+    }
+    let network = compact_star_from_edge_vec(20, &mut edges);
+    let sparse = spectral_sparsify(&network, 200, 50, 1e-10, 4);
+    assert!(sparse.num_arcs() < 200);
+    let total: f64 = sparse.arc_values().map(|(_, _, _, capacity)| capacity).sum();
+    assert!((total - 380.0).abs() < 0.2 * 380.0);
+    // the cut between the first and the last ten nodes has capacity 200
+    let cut: f64 = sparse.arc_values()
+        .filter(|&(i, j, _, _)| (i < 10) != (j < 10))
+        .map(|(_, _, _, capacity)| capacity)
+        .sum();
+    assert!((cut - 200.0).abs() < 0.3 * 200.0);
+    assert_eq!(sparse, spectral_sparsify(&network, 200, 50, 1e-10, 4));
+}
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+
+use super::super::{ Direction, Network, NodeId, NodeVec };
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::adjacency::reverse_adjacency;
+
+/// Returns the subgraph induced by `nodes`, i.e. these nodes and all arcs
+/// between them, with costs and capacities. Node `nodes[k]` gets the id `k`
+/// in the subgraph, so `nodes` also serves as the mapping from the new ids
+/// back to the original ones. Parallel arcs are all kept, and an empty
+/// `nodes` gives a network without nodes.
+pub fn induced_subgraph<N: Network>(network: &N, nodes: &NodeVec) -> CompactStar {
+    if nodes.is_empty() {
+        return empty_compact_star();
+    }
+    let invalid = network.invalid_id();
+    let mut new_id = vec![invalid; network.num_nodes()];
+    for (k, node) in nodes.iter().enumerate() {
+        new_id[*node as usize] = k as NodeId;
+    }
+
+    let mut edges = Vec::new();
+    for (k, node) in nodes.iter().enumerate() {
+        for (adjacent_node, cost, capacity) in network.out_arcs(*node) {
+            let to = new_id[adjacent_node as usize];
+            if to == invalid {
+                continue;
+            }
+            edges.push((k as NodeId, to, cost, capacity));
+        }
+    }
+    compact_star_from_edge_vec(nodes.len(), &mut edges)
+}
+
+/// Carries per node data such as labels or attributes over to a subgraph or
+/// sample whose nodes have the original ids `nodes`: entry `k` of the result
+/// belongs to node `k` of the subgraph.
+pub fn remap_node_data<T: Clone>(data: &[T], nodes: &NodeVec) -> Vec<T> {
+    nodes.iter().map(|node| data[*node as usize].clone()).collect()
+}
+
+/// Extracts the ego network of `center`: the subgraph induced by all nodes
+/// within `radius` hops of it. `direction` determines whether hops follow
+/// the arcs (nodes reachable from `center`), go against them (nodes that
+/// reach `center`), or both.
+///
+/// Returns the subgraph and the original ids of its nodes. The nodes are
+/// numbered in order of their hop distance, so `center` gets the id `0`.
+pub fn ego_network<N: Network>(network: &N, center: NodeId, radius: usize, direction: Direction) -> (CompactStar, NodeVec) {
+    let n = network.num_nodes();
+    let reverse = match direction {
+        Direction::Forward => Vec::new(),
+        _ => reverse_adjacency(network)
+    };
+
+    let mut hops = vec![usize::MAX; n];
+    let mut nodes = vec![center];
+    let mut queue = VecDeque::new();
+    hops[center as usize] = 0;
+    queue.push_back(center);
+
+    while let Some(i) = queue.pop_front() {
+        let next_hops = hops[i as usize] + 1;
+        if next_hops > radius {
+            continue;
+        }
+        let mut neighbors = Vec::new();
+        if direction != Direction::Reverse {
+            neighbors.extend(network.adjacent(i));
+        }
+        if direction != Direction::Forward {
+            neighbors.extend(reverse[i as usize].iter().map(|&(tail, _)| tail));
+        }
+        for j in neighbors {
+            if hops[j as usize] == usize::MAX {
+                hops[j as usize] = next_hops;
+                nodes.push(j);
+                queue.push_back(j);
+            }
+        }
+    }
+
+    (induced_subgraph(network, &nodes), nodes)
+}
+
+#[test]
+fn test_induced_subgraph() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,1.0,10.0),
+        (1,2,2.0,20.0),
+        (2,0,3.0,30.0),
+        (2,3,4.0,40.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    let subgraph = induced_subgraph(&compact_star, &vec![2,0]);
+    assert_eq!(2, subgraph.num_nodes());
+    assert_eq!(1, subgraph.num_arcs());
+    assert_eq!(vec![1], subgraph.adjacent(0));
+    assert_eq!(Some(3.0), subgraph.cost(0,1));
+    assert_eq!(Some(30.0), subgraph.capacity(0,1));
+
+    let empty = induced_subgraph(&compact_star, &vec![]);
+    assert_eq!(0, empty.num_nodes());
+    assert_eq!(0, empty.num_arcs());
+}
+
+#[test]
+fn test_induced_subgraph_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,1.0,10.0),
+        (0,1,2.0,20.0),
+        (1,2,3.0,30.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let subgraph = induced_subgraph(&compact_star, &vec![0,1]);
+    assert_eq!(2, subgraph.num_arcs());
+    let mut arcs: Vec<_> = subgraph.out_arcs(0).collect();
+    arcs.sort_by(|a, b| a.1.total_cmp(&b.1));
+    assert_eq!(vec![(1,1.0,10.0), (1,2.0,20.0)], arcs);
+}
+
+#[test]
+fn test_ego_network() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a directed path 0 -> 1 -> 2 -> 3 -> 4
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (1,2,1.0,0.0),
+        (2,3,1.0,0.0),
+        (3,4,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(5, &mut edges);
+
+    let (ego, nodes) = ego_network(&compact_star, 2, 1, Direction::Forward);
+    assert_eq!(vec![2,3], nodes);
+    assert_eq!(vec![1], ego.adjacent(0));
+
+    let (_, nodes) = ego_network(&compact_star, 2, 2, Direction::Reverse);
+    assert_eq!(vec![2,1,0], nodes);
+
+    let (ego, nodes) = ego_network(&compact_star, 2, 1, Direction::Both);
+    assert_eq!(vec![2,3,1], nodes);
+    assert_eq!(2, ego.num_arcs());
+    assert_eq!(vec![0], ego.adjacent(2));
+}
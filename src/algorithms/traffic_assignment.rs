@@ -0,0 +1,397 @@
+use super::super::{ Capacity, Cost, DoubleVec, Network, NodeId };
+use super::super::cancel::{ Cancellation, Cancelled };
+use super::adjacency::{ CostAdjacency, adjacency_dijkstra };
+use super::od_matrix::ODMatrix;
+
+/// Origin-destination demand as `(origin, destination, volume)` triples.
+pub type Demand = Vec<(NodeId, NodeId, f64)>;
+
+/// A volume-delay function maps the traffic volume on an arc to the travel
+/// time on it.
+pub trait VolumeDelay {
+    /// Travel time on the arc at the given volume.
+    fn travel_time(&self, volume: f64) -> f64;
+    /// Integral of the travel time from `0` to `volume`, i.e. the arc's
+    /// contribution to the Beckmann objective.
+    fn integral(&self, volume: f64) -> f64;
+    /// Derivative of the travel time with respect to the volume.
+    fn derivative(&self, volume: f64) -> f64;
+}
+
+/// The volume-delay function of the Bureau of Public Roads,
+/// `t(v) = t0 * (1 + alpha * (v/c)^beta)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bpr {
+    pub free_flow_time: Cost,
+    pub capacity:       Capacity,
+    pub alpha:          f64,
+    pub beta:           f64,
+}
+
+impl Bpr {
+    pub fn new(free_flow_time: Cost, capacity: Capacity, alpha: f64, beta: f64) -> Bpr {
+        Bpr {
+            free_flow_time: free_flow_time,
+            capacity:       capacity,
+            alpha:          alpha,
+            beta:           beta,
+        }
+    }
+}
+
+impl VolumeDelay for Bpr {
+    /// Arcs without capacity are not congestible and always take the free
+    /// flow time.
+    fn travel_time(&self, volume: f64) -> f64 {
+        if self.capacity <= 0.0 {
+            return self.free_flow_time;
+        }
+        self.free_flow_time * (1.0 + self.alpha * (volume / self.capacity).powf(self.beta))
+    }
+
+    fn integral(&self, volume: f64) -> f64 {
+        if self.capacity <= 0.0 {
+            return self.free_flow_time * volume;
+        }
+        let congestion = self.alpha * self.capacity / (self.beta + 1.0)
+            * (volume / self.capacity).powf(self.beta + 1.0);
+        self.free_flow_time * (volume + congestion)
+    }
+
+    fn derivative(&self, volume: f64) -> f64 {
+        if self.capacity <= 0.0 || volume <= 0.0 {
+            return 0.0;
+        }
+        self.free_flow_time * self.alpha * self.beta / self.capacity
+            * (volume / self.capacity).powf(self.beta - 1.0)
+    }
+}
+
+/// The marginal cost `t(v) + v * t'(v)` of a volume-delay function, i.e.
+/// the increase of the total travel time on an arc caused by one more unit
+/// of volume. An equilibrium on marginal costs is the system optimum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MarginalCost<V> {
+    pub function: V,
+}
+
+impl<V: VolumeDelay> VolumeDelay for MarginalCost<V> {
+    fn travel_time(&self, volume: f64) -> f64 {
+        self.function.travel_time(volume) + volume * self.function.derivative(volume)
+    }
+
+    /// The total travel time `v * t(v)` on the arc.
+    fn integral(&self, volume: f64) -> f64 {
+        volume * self.function.travel_time(volume)
+    }
+
+    fn derivative(&self, volume: f64) -> f64 {
+        let h = 1e-6 * (1.0 + volume.abs());
+        (self.travel_time(volume + h) - self.travel_time(volume)) / h
+    }
+}
+
+/// Creates one BPR function per arc, using the arc cost as free flow time
+/// and the arc capacity as capacity. The common choice for the parameters
+/// is `alpha = 0.15` and `beta = 4.0`.
+pub fn bpr_functions<N: Network>(network: &N, alpha: f64, beta: f64) -> Vec<Bpr> {
+    network.arc_values()
+        .map(|(_, _, cost, capacity)| Bpr::new(cost, capacity, alpha, beta))
+        .collect()
+}
+
+/// Result of a traffic assignment. All vectors are indexed by arc, see
+/// `frank_wolfe` for the arc numbering.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    /// Traffic volume per arc.
+    pub flows:      DoubleVec,
+    /// Travel time per arc at the assigned volume.
+    pub times:      DoubleVec,
+    /// The relative gap between the total travel time and the total travel
+    /// time if everybody used a current shortest path.
+    pub gap:        f64,
+    /// The value of the objective function that was minimized.
+    pub objective:  f64,
+    pub iterations: usize,
+}
+
+/// Computes the user equilibrium of a static traffic assignment with the
+/// Frank-Wolfe algorithm.
+///
+/// Starting from an all-or-nothing assignment on free flow times, every
+/// iteration assigns all demand to the shortest paths with respect to the
+/// current travel times and moves the flows towards this assignment by the
+/// step size that minimizes the Beckmann objective (found by bisection).
+/// The loop stops when the relative gap drops below `eps` or after
+/// `max_iter` iterations.
+///
+/// Arcs are numbered in the order they are enumerated by
+/// `network.adjacent(i)` for all nodes `i` in ascending order, which is the
+/// order of the arc arrays of a `CompactStar`. `functions` holds one
+/// volume-delay function per arc in that order. `demand` is the volume
+/// between origin and destination pairs; a `Demand` list converts into it
+/// with `ODMatrix::from`.
+pub fn frank_wolfe<N: Network, V: VolumeDelay>(network: &N, functions: &[V], demand: &ODMatrix,
+                                               max_iter: usize, eps: f64) -> Assignment {
+    frank_wolfe_cancellable(network, functions, demand, max_iter, eps, &Cancellation::new())
+        .expect("Frank-Wolfe cannot be cancelled without a token")
+}
+
+/// Like `frank_wolfe`, but checks `cancellation` before every iteration.
+pub fn frank_wolfe_cancellable<N: Network, V: VolumeDelay>(network: &N, functions: &[V], demand: &ODMatrix,
+                                                           max_iter: usize, eps: f64,
+                                                           cancellation: &Cancellation) -> Result<Assignment, Cancelled> {
+    let arc_count = functions.len();
+    let mut adjacency = arc_adjacency(network);
+    let offsets = arc_offsets(&adjacency);
+    assert!(offsets[adjacency.len()] == arc_count);
+
+    let mut times: DoubleVec = functions.iter().map(|f| f.travel_time(0.0)).collect();
+    update_costs(&mut adjacency, &times);
+    let mut flows = all_or_nothing(&adjacency, &offsets, demand);
+    let mut gap = 1.0;
+    let mut iterations = 0;
+
+    while iterations < max_iter {
+        cancellation.check()?;
+        iterations += 1;
+        for a in 0..arc_count {
+            times[a] = functions[a].travel_time(flows[a]);
+        }
+        update_costs(&mut adjacency, &times);
+        let target = all_or_nothing(&adjacency, &offsets, demand);
+
+        gap = relative_gap(&times, &flows, &target);
+        if gap < eps {
+            break;
+        }
+
+        let direction: DoubleVec = (0..arc_count).map(|a| target[a] - flows[a]).collect();
+        let step = line_search(functions, &flows, &direction);
+        for a in 0..arc_count {
+            flows[a] += step * direction[a];
+        }
+    }
+
+    for a in 0..arc_count {
+        times[a] = functions[a].travel_time(flows[a]);
+    }
+    let objective = (0..arc_count).map(|a| functions[a].integral(flows[a])).sum();
+    Ok(Assignment {
+        flows:      flows,
+        times:      times,
+        gap:        gap,
+        objective:  objective,
+        iterations: iterations,
+    })
+}
+
+/// Computes the system optimal assignment, which minimizes the total travel
+/// time of all users, with the Frank-Wolfe algorithm on marginal costs.
+/// The reported `times` are the actual travel times on the arcs and the
+/// `objective` is the total travel time.
+/// See `frank_wolfe` for the parameters and the arc numbering.
+pub fn system_optimum<N: Network, V: VolumeDelay + Clone>(network: &N, functions: &[V], demand: &ODMatrix,
+                                                          max_iter: usize, eps: f64) -> Assignment {
+    let marginal: Vec<MarginalCost<V>> = functions.iter()
+        .map(|f| MarginalCost { function: f.clone() })
+        .collect();
+    let mut assignment = frank_wolfe(network, &marginal, demand, max_iter, eps);
+    for a in 0..functions.len() {
+        assignment.times[a] = functions[a].travel_time(assignment.flows[a]);
+    }
+    assignment.objective = total_travel_time(functions, &assignment.flows);
+    assignment
+}
+
+/// The sum of volume times travel time over all arcs.
+pub fn total_travel_time<V: VolumeDelay>(functions: &[V], flows: &DoubleVec) -> f64 {
+    (0..functions.len())
+        .map(|a| flows[a] * functions[a].travel_time(flows[a]))
+        .sum()
+}
+
+/// User equilibrium and system optimum of the same instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquilibriumComparison {
+    pub user_equilibrium:  Assignment,
+    pub system_optimum:    Assignment,
+    /// Total travel time in user equilibrium divided by the total travel
+    /// time in the system optimum. It is at least `1.0`, up to the
+    /// precision of the two assignments.
+    pub price_of_anarchy:  f64,
+}
+
+/// Solves both the user equilibrium and the system optimum of an instance
+/// and reports the price of anarchy.
+pub fn compare_equilibria<N: Network, V: VolumeDelay + Clone>(network: &N, functions: &[V], demand: &ODMatrix,
+                                                              max_iter: usize, eps: f64) -> EquilibriumComparison {
+    let user_equilibrium = frank_wolfe(network, functions, demand, max_iter, eps);
+    let system_optimum = system_optimum(network, functions, demand, max_iter, eps);
+    let ue_time = total_travel_time(functions, &user_equilibrium.flows);
+    let price_of_anarchy = if system_optimum.objective > 0.0 {
+        ue_time / system_optimum.objective
+    } else {
+        1.0
+    };
+    EquilibriumComparison {
+        user_equilibrium: user_equilibrium,
+        system_optimum:   system_optimum,
+        price_of_anarchy: price_of_anarchy,
+    }
+}
+
+/// The outgoing arcs of every node with costs, in arc order.
+fn arc_adjacency<N: Network>(network: &N) -> CostAdjacency {
+    (0..network.num_nodes())
+        .map(|i| network.adjacent(i as NodeId).into_iter().map(|j| (j, 0.0)).collect())
+        .collect()
+}
+
+/// `offsets[i]` is the number of the first arc leaving node `i`.
+fn arc_offsets(adjacency: &CostAdjacency) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(adjacency.len() + 1);
+    let mut sum = 0;
+    offsets.push(sum);
+    for arcs in adjacency {
+        sum += arcs.len();
+        offsets.push(sum);
+    }
+    offsets
+}
+
+fn update_costs(adjacency: &mut CostAdjacency, times: &DoubleVec) {
+    let mut a = 0;
+    for arcs in adjacency.iter_mut() {
+        for arc in arcs.iter_mut() {
+            arc.1 = times[a];
+            a += 1;
+        }
+    }
+}
+
+/// Assigns every demand to a single shortest path with respect to the costs
+/// in `adjacency`. Demand between disconnected nodes is dropped.
+fn all_or_nothing(adjacency: &CostAdjacency, offsets: &Vec<usize>, demand: &ODMatrix) -> DoubleVec {
+    let mut flows = vec![0.0; offsets[adjacency.len()]];
+    for origin in demand.origins() {
+        let (pred, _) = adjacency_dijkstra(adjacency, origin, None);
+        for (_, destination, volume) in demand.iter().filter(|&(o, _, _)| o == origin) {
+            let mut j = destination;
+            while j != origin {
+                let i = pred[j as usize];
+                if i as usize >= adjacency.len() {
+                    break;
+                }
+                flows[cheapest_arc(adjacency, offsets, i, j)] += volume;
+                j = i;
+            }
+        }
+    }
+    flows
+}
+
+/// The number of the cheapest arc from `i` to `j`.
+fn cheapest_arc(adjacency: &CostAdjacency, offsets: &Vec<usize>, i: NodeId, j: NodeId) -> usize {
+    let arcs = &adjacency[i as usize];
+    let mut best = 0;
+    for k in 0..arcs.len() {
+        if arcs[k].0 == j && (arcs[best].0 != j || arcs[k].1 < arcs[best].1) {
+            best = k;
+        }
+    }
+    offsets[i as usize] + best
+}
+
+fn relative_gap(times: &DoubleVec, flows: &DoubleVec, target: &DoubleVec) -> f64 {
+    let mut total = 0.0;
+    let mut shortest = 0.0;
+    for a in 0..times.len() {
+        total += times[a] * flows[a];
+        shortest += times[a] * target[a];
+    }
+    if total > 0.0 {
+        (total - shortest) / total
+    } else {
+        0.0
+    }
+}
+
+/// Finds the step size in `[0, 1]` along `direction` that minimizes the
+/// objective, by bisection on its derivative.
+fn line_search<V: VolumeDelay>(functions: &[V], flows: &DoubleVec, direction: &DoubleVec) -> f64 {
+    let derivative = |step: f64| -> f64 {
+        (0..flows.len())
+            .map(|a| functions[a].travel_time(flows[a] + step * direction[a]) * direction[a])
+            .sum()
+    };
+    if derivative(1.0) <= 0.0 {
+        return 1.0;
+    }
+    let mut lower = 0.0;
+    let mut upper = 1.0;
+    for _ in 0..40 {
+        let step = 0.5 * (lower + upper);
+        if derivative(step) < 0.0 {
+            lower = step;
+        } else {
+            upper = step;
+        }
+    }
+    0.5 * (lower + upper)
+}
+
+#[test]
+fn test_bpr() {
+    let bpr = Bpr::new(10.0, 100.0, 0.15, 4.0);
+    assert_eq!(10.0, bpr.travel_time(0.0));
+    assert_eq!(11.5, bpr.travel_time(100.0));
+    assert!((bpr.integral(100.0) - (1000.0 + 10.0 * 0.15 * 100.0 / 5.0)).abs() < 1e-9);
+    assert!((bpr.derivative(100.0) - 0.06).abs() < 1e-12);
+    assert_eq!(10.0, Bpr::new(10.0, 0.0, 0.15, 4.0).travel_time(50.0));
+}
+
+#[test]
+fn test_frank_wolfe() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // a congestible direct arc 0->1 and an uncongested detour via node 2
+    let mut edges = vec![
+        (0,1,1.0,1.0),
+        (0,2,1.5,0.0),
+        (2,1,0.5,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let functions = bpr_functions(&compact_star, 1.0, 1.0);
+    let demand = ODMatrix::from(&vec![(0, 1, 2.0)]);
+    let assignment = frank_wolfe(&compact_star, &functions, &demand, 200, 1e-6);
+    // in equilibrium both routes take 2.0 time units
+    assert!((assignment.flows[0] - 1.0).abs() < 1e-3);
+    assert!((assignment.flows[1] - 1.0).abs() < 1e-3);
+    assert!((assignment.flows[2] - 1.0).abs() < 1e-3);
+    assert!((assignment.times[0] - 2.0).abs() < 1e-3);
+    assert!(assignment.gap < 1e-3);
+}
+
+#[test]
+fn test_price_of_anarchy() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // Pigou's example: two parallel arcs with t(v) = 1 and t(v) = v
+    #[derive(Clone)]
+    struct Linear { constant: f64, slope: f64 }
+    impl VolumeDelay for Linear {
+        fn travel_time(&self, volume: f64) -> f64 { self.constant + self.slope * volume }
+        fn integral(&self, volume: f64) -> f64 { self.constant * volume + 0.5 * self.slope * volume * volume }
+        fn derivative(&self, _: f64) -> f64 { self.slope }
+    }
+    let mut edges = vec![
+        (0,1,1.0,0.0),
+        (0,1,0.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    let functions = vec![Linear { constant: 1.0, slope: 0.0 }, Linear { constant: 0.0, slope: 1.0 }];
+    let demand = ODMatrix::from(&vec![(0, 1, 1.0)]);
+    let comparison = compare_equilibria(&compact_star, &functions, &demand, 1000, 1e-8);
+    assert!((comparison.user_equilibrium.flows[1] - 1.0).abs() < 1e-2);
+    assert!((comparison.system_optimum.flows[1] - 0.5).abs() < 1e-2);
+    assert!((comparison.system_optimum.objective - 0.75).abs() < 1e-3);
+    assert!((comparison.price_of_anarchy - 4.0 / 3.0).abs() < 1e-2);
+}
@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+
+use super::super::NodeId;
+use super::super::compact_star::{ CompactStar, compact_star_from_edge_vec };
+use super::components::strongly_connected_components;
+
+/// A variable of a 2-SAT instance or its negation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Literal {
+    pub variable: usize,
+    pub positive: bool,
+}
+
+impl Literal {
+    /// The literal `x_variable`.
+    pub fn pos(variable: usize) -> Literal {
+        Literal { variable: variable, positive: true }
+    }
+
+    /// The literal `not x_variable`.
+    pub fn neg(variable: usize) -> Literal {
+        Literal { variable: variable, positive: false }
+    }
+
+    pub fn negate(self) -> Literal {
+        Literal { variable: self.variable, positive: !self.positive }
+    }
+
+    /// The node of the literal in the implication graph: `2 * variable` for
+    /// the positive and `2 * variable + 1` for the negative literal.
+    pub fn node(self) -> NodeId {
+        (2 * self.variable + if self.positive { 0 } else { 1 }) as NodeId
+    }
+}
+
+/// Found by `TwoSat::solve` if the clauses can't be satisfied: `variable`
+/// and its negation imply each other.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unsatisfiable {
+    pub variable: usize,
+}
+
+impl fmt::Display for Unsatisfiable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unsatisfiable: x{} and its negation imply each other", self.variable)
+    }
+}
+
+impl Error for Unsatisfiable {}
+
+/// A 2-SAT instance: a conjunction of clauses with two literals each, like
+/// "task 3 runs in the morning or task 5 runs in the morning". Solved in
+/// linear time by the strongly connected components of the implication
+/// graph (Aspvall, Plass, Tarjan: "A linear-time algorithm for testing the
+/// truth of certain quantified boolean formulas").
+#[derive(Debug, Clone, PartialEq)]
+pub struct TwoSat {
+    num_variables: usize,
+    clauses:       Vec<(Literal, Literal)>,
+}
+
+impl TwoSat {
+    /// An instance over the variables `0..num_variables` without clauses.
+    pub fn new(num_variables: usize) -> TwoSat {
+        TwoSat {
+            num_variables: num_variables,
+            clauses:       Vec::new(),
+        }
+    }
+
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    pub fn clauses(&self) -> &[(Literal, Literal)] {
+        &self.clauses
+    }
+
+    /// Adds the clause `a or b`. A single literal `a` is forced by the
+    /// clause `a or a`.
+    /// # Panics
+    /// If a literal refers to a variable beyond `num_variables`.
+    pub fn add_clause(&mut self, a: Literal, b: Literal) {
+        assert!(a.variable < self.num_variables && b.variable < self.num_variables, "unknown variable in clause");
+        self.clauses.push((a, b));
+    }
+
+    /// Adds the clauses for `a implies b`.
+    pub fn add_implication(&mut self, a: Literal, b: Literal) {
+        self.add_clause(a.negate(), b);
+    }
+
+    /// Adds the clauses for "not both `a` and `b`".
+    pub fn add_at_most_one(&mut self, a: Literal, b: Literal) {
+        self.add_clause(a.negate(), b.negate());
+    }
+
+    /// The implication graph with a node per literal, see `Literal::node`,
+    /// and the arcs `not a -> b` and `not b -> a` for every clause
+    /// `a or b`. All costs and capacities are `0.0`.
+    /// # Panics
+    /// If the instance has no variables.
+    pub fn implication_graph(&self) -> CompactStar {
+        let mut edges = Vec::with_capacity(2 * self.clauses.len());
+        for &(a, b) in &self.clauses {
+            edges.push((a.negate().node(), b.node(), 0.0, 0.0));
+            edges.push((b.negate().node(), a.node(), 0.0, 0.0));
+        }
+        compact_star_from_edge_vec(2 * self.num_variables, &mut edges)
+    }
+
+    /// A satisfying assignment, one value per variable. A variable is set
+    /// to `true` if its positive literal comes after its negation in the
+    /// topological order of the condensation, so no true literal implies a
+    /// false one.
+    /// # Errors
+    /// `Unsatisfiable` with the lowest variable that is in the same strongly
+    /// connected component as its negation.
+    pub fn solve(&self) -> Result<Vec<bool>, Unsatisfiable> {
+        if self.num_variables == 0 {
+            return Ok(Vec::new());
+        }
+        let (_, labels) = strongly_connected_components(&self.implication_graph());
+        (0..self.num_variables)
+            .map(|v| {
+                let (x, not_x) = (labels[Literal::pos(v).node() as usize], labels[Literal::neg(v).node() as usize]);
+                // components are numbered in reverse topological order
+                if x == not_x { Err(Unsatisfiable { variable: v }) } else { Ok(x < not_x) }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+fn satisfies(instance: &TwoSat, assignment: &[bool]) -> bool {
+    let value = |l: Literal| assignment[l.variable] == l.positive;
+    instance.clauses().iter().all(|&(a, b)| value(a) || value(b))
+}
+
+#[test]
+fn test_two_sat() {
+    use super::super::Network;
+    // (x0 or x1), (not x0 or x2), (not x1 or not x2), (x2 or x3), x0
+    let mut instance = TwoSat::new(4);
+    instance.add_clause(Literal::pos(0), Literal::pos(1));
+    instance.add_implication(Literal::pos(0), Literal::pos(2));
+    instance.add_at_most_one(Literal::pos(1), Literal::pos(2));
+    instance.add_clause(Literal::pos(2), Literal::pos(3));
+    instance.add_clause(Literal::pos(0), Literal::pos(0));
+    let assignment = instance.solve().unwrap();
+    assert!(satisfies(&instance, &assignment));
+    assert_eq!(vec![true, false, true], assignment[..3].to_vec());
+    assert_eq!(10, instance.implication_graph().num_arcs());
+
+    // forcing not x2 as well contradicts x0 -> x2
+    instance.add_clause(Literal::neg(2), Literal::neg(2));
+    assert_eq!(Err(Unsatisfiable { variable: 0 }), instance.solve());
+    assert_eq!(Ok(Vec::new()), TwoSat::new(0).solve());
+}
+
+#[test]
+fn test_two_sat_random() {
+    use super::super::random::Rng;
+    let mut rng = Rng::new(17);
+    for _ in 0..200 {
+        let n = 1 + rng.below(6);
+        let mut instance = TwoSat::new(n);
+        for _ in 0..rng.below(3 * n) {
+            let literal = |rng: &mut Rng| Literal { variable: rng.below(n), positive: rng.chance(0.5) };
+            let (a, b) = (literal(&mut rng), literal(&mut rng));
+            instance.add_clause(a, b);
+        }
+        let brute_force = (0..1u32 << n)
+            .map(|bits| (0..n).map(|v| bits & (1 << v) != 0).collect::<Vec<bool>>())
+            .any(|assignment| satisfies(&instance, &assignment));
+        match instance.solve() {
+            Ok(assignment) => assert!(satisfies(&instance, &assignment)),
+            Err(_) => assert!(!brute_force),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::VecDeque;
+use std::f64;
+
+use super::super::{ Network, NodeId, NodeVec };
+use super::cost_model::shortest_path_with_model;
+use super::path::Path;
+
+/// The type of a node or arc in a heterogeneous network, a small class
+/// number below `MAX_TYPES` defined by the application, e.g. `ROAD = 0`.
+pub type TypeTag = u8;
+
+/// The number of distinct type tags, so that sets of types fit a bit mask.
+pub const MAX_TYPES: usize = 64;
+
+/// The type tags of all nodes and arcs of a network, one byte each. Arcs
+/// are tagged in arc order (see `Network::arcs`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeTags {
+    node_types: Vec<TypeTag>,
+    arc_types:  Vec<TypeTag>,
+}
+
+impl TypeTags {
+    /// Tags for `network`, one per node and one per arc in arc order.
+    /// # Panics
+    /// If there isn't one tag per node and arc, or a tag is not below
+    /// `MAX_TYPES`.
+    pub fn new<N: Network>(network: &N, node_types: Vec<TypeTag>, arc_types: Vec<TypeTag>) -> TypeTags {
+        assert_eq!(network.num_nodes(), node_types.len(), "one type per node is required");
+        assert_eq!(network.num_arcs(), arc_types.len(), "one type per arc is required");
+        assert!(node_types.iter().chain(&arc_types).all(|t| (*t as usize) < MAX_TYPES),
+                "type tags must be below MAX_TYPES");
+        TypeTags {
+            node_types: node_types,
+            arc_types:  arc_types,
+        }
+    }
+
+    /// Tags for `network` with all nodes and arcs of type `0`.
+    pub fn untyped<N: Network>(network: &N) -> TypeTags {
+        TypeTags::new(network, vec![0; network.num_nodes()], vec![0; network.num_arcs()])
+    }
+
+    pub fn node_type(&self, node: NodeId) -> TypeTag {
+        self.node_types[node as usize]
+    }
+
+    /// The type of the arc with number `arc`.
+    pub fn arc_type(&self, arc: usize) -> TypeTag {
+        self.arc_types[arc]
+    }
+
+    pub fn node_types(&self) -> &[TypeTag] {
+        &self.node_types
+    }
+
+    pub fn arc_types(&self) -> &[TypeTag] {
+        &self.arc_types
+    }
+}
+
+/// The node and arc types a traversal may expand, as bit masks. By default
+/// all types are allowed. The methods that take types panic if one is not
+/// below `MAX_TYPES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TypeFilter {
+    nodes: u64,
+    arcs:  u64,
+}
+
+impl Default for TypeFilter {
+    fn default() -> TypeFilter {
+        TypeFilter { nodes: !0, arcs: !0 }
+    }
+}
+
+impl TypeFilter {
+    pub fn new() -> TypeFilter {
+        TypeFilter::default()
+    }
+
+    /// Allows only arcs of the given types.
+    pub fn only_arcs(mut self, types: &[TypeTag]) -> TypeFilter {
+        self.arcs = mask(types);
+        self
+    }
+
+    /// Allows only nodes of the given types.
+    pub fn only_nodes(mut self, types: &[TypeTag]) -> TypeFilter {
+        self.nodes = mask(types);
+        self
+    }
+
+    /// Excludes arcs of the given types.
+    pub fn skip_arcs(mut self, types: &[TypeTag]) -> TypeFilter {
+        self.arcs &= !mask(types);
+        self
+    }
+
+    /// Excludes nodes of the given types.
+    pub fn skip_nodes(mut self, types: &[TypeTag]) -> TypeFilter {
+        self.nodes &= !mask(types);
+        self
+    }
+
+    pub fn allows_node(&self, node_type: TypeTag) -> bool {
+        self.nodes & bit(node_type) != 0
+    }
+
+    pub fn allows_arc(&self, arc_type: TypeTag) -> bool {
+        self.arcs & bit(arc_type) != 0
+    }
+}
+
+fn bit(t: TypeTag) -> u64 {
+    assert!((t as usize) < MAX_TYPES, "type tags must be below MAX_TYPES");
+    1 << t
+}
+
+fn mask(types: &[TypeTag]) -> u64 {
+    types.iter().fold(0, |mask, t| mask | bit(*t))
+}
+
+/// Breadth first search from `start` over the allowed arcs into allowed
+/// nodes only. Returns predecessors and the visiting order like
+/// `breadth_first_search`; the order is empty if `start` itself is not
+/// allowed.
+pub fn typed_breadth_first_search<N: Network>(network: &N, tags: &TypeTags, filter: &TypeFilter,
+                                              start: NodeId) -> (NodeVec, NodeVec) {
+    let n = network.num_nodes();
+    let mut pred = vec![network.invalid_id(); n];
+    let mut order = NodeVec::new();
+    if !filter.allows_node(tags.node_type(start)) {
+        return (pred, order);
+    }
+    let first_arcs = network.first_arcs();
+
+    let mut visited = vec![false; n];
+    visited[start as usize] = true;
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(i) = queue.pop_front() {
+        order.push(i);
+        for (k, (j, _, _)) in network.out_arcs(i).enumerate() {
+            let arc = first_arcs[i as usize] + k;
+            if !visited[j as usize] && filter.allows_arc(tags.arc_type(arc)) && filter.allows_node(tags.node_type(j)) {
+                visited[j as usize] = true;
+                pred[j as usize] = i;
+                queue.push_back(j);
+            }
+        }
+    }
+    (pred, order)
+}
+
+/// The shortest path from `source` to `target` over the allowed arcs and
+/// through allowed nodes only. Returns `None` if there is no such path,
+/// which includes `source` or `target` being of a disallowed type.
+pub fn typed_shortest_path<N: Network>(network: &N, tags: &TypeTags, filter: &TypeFilter, source: NodeId,
+                                       target: NodeId) -> Option<Path> {
+    if !filter.allows_node(tags.node_type(source)) || !filter.allows_node(tags.node_type(target)) {
+        return None;
+    }
+    let arcs: Vec<(NodeId, f64)> = network.nodes()
+        .flat_map(|i| network.out_arcs(i).map(|(j, cost, _)| (j, cost)))
+        .collect();
+    let model = |arc: usize, &(head, cost): &(NodeId, f64)| {
+        if filter.allows_arc(tags.arc_type(arc)) && filter.allows_node(tags.node_type(head)) { cost } else { f64::INFINITY }
+    };
+    shortest_path_with_model(network, &arcs, &model, source, target)
+}
+
+#[cfg(test)]
+const ROAD: TypeTag = 0;
+#[cfg(test)]
+const FERRY: TypeTag = 1;
+#[cfg(test)]
+const ADMIN: TypeTag = 2;
+
+#[test]
+fn test_type_filter() {
+    let filter = TypeFilter::new().only_arcs(&[ROAD]).skip_nodes(&[ADMIN]);
+    assert!(filter.allows_arc(ROAD));
+    assert!(!filter.allows_arc(FERRY));
+    assert!(filter.allows_node(ROAD));
+    assert!(!filter.allows_node(ADMIN));
+    assert!(TypeFilter::default().allows_node(63));
+}
+
+#[test]
+#[should_panic(expected = "type tags must be below MAX_TYPES")]
+fn test_type_filter_rejects_large_tags() {
+    TypeFilter::new().only_arcs(&[MAX_TYPES as TypeTag]);
+}
+
+#[test]
+fn test_typed_traversal() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // 0 -> 1 -> 3 by road through the admin node 1, 0 -> 3 by ferry and
+    // 0 -> 2 -> 3 by road
+    let mut edges = vec![(0,1,1.0,0.0),
+                         (0,2,2.0,0.0),
+                         (0,3,1.0,0.0),
+                         (1,3,1.0,0.0),
+                         (2,3,2.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let tags = TypeTags::new(&network, vec![ROAD, ADMIN, ROAD, ROAD], vec![ROAD, ROAD, FERRY, ROAD, ROAD]);
+
+    let all = TypeFilter::new();
+    assert_eq!(&vec![0,3], typed_shortest_path(&network, &tags, &all, 0, 3).unwrap().nodes());
+    let roads = TypeFilter::new().only_arcs(&[ROAD]);
+    assert_eq!(&vec![0,1,3], typed_shortest_path(&network, &tags, &roads, 0, 3).unwrap().nodes());
+    let public_roads = roads.skip_nodes(&[ADMIN]);
+    let path = typed_shortest_path(&network, &tags, &public_roads, 0, 3).unwrap();
+    assert_eq!((&vec![0,2,3], 4.0), (path.nodes(), path.cost()));
+    assert_eq!(None, typed_shortest_path(&network, &tags, &public_roads, 0, 1));
+
+    let (pred, order) = typed_breadth_first_search(&network, &tags, &public_roads, 0);
+    assert_eq!(vec![0,2,3], order);
+    assert_eq!(vec![4,4,0,2], pred);
+    assert!(typed_breadth_first_search(&network, &tags, &public_roads, 1).1.is_empty());
+}
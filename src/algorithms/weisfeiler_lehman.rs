@@ -0,0 +1,114 @@
+use std::collections::{ BTreeMap, HashMap };
+
+use super::super::{ Network, NodeId };
+
+/// Sparse feature vector, mapping feature ids to counts.
+pub type SparseFeatures = BTreeMap<usize, usize>;
+
+/// Extracts Weisfeiler-Lehman subtree features (Shervashidze et al.:
+/// "Weisfeiler-Lehman Graph Kernels").
+///
+/// In every refinement round, each node's label is replaced by a compressed
+/// label for the pair of its own label and the sorted labels of its
+/// successors. The feature vector of a graph counts how often each label
+/// occurs over all rounds. The extractor keeps the compression dictionary,
+/// so feature ids are consistent across all graphs run through the same
+/// extractor, and the dot product of two feature vectors is the WL subtree
+/// kernel. For undirected graphs, store every edge as two arcs.
+pub struct WeisfeilerLehman {
+    dictionary: HashMap<Vec<usize>, usize>,
+}
+
+impl WeisfeilerLehman {
+    pub fn new() -> WeisfeilerLehman {
+        WeisfeilerLehman {
+            dictionary: HashMap::new(),
+        }
+    }
+
+    /// The number of distinct feature ids handed out so far.
+    pub fn num_features(&self) -> usize {
+        self.dictionary.len()
+    }
+
+    /// Computes the features of `network` after `rounds` refinement rounds,
+    /// starting from the given node labels (one per node).
+    pub fn features<N: Network>(&mut self, network: &N, initial_labels: &[usize], rounds: usize) -> SparseFeatures {
+        let n = network.num_nodes();
+        assert!(initial_labels.len() == n);
+        let adj_lists: Vec<Vec<NodeId>> = (0..n).map(|i| network.adjacent(i as NodeId)).collect();
+
+        // initial labels live in their own key space, marked by a leading usize::MAX
+        let mut labels: Vec<usize> = initial_labels.iter()
+            .map(|label| self.compress(vec![usize::MAX, *label]))
+            .collect();
+        let mut features = SparseFeatures::new();
+        count_labels(&labels, &mut features);
+
+        for _ in 0..rounds {
+            let mut next = Vec::with_capacity(n);
+            for i in 0..n {
+                let mut signature: Vec<usize> = adj_lists[i].iter().map(|j| labels[*j as usize]).collect();
+                signature.sort();
+                signature.insert(0, labels[i]);
+                next.push(self.compress(signature));
+            }
+            labels = next;
+            count_labels(&labels, &mut features);
+        }
+        features
+    }
+
+    fn compress(&mut self, signature: Vec<usize>) -> usize {
+        let next_id = self.dictionary.len();
+        *self.dictionary.entry(signature).or_insert(next_id)
+    }
+}
+
+fn count_labels(labels: &Vec<usize>, features: &mut SparseFeatures) {
+    for label in labels {
+        *features.entry(*label).or_insert(0) += 1;
+    }
+}
+
+/// Computes WL subtree features for a collection of graphs with a shared
+/// dictionary, starting from identical labels on all nodes.
+pub fn weisfeiler_lehman_features<N: Network>(graphs: &[N], rounds: usize) -> Vec<SparseFeatures> {
+    let mut extractor = WeisfeilerLehman::new();
+    graphs.iter()
+        .map(|graph| extractor.features(graph, &vec![0; graph.num_nodes()], rounds))
+        .collect()
+}
+
+/// The WL subtree kernel value, i.e. the dot product of two feature vectors.
+pub fn wl_kernel(first: &SparseFeatures, second: &SparseFeatures) -> usize {
+    first.iter()
+        .map(|(feature, count)| count * second.get(feature).map(|c| *c).unwrap_or(0))
+        .sum()
+}
+
+#[test]
+fn test_weisfeiler_lehman_features() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    // an undirected triangle and an undirected path with three nodes
+    let triangle = compact_star_from_edge_vec(3, &mut vec![
+        (0,1,0.0,0.0), (1,0,0.0,0.0),
+        (1,2,0.0,0.0), (2,1,0.0,0.0),
+        (2,0,0.0,0.0), (0,2,0.0,0.0)]);
+    let path = compact_star_from_edge_vec(3, &mut vec![
+        (0,1,0.0,0.0), (1,0,0.0,0.0),
+        (1,2,0.0,0.0), (2,1,0.0,0.0)]);
+    let features = weisfeiler_lehman_features(&[triangle, path], 1);
+
+    // round 0: one shared label; round 1: degree 2 (triangle), degree 1 and 2 (path)
+    let mut triangle_features = SparseFeatures::new();
+    triangle_features.insert(0, 3);
+    triangle_features.insert(1, 3);
+    let mut path_features = SparseFeatures::new();
+    path_features.insert(0, 3);
+    path_features.insert(2, 2);
+    path_features.insert(1, 1);
+    assert_eq!(triangle_features, features[0]);
+    assert_eq!(path_features, features[1]);
+    assert_eq!(9 + 3, wl_kernel(&features[0], &features[1]));
+}
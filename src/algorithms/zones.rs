@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use super::super::{ Cost, Error, Network, NodeId };
+use super::super::compact_star::{ CompactStar, try_compact_star_from_edge_vec };
+#[cfg(test)]
+use super::super::compact_star::compact_star_from_edge_vec;
+
+/// How `aggregate_zones` combines the costs of the arcs between two zones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostAggregation {
+    /// The cheapest arc, e.g. the fastest connection.
+    Min,
+    /// The average over the arcs.
+    Mean,
+}
+
+/// The zone level network of `network`: one node per zone of `zones` (with
+/// `num_zones` zones, `zones[i]` being the zone of node `i`) and one arc for
+/// every pair of zones connected by at least one arc. The arc's cost
+/// combines the costs of the arcs between the zones by `costs`, its
+/// capacity is the sum of their capacities. Arcs within a zone are dropped.
+/// # Errors
+/// `Error::NoNodes` if `num_zones` is `0`, `Error::NodeOutOfRange` for the
+/// first zone `>= num_zones`.
+/// # Panics
+/// If there isn't one zone per node.
+pub fn aggregate_zones<N: Network>(network: &N, zones: &[NodeId], num_zones: usize,
+                                   costs: CostAggregation) -> Result<CompactStar, Error> {
+    assert_eq!(network.num_nodes(), zones.len(), "one zone per node is required");
+    if num_zones == 0 {
+        return Err(Error::NoNodes);
+    }
+    if let Some(zone) = zones.iter().find(|zone| **zone as usize >= num_zones) {
+        return Err(Error::NodeOutOfRange { node: *zone, num_nodes: num_zones });
+    }
+    // (cost, capacity, number of arcs) of every connected pair of zones
+    let mut between: BTreeMap<(NodeId, NodeId), (Cost, f64, usize)> = BTreeMap::new();
+    for i in network.nodes() {
+        for (j, cost, capacity) in network.out_arcs(i) {
+            let (a, b) = (zones[i as usize], zones[j as usize]);
+            if a == b {
+                continue;
+            }
+            let entry = between.entry((a, b)).or_insert((cost, 0.0, 0));
+            entry.0 = match costs {
+                CostAggregation::Min => entry.0.min(cost),
+                CostAggregation::Mean if entry.2 == 0 => cost,
+                CostAggregation::Mean => entry.0 + cost,
+            };
+            entry.1 += capacity;
+            entry.2 += 1;
+        }
+    }
+    let mut edges = between.into_iter()
+        .map(|((a, b), (cost, capacity, count))| {
+            let cost = if costs == CostAggregation::Mean { cost / count as f64 } else { cost };
+            (a, b, cost, capacity)
+        })
+        .collect();
+    try_compact_star_from_edge_vec(num_zones, &mut edges)
+}
+
+#[test]
+fn test_aggregate_zones() {
+    // nodes 0, 1 in zone 0, nodes 2, 3 in zone 1 and node 4 in zone 2
+    let mut edges = vec![(0,1,1.0,5.0),
+                         (0,2,4.0,10.0),
+                         (1,3,2.0,20.0),
+                         (2,4,3.0,1.0),
+                         (3,0,7.0,2.0)];
+    let network = compact_star_from_edge_vec(5, &mut edges);
+    let zones = vec![0,0,1,1,2];
+
+    let zone_network = aggregate_zones(&network, &zones, 3, CostAggregation::Min).unwrap();
+    assert_eq!(3, zone_network.num_nodes());
+    assert_eq!(vec![(0,1), (1,0), (1,2)], zone_network.arcs().collect::<Vec<_>>());
+    assert_eq!(Some(2.0), zone_network.cost(0, 1));
+    assert_eq!(Some(30.0), zone_network.capacity(0, 1));
+    assert_eq!(Some(7.0), zone_network.cost(1, 0));
+
+    let zone_network = aggregate_zones(&network, &zones, 3, CostAggregation::Mean).unwrap();
+    assert_eq!(Some(3.0), zone_network.cost(0, 1));
+    assert_eq!(Some(3.0), zone_network.cost(1, 2));
+
+    assert!(match aggregate_zones(&network, &zones, 0, CostAggregation::Min) {
+        Err(Error::NoNodes) => true,
+        _ => false,
+    });
+    // node 4 is in zone 2, which two zones do not have
+    assert!(match aggregate_zones(&network, &[0,0,1,1,2], 2, CostAggregation::Min) {
+        Err(Error::NodeOutOfRange { node: 2, num_nodes: 2 }) => true,
+        _ => false,
+    });
+}
@@ -0,0 +1,81 @@
+//! Cooperative cancellation of long-running computations.
+//!
+//! Algorithms with a `_cancellable` variant check a `Cancellation` once per
+//! iteration or search and give up with `Cancelled` as soon as it was
+//! cancelled or its deadline has passed.
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::{ Duration, Instant };
+
+/// A cancellation token. Clones share the same flag, so a computation can
+/// be cancelled from another thread by calling `cancel` on a clone.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation {
+    flag:     Arc<AtomicBool>,
+    deadline: Option<Instant>,
+}
+
+/// The computation was cancelled before it finished.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "computation cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+impl Cancellation {
+    /// A token that is only cancelled by calling `cancel`.
+    pub fn new() -> Cancellation {
+        Cancellation::default()
+    }
+
+    /// A token that is cancelled at `deadline` at the latest.
+    pub fn with_deadline(deadline: Instant) -> Cancellation {
+        Cancellation {
+            flag:     Arc::new(AtomicBool::new(false)),
+            deadline: Some(deadline),
+        }
+    }
+
+    /// A token that is cancelled after `timeout` from now at the latest.
+    pub fn with_timeout(timeout: Duration) -> Cancellation {
+        Cancellation::with_deadline(Instant::now() + timeout)
+    }
+
+    pub fn cancel(&self) {
+        self.flag.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.flag.load(Ordering::Relaxed)
+            || self.deadline.map_or(false, |deadline| Instant::now() >= deadline)
+    }
+
+    /// `Err(Cancelled)` if the computation should stop.
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn test_cancellation() {
+    let token = Cancellation::new();
+    let clone = token.clone();
+    assert_eq!(Ok(()), token.check());
+    clone.cancel();
+    assert_eq!(Err(Cancelled), token.check());
+
+    assert!(Cancellation::with_timeout(Duration::from_secs(0)).is_cancelled());
+    assert!(!Cancellation::with_timeout(Duration::from_secs(3600)).is_cancelled());
+}
@@ -1,5 +1,5 @@
 use std::collections::VecDeque;
-use super::NodeId;
+use super::{ NodeId, NodeVec };
 
 /// Provides a common interface for stacks and queues, hiding the actual
 /// implementation. This implementation allows to turn breadth-first-search
@@ -82,6 +82,101 @@ impl Collection for Stack {
     }
 }
 
+/// Disjoint-set forest with union by rank and path halving, for tracking
+/// which nodes are connected while arcs are added one by one.
+/// The set of nodes grows on demand: any node id passed to `union` or
+/// `find` is added as a singleton set if it isn't known yet.
+pub struct UnionFind {
+    parent: Vec<NodeId>,
+    rank:   Vec<u8>,
+    sets:   usize,
+}
+
+impl UnionFind {
+    /// Creates `n` singleton sets for the nodes `0..n`.
+    pub fn new(n: usize) -> UnionFind {
+        UnionFind {
+            parent: (0..n).map(|i| i as NodeId).collect(),
+            rank:   vec![0; n],
+            sets:   n,
+        }
+    }
+
+    /// Returns the representative of the set containing `i`.
+    pub fn find(&mut self, i: NodeId) -> NodeId {
+        self.grow(i);
+        let mut current = i;
+        while self.parent[current as usize] != current {
+            let grandparent = self.parent[self.parent[current as usize] as usize];
+            self.parent[current as usize] = grandparent;
+            current = grandparent;
+        }
+        current
+    }
+
+    /// Merges the sets containing `i` and `j`. Returns `false` if they were
+    /// already in the same set.
+    pub fn union(&mut self, i: NodeId, j: NodeId) -> bool {
+        let root_i = self.find(i);
+        let root_j = self.find(j);
+        if root_i == root_j {
+            return false;
+        }
+        let (ri, rj) = (root_i as usize, root_j as usize);
+        if self.rank[ri] < self.rank[rj] {
+            self.parent[ri] = root_j;
+        } else if self.rank[ri] > self.rank[rj] {
+            self.parent[rj] = root_i;
+        } else {
+            self.parent[rj] = root_i;
+            self.rank[ri] += 1;
+        }
+        self.sets -= 1;
+        true
+    }
+
+    /// The number of nodes known to the structure.
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The number of disjoint sets, i.e. connected components.
+    pub fn count(&self) -> usize {
+        self.sets
+    }
+
+    /// Returns a label in `0..count()` for every node, numbering the sets in
+    /// order of their smallest node id.
+    pub fn labels(&mut self) -> NodeVec {
+        let n = self.len();
+        let mut label_of_root = vec![n as NodeId; n];
+        let mut labels = Vec::with_capacity(n);
+        let mut next = 0;
+        for i in 0..n {
+            let root = self.find(i as NodeId) as usize;
+            if label_of_root[root] as usize == n {
+                label_of_root[root] = next;
+                next += 1;
+            }
+            labels.push(label_of_root[root]);
+        }
+        labels
+    }
+
+    fn grow(&mut self, i: NodeId) {
+        while self.parent.len() <= i as usize {
+            let id = self.parent.len() as NodeId;
+            self.parent.push(id);
+            self.rank.push(0);
+            self.sets += 1;
+        }
+    }
+}
+
 #[test]
 fn test_queue_impl() {
     let mut queue = Queue::new();
@@ -117,3 +212,25 @@ fn test_stack_impl() {
     stack.pop();
     assert!(stack.is_empty());
 }
+
+#[test]
+fn test_union_find() {
+    let mut union_find = UnionFind::new(4);
+    assert_eq!(4, union_find.count());
+    assert!(union_find.union(0, 1));
+    assert!(union_find.union(3, 2));
+    assert!(!union_find.union(1, 0));
+    assert_eq!(2, union_find.count());
+    assert_eq!(union_find.find(0), union_find.find(1));
+    assert!(union_find.find(1) != union_find.find(2));
+    assert_eq!(vec![0,0,1,1], union_find.labels());
+}
+
+#[test]
+fn test_union_find_grows() {
+    let mut union_find = UnionFind::new(0);
+    union_find.union(2, 5);
+    assert_eq!(6, union_find.len());
+    assert_eq!(5, union_find.count());
+    assert_eq!(vec![0,1,2,3,4,2], union_find.labels());
+}
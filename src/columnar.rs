@@ -0,0 +1,259 @@
+//! Builds networks from columnar edge tables: Arrow record batches (feature
+//! `arrow`) and Parquet files (feature `parquet`).
+//!
+//! Every row of a table is one arc. The tail and head columns hold node ids
+//! as integers, cost and capacity columns hold integers or floats. Column
+//! names are configurable with `EdgeColumns`; cost and capacity columns are
+//! optional and default to `0.0` like in the text parser.
+
+use std::error::Error;
+use std::fmt;
+
+use arrow_array::{ Array, Float32Array, Float64Array, Int32Array, Int64Array, RecordBatch, UInt32Array, UInt64Array };
+use arrow_schema::ArrowError;
+
+use super::{ NodeId, NodeVec, DoubleVec };
+use super::compact_star::{ CompactStar, compact_star_from_edge_vec };
+use super::parse::Edge;
+
+/// The names of the columns holding the arcs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeColumns {
+    pub from:     String,
+    pub to:       String,
+    pub cost:     Option<String>,
+    pub capacity: Option<String>,
+}
+
+impl Default for EdgeColumns {
+    /// Columns `from`, `to`, `cost` and `capacity`.
+    fn default() -> EdgeColumns {
+        EdgeColumns {
+            from:     "from".to_string(),
+            to:       "to".to_string(),
+            cost:     Some("cost".to_string()),
+            capacity: Some("capacity".to_string()),
+        }
+    }
+}
+
+/// Reasons for rejecting an edge table.
+#[derive(Debug)]
+pub enum ColumnarError {
+    /// A configured column is not part of the table.
+    MissingColumn(String),
+    /// A column has a data type that can't be converted.
+    InvalidType { column: String, data_type: String },
+    /// A column contains a null value.
+    NullValue { column: String, row: usize },
+    /// A node id column contains a negative value or one that is too large
+    /// for a `NodeId`.
+    InvalidNode { column: String, row: usize },
+    Arrow(ArrowError),
+    #[cfg(feature = "parquet")]
+    Parquet(::parquet::errors::ParquetError),
+    Io(::std::io::Error),
+}
+
+impl fmt::Display for ColumnarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ColumnarError::MissingColumn(ref column) => write!(f, "no column `{}`", column),
+            ColumnarError::InvalidType { ref column, ref data_type } =>
+                write!(f, "column `{}` has unsupported type {}", column, data_type),
+            ColumnarError::NullValue { ref column, row } => write!(f, "column `{}` is null in row {}", column, row),
+            ColumnarError::InvalidNode { ref column, row } => write!(f, "column `{}` has no valid node id in row {}", column, row),
+            ColumnarError::Arrow(ref e) => write!(f, "{}", e),
+            #[cfg(feature = "parquet")]
+            ColumnarError::Parquet(ref e) => write!(f, "{}", e),
+            ColumnarError::Io(ref e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ColumnarError {}
+
+impl From<ArrowError> for ColumnarError {
+    fn from(e: ArrowError) -> ColumnarError {
+        ColumnarError::Arrow(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<::parquet::errors::ParquetError> for ColumnarError {
+    fn from(e: ::parquet::errors::ParquetError) -> ColumnarError {
+        ColumnarError::Parquet(e)
+    }
+}
+
+impl From<::std::io::Error> for ColumnarError {
+    fn from(e: ::std::io::Error) -> ColumnarError {
+        ColumnarError::Io(e)
+    }
+}
+
+/// Appends the arcs of `batch` to `edges`.
+pub fn edges_from_record_batch(batch: &RecordBatch, columns: &EdgeColumns, edges: &mut Vec<Edge>) -> Result<(), ColumnarError> {
+    let from = node_column(batch, &columns.from)?;
+    let to = node_column(batch, &columns.to)?;
+    let costs = match columns.cost {
+        Some(ref name) => value_column(batch, name)?,
+        None => vec![0.0; batch.num_rows()],
+    };
+    let capacities = match columns.capacity {
+        Some(ref name) => value_column(batch, name)?,
+        None => vec![0.0; batch.num_rows()],
+    };
+    edges.reserve(batch.num_rows());
+    for row in 0..batch.num_rows() {
+        edges.push((from[row], to[row], costs[row], capacities[row]));
+    }
+    Ok(())
+}
+
+/// Builds a network from the arcs of all `batches`. The network has
+/// `nodes` nodes, or one more than the largest node id if `nodes` is `None`.
+/// # Panics
+/// If an arc refers to a node `>= nodes`.
+pub fn compact_star_from_record_batches<I>(batches: I, columns: &EdgeColumns, nodes: Option<usize>) -> Result<CompactStar, ColumnarError>
+where I: IntoIterator<Item=Result<RecordBatch, ArrowError>> {
+    let mut edges = Vec::new();
+    for batch in batches {
+        edges_from_record_batch(&batch?, columns, &mut edges)?;
+    }
+    let nodes = nodes.unwrap_or_else(|| {
+        edges.iter().map(|&(from, to, _, _)| from.max(to) as usize + 1).max().unwrap_or(0)
+    });
+    Ok(compact_star_from_edge_vec(nodes, &mut edges))
+}
+
+/// Builds a network from a Parquet file, see
+/// `compact_star_from_record_batches`. Only the configured columns are
+/// read.
+#[cfg(feature = "parquet")]
+pub fn compact_star_from_parquet<P>(path: P, columns: &EdgeColumns, nodes: Option<usize>) -> Result<CompactStar, ColumnarError>
+where P: AsRef<::std::path::Path> {
+    use parquet::arrow::ProjectionMask;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    let file = ::std::fs::File::open(path)?;
+    let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
+    let names: Vec<&str> = [Some(&columns.from), Some(&columns.to), columns.cost.as_ref(), columns.capacity.as_ref()]
+        .iter()
+        .filter_map(|name| name.map(|name| name.as_str()))
+        .collect();
+    let mut indices = Vec::with_capacity(names.len());
+    for name in names {
+        let index = builder.schema().index_of(name).map_err(|_| ColumnarError::MissingColumn(name.to_string()))?;
+        indices.push(index);
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), indices);
+    let reader = builder.with_projection(mask).build()?;
+    compact_star_from_record_batches(reader, columns, nodes)
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a dyn Array, ColumnarError> {
+    let array = batch.column_by_name(name).ok_or_else(|| ColumnarError::MissingColumn(name.to_string()))?;
+    if let Some(row) = (0..array.len()).find(|row| array.is_null(*row)) {
+        return Err(ColumnarError::NullValue { column: name.to_string(), row: row });
+    }
+    Ok(array.as_ref())
+}
+
+fn invalid_type(name: &str, array: &dyn Array) -> ColumnarError {
+    ColumnarError::InvalidType { column: name.to_string(), data_type: format!("{}", array.data_type()) }
+}
+
+fn node_column(batch: &RecordBatch, name: &str) -> Result<NodeVec, ColumnarError> {
+    let array = column(batch, name)?;
+    let values: Vec<i128> = if let Some(a) = array.as_any().downcast_ref::<UInt32Array>() {
+        return Ok(a.values().to_vec());
+    } else if let Some(a) = array.as_any().downcast_ref::<UInt64Array>() {
+        a.values().iter().map(|v| *v as i128).collect()
+    } else if let Some(a) = array.as_any().downcast_ref::<Int32Array>() {
+        a.values().iter().map(|v| *v as i128).collect()
+    } else if let Some(a) = array.as_any().downcast_ref::<Int64Array>() {
+        a.values().iter().map(|v| *v as i128).collect()
+    } else {
+        return Err(invalid_type(name, array));
+    };
+    values.iter()
+        .enumerate()
+        .map(|(row, v)| if *v >= 0 && *v < NodeId::max_value() as i128 {
+            Ok(*v as NodeId)
+        } else {
+            Err(ColumnarError::InvalidNode { column: name.to_string(), row: row })
+        })
+        .collect()
+}
+
+fn value_column(batch: &RecordBatch, name: &str) -> Result<DoubleVec, ColumnarError> {
+    let array = column(batch, name)?;
+    if let Some(a) = array.as_any().downcast_ref::<Float64Array>() {
+        Ok(a.values().to_vec())
+    } else if let Some(a) = array.as_any().downcast_ref::<Float32Array>() {
+        Ok(a.values().iter().map(|v| *v as f64).collect())
+    } else if let Some(a) = array.as_any().downcast_ref::<Int64Array>() {
+        Ok(a.values().iter().map(|v| *v as f64).collect())
+    } else if let Some(a) = array.as_any().downcast_ref::<Int32Array>() {
+        Ok(a.values().iter().map(|v| *v as f64).collect())
+    } else {
+        Err(invalid_type(name, array))
+    }
+}
+
+#[test]
+fn test_compact_star_from_record_batches() {
+    use std::sync::Arc;
+    use arrow_array::ArrayRef;
+    let batch = RecordBatch::try_from_iter(vec![
+        ("src", Arc::new(Int64Array::from(vec![0, 2, 0])) as ArrayRef),
+        ("dst", Arc::new(UInt32Array::from(vec![1, 1, 2])) as ArrayRef),
+        ("minutes", Arc::new(Float32Array::from(vec![2.5, 1.0, 4.0])) as ArrayRef),
+    ]).unwrap();
+    let columns = EdgeColumns {
+        from:     "src".to_string(),
+        to:       "dst".to_string(),
+        cost:     Some("minutes".to_string()),
+        capacity: None,
+    };
+    let compact_star = compact_star_from_record_batches(vec![Ok(batch.clone())], &columns, None).unwrap();
+    let mut edges = vec![(0,1,2.5,0.0), (2,1,1.0,0.0), (0,2,4.0,0.0)];
+    assert_eq!(compact_star_from_edge_vec(3, &mut edges), compact_star);
+
+    let missing = compact_star_from_record_batches(vec![Ok(batch.clone())], &EdgeColumns::default(), None);
+    assert_eq!("no column `from`", missing.unwrap_err().to_string());
+
+    let negative = RecordBatch::try_from_iter(vec![
+        ("src", Arc::new(Int32Array::from(vec![0, -1])) as ArrayRef),
+        ("dst", Arc::new(Int32Array::from(vec![1, 1])) as ArrayRef),
+    ]).unwrap();
+    let columns = EdgeColumns { cost: None, ..columns };
+    assert_eq!("column `src` has no valid node id in row 1",
+               compact_star_from_record_batches(vec![Ok(negative)], &columns, None).unwrap_err().to_string());
+}
+
+#[cfg(feature = "parquet")]
+#[test]
+fn test_compact_star_from_parquet() {
+    use std::{ env, fs, process };
+    use std::sync::Arc;
+    use arrow_array::ArrayRef;
+    use parquet::arrow::ArrowWriter;
+    let batch = RecordBatch::try_from_iter(vec![
+        ("from", Arc::new(UInt32Array::from(vec![0, 1])) as ArrayRef),
+        ("to", Arc::new(UInt32Array::from(vec![1, 2])) as ArrayRef),
+        ("cost", Arc::new(Float64Array::from(vec![1.5, 2.5])) as ArrayRef),
+        ("capacity", Arc::new(Float64Array::from(vec![10.0, 20.0])) as ArrayRef),
+        ("comment", Arc::new(Int32Array::from(vec![7, 8])) as ArrayRef),
+    ]).unwrap();
+    let path = env::temp_dir().join(format!("network-test-edges-{}.parquet", process::id()));
+    let mut writer = ArrowWriter::try_new(fs::File::create(&path).unwrap(), batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    let compact_star = compact_star_from_parquet(&path, &EdgeColumns::default(), Some(4)).unwrap();
+    let mut edges = vec![(0,1,1.5,10.0), (1,2,2.5,20.0)];
+    assert_eq!(compact_star_from_edge_vec(4, &mut edges), compact_star);
+    fs::remove_file(&path).unwrap();
+}
@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{ BTreeMap, HashMap };
+use std::fmt;
 
-use super::{Capacity, Cost, DoubleVec, NodeId, NodeVec, Network};
+use super::{Capacity, Cost, DoubleVec, Error, NodeId, NodeVec, Network};
+use super::pretty::{ LabeledDisplay, node_label };
 
 /// CompactStar representation of a network.
 /// See: Ahuja, Magnati, Orlin: "Network Flows" for details.
@@ -13,7 +15,9 @@ pub struct CompactStar {
     trace:      NodeVec,
     costs:      DoubleVec,
     capacities: DoubleVec,
-    cost_sum:   Cost
+    cost_sum:   Cost,
+    arc_attributes:  BTreeMap<String, DoubleVec>,
+    node_attributes: BTreeMap<String, DoubleVec>,
 }
 
 impl CompactStar {
@@ -26,10 +30,177 @@ impl CompactStar {
             trace:      Vec::with_capacity(edges),
             costs:      Vec::with_capacity(edges),
             capacities: Vec::with_capacity(edges),
-            cost_sum:   0.0
+            cost_sum:   0.0,
+            arc_attributes:  BTreeMap::new(),
+            node_attributes: BTreeMap::new(),
         }
     }
 
+    /// Builds a network directly from its forward star arrays, taking
+    /// ownership of them without copying: the arcs leaving node `i` are
+    /// `point[i]..point[i+1]`, with heads, costs and capacities at the same
+    /// positions in `head`, `costs` and `capacities`. Only the tail and
+    /// reverse star arrays are derived, in linear time.
+    ///
+    /// Use `try_from_raw_parts` to have the arrays validated.
+    ///
+    /// # Safety
+    /// The caller has to guarantee the conditions checked by
+    /// `try_from_raw_parts`. Algorithms may panic or return wrong results on
+    /// networks that violate them.
+    pub unsafe fn from_raw_parts(point: NodeVec, head: NodeVec, costs: DoubleVec, capacities: DoubleVec) -> CompactStar {
+        let nodes = point.len() - 1;
+        let mut tail = Vec::with_capacity(head.len());
+        for i in 0..nodes {
+            for _ in point[i]..point[i+1] {
+                tail.push(i as NodeId);
+            }
+        }
+        let mut in_degree: NodeVec = vec![0; nodes];
+        for to in &head {
+            in_degree[*to as usize] += 1;
+        }
+        let mut compact_star = CompactStar {
+            point:      point,
+            rpoint:     Vec::with_capacity(nodes + 1),
+            tail:       tail,
+            head:       head,
+            trace:      Vec::new(),
+            cost_sum:   costs.iter().sum(),
+            costs:      costs,
+            capacities: capacities,
+            arc_attributes:  BTreeMap::new(),
+            node_attributes: BTreeMap::new(),
+        };
+        compact_star.build_reverse_star(in_degree);
+        compact_star
+    }
+
+    /// Like `from_raw_parts`, but checks that `point` starts at `0`, is
+    /// non-decreasing and ends at the number of arcs, that `head`, `costs`
+    /// and `capacities` have one entry per arc and that every head is a node.
+    pub fn try_from_raw_parts(point: NodeVec, head: NodeVec, costs: DoubleVec, capacities: DoubleVec) -> Result<CompactStar, RawPartsError> {
+        if point.first() != Some(&0) {
+            return Err(RawPartsError::InvalidPoint { node: 0 });
+        }
+        for i in 1..point.len() {
+            if point[i] < point[i-1] {
+                return Err(RawPartsError::InvalidPoint { node: i });
+            }
+        }
+        if point[point.len() - 1] as usize != head.len() {
+            return Err(RawPartsError::InvalidPoint { node: point.len() - 1 });
+        }
+        if costs.len() != head.len() || capacities.len() != head.len() {
+            return Err(RawPartsError::LengthMismatch);
+        }
+        let nodes = point.len() - 1;
+        if let Some(arc) = head.iter().position(|to| *to as usize >= nodes) {
+            return Err(RawPartsError::InvalidHead { arc: arc });
+        }
+        Ok(unsafe { CompactStar::from_raw_parts(point, head, costs, capacities) })
+    }
+
+    /// Derives `rpoint` and `trace` from `head` by a counting sort of the
+    /// arcs by head.
+    fn build_reverse_star(&mut self, in_degree: NodeVec) {
+        let mut next = Vec::with_capacity(in_degree.len());
+        let mut head_index = 0;
+        for degree in in_degree {
+            self.rpoint.push(head_index);
+            next.push(head_index);
+            head_index += degree;
+        }
+        self.rpoint.push(head_index);
+        self.trace = vec![0; self.head.len()];
+        for (arc, to) in self.head.iter().enumerate() {
+            self.trace[next[*to as usize] as usize] = arc as NodeId;
+            next[*to as usize] += 1;
+        }
+    }
+
+    /// The arcs as `(from, to, cost, capacity)` tuples in arc order. Feeding
+    /// them to `compact_star_from_edge_vec` rebuilds the same network.
+    pub fn edges(&self) -> Vec<(NodeId, NodeId, Cost, Capacity)> {
+        (0..self.head.len())
+            .map(|k| (self.tail[k], self.head[k], self.costs[k], self.capacities[k]))
+            .collect()
+    }
+
+    /// The forward star arrays `(point, head, costs, capacities)` as
+    /// taken by `from_raw_parts`: the arcs leaving node `i` are
+    /// `point[i]..point[i+1]`. Together they form the adjacency matrix in
+    /// compressed sparse row format.
+    pub fn raw_parts(&self) -> (&[NodeId], &[NodeId], &[Cost], &[Capacity]) {
+        (&self.point, &self.head, &self.costs, &self.capacities)
+    }
+
+    /// Adds the arc attribute `name`, like a length, travel time or toll
+    /// next to the cost, with one value per arc in arc order. An attribute
+    /// of the same name is replaced. Networks derived from this one, like
+    /// subgraphs, don't keep the attributes.
+    /// # Panics
+    /// If there isn't exactly one value per arc.
+    pub fn with_arc_attribute(mut self, name: &str, values: DoubleVec) -> CompactStar {
+        assert_eq!(self.head.len(), values.len(), "arc attribute {} needs one value per arc", name);
+        self.arc_attributes.insert(name.to_string(), values);
+        self
+    }
+
+    /// Adds the node attribute `name`, like a coordinate, with one value
+    /// per node. An attribute of the same name is replaced.
+    /// # Panics
+    /// If there isn't exactly one value per node.
+    pub fn with_node_attribute(mut self, name: &str, values: DoubleVec) -> CompactStar {
+        assert_eq!(self.num_nodes(), values.len(), "node attribute {} needs one value per node", name);
+        self.node_attributes.insert(name.to_string(), values);
+        self
+    }
+
+    /// The value of the arc attribute `name` of the arc from `from` to `to`,
+    /// or of the first of several parallel arcs. `None` if there is no such
+    /// attribute or arc.
+    pub fn attr(&self, name: &str, from: NodeId, to: NodeId) -> Option<f64> {
+        self.arc_attributes.get(name).and_then(|values| self.get(from, to, values))
+    }
+
+    /// The value of the node attribute `name` of `node`, `None` if there is
+    /// no such attribute or node.
+    pub fn node_attr(&self, name: &str, node: NodeId) -> Option<f64> {
+        self.node_attributes.get(name).and_then(|values| values.get(node as usize)).map(|value| *value)
+    }
+
+    /// All values of the arc attribute `name` in arc order.
+    pub fn arc_attribute(&self, name: &str) -> Option<&[f64]> {
+        self.arc_attributes.get(name).map(|values| &values[..])
+    }
+
+    /// All values of the node attribute `name`.
+    pub fn node_attribute(&self, name: &str) -> Option<&[f64]> {
+        self.node_attributes.get(name).map(|values| &values[..])
+    }
+
+    /// The names of the arc attributes in alphabetical order.
+    pub fn arc_attribute_names(&self) -> Vec<&str> {
+        self.arc_attributes.keys().map(|name| name.as_str()).collect()
+    }
+
+    /// Makes the arc attribute `name` the cost of the arcs, so that all
+    /// algorithms run on it. The former costs are kept as the attribute
+    /// `cost`.
+    /// # Errors
+    /// `Error::UnknownAttribute` if there is no arc attribute `name`.
+    pub fn with_costs_from_attribute(mut self, name: &str) -> Result<CompactStar, Error> {
+        let values = match self.arc_attributes.get(name) {
+            Some(values) => values.clone(),
+            None => return Err(Error::UnknownAttribute(name.to_string())),
+        };
+        let costs = ::std::mem::replace(&mut self.costs, values);
+        self.arc_attributes.insert("cost".to_string(), costs);
+        self.cost_sum = self.costs.iter().sum();
+        Ok(self)
+    }
+
     fn get_head(&self, from: NodeId, to: NodeId) -> Option<NodeId> {
         let i = from as usize;
         let lower = match self.point.get(i).map(|p| *p) {
@@ -106,6 +277,72 @@ impl Network for CompactStar {
     fn infinity(&self) -> Cost {
         self.cost_sum
     }
+
+    fn out_arcs(&self, i: NodeId) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        let range = match (self.point.get(i as usize), self.point.get(i as usize + 1)) {
+            (Some(lower), Some(upper)) => *lower as usize..*upper as usize,
+            _ => 0..0
+        };
+        self.head[range.clone()].iter()
+            .zip(&self.costs[range.clone()])
+            .zip(&self.capacities[range])
+            .map(|((j, cost), capacity)| (*j, *cost, *capacity))
+    }
+
+    fn incoming(&self, i: NodeId) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        let range = match (self.rpoint.get(i as usize), self.rpoint.get(i as usize + 1)) {
+            (Some(lower), Some(upper)) => *lower as usize..*upper as usize,
+            _ => 0..0
+        };
+        self.trace[range].iter().map(move |arc| {
+            let arc = *arc as usize;
+            (self.tail[arc], self.costs[arc], self.capacities[arc])
+        })
+    }
+
+    fn in_degree(&self, i: NodeId) -> usize {
+        match (self.rpoint.get(i as usize), self.rpoint.get(i as usize + 1)) {
+            (Some(lower), Some(upper)) => (upper - lower) as usize,
+            _ => 0
+        }
+    }
+
+    fn out_degree(&self, i: NodeId) -> usize {
+        match (self.point.get(i as usize), self.point.get(i as usize + 1)) {
+            (Some(lower), Some(upper)) => (upper - lower) as usize,
+            _ => 0
+        }
+    }
+
+    fn total_cost(&self) -> Cost {
+        self.cost_sum
+    }
+}
+
+/// Prints one line per node with its adjacent nodes and the (cost, capacity) of the arcs,
+/// e.g. `0 -> 1 (25, 30), 2 (35, 50)`. Meant for small networks.
+impl LabeledDisplay for CompactStar {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        for i in 0..self.num_nodes() {
+            let from = i as NodeId;
+            write!(f, "{} ->", node_label(labels, from))?;
+            let lower = self.point[i] as usize;
+            let upper = self.point[i+1] as usize;
+            for index in lower..upper {
+                let separator = if index == lower { " " } else { ", " };
+                write!(f, "{}{} ({}, {})", separator, node_label(labels, self.head[index]),
+                   self.costs[index], self.capacities[index])?;
+            }
+            writeln!(f, "")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CompactStar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
 }
 
 /// Creates a network in compact star representation from a number of nodes and a list of edges.
@@ -115,6 +352,26 @@ impl Network for CompactStar {
 /// numbered. That means, there are no gaps allowed.
 /// * `edges` - (from, to, cost (length), capacity) tuples. These will be sorted by from-node
 /// before building the compact star.
+///
+/// Self-loops are kept as regular arcs and nodes without arcs are kept with an empty
+/// adjacency. Use `compact_star_with_options` to choose differently.
+///
+/// # Panics
+/// If `nodes` is `0` or an edge refers to a node `>= nodes`, see
+/// `try_compact_star_from_edge_vec` for untrusted input.
+///
+/// # Example
+/// ```
+/// use network::prelude::*;
+///
+/// let mut edges = vec![(0,1,25.0,30.0),
+///                      (0,2,35.0,50.0),
+///                      (2,1,45.0,10.0)];
+/// let compact_star = compact_star_from_edge_vec(3, &mut edges);
+/// assert_eq!(3, compact_star.num_nodes());
+/// assert_eq!(vec![1,2], compact_star.adjacent(0));
+/// assert_eq!(Some(45.0), compact_star.cost(2,1));
+/// ```
 pub fn compact_star_from_edge_vec(nodes: usize, edges: &mut Vec<(NodeId, NodeId, Cost, Capacity)>) -> CompactStar {
     edges.sort_by(|&(n0, _, _, _), &(o0, _, _, _)| n0.cmp(&o0));
     let mut compact_star = CompactStar::new(nodes, edges.len());
@@ -169,6 +426,207 @@ pub fn compact_star_from_edge_vec(nodes: usize, edges: &mut Vec<(NodeId, NodeId,
     compact_star
 }
 
+/// The network without nodes, which `compact_star_from_edge_vec` can't
+/// build. Derived networks such as subgraphs of an empty node set use it.
+pub(crate) fn empty_compact_star() -> CompactStar {
+    unsafe { CompactStar::from_raw_parts(vec![0], Vec::new(), Vec::new(), Vec::new()) }
+}
+
+/// Like `compact_star_from_edge_vec`, but checks the node count and the
+/// node ids of the edges first.
+/// # Errors
+/// `Error::NoNodes` if `nodes` is `0`, `Error::NodeOutOfRange` for the
+/// first edge with a node `>= nodes`.
+pub fn try_compact_star_from_edge_vec(nodes: usize, edges: &mut Vec<(NodeId, NodeId, Cost, Capacity)>) -> Result<CompactStar, Error> {
+    if nodes == 0 {
+        return Err(Error::NoNodes);
+    }
+    for &(from, to, _, _) in edges.iter() {
+        if let Some(node) = [from, to].iter().find(|node| **node as usize >= nodes) {
+            return Err(Error::NodeOutOfRange { node: *node, num_nodes: nodes });
+        }
+    }
+    Ok(compact_star_from_edge_vec(nodes, edges))
+}
+
+/// Like `try_compact_star_from_edge_vec`, but also adds the arc attributes
+/// in `attributes`, given with one value per edge in the order of `edges`
+/// like those of `parse::ParsedEdges`. They are reordered along with the
+/// edges.
+/// # Panics
+/// If an attribute doesn't have one value per edge.
+pub fn try_compact_star_with_attributes(nodes: usize, edges: &mut Vec<(NodeId, NodeId, Cost, Capacity)>,
+                                        attributes: BTreeMap<String, DoubleVec>) -> Result<CompactStar, Error> {
+    // the same stable sort by tail as in compact_star_from_edge_vec
+    let mut order: Vec<usize> = (0..edges.len()).collect();
+    order.sort_by_key(|k| edges[*k].0);
+    let mut compact_star = try_compact_star_from_edge_vec(nodes, edges)?;
+    for (name, values) in attributes {
+        assert_eq!(order.len(), values.len(), "arc attribute {} needs one value per edge", name);
+        let sorted = order.iter().map(|k| values[*k]).collect();
+        compact_star = compact_star.with_arc_attribute(&name, sorted);
+    }
+    Ok(compact_star)
+}
+
+/// Builds a `CompactStar` from edges that are already sorted by tail, in a
+/// single pass and without collecting the edges first. The result is the
+/// same as that of `compact_star_from_edge_vec` on the same edges.
+/// # Panics
+/// If the edges are not sorted by tail or refer to nodes `>= nodes`.
+pub(crate) fn compact_star_from_sorted_edges<I>(nodes: usize, edges: I) -> CompactStar
+where I: Iterator<Item=(NodeId, NodeId, Cost, Capacity)> {
+    let mut compact_star = CompactStar::new(nodes, edges.size_hint().0);
+    let mut in_degree: NodeVec = vec![0; nodes];
+    for (from, to, cost, cap) in edges {
+        assert!((from as usize) < nodes && (to as usize) < nodes, "arc ({}, {}) out of range", from, to);
+        assert!(compact_star.point.len() <= from as usize + 1, "edges must be sorted by tail");
+        while compact_star.point.len() <= from as usize {
+            compact_star.point.push(compact_star.tail.len() as NodeId);
+        }
+        compact_star.tail.push(from);
+        compact_star.head.push(to);
+        compact_star.costs.push(cost);
+        compact_star.cost_sum += cost;
+        compact_star.capacities.push(cap);
+        in_degree[to as usize] += 1;
+    }
+    while compact_star.point.len() <= nodes {
+        compact_star.point.push(compact_star.tail.len() as NodeId);
+    }
+    compact_star.build_reverse_star(in_degree);
+    compact_star
+}
+
+/// Reasons for `CompactStar::try_from_raw_parts` to reject its arrays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawPartsError {
+    /// `point` is empty, doesn't start at `0`, decreases at `node` or
+    /// doesn't end at the number of arcs.
+    InvalidPoint { node: usize },
+    /// `costs` or `capacities` don't have one entry per arc.
+    LengthMismatch,
+    /// The head of `arc` is not a node of the network.
+    InvalidHead { arc: usize },
+}
+
+impl fmt::Display for RawPartsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RawPartsError::InvalidPoint { node } => write!(f, "invalid point entry for node {}", node),
+            RawPartsError::LengthMismatch => write!(f, "costs and capacities need one entry per arc"),
+            RawPartsError::InvalidHead { arc } => write!(f, "head of arc {} is not a node", arc),
+        }
+    }
+}
+
+impl std::error::Error for RawPartsError {}
+
+/// How to treat arcs whose tail and head are the same node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelfLoops {
+    /// Keep self-loops as regular arcs.
+    Keep,
+    /// Silently remove self-loops.
+    Drop,
+    /// Refuse to build a network that contains self-loops.
+    Error,
+}
+
+/// How to treat nodes without any incoming or outgoing arc.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IsolatedNodes {
+    /// Keep isolated nodes with an empty adjacency.
+    Keep,
+    /// Remove isolated nodes and renumber the remaining ones consecutively.
+    Remove,
+}
+
+/// Policies applied when building a `CompactStar`. The default keeps
+/// self-loops and isolated nodes, like `compact_star_from_edge_vec`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConstructionOptions {
+    pub self_loops:     SelfLoops,
+    pub isolated_nodes: IsolatedNodes,
+}
+
+impl Default for ConstructionOptions {
+    fn default() -> ConstructionOptions {
+        ConstructionOptions {
+            self_loops:     SelfLoops::Keep,
+            isolated_nodes: IsolatedNodes::Keep,
+        }
+    }
+}
+
+/// Returned when a self-loop is found and the policy is `SelfLoops::Error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SelfLoopFound {
+    pub node: NodeId,
+}
+
+impl fmt::Display for SelfLoopFound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "self-loop at node {}", self.node)
+    }
+}
+
+impl std::error::Error for SelfLoopFound {}
+
+/// Creates a network in compact star representation like `compact_star_from_edge_vec`,
+/// applying the given policies for self-loops and isolated nodes.
+///
+/// Returns the network together with the original id of every node, which is the
+/// identity unless isolated nodes were removed. `edges` keeps the original node ids.
+/// A network without nodes, e.g. after removing all isolated ones, is empty.
+/// # Errors
+/// `Error::NodeOutOfRange` for the first edge with a node `>= nodes`,
+/// `Error::SelfLoop` for the first self-loop if the policy is `SelfLoops::Error`.
+pub fn compact_star_with_options(nodes: usize, edges: &mut Vec<(NodeId, NodeId, Cost, Capacity)>,
+                                 options: &ConstructionOptions) -> Result<(CompactStar, NodeVec), Error> {
+    for &(from, to, _, _) in edges.iter() {
+        if let Some(node) = [from, to].iter().find(|node| **node as usize >= nodes) {
+            return Err(Error::NodeOutOfRange { node: *node, num_nodes: nodes });
+        }
+    }
+    if let Some(&(node, _, _, _)) = edges.iter().find(|&&(from, to, _, _)| from == to) {
+        match options.self_loops {
+            SelfLoops::Keep  => {},
+            SelfLoops::Drop  => edges.retain(|&(from, to, _, _)| from != to),
+            SelfLoops::Error => return Err(Error::from(SelfLoopFound { node: node })),
+        }
+    }
+
+    if options.isolated_nodes == IsolatedNodes::Keep {
+        let original_ids = (0..nodes).map(|i| i as NodeId).collect();
+        if nodes == 0 {
+            return Ok((empty_compact_star(), original_ids));
+        }
+        return Ok((compact_star_from_edge_vec(nodes, edges), original_ids));
+    }
+
+    let mut is_used = vec![false; nodes];
+    for &(from, to, _, _) in edges.iter() {
+        is_used[from as usize] = true;
+        is_used[to as usize] = true;
+    }
+    let mut new_id = vec![0; nodes];
+    let mut original_ids = NodeVec::new();
+    for i in 0..nodes {
+        if is_used[i] {
+            new_id[i] = original_ids.len() as NodeId;
+            original_ids.push(i as NodeId);
+        }
+    }
+    if original_ids.is_empty() {
+        return Ok((empty_compact_star(), original_ids));
+    }
+    let mut renumbered: Vec<(NodeId, NodeId, Cost, Capacity)> = edges.iter()
+        .map(|&(from, to, cost, cap)| (new_id[from as usize], new_id[to as usize], cost, cap))
+        .collect();
+    Ok((compact_star_from_edge_vec(original_ids.len(), &mut renumbered), original_ids))
+}
+
 // ================================= TESTS ====================================
 
 #[test]
@@ -198,6 +656,11 @@ fn setup_sample_network() {
     assert_eq!(vec![1], compact_star.adjacent(2));
     assert_eq!(vec![2,4], compact_star.adjacent(3));
     assert_eq!(vec![2,3], compact_star.adjacent(4));
+    assert_eq!(vec![(2,25.0,20.0), (3,35.0,50.0)], compact_star.out_arcs(4).collect::<Vec<_>>());
+    assert_eq!(0, compact_star.out_arcs(5).count());
+    assert_eq!(vec![(0,35.0,50.0), (3,15.0,30.0), (4,25.0,20.0)], compact_star.incoming(2).collect::<Vec<_>>());
+    assert_eq!(0, compact_star.in_degree(0));
+    assert_eq!(2, compact_star.in_degree(3));
 
     assert_eq!(5, compact_star.invalid_id());
 }
@@ -243,3 +706,134 @@ fn test_compact_start_from_edge_vec2() {
     assert_eq!(6, compact_star.num_nodes());
     assert_eq!(vec![0,2,4,6,7,9,9], compact_star.point);
 }
+
+#[test]
+fn test_compact_star_with_options() {
+    let edges = vec![
+        (0,0,1.0,0.0),
+        (0,2,2.0,0.0),
+        (2,4,3.0,0.0)];
+
+    let (compact_star, ids) = compact_star_with_options(5, &mut edges.clone(), &ConstructionOptions::default()).unwrap();
+    assert_eq!(5, compact_star.num_nodes());
+    assert_eq!(vec![0,2], compact_star.adjacent(0));
+    assert_eq!(vec![0,1,2,3,4], ids);
+
+    let options = ConstructionOptions { self_loops: SelfLoops::Error, isolated_nodes: IsolatedNodes::Keep };
+    assert!(match compact_star_with_options(5, &mut edges.clone(), &options) {
+        Err(Error::SelfLoop(SelfLoopFound { node: 0 })) => true,
+        _ => false,
+    });
+
+    let options = ConstructionOptions { self_loops: SelfLoops::Drop, isolated_nodes: IsolatedNodes::Remove };
+    let (compact_star, ids) = compact_star_with_options(5, &mut edges.clone(), &options).unwrap();
+    assert_eq!(vec![0,2,4], ids);
+    assert_eq!(3, compact_star.num_nodes());
+    assert_eq!(vec![1], compact_star.adjacent(0));
+    assert_eq!(vec![2], compact_star.adjacent(1));
+    assert_eq!(Some(3.0), compact_star.cost(1,2));
+
+    // nothing is left after removing the self-loop and the isolated nodes
+    let (compact_star, ids) = compact_star_with_options(2, &mut vec![(0,0,1.0,0.0)], &options).unwrap();
+    assert_eq!((0, 0), (compact_star.num_nodes(), ids.len()));
+    let (compact_star, _) = compact_star_with_options(3, &mut Vec::new(), &options).unwrap();
+    assert_eq!(0, compact_star.num_nodes());
+    let (compact_star, _) = compact_star_with_options(0, &mut Vec::new(), &ConstructionOptions::default()).unwrap();
+    assert_eq!(0, compact_star.num_nodes());
+
+    assert!(match compact_star_with_options(3, &mut vec![(0,3,1.0,0.0)], &options) {
+        Err(Error::NodeOutOfRange { node: 3, num_nodes: 3 }) => true,
+        _ => false,
+    });
+}
+
+#[test]
+fn test_provided_network_methods() {
+    let mut edges = vec![
+        (0,1,6.0,0.0),
+        (0,2,4.0,0.0),
+        (2,0,2.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(4, &mut edges);
+    assert_eq!(2, compact_star.out_degree(0));
+    assert_eq!(0, compact_star.out_degree(3));
+    assert!(compact_star.has_arc(2, 0));
+    assert!(!compact_star.has_arc(1, 0));
+    assert_eq!(vec![0,1,2,3], compact_star.nodes().collect::<Vec<_>>());
+    assert_eq!(vec![(0,1),(0,2),(2,0)], compact_star.arcs().collect::<Vec<_>>());
+    assert_eq!(12.0, compact_star.total_cost());
+
+    // parallel arcs keep their own values
+    let mut edges = vec![(0,1,5.0,1.0), (0,1,3.0,2.0), (1,0,1.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(2, &mut edges);
+    assert_eq!(vec![(0,1,5.0,1.0), (0,1,3.0,2.0), (1,0,1.0,0.0)], compact_star.arc_values().collect::<Vec<_>>());
+    assert_eq!(Some(3.0), compact_star.cheapest_cost(0, 1));
+    assert_eq!(None, compact_star.cheapest_cost(1, 1));
+}
+
+#[test]
+fn test_display() {
+    let mut edges = vec![
+        (0,1,25.0,30.0),
+        (0,2,35.0,50.0),
+        (2,1,45.0,10.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    assert_eq!("0 -> 1 (25, 30), 2 (35, 50)\n1 ->\n2 -> 1 (45, 10)\n", format!("{}", compact_star));
+    let labels = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    assert_eq!("a -> b (25, 30), c (35, 50)\nb ->\nc -> b (45, 10)\n",
+               format!("{}", compact_star.with_labels(&labels)));
+}
+
+#[test]
+fn test_from_raw_parts() {
+    let mut edges = vec![(0,1,25.0,30.0),
+                         (0,2,35.0,50.0),
+                         (2,1,45.0,10.0),
+                         (2,0,15.0,20.0)];
+    let expected = compact_star_from_edge_vec(4, &mut edges);
+    let compact_star = CompactStar::try_from_raw_parts(vec![0,2,2,4,4],
+                                                       vec![1,2,1,0],
+                                                       vec![25.0,35.0,45.0,15.0],
+                                                       vec![30.0,50.0,10.0,20.0]);
+    assert_eq!(Ok(expected), compact_star);
+
+    assert_eq!(Err(RawPartsError::InvalidPoint { node: 0 }), CompactStar::try_from_raw_parts(vec![], vec![], vec![], vec![]));
+    assert_eq!(Err(RawPartsError::InvalidPoint { node: 2 }), CompactStar::try_from_raw_parts(vec![0,2,1], vec![1,0], vec![1.0,1.0], vec![1.0,1.0]));
+    assert_eq!(Err(RawPartsError::InvalidPoint { node: 2 }), CompactStar::try_from_raw_parts(vec![0,1,1], vec![1,0], vec![1.0,1.0], vec![1.0,1.0]));
+    assert_eq!(Err(RawPartsError::LengthMismatch), CompactStar::try_from_raw_parts(vec![0,1,2], vec![1,0], vec![1.0], vec![1.0,1.0]));
+    assert_eq!(Err(RawPartsError::InvalidHead { arc: 1 }), CompactStar::try_from_raw_parts(vec![0,1,2], vec![1,2], vec![1.0,1.0], vec![1.0,1.0]));
+}
+
+#[test]
+fn test_try_compact_star_from_edge_vec() {
+    let mut edges = vec![(0,1,25.0,30.0), (2,1,45.0,10.0)];
+    assert_eq!(3, try_compact_star_from_edge_vec(3, &mut edges.clone()).unwrap().num_nodes());
+    match try_compact_star_from_edge_vec(2, &mut edges) {
+        Err(Error::NodeOutOfRange { node: 2, num_nodes: 2 }) => {},
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(match try_compact_star_from_edge_vec(0, &mut Vec::new()) { Err(Error::NoNodes) => true, _ => false });
+}
+
+#[test]
+fn test_attributes() {
+    let mut edges = vec![(1,0,4.0,0.0), (0,1,1.0,0.0), (0,2,2.0,0.0)];
+    let mut attributes = BTreeMap::new();
+    attributes.insert("time".to_string(), vec![40.0, 10.0, 20.0]);
+    let compact_star = try_compact_star_with_attributes(3, &mut edges, attributes).unwrap()
+        .with_node_attribute("x", vec![0.5, 1.5, 2.5]);
+    assert_eq!(Some(&[10.0, 20.0, 40.0][..]), compact_star.arc_attribute("time"));
+    assert_eq!(Some(40.0), compact_star.attr("time", 1, 0));
+    assert_eq!(None, compact_star.attr("time", 2, 0));
+    assert_eq!(None, compact_star.attr("toll", 0, 1));
+    assert_eq!(Some(1.5), compact_star.node_attr("x", 1));
+    assert_eq!(vec!["time"], compact_star.arc_attribute_names());
+
+    let by_time = compact_star.with_costs_from_attribute("time").unwrap();
+    assert_eq!(Some(20.0), by_time.cost(0, 2));
+    assert_eq!(70.0, by_time.total_cost());
+    assert_eq!(Some(2.0), by_time.attr("cost", 0, 2));
+    match by_time.with_costs_from_attribute("toll") {
+        Err(Error::UnknownAttribute(name)) => assert_eq!("toll", name),
+        _ => panic!("expected an unknown attribute"),
+    }
+}
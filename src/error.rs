@@ -0,0 +1,129 @@
+//! The error type of the fallible operations of this crate, from reading
+//! files to building networks, so that applications can handle all of them
+//! with a single `Result<_, network::Error>`.
+
+use std::error;
+use std::fmt;
+use std::io;
+
+use super::NodeId;
+use super::compact_star::{ RawPartsError, SelfLoopFound };
+use super::io::csv::CsvError;
+use super::io::dimacs::DimacsError;
+use super::journal::JournalError;
+use super::parse::ParseError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// An edge list didn't match its pattern, or the pattern is invalid.
+    Parse(ParseError),
+    /// A DIMACS file is malformed.
+    Dimacs(DimacsError),
+    /// A CSV file is malformed.
+    Csv(CsvError),
+    /// The arrays given to `CompactStar::try_from_raw_parts` are invalid.
+    RawParts(RawPartsError),
+    /// A self-loop was found where `SelfLoops::Error` forbids them.
+    SelfLoop(SelfLoopFound),
+    /// A journal can't be read or replayed.
+    Journal(JournalError),
+    /// A network needs at least one node.
+    NoNodes,
+    /// An edge refers to a node outside of `0..num_nodes`.
+    NodeOutOfRange { node: NodeId, num_nodes: usize },
+    /// A network has no attribute of this name.
+    UnknownAttribute(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => write!(f, "{}", e),
+            Error::Parse(ref e) => write!(f, "{}", e),
+            Error::Dimacs(ref e) => write!(f, "{}", e),
+            Error::Csv(ref e) => write!(f, "{}", e),
+            Error::RawParts(ref e) => write!(f, "{}", e),
+            Error::SelfLoop(ref e) => write!(f, "{}", e),
+            Error::Journal(ref e) => write!(f, "{}", e),
+            Error::NoNodes => write!(f, "the network has no nodes"),
+            Error::NodeOutOfRange { node, num_nodes } =>
+                write!(f, "node {} out of range for a network of {} nodes", node, num_nodes),
+            Error::UnknownAttribute(ref name) => write!(f, "unknown attribute {}", name),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Parse(ref e) => Some(e),
+            Error::Dimacs(ref e) => Some(e),
+            Error::Csv(ref e) => Some(e),
+            Error::RawParts(ref e) => Some(e),
+            Error::SelfLoop(ref e) => Some(e),
+            Error::Journal(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl From<DimacsError> for Error {
+    fn from(e: DimacsError) -> Error {
+        Error::Dimacs(e)
+    }
+}
+
+impl From<CsvError> for Error {
+    fn from(e: CsvError) -> Error {
+        Error::Csv(e)
+    }
+}
+
+impl From<RawPartsError> for Error {
+    fn from(e: RawPartsError) -> Error {
+        Error::RawParts(e)
+    }
+}
+
+impl From<SelfLoopFound> for Error {
+    fn from(e: SelfLoopFound) -> Error {
+        Error::SelfLoop(e)
+    }
+}
+
+impl From<JournalError> for Error {
+    fn from(e: JournalError) -> Error {
+        Error::Journal(e)
+    }
+}
+
+#[test]
+fn test_error() {
+    use std::error::Error as StdError;
+    let error = Error::from(ParseError::NoMatch { line: 3 });
+    assert_eq!("line 3: does not match the pattern", error.to_string());
+    assert!(error.source().is_some());
+    let error = Error::NodeOutOfRange { node: 7, num_nodes: 5 };
+    assert_eq!("node 7 out of range for a network of 5 nodes", error.to_string());
+    assert!(error.source().is_none());
+    let error = Error::from(JournalError::MissingArc { from: 1, to: 2 });
+    assert_eq!("arc 1 -> 2 does not exist", error.to_string());
+    assert!(error.source().is_some());
+    let error = Error::from(RawPartsError::LengthMismatch);
+    assert_eq!("costs and capacities need one entry per arc", error.to_string());
+}
@@ -0,0 +1,326 @@
+//! Random network generators. All generators take a seed and produce the
+//! same network for the same parameters and seed.
+
+use super::{ Capacity, Cost, NodeId, NodeVec };
+use super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::random::Rng;
+
+/// Distribution of randomly drawn arc values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distribution {
+    /// Always the given value.
+    Constant(f64),
+    /// Uniformly distributed in `[low, high)`.
+    Uniform(f64, f64),
+}
+
+impl Distribution {
+    fn sample(&self, rng: &mut Rng) -> f64 {
+        match *self {
+            Distribution::Constant(value) => value,
+            Distribution::Uniform(low, high) => low + (high - low) * rng.next_f64(),
+        }
+    }
+}
+
+/// The distributions of arc costs and capacities of a generated network.
+/// The default gives every arc cost and capacity `1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArcWeights {
+    pub cost:     Distribution,
+    pub capacity: Distribution,
+}
+
+impl Default for ArcWeights {
+    fn default() -> ArcWeights {
+        ArcWeights {
+            cost:     Distribution::Constant(1.0),
+            capacity: Distribution::Constant(1.0),
+        }
+    }
+}
+
+impl ArcWeights {
+    fn arc(&self, from: NodeId, to: NodeId, rng: &mut Rng) -> (NodeId, NodeId, Cost, Capacity) {
+        let cost = self.cost.sample(rng);
+        let capacity = self.capacity.sample(rng);
+        (from, to, cost, capacity)
+    }
+
+    /// Adds an undirected edge as two opposite arcs of the same cost and
+    /// capacity.
+    fn edge(&self, a: NodeId, b: NodeId, rng: &mut Rng, edges: &mut Vec<(NodeId, NodeId, Cost, Capacity)>) {
+        let (_, _, cost, capacity) = self.arc(a, b, rng);
+        edges.push((a, b, cost, capacity));
+        edges.push((b, a, cost, capacity));
+    }
+}
+
+/// Erdős–Rényi random network `G(n, p)`: every one of the `n * (n - 1)`
+/// possible arcs (no self-loops) is present independently with probability
+/// `p`. Runs in `O(n + m)` by skipping over absent arcs with geometrically
+/// distributed jumps (Batagelj, Brandes: "Efficient generation of large
+/// random networks").
+/// # Panics
+/// If `n` is `0`.
+pub fn gnp(n: usize, p: f64, weights: &ArcWeights, seed: u64) -> CompactStar {
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::new();
+    sample_pairs(n, n, true, p, &mut rng, |from, to, rng| {
+        edges.push(weights.arc(from as NodeId, to as NodeId, rng));
+    });
+    compact_star_from_edge_vec(n, &mut edges)
+}
+
+/// The complete network on `n` nodes with all `n * (n - 1)` arcs.
+/// # Panics
+/// If `n` is `0`.
+pub fn complete(n: usize, weights: &ArcWeights, seed: u64) -> CompactStar {
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::with_capacity(n * n.saturating_sub(1));
+    for i in 0..n {
+        for j in (0..n).filter(|j| *j != i) {
+            edges.push(weights.arc(i as NodeId, j as NodeId, &mut rng));
+        }
+    }
+    compact_star_from_edge_vec(n, &mut edges)
+}
+
+/// The grid of `rows` by `columns` nodes, node `r * columns + c` in row `r`
+/// and column `c`, with edges between horizontal and vertical neighbors.
+/// Every edge is a pair of opposite arcs of the same cost and capacity, as
+/// in a road network.
+/// # Panics
+/// If `rows` or `columns` is `0`.
+pub fn grid(rows: usize, columns: usize, weights: &ArcWeights, seed: u64) -> CompactStar {
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::new();
+    for r in 0..rows {
+        for c in 0..columns {
+            let i = (r * columns + c) as NodeId;
+            if c + 1 < columns {
+                weights.edge(i, i + 1, &mut rng, &mut edges);
+            }
+            if r + 1 < rows {
+                weights.edge(i, i + columns as NodeId, &mut rng, &mut edges);
+            }
+        }
+    }
+    compact_star_from_edge_vec(rows * columns, &mut edges)
+}
+
+/// Barabási–Albert preferential attachment (Barabási, Albert: "Emergence of
+/// scaling in random networks"): starting with `m` isolated nodes, every
+/// further node is joined by edges to `m` distinct earlier nodes, chosen
+/// with probability proportional to their degree. The degrees follow a
+/// power law. Edges are pairs of opposite arcs.
+/// # Panics
+/// If `m` is `0` or not less than `n`.
+pub fn barabasi_albert(n: usize, m: usize, weights: &ArcWeights, seed: u64) -> CompactStar {
+    assert!(m > 0 && m < n, "expected 0 < m < n");
+    let mut rng = Rng::new(seed);
+    let mut edges = Vec::with_capacity(2 * m * (n - m));
+    // every node once per edge end, so a uniform pick is proportional to
+    // the degree
+    let mut ends: Vec<NodeId> = Vec::with_capacity(2 * m * (n - m));
+    let mut targets: Vec<NodeId> = (0..m as NodeId).collect();
+    for i in m..n {
+        for j in &targets {
+            weights.edge(i as NodeId, *j, &mut rng, &mut edges);
+            ends.push(i as NodeId);
+            ends.push(*j);
+        }
+        targets.clear();
+        while targets.len() < m {
+            let j = ends[rng.below(ends.len())];
+            if !targets.contains(&j) {
+                targets.push(j);
+            }
+        }
+    }
+    compact_star_from_edge_vec(n, &mut edges)
+}
+
+/// Watts–Strogatz small world network (Watts, Strogatz: "Collective
+/// dynamics of 'small-world' networks"): a ring of `n` nodes, each joined
+/// to its `k / 2` nearest neighbors on both sides, where the far end of
+/// every edge is rewired with probability `beta` to a uniformly chosen node,
+/// avoiding loops and parallel edges. Edges are pairs of opposite arcs.
+/// # Panics
+/// If `k` is odd or not less than `n`.
+pub fn watts_strogatz(n: usize, k: usize, beta: f64, weights: &ArcWeights, seed: u64) -> CompactStar {
+    assert!(k % 2 == 0 && k < n, "expected an even k < n");
+    let mut rng = Rng::new(seed);
+    let mut neighbors: Vec<Vec<usize>> = (0..n)
+        .map(|i| (1..k / 2 + 1).flat_map(|d| vec![(i + d) % n, (i + n - d) % n]).collect())
+        .collect();
+    for d in 1..k / 2 + 1 {
+        for i in 0..n {
+            let j = (i + d) % n;
+            if !rng.chance(beta) || neighbors[i].len() == n - 1 {
+                continue;
+            }
+            let mut target = rng.below(n);
+            while target == i || neighbors[i].contains(&target) {
+                target = rng.below(n);
+            }
+            neighbors[i].retain(|l| *l != j);
+            neighbors[j].retain(|l| *l != i);
+            neighbors[i].push(target);
+            neighbors[target].push(i);
+        }
+    }
+    let mut edges = Vec::with_capacity(n * k);
+    for i in 0..n {
+        for j in neighbors[i].iter().filter(|j| **j > i) {
+            weights.edge(i as NodeId, *j as NodeId, &mut rng, &mut edges);
+        }
+    }
+    compact_star_from_edge_vec(n, &mut edges)
+}
+
+/// Visits every pair `(i, j)` in `[0, rows) x [0, columns)` independently
+/// with probability `p`, in row-major order, skipping the diagonal if
+/// `no_loops`. Absent pairs are skipped with geometrically distributed
+/// jumps.
+fn sample_pairs<F>(rows: usize, columns: usize, no_loops: bool, p: f64, rng: &mut Rng, mut visit: F)
+where F: FnMut(usize, usize, &mut Rng) {
+    let row_length = if no_loops { columns.saturating_sub(1) } else { columns };
+    let pairs = rows * row_length;
+    if p <= 0.0 || pairs == 0 {
+        return;
+    }
+    let log_q = (1.0 - p).ln();
+    let mut k: usize = 0;
+    loop {
+        if p < 1.0 {
+            let skip = ((1.0 - rng.next_f64()).ln() / log_q).floor();
+            if skip >= (pairs - k) as f64 {
+                break;
+            }
+            k += skip as usize;
+        }
+        if k >= pairs {
+            break;
+        }
+        let from = k / row_length;
+        let mut to = k % row_length;
+        if no_loops && to >= from {
+            to += 1;
+        }
+        visit(from, to, rng);
+        k += 1;
+    }
+}
+
+/// Stochastic block model: the nodes are split into consecutive blocks of
+/// the given sizes, and an arc from a node in block `r` to another node in
+/// block `s` is present independently with probability
+/// `probabilities[r][s]`. Returns the network and the block of every node;
+/// without any nodes the network is empty.
+/// # Panics
+/// If `probabilities` is not a square matrix with one row per block.
+pub fn stochastic_block_model(block_sizes: &[usize], probabilities: &[Vec<f64>], weights: &ArcWeights, seed: u64) -> (CompactStar, NodeVec) {
+    assert_eq!(block_sizes.len(), probabilities.len());
+    assert!(probabilities.iter().all(|row| row.len() == block_sizes.len()));
+    let mut rng = Rng::new(seed);
+    let mut first = Vec::with_capacity(block_sizes.len());
+    let mut blocks = NodeVec::new();
+    for (block, size) in block_sizes.iter().enumerate() {
+        first.push(blocks.len());
+        blocks.extend(::std::iter::repeat(block as NodeId).take(*size));
+    }
+
+    let mut edges = Vec::new();
+    for r in 0..block_sizes.len() {
+        for s in 0..block_sizes.len() {
+            let (offset_r, offset_s) = (first[r], first[s]);
+            sample_pairs(block_sizes[r], block_sizes[s], r == s, probabilities[r][s], &mut rng, |from, to, rng| {
+                edges.push(weights.arc((offset_r + from) as NodeId, (offset_s + to) as NodeId, rng));
+            });
+        }
+    }
+    if blocks.is_empty() {
+        return (empty_compact_star(), blocks);
+    }
+    (compact_star_from_edge_vec(blocks.len(), &mut edges), blocks)
+}
+
+#[test]
+fn test_gnp() {
+    use super::Network;
+    let complete = gnp(5, 1.0, &ArcWeights::default(), 1);
+    assert_eq!(20, complete.num_arcs());
+    assert_eq!(vec![0,1,3,4], complete.adjacent(2));
+    assert_eq!(0, gnp(5, 0.0, &ArcWeights::default(), 1).num_arcs());
+
+    let weights = ArcWeights { cost: Distribution::Uniform(1.0, 2.0), capacity: Distribution::Constant(3.0) };
+    let sparse = gnp(200, 0.05, &weights, 7);
+    assert!(sparse.num_arcs() > 1700 && sparse.num_arcs() < 2300);
+    assert!(sparse.arcs().all(|(i, j)| i != j));
+    assert!(sparse.arcs().all(|(i, j)| sparse.cost(i, j).unwrap() >= 1.0 && sparse.capacity(i, j) == Some(3.0)));
+    assert_eq!(sparse, gnp(200, 0.05, &weights, 7));
+}
+
+#[test]
+fn test_stochastic_block_model() {
+    use super::Network;
+    let probabilities = vec![vec![1.0, 0.0], vec![0.5, 1.0]];
+    let (network, blocks) = stochastic_block_model(&[3, 2], &probabilities, &ArcWeights::default(), 3);
+    assert_eq!(vec![0,0,0,1,1], blocks);
+    assert_eq!(vec![1,2], network.adjacent(0));
+    assert!(network.arcs().all(|(i, j)| i != j && (blocks[i as usize], blocks[j as usize]) != (0, 1)));
+    // 3 * 2 + 2 * 1 arcs within the blocks, about half of the 6 from block 1 to 0
+    assert!(network.num_arcs() >= 8 && network.num_arcs() <= 14);
+    assert_eq!((network, blocks), stochastic_block_model(&[3, 2], &probabilities, &ArcWeights::default(), 3));
+
+    let (empty, blocks) = stochastic_block_model(&[], &[], &ArcWeights::default(), 3);
+    assert_eq!(0, empty.num_nodes());
+    assert!(blocks.is_empty());
+    let (empty, _) = stochastic_block_model(&[0, 0], &probabilities, &ArcWeights::default(), 3);
+    assert_eq!(0, empty.num_nodes());
+}
+
+#[test]
+fn test_complete_and_grid() {
+    use super::Network;
+    let network = complete(4, &ArcWeights::default(), 1);
+    assert_eq!(12, network.num_arcs());
+    assert!(network.arcs().all(|(i, j)| i != j));
+
+    let weights = ArcWeights { cost: Distribution::Uniform(1.0, 5.0), capacity: Distribution::Constant(1.0) };
+    let network = grid(3, 4, &weights, 2);
+    // 3 * 3 horizontal and 2 * 4 vertical edges
+    assert_eq!(2 * 17, network.num_arcs());
+    assert_eq!(vec![1, 4], network.adjacent(0));
+    assert_eq!(vec![1, 4, 6, 9], network.adjacent(5));
+    assert!(network.arcs().all(|(i, j)| network.cost(i, j) == network.cost(j, i)));
+}
+
+#[test]
+fn test_barabasi_albert() {
+    use super::Network;
+    let network = barabasi_albert(300, 2, &ArcWeights::default(), 5);
+    assert_eq!(2 * 2 * 298, network.num_arcs());
+    assert!(network.nodes().skip(2).all(|i| network.out_degree(i) >= 2));
+    assert!(network.arcs().all(|(i, j)| i != j && network.has_arc(j, i)));
+    // preferential attachment gives hubs far above the average degree of 4
+    assert!(network.nodes().map(|i| network.out_degree(i)).max().unwrap() > 15);
+    assert_eq!(network, barabasi_albert(300, 2, &ArcWeights::default(), 5));
+}
+
+#[test]
+fn test_watts_strogatz() {
+    use super::Network;
+    let ring = watts_strogatz(10, 4, 0.0, &ArcWeights::default(), 1);
+    assert_eq!(40, ring.num_arcs());
+    let mut adjacent = ring.adjacent(0);
+    adjacent.sort();
+    assert_eq!(vec![1, 2, 8, 9], adjacent);
+
+    let rewired = watts_strogatz(100, 6, 0.3, &ArcWeights::default(), 1);
+    assert_eq!(600, rewired.num_arcs());
+    assert!(rewired.arcs().all(|(i, j)| i != j && rewired.has_arc(j, i)));
+    let far = rewired.arcs().filter(|&(i, j)| { let d = (i as i64 - j as i64).abs(); d > 3 && d < 97 }).count();
+    assert!(far > 100 && far < 260);
+}
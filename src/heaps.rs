@@ -1,18 +1,19 @@
 use std::cmp::{Ord, Ordering};
 use std::collections::BinaryHeap as RHeap;
-use super::{ Cost, NodeId };
+use super::{ Cost, NodeId, Weight };
 
 /// minimalistic heap trait restricted for `(NodeId, Cost)` tuples
 /// 
 /// It provides a more common interface than the original Rust implementation
-/// suggests.
-pub trait Heap {
+/// suggests. The key type `K` defaults to `Cost`; `IndexedHeap` also takes
+/// other `Weight`s.
+pub trait Heap<K = Cost> {
     /// Find the min element in `O(1)` time.
     fn find_min(&self) -> Option<NodeId>;
     /// Return the current number of elements in the heap.
     fn size(&self) -> usize;
     fn is_empty(&self) -> bool;
-    fn insert(&mut self, node_id: NodeId, cost: Cost);
+    fn insert(&mut self, node_id: NodeId, cost: K);
     /// Remove the current minimal element.
     fn delete_min(&mut self);
 }
@@ -34,6 +35,10 @@ impl BinaryHeap {
             inner_heap: RHeap::with_capacity(capacity)
         }
     }
+    /// Removes all elements, keeping the allocated memory.
+    pub fn clear(&mut self) {
+        self.inner_heap.clear();
+    }
 }
 
 impl Heap for BinaryHeap {
@@ -59,6 +64,470 @@ impl Heap for BinaryHeap {
     }
 }
 
+/// Heaps that hold every node at most once and can lower its cost in place.
+/// `insert` of a node that is already in the heap lowers its cost if the
+/// new cost is smaller and is ignored otherwise, so searches written for the
+/// plain `Heap` trait work unchanged, just without duplicate entries.
+pub trait DecreaseKey<K = Cost>: Heap<K> {
+    /// Lowers the cost of `node`. Does nothing if `node` is not in the heap
+    /// or `cost` is not smaller than its current cost.
+    fn decrease_key(&mut self, node_id: NodeId, cost: K);
+    fn contains(&self, node_id: NodeId) -> bool;
+}
+
+/// Marks nodes that are not in an `IndexedHeap`.
+const NOT_IN_HEAP: usize = usize::MAX;
+
+/// Array backed binary min heap with a position map from node ids to heap
+/// slots, so that `decrease_key` can sift a node up in `O(log n)` time. It
+/// holds every node at most once, at most `n` entries for `n` nodes, where
+/// `BinaryHeap` keeps one entry per successful relaxation. The costs can be
+/// any `Weight`.
+pub struct IndexedHeap<K = Cost> {
+    /// `(cost, node)` in heap order.
+    slots:     Vec<(K, NodeId)>,
+    /// The slot of every node id, `NOT_IN_HEAP` if absent.
+    positions: Vec<usize>,
+}
+
+impl<K: Weight> IndexedHeap<K> {
+    /// A heap for node ids below `capacity`; larger ids grow the heap.
+    pub fn with_capacity(capacity: usize) -> Self {
+        IndexedHeap {
+            slots:     Vec::with_capacity(capacity),
+            positions: vec![NOT_IN_HEAP; capacity],
+        }
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.slots.swap(a, b);
+        self.positions[self.slots[a].1 as usize] = a;
+        self.positions[self.slots[b].1 as usize] = b;
+    }
+
+    fn sift_up(&mut self, mut slot: usize) {
+        while slot > 0 {
+            let parent = (slot - 1) / 2;
+            if self.slots[parent].0 <= self.slots[slot].0 {
+                break;
+            }
+            self.swap(parent, slot);
+            slot = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut slot: usize) {
+        loop {
+            let mut smallest = slot;
+            for child in 2 * slot + 1..(2 * slot + 3).min(self.slots.len()) {
+                if self.slots[child].0 < self.slots[smallest].0 {
+                    smallest = child;
+                }
+            }
+            if smallest == slot {
+                break;
+            }
+            self.swap(slot, smallest);
+            slot = smallest;
+        }
+    }
+}
+
+impl<K: Weight> Heap<K> for IndexedHeap<K> {
+    fn find_min(&self) -> Option<NodeId> {
+        self.slots.first().map(|&(_, node)| node)
+    }
+    fn size(&self) -> usize {
+        self.slots.len()
+    }
+    fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+    fn insert(&mut self, node_id: NodeId, cost: K) {
+        if self.contains(node_id) {
+            return self.decrease_key(node_id, cost);
+        }
+        let x = node_id as usize;
+        if x >= self.positions.len() {
+            self.positions.resize(x + 1, NOT_IN_HEAP);
+        }
+        self.positions[x] = self.slots.len();
+        self.slots.push((cost, node_id));
+        let last = self.slots.len() - 1;
+        self.sift_up(last);
+    }
+    fn delete_min(&mut self) {
+        if self.slots.is_empty() {
+            return;
+        }
+        let last = self.slots.len() - 1;
+        self.swap(0, last);
+        let (_, node) = self.slots.pop().unwrap();
+        self.positions[node as usize] = NOT_IN_HEAP;
+        self.sift_down(0);
+    }
+}
+
+impl<K: Weight> DecreaseKey<K> for IndexedHeap<K> {
+    fn decrease_key(&mut self, node_id: NodeId, cost: K) {
+        if !self.contains(node_id) {
+            return;
+        }
+        let slot = self.positions[node_id as usize];
+        if cost < self.slots[slot].0 {
+            self.slots[slot].0 = cost;
+            self.sift_up(slot);
+        }
+    }
+    fn contains(&self, node_id: NodeId) -> bool {
+        self.positions.get(node_id as usize).map_or(false, |slot| *slot != NOT_IN_HEAP)
+    }
+}
+
+/// Node of a `FibonacciHeap`. Nodes are stored at the index of their id, the
+/// sibling lists are circular and doubly linked.
+#[derive(Copy, Clone, Debug)]
+struct FibonacciEntry {
+    cost:    Cost,
+    parent:  Option<usize>,
+    child:   Option<usize>,
+    left:    usize,
+    right:   usize,
+    degree:  usize,
+    marked:  bool,
+    in_heap: bool,
+}
+
+/// Fibonacci heap (Fredman, Tarjan: "Fibonacci heaps and their uses in
+/// improved network optimization algorithms"): `insert` and `decrease_key`
+/// in `O(1)` and `delete_min` in `O(log n)` amortized time, which gives
+/// Dijkstra's algorithm its `O(m + n log n)` bound.
+pub struct FibonacciHeap {
+    entries: Vec<FibonacciEntry>,
+    min:     Option<usize>,
+    size:    usize,
+}
+
+impl FibonacciHeap {
+    /// A heap for node ids below `capacity`; larger ids grow the heap.
+    pub fn with_capacity(capacity: usize) -> Self {
+        FibonacciHeap {
+            entries: Vec::with_capacity(capacity),
+            min:     None,
+            size:    0,
+        }
+    }
+
+    /// Removes `x` from its sibling list.
+    fn unlink(&mut self, x: usize) {
+        let (left, right) = (self.entries[x].left, self.entries[x].right);
+        self.entries[left].right = right;
+        self.entries[right].left = left;
+        self.entries[x].left = x;
+        self.entries[x].right = x;
+    }
+
+    /// Adds `x` to the sibling list of `member`.
+    fn splice(&mut self, x: usize, member: usize) {
+        let right = self.entries[member].right;
+        self.entries[member].right = x;
+        self.entries[x].left = member;
+        self.entries[x].right = right;
+        self.entries[right].left = x;
+    }
+
+    fn siblings(&self, first: usize) -> Vec<usize> {
+        let mut siblings = vec![first];
+        let mut x = self.entries[first].right;
+        while x != first {
+            siblings.push(x);
+            x = self.entries[x].right;
+        }
+        siblings
+    }
+
+    /// Adds the detached `x` to the root list.
+    fn add_root(&mut self, x: usize) {
+        self.entries[x].parent = None;
+        self.entries[x].marked = false;
+        match self.min {
+            None => self.min = Some(x),
+            Some(min) => {
+                self.splice(x, min);
+                if self.entries[x].cost < self.entries[min].cost {
+                    self.min = Some(x);
+                }
+            },
+        }
+    }
+
+    /// Makes the root `y` a child of the root `x`.
+    fn link(&mut self, y: usize, x: usize) {
+        self.unlink(y);
+        self.entries[y].parent = Some(x);
+        self.entries[y].marked = false;
+        match self.entries[x].child {
+            None => self.entries[x].child = Some(y),
+            Some(child) => self.splice(y, child),
+        }
+        self.entries[x].degree += 1;
+    }
+
+    /// Melds roots of equal degree until all degrees differ.
+    fn consolidate(&mut self, first: usize) {
+        let mut by_degree: Vec<Option<usize>> = Vec::new();
+        for root in self.siblings(first) {
+            let mut x = root;
+            let mut degree = self.entries[x].degree;
+            loop {
+                if degree >= by_degree.len() {
+                    by_degree.resize(degree + 1, None);
+                }
+                let y = match by_degree[degree].take() {
+                    Some(y) => y,
+                    None => break,
+                };
+                let (parent, child) = if self.entries[y].cost < self.entries[x].cost { (y, x) } else { (x, y) };
+                self.link(child, parent);
+                x = parent;
+                degree += 1;
+            }
+            by_degree[degree] = Some(x);
+        }
+        self.min = None;
+        for x in by_degree.into_iter().filter_map(|x| x) {
+            if self.min.map_or(true, |min| self.entries[x].cost < self.entries[min].cost) {
+                self.min = Some(x);
+            }
+        }
+    }
+
+    /// Moves `x` from the children of `parent` to the root list.
+    fn cut(&mut self, x: usize, parent: usize) {
+        if self.entries[parent].child == Some(x) {
+            let right = self.entries[x].right;
+            self.entries[parent].child = if right == x { None } else { Some(right) };
+        }
+        self.unlink(x);
+        self.entries[parent].degree -= 1;
+        self.add_root(x);
+    }
+}
+
+impl Heap for FibonacciHeap {
+    fn find_min(&self) -> Option<NodeId> {
+        self.min.map(|min| min as NodeId)
+    }
+    fn size(&self) -> usize {
+        self.size
+    }
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    fn insert(&mut self, node_id: NodeId, cost: Cost) {
+        let x = node_id as usize;
+        if self.contains(node_id) {
+            return self.decrease_key(node_id, cost);
+        }
+        if x >= self.entries.len() {
+            let unused = FibonacciEntry { cost: 0.0, parent: None, child: None, left: 0, right: 0,
+                                          degree: 0, marked: false, in_heap: false };
+            self.entries.resize(x + 1, unused);
+        }
+        self.entries[x] = FibonacciEntry { cost: cost, parent: None, child: None, left: x, right: x,
+                                           degree: 0, marked: false, in_heap: true };
+        self.add_root(x);
+        self.size += 1;
+    }
+    fn delete_min(&mut self) {
+        let z = match self.min {
+            Some(z) => z,
+            None => return,
+        };
+        if let Some(child) = self.entries[z].child.take() {
+            for x in self.siblings(child) {
+                self.unlink(x);
+                self.entries[x].parent = None;
+                self.splice(x, z);
+            }
+        }
+        let next = self.entries[z].right;
+        self.unlink(z);
+        self.entries[z].in_heap = false;
+        self.size -= 1;
+        if next == z {
+            self.min = None;
+        } else {
+            self.consolidate(next);
+        }
+    }
+}
+
+impl DecreaseKey for FibonacciHeap {
+    fn decrease_key(&mut self, node_id: NodeId, cost: Cost) {
+        let x = node_id as usize;
+        if !self.contains(node_id) || cost >= self.entries[x].cost {
+            return;
+        }
+        self.entries[x].cost = cost;
+        if let Some(parent) = self.entries[x].parent {
+            if cost < self.entries[parent].cost {
+                self.cut(x, parent);
+                // cascading cut: cut marked ancestors, mark the first unmarked
+                let mut y = parent;
+                while let Some(grand_parent) = self.entries[y].parent {
+                    if !self.entries[y].marked {
+                        self.entries[y].marked = true;
+                        break;
+                    }
+                    self.cut(y, grand_parent);
+                    y = grand_parent;
+                }
+            }
+        }
+        if cost < self.entries[self.min.unwrap()].cost {
+            self.min = Some(x);
+        }
+    }
+    fn contains(&self, node_id: NodeId) -> bool {
+        self.entries.get(node_id as usize).map_or(false, |entry| entry.in_heap)
+    }
+}
+
+/// Node of a `PairingHeap`, stored at the index of its id. `prev` is the
+/// parent for a first child and the left sibling otherwise.
+#[derive(Copy, Clone, Debug)]
+struct PairingEntry {
+    cost:    Cost,
+    child:   Option<usize>,
+    sibling: Option<usize>,
+    prev:    Option<usize>,
+    in_heap: bool,
+}
+
+/// Pairing heap (Fredman et al.: "The pairing heap: A new form of
+/// self-adjusting heap"): simpler than a Fibonacci heap and usually faster
+/// in practice, with `O(1)` `insert` and `O(log n)` amortized `delete_min`
+/// and `decrease_key`.
+pub struct PairingHeap {
+    entries: Vec<PairingEntry>,
+    root:    Option<usize>,
+    size:    usize,
+}
+
+impl PairingHeap {
+    /// A heap for node ids below `capacity`; larger ids grow the heap.
+    pub fn with_capacity(capacity: usize) -> Self {
+        PairingHeap {
+            entries: Vec::with_capacity(capacity),
+            root:    None,
+            size:    0,
+        }
+    }
+
+    /// Melds two detached trees and returns the new root.
+    fn meld(&mut self, a: usize, b: usize) -> usize {
+        let (root, child) = if self.entries[b].cost < self.entries[a].cost { (b, a) } else { (a, b) };
+        let first = self.entries[root].child;
+        self.entries[child].sibling = first;
+        if let Some(first) = first {
+            self.entries[first].prev = Some(child);
+        }
+        self.entries[child].prev = Some(root);
+        self.entries[root].child = Some(child);
+        root
+    }
+
+    fn meld_root(&mut self, x: usize) {
+        let root = match self.root {
+            Some(root) => self.meld(root, x),
+            None => x,
+        };
+        self.entries[root].prev = None;
+        self.entries[root].sibling = None;
+        self.root = Some(root);
+    }
+}
+
+impl Heap for PairingHeap {
+    fn find_min(&self) -> Option<NodeId> {
+        self.root.map(|root| root as NodeId)
+    }
+    fn size(&self) -> usize {
+        self.size
+    }
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+    fn insert(&mut self, node_id: NodeId, cost: Cost) {
+        let x = node_id as usize;
+        if self.contains(node_id) {
+            return self.decrease_key(node_id, cost);
+        }
+        let entry = PairingEntry { cost: cost, child: None, sibling: None, prev: None, in_heap: true };
+        if x >= self.entries.len() {
+            let unused = PairingEntry { in_heap: false, ..entry };
+            self.entries.resize(x + 1, unused);
+        }
+        self.entries[x] = entry;
+        self.meld_root(x);
+        self.size += 1;
+    }
+    /// Melds the children of the root in two passes: pairwise from left to
+    /// right, then the pairs from right to left.
+    fn delete_min(&mut self) {
+        let root = match self.root.take() {
+            Some(root) => root,
+            None => return,
+        };
+        let mut children = Vec::new();
+        let mut next = self.entries[root].child.take();
+        while let Some(child) = next {
+            next = self.entries[child].sibling.take();
+            self.entries[child].prev = None;
+            children.push(child);
+        }
+        self.entries[root].in_heap = false;
+        self.size -= 1;
+
+        let mut pairs = Vec::with_capacity((children.len() + 1) / 2);
+        for pair in children.chunks(2) {
+            pairs.push(if pair.len() == 2 { self.meld(pair[0], pair[1]) } else { pair[0] });
+        }
+        while let Some(tree) = pairs.pop() {
+            self.meld_root(tree);
+        }
+    }
+}
+
+impl DecreaseKey for PairingHeap {
+    fn decrease_key(&mut self, node_id: NodeId, cost: Cost) {
+        let x = node_id as usize;
+        if !self.contains(node_id) || cost >= self.entries[x].cost {
+            return;
+        }
+        self.entries[x].cost = cost;
+        if self.root == Some(x) {
+            return;
+        }
+        // detach the subtree of `x` and meld it with the root
+        let prev = self.entries[x].prev.take().unwrap();
+        let sibling = self.entries[x].sibling.take();
+        if self.entries[prev].child == Some(x) {
+            self.entries[prev].child = sibling;
+        } else {
+            self.entries[prev].sibling = sibling;
+        }
+        if let Some(sibling) = sibling {
+            self.entries[sibling].prev = Some(prev);
+        }
+        self.meld_root(x);
+    }
+    fn contains(&self, node_id: NodeId) -> bool {
+        self.entries.get(node_id as usize).map_or(false, |entry| entry.in_heap)
+    }
+}
+
 /// Heap element, wraps a tuple of node id and respective costs
 #[derive(Copy, Clone, Debug, PartialEq)]
 struct HeapMember {
@@ -153,3 +622,66 @@ fn test_binary_heap() {
     binary_heap.insert(0,0.0);
     assert_eq!(Some(0), binary_heap.find_min());
 }
+
+#[cfg(test)]
+fn check_decrease_key_heap<H: DecreaseKey>(mut heap: H) {
+    use super::random::Rng;
+    heap.insert(3, 3.0);
+    heap.insert(1, 1.0);
+    heap.insert(2, 2.0);
+    heap.insert(1, 5.0);
+    assert_eq!(3, heap.size());
+    assert_eq!(Some(1), heap.find_min());
+    heap.decrease_key(3, 0.5);
+    assert_eq!(Some(3), heap.find_min());
+    heap.delete_min();
+    assert!(!heap.contains(3));
+    heap.insert(2, 0.0);
+    assert_eq!(Some(2), heap.find_min());
+    heap.delete_min();
+    heap.delete_min();
+    assert!(heap.is_empty());
+    assert_eq!(None, heap.find_min());
+
+    // against a sorted list under random inserts, decreases and deletions
+    let mut rng = Rng::new(11);
+    let mut costs: Vec<Option<f64>> = vec![None; 200];
+    for round in 0..2000 {
+        let node = rng.below(200);
+        let cost = rng.next_f64() * 100.0;
+        match costs[node] {
+            Some(current) if cost < current => {
+                heap.decrease_key(node as NodeId, cost);
+                costs[node] = Some(cost);
+            },
+            Some(_) => {},
+            None => {
+                heap.insert(node as NodeId, cost);
+                costs[node] = Some(cost);
+            },
+        }
+        if round % 3 == 0 {
+            let min = heap.find_min().unwrap() as usize;
+            let expected = costs.iter().filter_map(|c| *c).fold(f64::INFINITY, f64::min);
+            assert_eq!(Some(expected), costs[min]);
+            heap.delete_min();
+            costs[min] = None;
+        }
+        assert_eq!(costs.iter().filter(|c| c.is_some()).count(), heap.size());
+    }
+}
+
+#[test]
+fn test_indexed_heap() {
+    check_decrease_key_heap(IndexedHeap::with_capacity(5));
+}
+
+#[test]
+fn test_fibonacci_heap() {
+    check_decrease_key_heap(FibonacciHeap::with_capacity(0));
+}
+
+#[test]
+fn test_pairing_heap() {
+    check_decrease_key_heap(PairingHeap::with_capacity(10));
+}
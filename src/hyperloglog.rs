@@ -0,0 +1,100 @@
+use super::NodeId;
+use super::random::mix;
+
+/// HyperLogLog counter for estimating the number of distinct node ids added
+/// to it, see Flajolet et al.: "HyperLogLog: the analysis of a near-optimal
+/// cardinality estimation algorithm".
+///
+/// A counter with `2^b` registers uses `2^b` bytes and has a relative
+/// standard error of about `1.04 / sqrt(2^b)`. Counters of equal size can be
+/// merged, the result estimates the size of the union.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyperLogLog {
+    log2_registers: u8,
+    registers:      Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Creates an empty counter with `2^log2_registers` registers.
+    /// # Panics
+    /// If `log2_registers` is not within `4..17`.
+    pub fn new(log2_registers: u8) -> HyperLogLog {
+        assert!(log2_registers >= 4 && log2_registers <= 16);
+        HyperLogLog {
+            log2_registers: log2_registers,
+            registers:      vec![0; 1 << log2_registers],
+        }
+    }
+
+    pub fn insert(&mut self, id: NodeId) {
+        let hash = mix(id as u64);
+        let b = self.log2_registers as u32;
+        let index = (hash >> (64 - b)) as usize;
+        let rank = ((hash << b).leading_zeros()).min(64 - b) + 1;
+        if self.registers[index] < rank as u8 {
+            self.registers[index] = rank as u8;
+        }
+    }
+
+    /// Merges `other` into this counter and returns whether any register
+    /// changed.
+    pub fn union(&mut self, other: &HyperLogLog) -> bool {
+        assert!(self.log2_registers == other.log2_registers);
+        let mut changed = false;
+        for (mine, theirs) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if *theirs > *mine {
+                *mine = *theirs;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Estimates the number of distinct ids inserted so far, with linear
+    /// counting for small cardinalities.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _  => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let mut sum = 0.0;
+        let mut zeros = 0;
+        for register in &self.registers {
+            sum += 2.0f64.powi(-(*register as i32));
+            if *register == 0 {
+                zeros += 1;
+            }
+        }
+        let estimate = alpha * m * m / sum;
+        if estimate <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            estimate
+        }
+    }
+}
+
+#[test]
+fn test_hyperloglog_estimate() {
+    let mut counter = HyperLogLog::new(10);
+    for id in 0..10000 {
+        counter.insert(id);
+        counter.insert(id);
+    }
+    let estimate = counter.estimate();
+    assert!((estimate - 10000.0).abs() < 1000.0);
+}
+
+#[test]
+fn test_hyperloglog_union() {
+    let mut first = HyperLogLog::new(8);
+    let mut second = HyperLogLog::new(8);
+    for id in 0..20 { first.insert(id); }
+    for id in 10..40 { second.insert(id); }
+    assert!(first.union(&second));
+    assert!(!first.union(&second));
+    assert!((first.estimate() - 40.0).abs() < 2.0);
+}
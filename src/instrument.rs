@@ -0,0 +1,76 @@
+//! Instrumentation hooks for algorithms.
+//!
+//! Algorithms with an `_instrumented` variant report their progress into an
+//! `Instrumentation`. All methods have empty default implementations, so an
+//! implementation only overrides the events it is interested in. The plain
+//! variants of the algorithms report into `NoInstrumentation`, which
+//! compiles down to nothing.
+
+use std::time::Duration;
+
+use super::NodeId;
+
+pub trait Instrumentation {
+    /// A node got its final label, e.g. was removed from the heap in
+    /// Dijkstra's algorithm.
+    fn node_settled(&mut self, _node: NodeId) {}
+    /// The arc `(from, to)` was scanned while relaxing the labels of
+    /// `from`'s successors.
+    fn arc_relaxed(&mut self, _from: NodeId, _to: NodeId) {}
+    /// An iteration of an iterative algorithm, counting from `0`, was
+    /// completed.
+    fn iteration(&mut self, _iteration: usize) {}
+    /// The algorithm finished after the given wall time.
+    fn finished(&mut self, _elapsed: Duration) {}
+}
+
+/// Ignores all events.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoInstrumentation;
+
+impl Instrumentation for NoInstrumentation {}
+
+/// Counts all events, summed over every run it was passed to.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Counters {
+    pub nodes_settled: usize,
+    pub arcs_relaxed:  usize,
+    pub iterations:    usize,
+    pub elapsed:       Duration,
+}
+
+impl Instrumentation for Counters {
+    fn node_settled(&mut self, _node: NodeId) {
+        self.nodes_settled += 1;
+    }
+
+    fn arc_relaxed(&mut self, _from: NodeId, _to: NodeId) {
+        self.arcs_relaxed += 1;
+    }
+
+    fn iteration(&mut self, _iteration: usize) {
+        self.iterations += 1;
+    }
+
+    fn finished(&mut self, elapsed: Duration) {
+        self.elapsed += elapsed;
+    }
+}
+
+impl<'a, I: Instrumentation + ?Sized> Instrumentation for &'a mut I {
+    fn node_settled(&mut self, node: NodeId) {
+        (**self).node_settled(node)
+    }
+
+    fn arc_relaxed(&mut self, from: NodeId, to: NodeId) {
+        (**self).arc_relaxed(from, to)
+    }
+
+    fn iteration(&mut self, iteration: usize) {
+        (**self).iteration(iteration)
+    }
+
+    fn finished(&mut self, elapsed: Duration) {
+        (**self).finished(elapsed)
+    }
+}
@@ -0,0 +1,106 @@
+//! Reader for origin-destination demand in CSV files, one
+//! `origin,destination,volume` line per pair with nodes given by name.
+//! Blank lines and lines starting with `#` are skipped, as is a header line
+//! whose volume is not a number.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use network::io::csv;
+//!
+//! let node_to_id: HashMap<String, u32> = vec![("a".to_string(), 0), ("b".to_string(), 1)].into_iter().collect();
+//! let input = "origin,destination,volume\na,b,10\nb,a,2.5\n";
+//! let matrix = csv::read_od_matrix(input.as_bytes(), &node_to_id).unwrap();
+//! assert_eq!(10.0, matrix.get(0, 1));
+//! assert_eq!(12.5, matrix.total());
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{ self, BufRead };
+
+use super::super::NodeId;
+use super::super::algorithms::ODMatrix;
+
+/// Reasons why a CSV file can't be read. Lines are counted from 1.
+#[derive(Debug)]
+pub enum CsvError {
+    Io(io::Error),
+    /// A line without exactly three fields.
+    InvalidLine { line: usize },
+    /// A node name that is not in the network.
+    UnknownNode { line: usize, name: String },
+    /// A volume that is not a non-negative number.
+    InvalidVolume { line: usize, value: String },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CsvError::Io(ref e) => write!(f, "{}", e),
+            CsvError::InvalidLine { line } => write!(f, "line {}: expected origin, destination and volume", line),
+            CsvError::UnknownNode { line, ref name } => write!(f, "line {}: unknown node {}", line, name),
+            CsvError::InvalidVolume { line, ref value } => write!(f, "line {}: `{}` is not a valid volume", line, value),
+        }
+    }
+}
+
+impl Error for CsvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            CsvError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CsvError {
+    fn from(e: io::Error) -> CsvError {
+        CsvError::Io(e)
+    }
+}
+
+/// Reads origin-destination demand, see the module documentation. Demand
+/// given twice for a pair is summed up.
+pub fn read_od_matrix<R: BufRead>(reader: R, node_to_id: &HashMap<String, NodeId>) -> Result<ODMatrix, CsvError> {
+    let mut matrix = ODMatrix::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+        if fields.len() != 3 {
+            return Err(CsvError::InvalidLine { line: number });
+        }
+        let volume = match fields[2].parse::<f64>() {
+            Ok(volume) if volume >= 0.0 => volume,
+            Err(_) if number == 1 => continue,
+            _ => return Err(CsvError::InvalidVolume { line: number, value: fields[2].to_string() }),
+        };
+        let node = |name: &str| node_to_id.get(name)
+            .map(|id| *id)
+            .ok_or(CsvError::UnknownNode { line: number, name: name.to_string() });
+        matrix.add(node(fields[0])?, node(fields[1])?, volume);
+    }
+    Ok(matrix)
+}
+
+#[test]
+fn test_read_od_matrix() {
+    let node_to_id: HashMap<String, NodeId> = vec![("a".to_string(), 0), ("b".to_string(), 1)].into_iter().collect();
+    let matrix = read_od_matrix("# demand\n\na, b, 1\na,b,2\n".as_bytes(), &node_to_id).unwrap();
+    assert_eq!(vec![(0, 1, 3.0)], matrix.to_demand());
+    // zero volumes are accepted but not stored
+    let matrix = read_od_matrix("a,b,1\nb,a,0\n".as_bytes(), &node_to_id).unwrap();
+    assert_eq!(1, matrix.len());
+
+    let error = read_od_matrix("a,b,1\na,c,2\n".as_bytes(), &node_to_id).unwrap_err();
+    assert_eq!("line 2: unknown node c", error.to_string());
+    let error = read_od_matrix("a,b,1\nb,a,x\n".as_bytes(), &node_to_id).unwrap_err();
+    assert_eq!("line 2: `x` is not a valid volume", error.to_string());
+    let error = read_od_matrix("a,b\n".as_bytes(), &node_to_id).unwrap_err();
+    assert_eq!("line 1: expected origin, destination and volume", error.to_string());
+}
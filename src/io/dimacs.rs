@@ -0,0 +1,239 @@
+//! The DIMACS formats for shortest path (`.gr`) and maximum flow (`.max`)
+//! problems, as used by the DIMACS implementation challenges.
+//!
+//! Both formats have a problem line `p sp <nodes> <arcs>` or
+//! `p max <nodes> <arcs>`, followed by one `a <from> <to> <value>` line per
+//! arc. Maximum flow problems name their source and sink with `n <node> s`
+//! and `n <node> t`. Lines starting with `c` are comments. Nodes are
+//! numbered from 1 in the files and from 0 in the networks.
+//!
+//! ```text
+//! c a small shortest path problem
+//! p sp 3 2
+//! a 1 2 5
+//! a 2 3 7
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::io::{ self, BufRead, Write };
+
+use super::super::{ Network, NodeId };
+use super::super::compact_star::{ CompactStar, try_compact_star_from_edge_vec };
+
+/// The kind of problem a DIMACS file describes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Problem {
+    /// `p sp`, arc values are costs.
+    ShortestPath,
+    /// `p max`, arc values are capacities.
+    MaxFlow,
+}
+
+/// A network read from a DIMACS file. Source and sink are only given by
+/// maximum flow problems.
+#[derive(Debug, PartialEq)]
+pub struct DimacsNetwork {
+    pub problem: Problem,
+    pub network: CompactStar,
+    pub source:  Option<NodeId>,
+    pub sink:    Option<NodeId>,
+}
+
+/// Reasons why a DIMACS file can't be read. Lines are counted from 1.
+#[derive(Debug)]
+pub enum DimacsError {
+    Io(io::Error),
+    /// No problem line before the first node or arc line.
+    MissingProblem,
+    /// A second problem line, or one of an unknown problem.
+    InvalidProblem { line: usize },
+    /// A line that doesn't fit the format.
+    InvalidLine { line: usize },
+    /// A node outside of `1..=nodes`.
+    InvalidNode { line: usize, node: u64 },
+    /// The number of arc lines differs from the problem line.
+    ArcCount { expected: usize, found: usize },
+    /// The problem line declares no nodes.
+    NoNodes,
+}
+
+impl fmt::Display for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DimacsError::Io(ref e) => write!(f, "{}", e),
+            DimacsError::MissingProblem => write!(f, "missing problem line"),
+            DimacsError::InvalidProblem { line } => write!(f, "line {}: unexpected problem line", line),
+            DimacsError::InvalidLine { line } => write!(f, "line {}: malformed line", line),
+            DimacsError::InvalidNode { line, node } => write!(f, "line {}: node {} out of range", line, node),
+            DimacsError::ArcCount { expected, found } => write!(f, "expected {} arcs, found {}", expected, found),
+            DimacsError::NoNodes => write!(f, "the problem has no nodes"),
+        }
+    }
+}
+
+impl Error for DimacsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            DimacsError::Io(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DimacsError {
+    fn from(e: io::Error) -> DimacsError {
+        DimacsError::Io(e)
+    }
+}
+
+/// Reads a shortest path or maximum flow problem. Arcs keep the order of
+/// the file for every tail node. Shortest path arcs get capacity `0.0`,
+/// maximum flow arcs cost `0.0`.
+pub fn read<R: BufRead>(reader: R) -> Result<DimacsNetwork, DimacsError> {
+    let mut problem = None;
+    let mut nodes = 0;
+    let mut expected_arcs = 0;
+    let mut edges = Vec::new();
+    let mut source = None;
+    let mut sink = None;
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let number = index + 1;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let invalid = DimacsError::InvalidLine { line: number };
+        match fields.first().map(|f| *f) {
+            None | Some("c") => continue,
+            Some("p") => {
+                if problem.is_some() || fields.len() != 4 {
+                    return Err(DimacsError::InvalidProblem { line: number });
+                }
+                problem = match fields[1] {
+                    "sp"  => Some(Problem::ShortestPath),
+                    "max" => Some(Problem::MaxFlow),
+                    _     => return Err(DimacsError::InvalidProblem { line: number }),
+                };
+                nodes = fields[2].parse::<usize>().map_err(|_| DimacsError::InvalidLine { line: number })?;
+                expected_arcs = fields[3].parse::<usize>().map_err(|_| DimacsError::InvalidLine { line: number })?;
+                edges.reserve(expected_arcs);
+            },
+            Some("a") => {
+                let kind = problem.ok_or(DimacsError::MissingProblem)?;
+                if fields.len() != 4 {
+                    return Err(invalid);
+                }
+                let from = node(fields[1], nodes, number)?;
+                let to = node(fields[2], nodes, number)?;
+                let value = fields[3].parse::<f64>().map_err(|_| DimacsError::InvalidLine { line: number })?;
+                edges.push(match kind {
+                    Problem::ShortestPath => (from, to, value, 0.0),
+                    Problem::MaxFlow      => (from, to, 0.0, value),
+                });
+            },
+            Some("n") => {
+                if problem != Some(Problem::MaxFlow) || fields.len() != 3 {
+                    return Err(invalid);
+                }
+                let id = node(fields[1], nodes, number)?;
+                match fields[2] {
+                    "s" => source = Some(id),
+                    "t" => sink = Some(id),
+                    _   => return Err(invalid),
+                }
+            },
+            Some(_) => return Err(invalid),
+        }
+    }
+
+    let problem = problem.ok_or(DimacsError::MissingProblem)?;
+    if edges.len() != expected_arcs {
+        return Err(DimacsError::ArcCount { expected: expected_arcs, found: edges.len() });
+    }
+    // the arc lines are checked against the node count already
+    let network = try_compact_star_from_edge_vec(nodes, &mut edges).map_err(|_| DimacsError::NoNodes)?;
+    Ok(DimacsNetwork {
+        problem: problem,
+        network: network,
+        source:  source,
+        sink:    sink,
+    })
+}
+
+fn node(field: &str, nodes: usize, line: usize) -> Result<NodeId, DimacsError> {
+    let node = field.parse::<u64>().map_err(|_| DimacsError::InvalidLine { line: line })?;
+    if node == 0 || node > nodes as u64 {
+        return Err(DimacsError::InvalidNode { line: line, node: node });
+    }
+    Ok((node - 1) as NodeId)
+}
+
+/// Writes `network` as shortest path problem with the arc costs.
+pub fn write_shortest_path<N: Network, W: Write>(network: &N, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "p sp {} {}", network.num_nodes(), network.num_arcs())?;
+    for (from, to, cost, _) in network.arc_values() {
+        writeln!(writer, "a {} {} {}", from + 1, to + 1, cost)?;
+    }
+    Ok(())
+}
+
+/// Writes `network` as maximum flow problem from `source` to `sink` with
+/// the arc capacities.
+pub fn write_max_flow<N: Network, W: Write>(network: &N, source: NodeId, sink: NodeId, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "p max {} {}", network.num_nodes(), network.num_arcs())?;
+    writeln!(writer, "n {} s", source + 1)?;
+    writeln!(writer, "n {} t", sink + 1)?;
+    for (from, to, _, capacity) in network.arc_values() {
+        writeln!(writer, "a {} {} {}", from + 1, to + 1, capacity)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_read_shortest_path() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let input = "c test\np sp 3 3\n\na 1 2 5\na 2 3 7.5\na 1 3 20\n";
+    let dimacs = read(input.as_bytes()).unwrap();
+    assert_eq!(Problem::ShortestPath, dimacs.problem);
+    let mut edges = vec![(0,1,5.0,0.0), (1,2,7.5,0.0), (0,2,20.0,0.0)];
+    assert_eq!(compact_star_from_edge_vec(3, &mut edges), dimacs.network);
+    assert_eq!(None, dimacs.source);
+
+    let mut written = Vec::new();
+    write_shortest_path(&dimacs.network, &mut written).unwrap();
+    assert_eq!("p sp 3 3\na 1 2 5\na 1 3 20\na 2 3 7.5\n", String::from_utf8(written).unwrap());
+}
+
+#[test]
+fn test_read_max_flow() {
+    let input = "p max 4 3\nn 1 s\nn 4 t\na 1 2 3\na 2 4 2\na 1 3 1\n";
+    let dimacs = read(input.as_bytes()).unwrap();
+    assert_eq!(Problem::MaxFlow, dimacs.problem);
+    assert_eq!((Some(0), Some(3)), (dimacs.source, dimacs.sink));
+    assert_eq!(Some(3.0), dimacs.network.capacity(0, 1));
+
+    let mut written = Vec::new();
+    write_max_flow(&dimacs.network, 0, 3, &mut written).unwrap();
+    let reread = read(&written[..]).unwrap();
+    assert_eq!(dimacs, reread);
+}
+
+#[test]
+fn test_write_parallel_arcs() {
+    let input = "p sp 2 2\na 1 2 5\na 1 2 3\n";
+    let dimacs = read(input.as_bytes()).unwrap();
+    let mut written = Vec::new();
+    write_shortest_path(&dimacs.network, &mut written).unwrap();
+    assert_eq!(input, String::from_utf8(written).unwrap());
+}
+
+#[test]
+fn test_read_errors() {
+    let error = |input: &str| format!("{}", read(input.as_bytes()).unwrap_err());
+    assert_eq!("missing problem line", error("a 1 2 3\n"));
+    assert_eq!("line 2: unexpected problem line", error("p sp 1 0\np sp 1 0\n"));
+    assert_eq!("line 2: node 3 out of range", error("p sp 2 1\na 1 3 1\n"));
+    assert_eq!("line 2: malformed line", error("p sp 2 1\na 1 2 x\n"));
+    assert_eq!("expected 2 arcs, found 1", error("p sp 2 2\na 1 2 1\n"));
+    assert_eq!("the problem has no nodes", error("p sp 0 0\n"));
+}
@@ -0,0 +1,88 @@
+//! Distance matrices for external routing solvers, computed with
+//! `algorithms::distance_table` between a subset of the nodes. The
+//! matrices are asymmetric in general: row `i`, column `j` is the length of
+//! the shortest path from the `i`-th to the `j`-th node. Unreachable pairs
+//! have the network's infinity, which exceeds every path length and thus
+//! works as penalty in most solvers.
+//!
+//! Three formats are supported: a plain matrix of whitespace separated
+//! rows, a TSPLIB `ATSP` problem with an explicit full matrix, and a sparse
+//! list of `from to distance` lines for the pairs within a threshold.
+
+use std::io::{ self, Write };
+
+use super::super::DoubleVec;
+
+/// Writes `table` as plain matrix, one line per row.
+pub fn write_plain<W: Write>(table: &[DoubleVec], writer: &mut W) -> io::Result<()> {
+    for row in table {
+        let row: Vec<String> = row.iter().map(|d| d.to_string()).collect();
+        writeln!(writer, "{}", row.join(" "))?;
+    }
+    Ok(())
+}
+
+/// Writes the square `table` as asymmetric TSP in TSPLIB format, named
+/// `name`. TSPLIB weights are integers, so the distances are multiplied by
+/// `scale` and rounded, e.g. `scale = 1000.0` for kilometers with meter
+/// precision.
+/// # Panics
+/// If `table` is not square.
+pub fn write_tsplib<W: Write>(table: &[DoubleVec], name: &str, scale: f64, writer: &mut W) -> io::Result<()> {
+    assert!(table.iter().all(|row| row.len() == table.len()), "TSPLIB needs a square distance matrix");
+    writeln!(writer, "NAME: {}", name)?;
+    writeln!(writer, "TYPE: ATSP")?;
+    writeln!(writer, "DIMENSION: {}", table.len())?;
+    writeln!(writer, "EDGE_WEIGHT_TYPE: EXPLICIT")?;
+    writeln!(writer, "EDGE_WEIGHT_FORMAT: FULL_MATRIX")?;
+    writeln!(writer, "EDGE_WEIGHT_SECTION")?;
+    for row in table {
+        let row: Vec<String> = row.iter().map(|d| ((d * scale).round() as i64).to_string()).collect();
+        writeln!(writer, "{}", row.join(" "))?;
+    }
+    writeln!(writer, "EOF")
+}
+
+/// Writes one `from to distance` line for every entry of `table` of at most
+/// `threshold`, rows labelled by `row_labels` and columns by
+/// `column_labels`. Pairs of the same label are left out.
+pub fn write_sparse<W: Write>(table: &[DoubleVec], threshold: f64, row_labels: &[String], column_labels: &[String],
+                              writer: &mut W) -> io::Result<()> {
+    for (row, from) in table.iter().zip(row_labels) {
+        for (d, to) in row.iter().zip(column_labels) {
+            if *d <= threshold && from != to {
+                writeln!(writer, "{} {} {}", from, to, d)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_write_distance_matrix() {
+    use super::super::Network;
+    use super::super::algorithms::distance_table;
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.5,0.0), (1,2,2.0,0.0), (2,0,4.0,0.0), (1,3,1.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let table = distance_table(&network, &[0, 1, 2], &[0, 1, 2]);
+
+    let mut written = Vec::new();
+    write_plain(&table, &mut written).unwrap();
+    assert_eq!("0 1.5 3.5\n6 0 2\n4 5.5 0\n", String::from_utf8(written).unwrap());
+
+    let mut written = Vec::new();
+    write_tsplib(&table, "triangle", 10.0, &mut written).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert!(written.starts_with("NAME: triangle\nTYPE: ATSP\nDIMENSION: 3\n"));
+    assert!(written.ends_with("EDGE_WEIGHT_SECTION\n0 15 35\n60 0 20\n40 55 0\nEOF\n"));
+
+    let labels: Vec<String> = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    let mut written = Vec::new();
+    write_sparse(&table, 4.0, &labels, &labels, &mut written).unwrap();
+    assert_eq!("a b 1.5\na c 3.5\nb c 2\nc a 4\n", String::from_utf8(written).unwrap());
+
+    // node 3 can't reach the others
+    let table = distance_table(&network, &[3], &[0, 3]);
+    assert_eq!(vec![vec![network.infinity(), 0.0]], table);
+}
@@ -0,0 +1,150 @@
+//! Graphviz DOT output of networks, optionally with a search result
+//! highlighted. Nodes are written by id with their name as label, arcs
+//! are labeled with their cost.
+//!
+//! ```text
+//! digraph network {
+//!   0 [label="a"];
+//!   1 [label="b"];
+//!   0 -> 1 [label="5"];
+//! }
+//! ```
+//!
+//! Render with e.g. `dot -Tsvg network.dot > network.svg`.
+
+use std::collections::HashSet;
+use std::io::{ self, Write };
+
+use super::super::{ Network, NodeId };
+use super::super::pretty::node_label;
+
+/// Color of highlighted nodes and arcs.
+const HIGHLIGHT: &'static str = "red";
+
+/// Writes `network` in DOT format. `labels` names the nodes, missing names
+/// are replaced by node ids.
+pub fn write<N: Network, W: Write>(network: &N, labels: Option<&[String]>, writer: &mut W) -> io::Result<()> {
+    write_highlighted(network, labels, &HashSet::new(), &HashSet::new(), writer)
+}
+
+/// Writes `network` in DOT format with the search tree given by the
+/// predecessor list `pred`, as returned by e.g. `dijkstra` or
+/// `breadth_first_search`, highlighted. Predecessors outside of the node
+/// range mark the root and unreached nodes.
+pub fn write_tree<N: Network, W: Write>(network: &N, pred: &[NodeId], labels: Option<&[String]>,
+                                       writer: &mut W) -> io::Result<()> {
+    let n = network.num_nodes();
+    let mut nodes = HashSet::new();
+    let mut arcs = HashSet::new();
+    for (to, from) in pred.iter().enumerate() {
+        if (*from as usize) < n {
+            nodes.insert(*from);
+            nodes.insert(to as NodeId);
+            arcs.insert((*from, to as NodeId));
+        }
+    }
+    write_highlighted(network, labels, &nodes, &arcs, writer)
+}
+
+/// Writes `network` in DOT format with the path from `source` to `target`
+/// in the predecessor list `pred` highlighted. Nothing is highlighted if
+/// `target` was not reached from `source`.
+pub fn write_path<N: Network, W: Write>(network: &N, pred: &[NodeId], source: NodeId, target: NodeId,
+                                       labels: Option<&[String]>, writer: &mut W) -> io::Result<()> {
+    let n = network.num_nodes();
+    let mut nodes = HashSet::new();
+    let mut arcs = HashSet::new();
+    let mut current = target;
+    nodes.insert(current);
+    while current != source {
+        let from = pred[current as usize];
+        if from as usize >= n || nodes.contains(&from) {
+            nodes.clear();
+            arcs.clear();
+            break;
+        }
+        nodes.insert(from);
+        arcs.insert((from, current));
+        current = from;
+    }
+    write_highlighted(network, labels, &nodes, &arcs, writer)
+}
+
+fn write_highlighted<N: Network, W: Write>(network: &N, labels: Option<&[String]>, nodes: &HashSet<NodeId>,
+                                          arcs: &HashSet<(NodeId, NodeId)>, writer: &mut W) -> io::Result<()> {
+    writeln!(writer, "digraph network {{")?;
+    for i in network.nodes() {
+        write!(writer, "  {} [label={}", i, dot_string(&node_label(labels, i)))?;
+        if nodes.contains(&i) {
+            write!(writer, ", color={}", HIGHLIGHT)?;
+        }
+        writeln!(writer, "];")?;
+    }
+    for (from, to, cost, _) in network.arc_values() {
+        write!(writer, "  {} -> {} [label=\"{}\"", from, to, cost)?;
+        if arcs.contains(&(from, to)) {
+            write!(writer, ", color={}, penwidth=2", HIGHLIGHT)?;
+        }
+        writeln!(writer, "];")?;
+    }
+    writeln!(writer, "}}")
+}
+
+/// Quotes `value` as a DOT string.
+fn dot_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"'  => quoted.push_str("\\\""),
+            '\n' => quoted.push_str("\\n"),
+            c    => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[test]
+fn test_write_dot() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,5.0,0.0), (1,2,7.5,0.0), (0,2,20.0,0.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let labels = vec!["a".to_string(), "b \"x\"".to_string()];
+
+    let mut written = Vec::new();
+    write(&network, Some(&labels), &mut written).unwrap();
+    assert_eq!("digraph network {\n  0 [label=\"a\"];\n  1 [label=\"b \\\"x\\\"\"];\n  2 [label=\"2\"];\n  \
+                0 -> 1 [label=\"5\"];\n  0 -> 2 [label=\"20\"];\n  1 -> 2 [label=\"7.5\"];\n}\n",
+               String::from_utf8(written).unwrap());
+
+    let pred = vec![3, 0, 1];
+    let mut written = Vec::new();
+    write_path(&network, &pred, 0, 2, None, &mut written).unwrap();
+    let dot = String::from_utf8(written).unwrap();
+    assert!(dot.contains("  1 -> 2 [label=\"7.5\", color=red, penwidth=2];\n"));
+    assert!(dot.contains("  0 -> 2 [label=\"20\"];\n"));
+    assert!(dot.contains("  2 [label=\"2\", color=red];\n"));
+
+    let mut written = Vec::new();
+    write_tree(&network, &[3, 0, 0], None, &mut written).unwrap();
+    let dot = String::from_utf8(written).unwrap();
+    assert_eq!(2, dot.matches("penwidth").count());
+    assert!(dot.contains("  0 -> 2 [label=\"20\", color=red, penwidth=2];\n"));
+
+    // unreached targets highlight nothing
+    let mut written = Vec::new();
+    write_path(&network, &[3, 3, 3], 0, 2, None, &mut written).unwrap();
+    assert!(!String::from_utf8(written).unwrap().contains("red"));
+}
+
+#[test]
+fn test_write_dot_parallel_arcs() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,5.0,0.0), (0,1,3.0,0.0)];
+    let network = compact_star_from_edge_vec(2, &mut edges);
+    let mut written = Vec::new();
+    write(&network, None, &mut written).unwrap();
+    let dot = String::from_utf8(written).unwrap();
+    assert!(dot.contains("  0 -> 1 [label=\"5\"];\n  0 -> 1 [label=\"3\"];\n"));
+}
@@ -0,0 +1,37 @@
+//! Plain edge lists with one `from to cost capacity` line per arc, nodes
+//! named by their number. The test tool reads them with its default
+//! pattern, which takes the cost; the capacity needs a pattern with
+//! `P<cap>`.
+//!
+//! ```text
+//! 0 1 1.500000 1.000000
+//! 1 0 1.500000 1.000000
+//! ```
+
+use std::io::{ self, Write };
+
+use super::super::Network;
+
+/// Writes every arc of `network` in arc order.
+pub fn write<N: Network, W: Write>(network: &N, writer: &mut W) -> io::Result<()> {
+    for i in network.nodes() {
+        for (j, cost, capacity) in network.out_arcs(i) {
+            writeln!(writer, "{} {} {:.6} {:.6}", i, j, cost, capacity)?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_write_edge_list() {
+    use super::super::compact_star::compact_star_from_edge_vec;
+    use super::super::parse::{ DEFAULT_PATTERN, parse_edges };
+    let mut edges = vec![(0,1,1.5,2.0), (1,2,0.25,1.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let mut written = Vec::new();
+    write(&network, &mut written).unwrap();
+    let written = String::from_utf8(written).unwrap();
+    assert_eq!("0 1 1.500000 2.000000\n1 2 0.250000 1.000000\n", written);
+    let parsed = parse_edges(&written, DEFAULT_PATTERN, false, 0).unwrap();
+    assert_eq!(vec![(0,1,1.5,0.0), (1,2,0.25,0.0)], parsed.edges);
+}
@@ -0,0 +1,7 @@
+//! Reading and writing networks in standard file formats.
+
+pub mod csv;
+pub mod dimacs;
+pub mod distance_matrix;
+pub mod edge_list;
+pub mod dot;
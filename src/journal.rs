@@ -0,0 +1,242 @@
+//! Journals of network changes.
+//!
+//! A `Journal` records arc and node changes in the order they were made.
+//! It can be replayed onto an edge list or an existing `CompactStar`, which
+//! yields a rebuilt network, and written to or read from a plain text patch
+//! with one change per line:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! node
+//! add 0 3 2.5 10
+//! update 1 2 4 10
+//! remove 0 1
+//! ```
+
+use std::error::Error;
+use std::fmt;
+
+use super::{ Capacity, Cost, Network, NodeId };
+use super::compact_star::{ CompactStar, compact_star_from_edge_vec, empty_compact_star };
+use super::parse::Edge;
+
+/// A single change of a network.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    /// Appends a node with the next free id.
+    AddNode,
+    AddArc { from: NodeId, to: NodeId, cost: Cost, capacity: Capacity },
+    /// Removes the first arc from `from` to `to`.
+    RemoveArc { from: NodeId, to: NodeId },
+    /// Sets cost and capacity of the first arc from `from` to `to`.
+    UpdateArc { from: NodeId, to: NodeId, cost: Cost, capacity: Capacity },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Change::AddNode => write!(f, "node"),
+            Change::AddArc { from, to, cost, capacity } => write!(f, "add {} {} {} {}", from, to, cost, capacity),
+            Change::RemoveArc { from, to } => write!(f, "remove {} {}", from, to),
+            Change::UpdateArc { from, to, cost, capacity } => write!(f, "update {} {} {} {}", from, to, cost, capacity),
+        }
+    }
+}
+
+/// Reasons why a journal can't be read or replayed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JournalError {
+    /// A change refers to a node that doesn't exist at that point.
+    InvalidNode { node: NodeId },
+    /// A change refers to an arc that doesn't exist at that point.
+    MissingArc { from: NodeId, to: NodeId },
+    /// A line of a patch is malformed; lines are counted from 1.
+    InvalidPatch { line: usize },
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JournalError::InvalidNode { node } => write!(f, "node {} does not exist", node),
+            JournalError::MissingArc { from, to } => write!(f, "arc {} -> {} does not exist", from, to),
+            JournalError::InvalidPatch { line } => write!(f, "line {}: malformed change", line),
+        }
+    }
+}
+
+impl Error for JournalError {}
+
+/// An ordered list of changes.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Journal {
+    changes: Vec<Change>,
+}
+
+impl Journal {
+    pub fn new() -> Journal {
+        Journal::default()
+    }
+
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.changes.clear();
+    }
+
+    pub fn record(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    pub fn add_node(&mut self) {
+        self.record(Change::AddNode);
+    }
+
+    pub fn add_arc(&mut self, from: NodeId, to: NodeId, cost: Cost, capacity: Capacity) {
+        self.record(Change::AddArc { from: from, to: to, cost: cost, capacity: capacity });
+    }
+
+    pub fn remove_arc(&mut self, from: NodeId, to: NodeId) {
+        self.record(Change::RemoveArc { from: from, to: to });
+    }
+
+    pub fn update_arc(&mut self, from: NodeId, to: NodeId, cost: Cost, capacity: Capacity) {
+        self.record(Change::UpdateArc { from: from, to: to, cost: cost, capacity: capacity });
+    }
+
+    /// Applies all changes in order to a network with `num_nodes` nodes and
+    /// the arcs `edges`. Added arcs are appended to `edges`, removed arcs
+    /// are taken out without changing the order of the others. Stops at the
+    /// first change that can't be applied; the changes before it remain
+    /// applied.
+    pub fn apply(&self, num_nodes: &mut usize, edges: &mut Vec<Edge>) -> Result<(), JournalError> {
+        for change in &self.changes {
+            match *change {
+                Change::AddNode => *num_nodes += 1,
+                Change::AddArc { from, to, cost, capacity } => {
+                    check_node(from, *num_nodes)?;
+                    check_node(to, *num_nodes)?;
+                    edges.push((from, to, cost, capacity));
+                },
+                Change::RemoveArc { from, to } => {
+                    let k = find_arc(edges, from, to)?;
+                    edges.remove(k);
+                },
+                Change::UpdateArc { from, to, cost, capacity } => {
+                    let k = find_arc(edges, from, to)?;
+                    edges[k] = (from, to, cost, capacity);
+                },
+            }
+        }
+        Ok(())
+    }
+
+    /// Rebuilds `network` with all changes applied. A network that still has
+    /// no nodes afterwards is rebuilt empty.
+    pub fn replay(&self, network: &CompactStar) -> Result<CompactStar, JournalError> {
+        let mut num_nodes = network.num_nodes();
+        let mut edges = network.edges();
+        self.apply(&mut num_nodes, &mut edges)?;
+        if num_nodes == 0 {
+            return Ok(empty_compact_star());
+        }
+        Ok(compact_star_from_edge_vec(num_nodes, &mut edges))
+    }
+
+    /// The journal as a patch, one change per line.
+    pub fn to_patch(&self) -> String {
+        self.changes.iter().map(|change| format!("{}\n", change)).collect()
+    }
+
+    /// Reads a patch as written by `to_patch`.
+    pub fn from_patch(patch: &str) -> Result<Journal, JournalError> {
+        let mut journal = Journal::new();
+        for (index, line) in patch.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let change = parse_change(line).ok_or(JournalError::InvalidPatch { line: index + 1 })?;
+            journal.record(change);
+        }
+        Ok(journal)
+    }
+}
+
+fn check_node(node: NodeId, num_nodes: usize) -> Result<(), JournalError> {
+    if (node as usize) < num_nodes {
+        Ok(())
+    } else {
+        Err(JournalError::InvalidNode { node: node })
+    }
+}
+
+fn find_arc(edges: &[Edge], from: NodeId, to: NodeId) -> Result<usize, JournalError> {
+    edges.iter()
+        .position(|&(i, j, _, _)| i == from && j == to)
+        .ok_or(JournalError::MissingArc { from: from, to: to })
+}
+
+fn parse_change(line: &str) -> Option<Change> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let node = |k: usize| fields[k].parse::<NodeId>().ok();
+    let value = |k: usize| fields[k].parse::<f64>().ok();
+    match (fields[0], fields.len()) {
+        ("node", 1) => Some(Change::AddNode),
+        ("add", 5) => Some(Change::AddArc { from: node(1)?, to: node(2)?, cost: value(3)?, capacity: value(4)? }),
+        ("remove", 3) => Some(Change::RemoveArc { from: node(1)?, to: node(2)? }),
+        ("update", 5) => Some(Change::UpdateArc { from: node(1)?, to: node(2)?, cost: value(3)?, capacity: value(4)? }),
+        _ => None,
+    }
+}
+
+#[test]
+fn test_replay() {
+    let mut edges = vec![(0,1,1.0,5.0), (1,2,2.0,5.0), (0,1,3.0,5.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+
+    let mut journal = Journal::new();
+    journal.add_node();
+    journal.add_arc(2, 3, 4.0, 1.0);
+    journal.remove_arc(0, 1);
+    journal.update_arc(1, 2, 2.5, 6.0);
+    let rebuilt = journal.replay(&network).unwrap();
+
+    let mut expected_edges = vec![(0,1,3.0,5.0), (1,2,2.5,6.0), (2,3,4.0,1.0)];
+    assert_eq!(compact_star_from_edge_vec(4, &mut expected_edges), rebuilt);
+
+    let mut failing = Journal::new();
+    failing.remove_arc(2, 0);
+    assert_eq!(Err(JournalError::MissingArc { from: 2, to: 0 }), failing.replay(&network));
+    failing.clear();
+    failing.add_arc(0, 3, 1.0, 1.0);
+    assert_eq!(Err(JournalError::InvalidNode { node: 3 }), failing.replay(&network));
+
+    assert_eq!(empty_compact_star(), Journal::new().replay(&empty_compact_star()).unwrap());
+    let mut growing = Journal::new();
+    growing.add_node();
+    assert_eq!(1, growing.replay(&empty_compact_star()).unwrap().num_nodes());
+}
+
+#[test]
+fn test_patch() {
+    let mut journal = Journal::new();
+    journal.add_node();
+    journal.add_arc(0, 3, 2.5, 10.0);
+    journal.update_arc(1, 2, 4.0, 10.0);
+    journal.remove_arc(0, 1);
+    let patch = journal.to_patch();
+    assert_eq!("node\nadd 0 3 2.5 10\nupdate 1 2 4 10\nremove 0 1\n", patch);
+    assert_eq!(Ok(journal), Journal::from_patch(&format!("# nightly delta\n\n{}", patch)));
+    assert_eq!(Err(JournalError::InvalidPatch { line: 2 }), Journal::from_patch("node\nadd 0 x 1 1\n"));
+    assert_eq!(Err(JournalError::InvalidPatch { line: 1 }), Journal::from_patch("remove 0"));
+}
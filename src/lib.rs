@@ -9,10 +9,45 @@
 #![crate_name="network"]
 #![crate_type="lib"]
 
+extern crate regex;
+#[cfg(feature = "arrow")]
+extern crate arrow_array;
+#[cfg(feature = "arrow")]
+extern crate arrow_schema;
+#[cfg(feature = "parquet")]
+extern crate parquet;
+
+use std::ops::Range;
+
 pub mod compact_star;
+pub mod adjacency_list;
 pub mod algorithms;
+pub mod pretty;
+pub mod prelude;
+pub mod generators;
+pub mod parse;
+pub mod cancel;
+pub mod instrument;
+pub mod query_engine;
+pub mod query;
+pub mod journal;
+pub mod out_of_core;
+pub mod linalg;
+pub mod io;
+pub mod error;
+pub mod weight;
+#[cfg(feature = "arrow")]
+pub mod columnar;
 mod collections;
 mod heaps;
+mod hyperloglog;
+mod random;
+#[cfg(test)]
+mod properties;
+
+pub use collections::UnionFind;
+pub use error::Error;
+pub use weight::{ OrderedFloat, Weight };
 
 pub type DoubleVec = Vec<f64>;
 pub type Capacity  = f64;
@@ -20,6 +55,17 @@ pub type Cost      = f64;
 pub type NodeId    = u32;
 pub type NodeVec   = Vec<NodeId>;
 
+/// The direction in which arcs are followed when traversing a network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Follow arcs from tail to head.
+    Forward,
+    /// Follow arcs from head to tail.
+    Reverse,
+    /// Follow arcs in both directions, i.e. treat the network as undirected.
+    Both,
+}
+
 pub trait Network {
     /// Returns a vec of adjecent nodes, identified by their id
     fn adjacent(&self, i: NodeId) -> Vec<NodeId>;
@@ -33,4 +79,126 @@ pub trait Network {
     /// too unnatural. 
     fn invalid_id(&self) -> NodeId;
     fn infinity(&self) -> Cost;
+
+    /// Returns the arcs leaving node `i` as `(head, cost, capacity)`
+    /// triples, in the order of `adjacent(i)`, without allocating. Parallel
+    /// arcs come with their own cost and capacity. The default goes
+    /// through `adjacent`, `cost` and `capacity`; implementations should
+    /// iterate their arc storage directly.
+    fn out_arcs(&self, i: NodeId) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        self.adjacent(i).into_iter().map(move |j| (j, self.cost(i, j).unwrap(), self.capacity(i, j).unwrap()))
+    }
+
+    /// Returns the arcs entering node `i` as `(tail, cost, capacity)`
+    /// triples, ordered by arc number. The default scans all arcs of the
+    /// network; implementations that keep a reverse star should override it.
+    fn incoming(&self, i: NodeId) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        self.nodes()
+            .flat_map(move |k| self.out_arcs(k).filter(move |arc| arc.0 == i).map(move |(_, cost, capacity)| (k, cost, capacity)))
+    }
+
+    /// Returns the arcs at node `i` that a traversal in `direction` follows,
+    /// as `(neighbor, cost, capacity)` triples: the leaving arcs for
+    /// `Forward`, the entering ones for `Reverse`, and the leaving followed
+    /// by the entering ones for `Both`. This treats a directed network as
+    /// undirected without duplicating its arcs.
+    fn followed_arcs(&self, i: NodeId, direction: Direction) -> impl Iterator<Item = (NodeId, Cost, Capacity)> + '_ {
+        let forward = if direction != Direction::Reverse { Some(self.out_arcs(i)) } else { None };
+        let reverse = if direction != Direction::Forward { Some(self.incoming(i)) } else { None };
+        forward.into_iter().flatten().chain(reverse.into_iter().flatten())
+    }
+
+    /// Returns the number of arcs entering node `i`.
+    fn in_degree(&self, i: NodeId) -> usize {
+        self.incoming(i).count()
+    }
+
+    /// Returns the number of arcs leaving node `i`.
+    fn out_degree(&self, i: NodeId) -> usize {
+        self.adjacent(i).len()
+    }
+
+    /// Returns whether there is an arc from `from` to `to`.
+    fn has_arc(&self, from: NodeId, to: NodeId) -> bool {
+        self.cost(from, to).is_some()
+    }
+
+    /// Returns an iterator over all node ids.
+    fn nodes(&self) -> Range<NodeId> {
+        0..(self.num_nodes() as NodeId)
+    }
+
+    /// Returns an iterator over all arcs as `(from, to)` pairs, ordered by
+    /// `from` and in order of `adjacent(from)`. The position of an arc in
+    /// this sequence is its arc number.
+    fn arcs(&self) -> Arcs<'_, Self> {
+        Arcs {
+            network:  self,
+            from:     0,
+            adjacent: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Returns all arcs as `(from, to, cost, capacity)` in arc order, with
+    /// the own cost and capacity of every parallel arc.
+    fn arc_values(&self) -> impl Iterator<Item = (NodeId, NodeId, Cost, Capacity)> + '_ {
+        self.nodes().flat_map(move |i| self.out_arcs(i).map(move |(j, cost, capacity)| (i, j, cost, capacity)))
+    }
+
+    /// Returns the cost of the cheapest arc from `from` to `to`, the cost
+    /// that a path along these two nodes takes. Unlike `cost`, this looks at
+    /// all parallel arcs.
+    fn cheapest_cost(&self, from: NodeId, to: NodeId) -> Option<Cost> {
+        self.out_arcs(from).filter(|arc| arc.0 == to).map(|(_, cost, _)| cost).fold(None, |min, cost| match min {
+            Some(min) if min <= cost => Some(min),
+            _ => Some(cost),
+        })
+    }
+
+    /// Returns the number of the first arc leaving every node, so the `k`-th
+    /// arc of `out_arcs(i)` has number `first_arcs()[i] + k`. A last entry
+    /// holds the number of arcs, so the arcs of `i` are
+    /// `first_arcs()[i]..first_arcs()[i + 1]`. Per arc data such as weights
+    /// or attributes is indexed this way.
+    fn first_arcs(&self) -> Vec<usize> {
+        let mut first_arcs = Vec::with_capacity(self.num_nodes() + 1);
+        let mut first_arc = 0;
+        for i in self.nodes() {
+            first_arcs.push(first_arc);
+            first_arc += self.out_degree(i);
+        }
+        first_arcs.push(first_arc);
+        first_arcs
+    }
+
+    /// Returns the sum of all arc costs.
+    fn total_cost(&self) -> Cost {
+        self.nodes().flat_map(|i| self.out_arcs(i)).map(|(_, cost, _)| cost).sum()
+    }
+}
+
+/// Iterator over the arcs of a network, see `Network::arcs`.
+pub struct Arcs<'a, N: 'a + ?Sized> {
+    network:  &'a N,
+    from:     NodeId,
+    adjacent: NodeVec,
+    position: usize,
+}
+
+impl<'a, N: Network + ?Sized> Iterator for Arcs<'a, N> {
+    type Item = (NodeId, NodeId);
+
+    fn next(&mut self) -> Option<(NodeId, NodeId)> {
+        while self.position >= self.adjacent.len() {
+            if self.from as usize >= self.network.num_nodes() {
+                return None;
+            }
+            self.adjacent = self.network.adjacent(self.from);
+            self.position = 0;
+            self.from += 1;
+        }
+        self.position += 1;
+        Some((self.from - 1, self.adjacent[self.position - 1]))
+    }
 }
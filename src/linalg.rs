@@ -0,0 +1,316 @@
+//! Sparse linear algebra on the adjacency matrix of a network. The
+//! operators work directly on the compressed sparse row arrays of a
+//! `CompactStar`, and the iterative solvers only need matrix vector
+//! products, so Laplacian systems and linear formulations of ranking
+//! problems are solved without copying the network into another format.
+
+use super::{ DoubleVec, NodeId };
+use super::compact_star::CompactStar;
+
+/// A square matrix that can be multiplied with vectors.
+pub trait LinearOperator {
+    /// The number of rows and columns.
+    fn dim(&self) -> usize;
+
+    /// Computes `y = A x`.
+    fn apply(&self, x: &[f64], y: &mut [f64]);
+
+    /// The diagonal entries, needed by `jacobi`.
+    fn diagonal(&self) -> DoubleVec;
+}
+
+/// Which arc values become the matrix entries.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Entries {
+    Ones,
+    Costs,
+    Capacities,
+    /// `1 / cost`, for costs that are resistances or lengths.
+    InverseCosts,
+}
+
+/// The value of every arc, in arc order.
+pub fn entry_values(network: &CompactStar, entries: Entries) -> DoubleVec {
+    let (_, head, costs, capacities) = network.raw_parts();
+    match entries {
+        Entries::Ones       => vec![1.0; head.len()],
+        Entries::Costs      => costs.to_vec(),
+        Entries::Capacities => capacities.to_vec(),
+        Entries::InverseCosts => costs.iter().map(|c| 1.0 / c).collect(),
+    }
+}
+
+/// The adjacency matrix of a network, `A[i][j]` being the sum of the
+/// entries of all arcs from `i` to `j`.
+#[derive(Debug)]
+pub struct AdjacencyOperator<'a> {
+    point:  &'a [NodeId],
+    head:   &'a [NodeId],
+    values: DoubleVec,
+}
+
+impl<'a> AdjacencyOperator<'a> {
+    pub fn new(network: &'a CompactStar, entries: Entries) -> AdjacencyOperator<'a> {
+        let (point, head, _, _) = network.raw_parts();
+        AdjacencyOperator {
+            point:  point,
+            head:   head,
+            values: entry_values(network, entries),
+        }
+    }
+
+    /// Computes `y = A^T x`, following the arcs forward: the value of
+    /// every node is distributed to its successors.
+    pub fn apply_transpose(&self, x: &[f64], y: &mut [f64]) {
+        for value in y.iter_mut() {
+            *value = 0.0;
+        }
+        for i in 0..self.dim() {
+            for arc in self.point[i] as usize..self.point[i + 1] as usize {
+                y[self.head[arc] as usize] += self.values[arc] * x[i];
+            }
+        }
+    }
+}
+
+impl<'a> LinearOperator for AdjacencyOperator<'a> {
+    fn dim(&self) -> usize {
+        self.point.len() - 1
+    }
+
+    fn apply(&self, x: &[f64], y: &mut [f64]) {
+        for i in 0..self.dim() {
+            let mut sum = 0.0;
+            for arc in self.point[i] as usize..self.point[i + 1] as usize {
+                sum += self.values[arc] * x[self.head[arc] as usize];
+            }
+            y[i] = sum;
+        }
+    }
+
+    fn diagonal(&self) -> DoubleVec {
+        let mut diagonal = vec![0.0; self.dim()];
+        for i in 0..self.dim() {
+            for arc in self.point[i] as usize..self.point[i + 1] as usize {
+                if self.head[arc] as usize == i {
+                    diagonal[i] += self.values[arc];
+                }
+            }
+        }
+        diagonal
+    }
+}
+
+/// The Laplacian `L = D - W` of the undirected network with symmetric
+/// weights `W = (A + A^T) / 2`, where `A` is the adjacency matrix and `D`
+/// holds the weighted degrees. An undirected network stored with both arc
+/// directions keeps its weights. Self-loops are ignored. `L` is symmetric
+/// and positive semidefinite, with the constant vectors as kernel on
+/// connected networks.
+#[derive(Debug, Clone)]
+pub struct Laplacian {
+    /// symmetric adjacency lists `(neighbor, weight)`
+    neighbors: Vec<Vec<(usize, f64)>>,
+    degrees:   DoubleVec,
+}
+
+impl Laplacian {
+    pub fn new(network: &CompactStar, entries: Entries) -> Laplacian {
+        Laplacian::with_values(network, &entry_values(network, entries))
+    }
+
+    /// Builds the Laplacian with the given entry for every arc.
+    pub fn with_values(network: &CompactStar, values: &[f64]) -> Laplacian {
+        let (point, head, _, _) = network.raw_parts();
+        assert_eq!(head.len(), values.len());
+        let n = point.len() - 1;
+        let mut neighbors = vec![Vec::new(); n];
+        let mut degrees = vec![0.0; n];
+        for i in 0..n {
+            for arc in point[i] as usize..point[i + 1] as usize {
+                let j = head[arc] as usize;
+                if i != j {
+                    let weight = values[arc] / 2.0;
+                    neighbors[i].push((j, weight));
+                    neighbors[j].push((i, weight));
+                    degrees[i] += weight;
+                    degrees[j] += weight;
+                }
+            }
+        }
+        Laplacian {
+            neighbors: neighbors,
+            degrees:   degrees,
+        }
+    }
+
+    /// The symmetric weights of all edges incident to `node`, one entry per
+    /// arc in either direction.
+    pub fn neighbors(&self, node: NodeId) -> &[(usize, f64)] {
+        &self.neighbors[node as usize]
+    }
+}
+
+impl LinearOperator for Laplacian {
+    fn dim(&self) -> usize {
+        self.degrees.len()
+    }
+
+    fn apply(&self, x: &[f64], y: &mut [f64]) {
+        for i in 0..self.dim() {
+            let mut sum = self.degrees[i] * x[i];
+            for &(j, weight) in &self.neighbors[i] {
+                sum -= weight * x[j];
+            }
+            y[i] = sum;
+        }
+    }
+
+    fn diagonal(&self) -> DoubleVec {
+        self.degrees.clone()
+    }
+}
+
+/// The result of an iterative solver.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub x:          DoubleVec,
+    pub iterations: usize,
+    /// The euclidean norm of `b - A x`.
+    pub residual:   f64,
+    /// Whether the residual dropped to `eps` within the iteration limit.
+    pub converged:  bool,
+}
+
+fn norm(x: &[f64]) -> f64 {
+    dot(x, x).sqrt()
+}
+
+fn dot(x: &[f64], y: &[f64]) -> f64 {
+    x.iter().zip(y).map(|(a, b)| a * b).sum()
+}
+
+/// Solves `A x = b` for a symmetric positive semidefinite `operator` by
+/// conjugate gradients, starting at zero, until the residual norm drops to
+/// `eps` or `max_iterations` is reached. For a singular `A` like a Laplacian,
+/// `b` has to be orthogonal to the kernel (sum to zero on every connected
+/// component), and the solution is the one orthogonal to the kernel.
+pub fn conjugate_gradient<O: LinearOperator>(operator: &O, b: &[f64], eps: f64, max_iterations: usize) -> Solution {
+    let n = operator.dim();
+    assert_eq!(n, b.len());
+    let mut x = vec![0.0; n];
+    let mut r = b.to_vec();
+    let mut p = r.clone();
+    let mut ap = vec![0.0; n];
+    let mut rr = dot(&r, &r);
+    let mut iterations = 0;
+    while rr.sqrt() > eps && iterations < max_iterations {
+        operator.apply(&p, &mut ap);
+        let pap = dot(&p, &ap);
+        if pap <= 0.0 {
+            break;
+        }
+        let alpha = rr / pap;
+        for i in 0..n {
+            x[i] += alpha * p[i];
+            r[i] -= alpha * ap[i];
+        }
+        let next_rr = dot(&r, &r);
+        for i in 0..n {
+            p[i] = r[i] + next_rr / rr * p[i];
+        }
+        rr = next_rr;
+        iterations += 1;
+    }
+    let residual = rr.sqrt();
+    Solution {
+        x:          x,
+        iterations: iterations,
+        residual:   residual,
+        converged:  residual <= eps,
+    }
+}
+
+/// Solves `A x = b` by Jacobi iteration, starting at zero, until the
+/// residual norm drops to `eps` or `max_iterations` is reached. Converges
+/// for strictly diagonally dominant matrices, like the linear system of
+/// PageRank.
+/// # Panics
+/// If a diagonal entry is zero.
+pub fn jacobi<O: LinearOperator>(operator: &O, b: &[f64], eps: f64, max_iterations: usize) -> Solution {
+    let n = operator.dim();
+    assert_eq!(n, b.len());
+    let diagonal = operator.diagonal();
+    assert!(diagonal.iter().all(|d| *d != 0.0), "Jacobi iteration needs a non-zero diagonal");
+    let mut x = vec![0.0; n];
+    let mut ax = vec![0.0; n];
+    let mut r = b.to_vec();
+    let mut iterations = 0;
+    while norm(&r) > eps && iterations < max_iterations {
+        for i in 0..n {
+            x[i] += r[i] / diagonal[i];
+        }
+        operator.apply(&x, &mut ax);
+        for i in 0..n {
+            r[i] = b[i] - ax[i];
+        }
+        iterations += 1;
+    }
+    let residual = norm(&r);
+    Solution {
+        x:          x,
+        iterations: iterations,
+        residual:   residual,
+        converged:  residual <= eps,
+    }
+}
+
+#[test]
+fn test_adjacency_operator() {
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,2.0,0.0), (0,2,3.0,0.0), (2,2,1.0,0.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let operator = AdjacencyOperator::new(&network, Entries::Costs);
+    let mut y = vec![0.0; 3];
+    operator.apply(&[1.0, 1.0, 2.0], &mut y);
+    assert_eq!(vec![8.0, 0.0, 2.0], y);
+    operator.apply_transpose(&[1.0, 1.0, 2.0], &mut y);
+    assert_eq!(vec![0.0, 2.0, 5.0], y);
+    assert_eq!(vec![0.0, 0.0, 1.0], operator.diagonal());
+}
+
+#[test]
+fn test_conjugate_gradient() {
+    use super::compact_star::compact_star_from_edge_vec;
+    // undirected path 0 - 1 - 2 with unit resistances
+    let mut edges = vec![(0,1,1.0,0.0), (1,0,1.0,0.0), (1,2,1.0,0.0), (2,1,1.0,0.0)];
+    let network = compact_star_from_edge_vec(3, &mut edges);
+    let laplacian = Laplacian::new(&network, Entries::Ones);
+    assert_eq!(vec![1.0, 2.0, 1.0], laplacian.diagonal());
+    let solution = conjugate_gradient(&laplacian, &[1.0, 0.0, -1.0], 1e-12, 100);
+    assert!(solution.converged);
+    assert!(solution.iterations <= 3);
+    // the potential difference is the effective resistance
+    assert!((solution.x[0] - solution.x[2] - 2.0).abs() < 1e-9);
+    assert!(solution.x.iter().sum::<f64>().abs() < 1e-9);
+}
+
+#[test]
+fn test_jacobi() {
+    /// `4` on the diagonal and `-1` elsewhere, strictly diagonally dominant
+    struct Shifted;
+    impl LinearOperator for Shifted {
+        fn dim(&self) -> usize { 3 }
+        fn apply(&self, x: &[f64], y: &mut [f64]) {
+            let sum: f64 = x.iter().sum();
+            for i in 0..3 {
+                y[i] = 4.0 * x[i] - sum + x[i];
+            }
+        }
+        fn diagonal(&self) -> DoubleVec { vec![4.0; 3] }
+    }
+    let solution = jacobi(&Shifted, &[2.0, 2.0, 2.0], 1e-12, 1000);
+    assert!(solution.converged);
+    assert!(solution.x.iter().all(|x| (x - 1.0).abs() < 1e-9));
+    assert!(!jacobi(&Shifted, &[2.0, 2.0, 2.0], 1e-12, 2).converged);
+}
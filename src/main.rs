@@ -12,26 +12,51 @@ extern crate regex;
 extern crate rustc_serialize;
 
 use regex::Regex;
-use std::collections::HashMap;
+use std::collections::{ BTreeMap, HashMap };
 use std::fs::File;
 use std::io::{ BufReader, BufRead };
 use std::path::Path;
 
-use network::NodeId;
+use network::{ Network, NodeId, UnionFind };
 use network::algorithms::{ breadth_first_search, depth_first_search, heap_dijkstra };
-use network::compact_star::{ compact_star_from_edge_vec };
+use network::compact_star::{ CompactStar, compact_star_from_edge_vec, try_compact_star_with_attributes };
+use network::io::dimacs::{ self, DimacsError };
 
 mod usage;
-use usage::{ get_args, DEFAULT_PATTERN, DEFAULT_SKIP };
+use usage::{ get_args, Args, DEFAULT_PATTERN, DEFAULT_SKIP };
 
 mod parse_text;
 use parse_text::{ Edge, edges_from_file };
 
 mod alg_runner;
-use alg_runner::run_algorithm;
+use alg_runner::{ Algorithm, run_algorithm, run_generate };
 
 fn main() {
-    let ref args = get_args();
+    let mut args = get_args();
+    if let Algorithm::generate = args.arg_algorithm {
+        return run_generate(&args);
+    }
+    let (compact_star, node_to_id) = match args.flag_format.as_ref().map(|f| f.as_str()) {
+        None | Some("regex") => read_pattern_input(&args),
+        Some("dimacs") => read_dimacs_input(&mut args),
+        Some(other) => {
+            eprintln!("Unknown input format {}, expected regex or dimacs.", other);
+            std::process::exit(1);
+        },
+    };
+
+    let compact_star = match args.flag_cost_attr.as_ref() {
+        Some(name) => compact_star.with_costs_from_attribute(name).unwrap_or_else(|e| {
+            eprintln!("Could not use {} as cost: {}", name, e);
+            std::process::exit(1);
+        }),
+        None => compact_star,
+    };
+    run_algorithm(&compact_star, &args, &node_to_id);
+}
+
+/// Reads the input file line by line with the regular expression pattern.
+fn read_pattern_input(args: &Args) -> (CompactStar, HashMap<String, NodeId>) {
     let pattern = &args.flag_pattern
         .as_ref()
         .unwrap_or(&DEFAULT_PATTERN.to_string())
@@ -42,18 +67,76 @@ fn main() {
 
     let mut node_to_id: HashMap<String, NodeId> = HashMap::new();
     let mut edges: Vec<Edge> = Vec::new();
+    let mut attributes = BTreeMap::new();
+    let mut components = UnionFind::new(0);
 
     edges_from_file(Path::new(file_name), 
                     pattern, 
                     is_undirected, 
                     skip, 
                     &mut node_to_id, 
-                    &mut edges);
+                    &mut edges,
+                    &mut components,
+                    &mut attributes)
+        .unwrap_or_else(|e| {
+            eprintln!("Could not read {}: {}", file_name, e);
+            std::process::exit(1);
+        });
+    check_components(args, &mut components);
     let num_nodes = node_to_id.len();
-    let compact_star = compact_star_from_edge_vec(num_nodes, &mut edges);
+    let compact_star = try_compact_star_with_attributes(num_nodes, &mut edges, attributes).unwrap_or_else(|e| {
+        eprintln!("Could not build a network from {}: {}", file_name, e);
+        std::process::exit(1);
+    });
+    (compact_star, node_to_id)
+}
+
+/// Reads a DIMACS shortest path or maximum flow file. Nodes are named by
+/// their DIMACS number, and the source and sink of a maximum flow problem
+/// become the default start and target nodes.
+fn read_dimacs_input(args: &mut Args) -> (CompactStar, HashMap<String, NodeId>) {
+    let file_name = args.arg_filename.clone();
+    let dimacs = File::open(&file_name)
+        .map_err(DimacsError::from)
+        .and_then(|file| dimacs::read(BufReader::new(file)))
+        .unwrap_or_else(|e| {
+            eprintln!("Could not read {}: {}", file_name, e);
+            std::process::exit(1);
+        });
+    let node_to_id: HashMap<String, NodeId> = dimacs.network.nodes()
+        .map(|i| ((i + 1).to_string(), i))
+        .collect();
+    if args.flag_start_node.is_none() {
+        args.flag_start_node = dimacs.source.map(|s| (s + 1).to_string());
+    }
+    if args.flag_target_node.is_none() {
+        args.flag_target_node = dimacs.sink.map(|t| (t + 1).to_string());
+    }
+    let mut components = UnionFind::new(dimacs.network.num_nodes());
+    for (from, to) in dimacs.network.arcs() {
+        components.union(from, to);
+    }
+    check_components(args, &mut components);
+    (dimacs.network, node_to_id)
+}
 
-    run_algorithm(&compact_star, args, &node_to_id);
-    let max_node_id = node_to_id.values().max().unwrap();
+/// Reports the number of (weakly) connected components found while reading
+/// the input and the size of the largest one if the input is not connected,
+/// and aborts in that case if --require-connected is given.
+fn check_components(args: &Args, components: &mut UnionFind) {
+    if components.count() > 1 {
+        let mut sizes = vec![0; components.count()];
+        for label in components.labels() {
+            sizes[label as usize] += 1;
+        }
+        let largest = sizes.iter().cloned().max().unwrap_or(0);
+        println!("Input has {} connected components, the largest has {} of {} nodes.",
+                 components.count(), largest, components.len());
+        if args.flag_require_connected {
+            eprintln!("Aborting: the input network must be connected.");
+            std::process::exit(1);
+        }
+    }
 }
 
 #[test]
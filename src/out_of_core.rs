@@ -0,0 +1,270 @@
+//! Construction of networks whose edge lists don't fit into memory.
+//!
+//! Edges are stored in binary edge files with one fixed size record per
+//! edge: tail and head as little endian `u32`, followed by cost and
+//! capacity as little endian `f64`. `compact_star_from_edge_file` sorts such
+//! a file by tail with an external merge sort and streams the sorted edges
+//! directly into the arrays of a `CompactStar`, so the edges are never held
+//! in memory as tuples and as arrays at the same time.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::{ self, File };
+use std::io::{ self, BufReader, BufWriter, Read, Write };
+use std::path::{ Path, PathBuf };
+use std::process;
+use std::sync::atomic::{ AtomicUsize, Ordering };
+
+use super::{ Error, NodeId };
+use super::compact_star::{ CompactStar, compact_star_from_sorted_edges };
+use super::parse::Edge;
+
+/// Size of one edge record in bytes.
+pub const EDGE_RECORD_SIZE: usize = 24;
+
+/// Numbers the calls of `compact_star_from_edge_file`, so that concurrent
+/// calls of one process don't share temporary file names.
+static NEXT_SORT: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes edges as binary records.
+pub struct EdgeWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> EdgeWriter<W> {
+    pub fn new(writer: W) -> EdgeWriter<W> {
+        EdgeWriter {
+            writer: writer,
+        }
+    }
+
+    pub fn write_edge(&mut self, edge: &Edge) -> io::Result<()> {
+        let &(from, to, cost, cap) = edge;
+        let mut record = [0; EDGE_RECORD_SIZE];
+        record[0..4].copy_from_slice(&from.to_le_bytes());
+        record[4..8].copy_from_slice(&to.to_le_bytes());
+        record[8..16].copy_from_slice(&cost.to_le_bytes());
+        record[16..24].copy_from_slice(&cap.to_le_bytes());
+        self.writer.write_all(&record)
+    }
+
+    /// Flushes and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads edges from binary records. Yields an error for a truncated record
+/// at the end of the input.
+pub struct EdgeReader<R: Read> {
+    reader: R,
+}
+
+impl<R: Read> EdgeReader<R> {
+    pub fn new(reader: R) -> EdgeReader<R> {
+        EdgeReader {
+            reader: reader,
+        }
+    }
+
+    pub fn read_edge(&mut self) -> io::Result<Option<Edge>> {
+        let mut record = [0; EDGE_RECORD_SIZE];
+        let mut filled = 0;
+        while filled < EDGE_RECORD_SIZE {
+            match self.reader.read(&mut record[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated edge record")),
+                Ok(k) => filled += k,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {},
+                Err(e) => return Err(e),
+            }
+        }
+        let mut bytes4 = [0; 4];
+        let mut bytes8 = [0; 8];
+        bytes4.copy_from_slice(&record[0..4]);
+        let from = NodeId::from_le_bytes(bytes4);
+        bytes4.copy_from_slice(&record[4..8]);
+        let to = NodeId::from_le_bytes(bytes4);
+        bytes8.copy_from_slice(&record[8..16]);
+        let cost = f64::from_le_bytes(bytes8);
+        bytes8.copy_from_slice(&record[16..24]);
+        let cap = f64::from_le_bytes(bytes8);
+        Ok(Some((from, to, cost, cap)))
+    }
+}
+
+impl<R: Read> Iterator for EdgeReader<R> {
+    type Item = io::Result<Edge>;
+
+    fn next(&mut self) -> Option<io::Result<Edge>> {
+        match self.read_edge() {
+            Ok(Some(edge)) => Some(Ok(edge)),
+            Ok(None) => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+/// Writes `edges` to a new binary edge file at `path`.
+pub fn write_edge_file<P: AsRef<Path>>(path: P, edges: &[Edge]) -> io::Result<()> {
+    let mut writer = EdgeWriter::new(BufWriter::new(File::create(path)?));
+    for edge in edges {
+        writer.write_edge(edge)?;
+    }
+    writer.finish().map(|_| ())
+}
+
+/// Builds a network with `nodes` nodes from the binary edge file at `path`.
+///
+/// The file is read in runs of at most `run_length` edges. Every run is
+/// sorted by tail in memory and written to a temporary file in `tmp_dir`.
+/// The runs are then merged and streamed into the network, so at most
+/// `run_length` edges are held in memory besides the network itself. Edges
+/// with the same tail keep their order in the file, which gives the same
+/// network as `compact_star_from_edge_vec`. The temporary files are removed
+/// afterwards, also on errors.
+/// # Errors
+/// `Error::Io` if reading or writing a file fails or the edge file ends
+/// with a truncated record, `Error::NoNodes` if `nodes` is `0` and
+/// `Error::NodeOutOfRange` for the first edge with a node `>= nodes`.
+/// # Panics
+/// If `run_length == 0`.
+pub fn compact_star_from_edge_file<P, Q>(path: P, nodes: usize, run_length: usize, tmp_dir: Q) -> Result<CompactStar, Error>
+where P: AsRef<Path>, Q: AsRef<Path> {
+    assert!(run_length > 0);
+    if nodes == 0 {
+        return Err(Error::NoNodes);
+    }
+    let mut runs = Vec::new();
+    let result = write_sorted_runs(path.as_ref(), nodes, run_length, tmp_dir.as_ref(), &mut runs)
+        .and_then(|_| Ok(merge_runs(&runs, nodes)?));
+    for run in &runs {
+        let _ = fs::remove_file(run);
+    }
+    result
+}
+
+fn write_sorted_runs(path: &Path, nodes: usize, run_length: usize, tmp_dir: &Path, runs: &mut Vec<PathBuf>) -> Result<(), Error> {
+    let mut reader = EdgeReader::new(BufReader::new(File::open(path)?));
+    let mut run = Vec::with_capacity(run_length);
+    let sort = NEXT_SORT.fetch_add(1, Ordering::Relaxed);
+    loop {
+        let edge = reader.read_edge()?;
+        if let Some(edge) = edge {
+            if let Some(node) = [edge.0, edge.1].iter().find(|node| **node as usize >= nodes) {
+                return Err(Error::NodeOutOfRange { node: *node, num_nodes: nodes });
+            }
+            run.push(edge);
+        }
+        if run.len() == run_length || (edge.is_none() && !run.is_empty()) {
+            run.sort_by_key(|&(from, _, _, _)| from);
+            let run_path = tmp_dir.join(format!("network-run-{}-{}-{}.bin", process::id(), sort, runs.len()));
+            runs.push(run_path.clone());
+            write_edge_file(&run_path, &run)?;
+            run.clear();
+        }
+        if edge.is_none() {
+            return Ok(());
+        }
+    }
+}
+
+fn merge_runs(runs: &[PathBuf], nodes: usize) -> io::Result<CompactStar> {
+    let mut readers = Vec::with_capacity(runs.len());
+    for run in runs {
+        readers.push(EdgeReader::new(BufReader::new(File::open(run)?)));
+    }
+    let mut merge = Merge {
+        readers: readers,
+        heads:   BinaryHeap::new(),
+        pending: Vec::new(),
+        error:   None,
+    };
+    for k in 0..merge.readers.len() {
+        merge.advance(k);
+    }
+    let compact_star = compact_star_from_sorted_edges(nodes, &mut merge);
+    match merge.error {
+        Some(e) => Err(e),
+        None => Ok(compact_star),
+    }
+}
+
+/// k-way merge of sorted runs. The heap orders the current first edges of
+/// the runs by tail and run number, which keeps the merge stable.
+struct Merge<R: Read> {
+    readers: Vec<EdgeReader<R>>,
+    heads:   BinaryHeap<Reverse<(NodeId, usize)>>,
+    pending: Vec<Option<Edge>>,
+    error:   Option<io::Error>,
+}
+
+impl<R: Read> Merge<R> {
+    fn advance(&mut self, k: usize) {
+        if self.pending.len() <= k {
+            self.pending.resize(k + 1, None);
+        }
+        match self.readers[k].read_edge() {
+            Ok(Some(edge)) => {
+                self.heads.push(Reverse((edge.0, k)));
+                self.pending[k] = Some(edge);
+            },
+            Ok(None) => self.pending[k] = None,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+impl<'a, R: Read> Iterator for &'a mut Merge<R> {
+    type Item = Edge;
+
+    fn next(&mut self) -> Option<Edge> {
+        if self.error.is_some() {
+            return None;
+        }
+        let Reverse((_, k)) = self.heads.pop()?;
+        let edge = self.pending[k].take();
+        self.advance(k);
+        edge
+    }
+}
+
+#[test]
+fn test_compact_star_from_edge_file() {
+    use std::env;
+    use super::compact_star::compact_star_from_edge_vec;
+    let edges = vec![(3,1,1.0,10.0),
+                     (0,2,2.0,20.0),
+                     (3,0,3.0,30.0),
+                     (1,2,4.0,40.0),
+                     (0,1,5.0,50.0),
+                     (3,2,6.0,60.0),
+                     (0,3,7.0,70.0)];
+    let dir = env::temp_dir();
+    let path = dir.join(format!("network-test-edges-{}.bin", process::id()));
+    write_edge_file(&path, &edges).unwrap();
+
+    let expected = compact_star_from_edge_vec(5, &mut edges.clone());
+    for run_length in 1..9 {
+        assert_eq!(expected, compact_star_from_edge_file(&path, 5, run_length, &dir).unwrap());
+    }
+    let leftovers = fs::read_dir(&dir).unwrap()
+        .filter(|entry| entry.as_ref().unwrap().file_name().to_string_lossy().starts_with(&format!("network-run-{}-", process::id())))
+        .count();
+    assert_eq!(0, leftovers);
+
+    // nodes out of range are an error
+    match compact_star_from_edge_file(&path, 3, 2, &dir) {
+        Err(Error::NodeOutOfRange { node: 3, num_nodes: 3 }) => {},
+        other => panic!("unexpected {:?}", other),
+    }
+    assert!(match compact_star_from_edge_file(&path, 0, 2, &dir) { Err(Error::NoNodes) => true, _ => false });
+
+    // a truncated record is an error
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.pop();
+    fs::write(&path, &bytes).unwrap();
+    assert!(compact_star_from_edge_file(&path, 5, 3, &dir).is_err());
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,201 @@
+//! Parsing of edge lists from text. All functions work on in-memory input
+//! and report malformed input as `ParseError` instead of panicking, so they
+//! are safe to call on untrusted data.
+
+use std::collections::{ BTreeMap, HashMap };
+use std::error::Error;
+use std::fmt;
+use std::str;
+
+use regex::Regex;
+
+use super::{ Capacity, Cost, DoubleVec, NodeId };
+
+/// Describes one edge (arc) in a network, regardless of actual network
+/// implementation.
+pub type Edge = (NodeId, NodeId, Cost, Capacity);
+
+/// Matches lines like `a.b   000.0345 ...` with a cost but no capacity.
+pub const DEFAULT_PATTERN: &'static str = "^(?P<from>[[:alnum:]]+).(?P<to>[[:alnum:]]+)\\s+(?P<cost>\\d+.\\d+).*$";
+
+/// Reasons for rejecting an input. Line numbers start at 1 and count the
+/// skipped header lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// The pattern is not a valid regular expression.
+    InvalidPattern(String),
+    /// The input is not valid UTF-8.
+    InvalidUtf8,
+    /// The line does not match the pattern.
+    NoMatch { line: usize },
+    /// The pattern did not capture the named group `from` or `to`.
+    MissingNode { line: usize, group: &'static str },
+    /// The captured cost or capacity is not a number.
+    InvalidNumber { line: usize, group: &'static str, value: String },
+    /// The value captured for the attribute `name` is not a number.
+    InvalidAttribute { line: usize, name: String, value: String },
+    /// The input has more distinct nodes than a `NodeId` can address.
+    TooManyNodes,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::InvalidPattern(ref reason) => write!(f, "invalid pattern: {}", reason),
+            ParseError::InvalidUtf8 => write!(f, "input is not valid UTF-8"),
+            ParseError::NoMatch { line } => write!(f, "line {}: does not match the pattern", line),
+            ParseError::MissingNode { line, group } => write!(f, "line {}: no `{}` node captured", line, group),
+            ParseError::InvalidNumber { line, group, ref value } =>
+                write!(f, "line {}: `{}` is not a valid {}", line, value, group),
+            ParseError::InvalidAttribute { line, ref name, ref value } =>
+                write!(f, "line {}: `{}` is not a valid {}", line, value, name),
+            ParseError::TooManyNodes => write!(f, "too many nodes"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Edges parsed from an input together with the mapping from node names to
+/// the assigned ids. Ids are assigned in order of first appearance.
+///
+/// Named groups of the pattern other than `from`, `to`, `cost` and `cap`
+/// are read as numeric arc attributes, with one value per edge in the
+/// order of `edges`; see `compact_star::try_compact_star_with_attributes`.
+/// A group that doesn't participate in a match gives `0.0`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ParsedEdges {
+    pub edges:      Vec<Edge>,
+    pub node_to_id: HashMap<String, NodeId>,
+    pub attributes: BTreeMap<String, DoubleVec>,
+}
+
+impl ParsedEdges {
+    pub fn num_nodes(&self) -> usize {
+        self.node_to_id.len()
+    }
+
+    fn node_id(&mut self, name: &str) -> Result<NodeId, ParseError> {
+        if let Some(id) = self.node_to_id.get(name) {
+            return Ok(*id);
+        }
+        if self.node_to_id.len() >= NodeId::max_value() as usize {
+            return Err(ParseError::TooManyNodes);
+        }
+        let id = self.node_to_id.len() as NodeId;
+        self.node_to_id.insert(name.to_string(), id);
+        Ok(id)
+    }
+}
+
+pub fn compile_pattern(pattern: &str) -> Result<Regex, ParseError> {
+    Regex::new(pattern).map_err(|e| ParseError::InvalidPattern(e.to_string()))
+}
+
+/// Parses one line into its node names, cost and capacity. Missing cost
+/// or capacity groups default to `0.0`.
+pub fn parse_line<'t>(line: &'t str, line_number: usize, regex: &Regex) -> Result<(&'t str, &'t str, Cost, Capacity), ParseError> {
+    let captures = match regex.captures(line) {
+        Some(captures) => captures,
+        None => return Err(ParseError::NoMatch { line: line_number }),
+    };
+    let node = |group| captures.name(group)
+        .ok_or(ParseError::MissingNode { line: line_number, group: group });
+    let number = |group| match captures.name(group) {
+        Some(value) => value.trim().parse::<f64>()
+            .map_err(|_| ParseError::InvalidNumber { line: line_number, group: group, value: value.to_string() }),
+        None => Ok(0.0),
+    };
+    let from = node("from")?;
+    let to = node("to")?;
+    Ok((from, to, number("cost")?, number("cap")?))
+}
+
+/// Parses an edge list. The first `skip` lines are headers, blank lines are
+/// ignored and every other line has to match `pattern`. For undirected
+/// inputs, every line yields an arc in both directions.
+pub fn parse_edges(input: &str, pattern: &str, is_undirected: bool, skip: usize) -> Result<ParsedEdges, ParseError> {
+    let regex = compile_pattern(pattern)?;
+    let mut parsed = ParsedEdges::default();
+    let attribute_names: Vec<&str> = regex.capture_names()
+        .filter_map(|name| name)
+        .filter(|name| !["from", "to", "cost", "cap"].contains(name))
+        .collect();
+    for name in &attribute_names {
+        parsed.attributes.insert(name.to_string(), Vec::new());
+    }
+    for (index, line) in input.lines().enumerate().skip(skip) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (from_s, to_s, cost, cap) = parse_line(line, index + 1, &regex)?;
+        let from = parsed.node_id(from_s)?;
+        let to = parsed.node_id(to_s)?;
+        parsed.edges.push((from, to, cost, cap));
+        if is_undirected {
+            parsed.edges.push((to, from, cost, cap));
+        }
+        if attribute_names.is_empty() {
+            continue;
+        }
+        let captures = regex.captures(line).unwrap();
+        for name in &attribute_names {
+            let value = match captures.name(name) {
+                Some(value) => value.trim().parse::<f64>()
+                    .map_err(|_| ParseError::InvalidAttribute { line: index + 1, name: name.to_string(), value: value.to_string() })?,
+                None => 0.0,
+            };
+            let values = parsed.attributes.get_mut(*name).unwrap();
+            values.push(value);
+            if is_undirected {
+                values.push(value);
+            }
+        }
+    }
+    Ok(parsed)
+}
+
+/// Like `parse_edges`, but for raw bytes that may not be valid UTF-8.
+pub fn parse_edges_from_bytes(input: &[u8], pattern: &str, is_undirected: bool, skip: usize) -> Result<ParsedEdges, ParseError> {
+    let text = str::from_utf8(input).map_err(|_| ParseError::InvalidUtf8)?;
+    parse_edges(text, pattern, is_undirected, skip)
+}
+
+#[test]
+fn test_parse_edges() {
+    let input = "from to cost\nnA.nB   001.5000 x\n\nnB.nC   002.2500\nnC.nA   000.2500 {DC}\n";
+    let parsed = parse_edges(input, DEFAULT_PATTERN, false, 1).unwrap();
+    assert_eq!(3, parsed.num_nodes());
+    assert_eq!(vec![(0,1,1.5,0.0), (1,2,2.25,0.0), (2,0,0.25,0.0)], parsed.edges);
+    assert_eq!(Some(&2), parsed.node_to_id.get("nC"));
+
+    let undirected = parse_edges("a.b 1.0\n", DEFAULT_PATTERN, true, 0).unwrap();
+    assert_eq!(vec![(0,1,1.0,0.0), (1,0,1.0,0.0)], undirected.edges);
+
+    let pattern = r"^(?P<from>\w+) (?P<to>\w+) (?P<cost>\S+) (?P<cap>\S+)$";
+    assert_eq!(vec![(0,1,2.0,3.0)], parse_edges("a b 2 3", pattern, false, 0).unwrap().edges);
+}
+
+#[test]
+fn test_parse_attributes() {
+    let pattern = r"^(?P<from>\w+) (?P<to>\w+) (?P<cost>\S+)(?: (?P<time>\S+))?$";
+    let parsed = parse_edges("a b 2 30\nb c 1\n", pattern, true, 0).unwrap();
+    assert_eq!(4, parsed.edges.len());
+    assert_eq!(Some(&vec![30.0, 30.0, 0.0, 0.0]), parsed.attributes.get("time"));
+    assert!(parse_edges("a.b 1.0", DEFAULT_PATTERN, false, 0).unwrap().attributes.is_empty());
+    assert_eq!(Err(ParseError::InvalidAttribute { line: 1, name: "time".to_string(), value: "x".to_string() }),
+               parse_edges("a b 2 x", pattern, false, 0));
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(Err(ParseError::NoMatch { line: 2 }), parse_edges("a.b 1.0\nfoo\n", DEFAULT_PATTERN, false, 0));
+    let pattern = r"^(?P<from>\w+) (?P<to>\w+) (?P<cost>\S+)$";
+    assert_eq!(Err(ParseError::InvalidNumber { line: 1, group: "cost", value: "x".to_string() }),
+               parse_edges("a b x", pattern, false, 0));
+    assert_eq!(Err(ParseError::MissingNode { line: 1, group: "to" }),
+               parse_edges("a", r"^(?P<from>\w+)$", false, 0));
+    assert!(match compile_pattern("(") { Err(ParseError::InvalidPattern(_)) => true, _ => false });
+    assert_eq!(Err(ParseError::InvalidUtf8), parse_edges_from_bytes(&[b'a', 0xff], DEFAULT_PATTERN, false, 0));
+    assert_eq!("line 2: does not match the pattern", ParseError::NoMatch { line: 2 }.to_string());
+}
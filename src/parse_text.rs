@@ -1,56 +1,13 @@
-use std::collections::HashMap;
-use regex::Regex;
+use std::collections::{ BTreeMap, HashMap };
 use std::fs::File;
-use std::io::{BufReader, BufRead};
+use std::io::{ self, BufRead, BufReader, Read };
 use std::path::Path;
 
-use network::{Capacity, Cost, NodeId};
-
-/// Describes one edge (arc) in a network, regardless of actual network
-/// implementation.
-pub type Edge = (NodeId, NodeId, Cost, Capacity);
-
-fn parse_pattern(p: &str) -> Regex {
-    Regex::new(p).ok().expect("Couldn't compile pattern.")
-}
-
-fn parse_line(line: &str, regex: &Regex, node_to_id: &mut HashMap<String, NodeId>, next_node: &mut NodeId) -> Edge {
-
-    let captures = regex.captures(line);
-    let from_s = captures.as_ref()
-        .and_then(|cgroup| cgroup.name("from"))
-        .unwrap_or("");
-    let to_s = captures.as_ref()
-        .and_then(|cgroup| cgroup.name("to"))
-        .unwrap_or("");
-    let cost: Cost = captures.as_ref()
-        .and_then(|cgroup| cgroup.name("cost"))
-        .and_then(|cstring| cstring.parse().ok())
-        .unwrap_or(0.0);
-    let cap: Capacity = captures.and_then(|cgroup| cgroup.name("cap"))
-        .and_then(|cstring| cstring.parse().ok())
-        .unwrap_or(0.0);
-
-    let from = if node_to_id.contains_key(from_s) {
-        node_to_id[from_s]
-    } else {
-        node_to_id.insert(from_s.to_string(), inc_node_counter(next_node));
-        node_to_id[from_s]
-    };
-    let to = if node_to_id.contains_key(to_s) {
-        node_to_id[to_s]
-    } else {
-        node_to_id.insert(to_s.to_string(), inc_node_counter(next_node));
-        node_to_id[to_s]
-    };
-
-    (from, to, cost, cap)
-}
-
-fn inc_node_counter(next_node: &mut NodeId) -> NodeId {
-    *next_node += 1;
-    *next_node - 1
-}
+use network::{DoubleVec, Error, NodeId, UnionFind};
+pub use network::parse::Edge;
+use network::parse::parse_edges_from_bytes;
+#[cfg(test)]
+use network::parse::compile_pattern;
 
 /// Read a list of edges from a file.
 ///
@@ -58,32 +15,108 @@ fn inc_node_counter(next_node: &mut NodeId) -> NodeId {
 /// lines is determined by the `skip` parameter.
 ///
 /// The result is stored in a mutable vector with correct `Edge` type.
-pub fn edges_from_file<P>(filename: P, pattern: &str, is_undirected: &bool, skip: usize, node_to_id: &mut HashMap<String,NodeId>, edges: &mut Vec<Edge>) 
+/// Every parsed edge is also merged into `components`, so the connected
+/// components of the input are known before any network is built.
+/// Further named groups of the pattern are stored in `attributes`, one
+/// value per edge.
+///
+/// Fails with `Error::Io` if the file can't be read and with `Error::Parse`
+/// if the pattern is invalid or a line doesn't match it.
+pub fn edges_from_file<P>(filename: P, pattern: &str, is_undirected: &bool, skip: usize, node_to_id: &mut HashMap<String,NodeId>, edges: &mut Vec<Edge>, components: &mut UnionFind, attributes: &mut BTreeMap<String, DoubleVec>) -> Result<(), Error>
+where P: AsRef<Path> {
+    let mut input = Vec::new();
+    File::open(filename)?.read_to_end(&mut input)?;
+
+    let parsed = parse_edges_from_bytes(&input, pattern, *is_undirected, skip)?;
+    for &(from, to, _, _) in &parsed.edges {
+        components.union(from, to);
+    }
+    edges.extend(parsed.edges);
+    node_to_id.extend(parsed.node_to_id);
+    attributes.extend(parsed.attributes);
+    Ok(())
+}
+
+/// Reads node coordinates from a file with one `name longitude latitude`
+/// line per node. Returns the `(longitude, latitude)` of every node in
+/// `node_to_id`; nodes without a line get `NaN` coordinates, names that
+/// are not in the network are ignored.
+pub fn coordinates_from_file<P>(filename: P, node_to_id: &HashMap<String,NodeId>) -> io::Result<Vec<(f64, f64)>>
 where P: AsRef<Path> {
-    let regex = parse_pattern(pattern);
-    let mut next_node: NodeId = 0;
-    let f = BufReader::new(File::open(filename).ok().expect("Opening the file went bad."));
+    let mut coordinates = vec![(::std::f64::NAN, ::std::f64::NAN); node_to_id.len()];
+    let reader = BufReader::new(File::open(filename)?);
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected name, longitude and latitude", index + 1));
+        if fields.len() != 3 {
+            return Err(invalid());
+        }
+        let longitude = fields[1].parse::<f64>().map_err(|_| invalid())?;
+        let latitude = fields[2].parse::<f64>().map_err(|_| invalid())?;
+        if let Some(id) = node_to_id.get(fields[0]) {
+            coordinates[*id as usize] = (longitude, latitude);
+        }
+    }
+    Ok(coordinates)
+}
 
-    for line in f.lines().skip(skip) {
-        let l = match line {
-            Ok(l) => l,
-            Err(_) => return
-        };
-        let (from, to, cost, cap) = parse_line(&l, &regex, node_to_id, &mut next_node);
-        edges.push((from, to, cost, cap));
-        if *is_undirected {
-            edges.push((to, from, cost, cap));
+/// Reads a value per node from a file with one `name value` line per node,
+/// like the profits of a closure problem. Nodes without a line get `0.0`,
+/// names that are not in the network are ignored.
+pub fn node_values_from_file<P>(filename: P, node_to_id: &HashMap<String,NodeId>) -> io::Result<DoubleVec>
+where P: AsRef<Path> {
+    let mut values = vec![0.0; node_to_id.len()];
+    let reader = BufReader::new(File::open(filename)?);
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.is_empty() {
+            continue;
+        }
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, format!("line {}: expected name and value", index + 1));
+        if fields.len() != 2 {
+            return Err(invalid());
+        }
+        let value = fields[1].parse::<f64>().map_err(|_| invalid())?;
+        if let Some(id) = node_to_id.get(fields[0]) {
+            values[*id as usize] = value;
+        }
+    }
+    Ok(values)
+}
+
+/// Reads a list of nodes from a file with one node name per line, in the
+/// order of the file. Fails with `io::ErrorKind::InvalidData` for names
+/// that are not in the network.
+pub fn nodes_from_file<P>(filename: P, node_to_id: &HashMap<String,NodeId>) -> io::Result<Vec<NodeId>>
+where P: AsRef<Path> {
+    let reader = BufReader::new(File::open(filename)?);
+    let mut nodes = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let name = line.trim();
+        if name.is_empty() {
+            continue;
+        }
+        match node_to_id.get(name) {
+            Some(id) => nodes.push(*id),
+            None => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("line {}: unknown node {}", index + 1, name))),
         }
     }
+    Ok(nodes)
 }
 
 #[test]
 fn test_pattern_match() {
     let pattern = "^(?P<from>[[:alnum:]]+).(?P<to>[[:alnum:]]+)\\s+(?P<cost>\\d+.\\d+).*$";
-    let regex = parse_pattern(pattern);
+    let regex = compile_pattern(pattern).unwrap();
     let to_match = "nW0770230N0388068.nW0770230N0388073   000.0345 065 11 {DC}";
     assert!(regex.is_match(to_match));
-    assert_eq!(parse_pattern(r"^([[:alnum:]]+)$").captures("nW0770230N0388068").unwrap().at(1), Some("nW0770230N0388068"));
+    assert_eq!(compile_pattern(r"^([[:alnum:]]+)$").unwrap().captures("nW0770230N0388068").unwrap().at(1), Some("nW0770230N0388068"));
     let caps = regex.captures(to_match).unwrap();
     assert_eq!(Some("nW0770230N0388068"), caps.at(1)); 
     assert_eq!(Some("nW0770230N0388073"), caps.at(2)); 
@@ -0,0 +1,23 @@
+//! Re-exports the commonly used types, traits and functions of this crate.
+//!
+//! ```
+//! use network::prelude::*;
+//!
+//! let mut edges = vec![(0,1,6.0,0.0),
+//!                      (0,2,4.0,0.0),
+//!                      (2,1,1.0,0.0)];
+//! let compact_star = compact_star_from_edge_vec(3, &mut edges);
+//! let path = shortest_path(&compact_star, 0, 1).unwrap();
+//! assert_eq!(&vec![0,2,1], path.nodes());
+//! assert_eq!("0 -> 2 -> 1 (cost: 5)", format!("{}", path));
+//! ```
+
+pub use super::{ Capacity, Cost, Direction, DoubleVec, Network, NodeId, NodeVec, OrderedFloat, UnionFind, Weight };
+pub use super::adjacency_list::AdjacencyList;
+pub use super::compact_star::{ CompactStar, ConstructionOptions, IsolatedNodes, SelfLoops,
+                               compact_star_from_edge_vec, compact_star_with_options };
+pub use super::algorithms::*;
+pub use super::cancel::{ Cancellation, Cancelled };
+pub use super::instrument::{ Counters, Instrumentation, NoInstrumentation };
+pub use super::query_engine::QueryEngine;
+pub use super::pretty::{ ArcValues, LabeledDisplay, Ranking };
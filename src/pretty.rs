@@ -0,0 +1,203 @@
+//! Human readable output for networks and algorithm results, optionally
+//! with node names instead of ids.
+
+use std::fmt;
+
+use super::{ Network, NodeId };
+use super::algorithms::Path;
+
+/// Formatting that can print node names instead of node ids.
+/// `labels[i]` is the name of node `i`; nodes without a name are printed
+/// with their id.
+pub trait LabeledDisplay {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result;
+
+    /// Wraps the value for printing with the given node names.
+    fn with_labels<'a>(&'a self, labels: &'a [String]) -> WithLabels<'a, Self> where Self: Sized {
+        WithLabels {
+            item:   self,
+            labels: labels,
+        }
+    }
+}
+
+/// A value printed with node names, see `LabeledDisplay::with_labels`.
+pub struct WithLabels<'a, T: 'a> {
+    item:   &'a T,
+    labels: &'a [String],
+}
+
+impl<'a, T: LabeledDisplay> fmt::Display for WithLabels<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.item.fmt_labeled(f, Some(self.labels))
+    }
+}
+
+/// Returns the name of node `i`, or its id if there is no name.
+pub fn node_label(labels: Option<&[String]>, i: NodeId) -> String {
+    labels.and_then(|names| names.get(i as usize))
+        .map(|name| name.clone())
+        .unwrap_or_else(|| i.to_string())
+}
+
+/// Per-node scores like PageRank or centrality values, printed as one
+/// `node: score` line per node in descending order of the scores.
+pub struct Ranking<'a> {
+    scores: &'a [f64],
+    top:    usize,
+}
+
+impl<'a> Ranking<'a> {
+    /// Prints at most `top` nodes with the highest scores.
+    pub fn new(scores: &'a [f64], top: usize) -> Ranking<'a> {
+        Ranking {
+            scores: scores,
+            top:    top,
+        }
+    }
+}
+
+impl<'a> LabeledDisplay for Ranking<'a> {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        let mut order: Vec<usize> = (0..self.scores.len()).collect();
+        order.sort_by(|&a, &b| self.scores[b].partial_cmp(&self.scores[a]).unwrap_or(::std::cmp::Ordering::Equal));
+        for (rank, i) in order.into_iter().take(self.top).enumerate() {
+            writeln!(f, "{:>4}. {}: {:e}", rank + 1, node_label(labels, i as NodeId), self.scores[i])?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> fmt::Display for Ranking<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
+}
+
+/// Per-arc values like flows, printed as one `from -> to: value` line per
+/// arc with a non-zero value. Values are given in arc order, see
+/// `Network::arcs`.
+pub struct ArcValues<'a, N: 'a> {
+    network: &'a N,
+    values:  &'a [f64],
+}
+
+impl<'a, N: Network> ArcValues<'a, N> {
+    pub fn new(network: &'a N, values: &'a [f64]) -> ArcValues<'a, N> {
+        ArcValues {
+            network: network,
+            values:  values,
+        }
+    }
+}
+
+impl<'a, N: Network> LabeledDisplay for ArcValues<'a, N> {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        for ((from, to), value) in self.network.arcs().zip(self.values.iter()) {
+            if *value != 0.0 {
+                writeln!(f, "{} -> {}: {}", node_label(labels, from), node_label(labels, to), value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, N: Network> fmt::Display for ArcValues<'a, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
+}
+
+/// Paths as a GeoJSON `FeatureCollection` with one `LineString` feature
+/// per path, in the given order. `coordinates[i]` is the `(longitude,
+/// latitude)` of node `i`. Every feature has the properties `rank`
+/// (starting at 1), `cost` and `nodes`, the node names or ids on the path.
+pub struct GeoJsonPaths<'a> {
+    paths:       &'a [Path],
+    coordinates: &'a [(f64, f64)],
+}
+
+impl<'a> GeoJsonPaths<'a> {
+    pub fn new(paths: &'a [Path], coordinates: &'a [(f64, f64)]) -> GeoJsonPaths<'a> {
+        GeoJsonPaths {
+            paths:       paths,
+            coordinates: coordinates,
+        }
+    }
+}
+
+/// Escapes a string for a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"'  => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c    => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+impl<'a> LabeledDisplay for GeoJsonPaths<'a> {
+    fn fmt_labeled(&self, f: &mut fmt::Formatter, labels: Option<&[String]>) -> fmt::Result {
+        write!(f, "{{\"type\":\"FeatureCollection\",\"features\":[")?;
+        for (rank, path) in self.paths.iter().enumerate() {
+            if rank > 0 {
+                write!(f, ",")?;
+            }
+            let points: Vec<String> = path.nodes().iter()
+                .map(|i| { let (x, y) = self.coordinates[*i as usize]; format!("[{},{}]", x, y) })
+                .collect();
+            let names: Vec<String> = path.nodes().iter()
+                .map(|i| json_string(&node_label(labels, *i)))
+                .collect();
+            write!(f, "{{\"type\":\"Feature\",\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[{}]}},", points.join(","))?;
+            write!(f, "\"properties\":{{\"rank\":{},\"cost\":{},\"nodes\":[{}]}}}}", rank + 1, path.cost(), names.join(","))?;
+        }
+        writeln!(f, "]}}")
+    }
+}
+
+impl<'a> fmt::Display for GeoJsonPaths<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_labeled(f, None)
+    }
+}
+
+#[test]
+fn test_ranking() {
+    let scores = vec![0.1, 0.5, 0.4];
+    assert_eq!("   1. 1: 5e-1\n   2. 2: 4e-1\n", format!("{}", Ranking::new(&scores, 2)));
+    let labels = vec!["a".to_string(), "b".to_string()];
+    assert_eq!("   1. b: 5e-1\n   2. 2: 4e-1\n", format!("{}", Ranking::new(&scores, 2).with_labels(&labels)));
+}
+
+#[test]
+fn test_arc_values() {
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![
+        (0,1,0.0,0.0),
+        (0,2,0.0,0.0),
+        (1,2,0.0,0.0)];
+    let compact_star = compact_star_from_edge_vec(3, &mut edges);
+    let flow = vec![1.5, 0.0, 1.5];
+    assert_eq!("0 -> 1: 1.5\n1 -> 2: 1.5\n", format!("{}", ArcValues::new(&compact_star, &flow)));
+}
+
+#[test]
+fn test_geojson_paths() {
+    let paths = vec![Path::new(vec![0, 2], 1.5), Path::new(vec![0, 1, 2], 2.0)];
+    let coordinates = vec![(8.5, 47.25), (8.0, 47.0), (9.0, 47.5)];
+    let expected = concat!(
+        "{\"type\":\"FeatureCollection\",\"features\":[",
+        "{\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[8.5,47.25],[9,47.5]]},",
+        "\"properties\":{\"rank\":1,\"cost\":1.5,\"nodes\":[\"a\",\"c\\\"\"]}},",
+        "{\"type\":\"Feature\",\"geometry\":{\"type\":\"LineString\",\"coordinates\":[[8.5,47.25],[8,47],[9,47.5]]},",
+        "\"properties\":{\"rank\":2,\"cost\":2,\"nodes\":[\"a\",\"1\",\"c\\\"\"]}}]}\n");
+    let labels = vec!["a".to_string(), "1".to_string(), "c\"".to_string()];
+    assert_eq!(expected, format!("{}", GeoJsonPaths::new(&paths, &coordinates).with_labels(&labels)));
+}
@@ -0,0 +1,241 @@
+//! Property tests on random networks. Every algorithm is checked against
+//! the optimality conditions of its result and, on small networks, against
+//! a brute force reference oracle.
+
+use std::f64;
+
+use super::{ Cost, DoubleVec, Network, NodeId, UnionFind };
+use super::compact_star::CompactStar;
+use super::algorithms::*;
+use super::generators::{ ArcWeights, Distribution, gnp };
+
+const EPS: f64 = 1e-9;
+
+/// Random networks of various densities with costs in `[1, 10)`.
+fn random_networks(n: usize) -> Vec<CompactStar> {
+    let weights = ArcWeights { cost: Distribution::Uniform(1.0, 10.0), capacity: Distribution::Uniform(1.0, 5.0) };
+    let mut networks = Vec::new();
+    for seed in 0..20 {
+        let p = 0.05 + 0.05 * (seed % 6) as f64;
+        networks.push(gnp(n, p, &weights, seed));
+    }
+    networks
+}
+
+/// All pairs shortest path distances by Floyd-Warshall, unreachable pairs
+/// are infinite.
+fn floyd_warshall<N: Network>(network: &N) -> Vec<DoubleVec> {
+    let n = network.num_nodes();
+    let mut d = vec![vec![f64::INFINITY; n]; n];
+    for i in 0..n {
+        d[i][i] = 0.0;
+    }
+    for (i, j, cost, _) in network.arc_values() {
+        if cost < d[i as usize][j as usize] {
+            d[i as usize][j as usize] = cost;
+        }
+    }
+    for k in 0..n {
+        for i in 0..n {
+            for j in 0..n {
+                if d[i][k] + d[k][j] < d[i][j] {
+                    d[i][j] = d[i][k] + d[k][j];
+                }
+            }
+        }
+    }
+    d
+}
+
+fn is_reachable<N: Network>(network: &N, pred: &Vec<NodeId>, source: NodeId, i: usize) -> bool {
+    i == source as usize || pred[i] != network.invalid_id()
+}
+
+/// The capacity of the arcs from the nodes in `source_side` to the others.
+fn cut_capacity<N: Network>(network: &N, source_side: &[bool]) -> f64 {
+    network.arc_values()
+        .filter(|&(i, j, _, _)| source_side[i as usize] && !source_side[j as usize])
+        .map(|(_, _, _, capacity)| capacity)
+        .sum()
+}
+
+/// The least capacity of any cut between `source` and `sink`, trying all
+/// subsets of the other nodes.
+fn brute_force_min_cut<N: Network>(network: &N, source: NodeId, sink: NodeId) -> f64 {
+    let others: Vec<usize> = (0..network.num_nodes()).filter(|i| *i != source as usize && *i != sink as usize).collect();
+    let mut best = f64::INFINITY;
+    for subset in 0..1usize << others.len() {
+        let mut source_side = vec![false; network.num_nodes()];
+        source_side[source as usize] = true;
+        for (k, i) in others.iter().enumerate() {
+            source_side[*i] = subset & (1 << k) != 0;
+        }
+        best = best.min(cut_capacity(network, &source_side));
+    }
+    best
+}
+
+/// The least weight of a spanning forest, trying all sets of `size` arcs
+/// without a cycle, where `size` is the number of arcs of any spanning
+/// forest.
+fn brute_force_forest_weight(arcs: &[(NodeId, NodeId, Cost)], num_nodes: usize, size: usize) -> Cost {
+    fn choose(arcs: &[(NodeId, NodeId, Cost)], num_nodes: usize, size: usize, first: usize,
+              chosen: &mut Vec<usize>, best: &mut Cost) {
+        if chosen.len() == size {
+            let mut components = UnionFind::new(num_nodes);
+            if chosen.iter().all(|a| components.union(arcs[*a].0, arcs[*a].1)) {
+                *best = best.min(chosen.iter().map(|a| arcs[*a].2).sum());
+            }
+            return;
+        }
+        for a in first..arcs.len() {
+            chosen.push(a);
+            choose(arcs, num_nodes, size, a + 1, chosen, best);
+            chosen.pop();
+        }
+    }
+    let mut best = f64::INFINITY;
+    choose(arcs, num_nodes, size, 0, &mut Vec::new(), &mut best);
+    best
+}
+
+fn path_cost<N: Network>(network: &N, nodes: &Vec<NodeId>) -> Cost {
+    nodes.windows(2).map(|arc| network.cheapest_cost(arc[0], arc[1]).unwrap()).sum()
+}
+
+#[test]
+fn dijkstra_satisfies_optimality_conditions() {
+    for network in random_networks(30) {
+        for source in 0..3 {
+            let (pred, dist) = heap_dijkstra(&network, source);
+            for (i, j, cost, _) in network.arc_values() {
+                if !is_reachable(&network, &pred, source, i as usize) {
+                    continue;
+                }
+                // no arc can be relaxed any further
+                assert!(dist[j as usize] <= dist[i as usize] + cost + EPS);
+            }
+            for j in 0..network.num_nodes() {
+                if j != source as usize && is_reachable(&network, &pred, source, j) {
+                    // tree arcs are tight
+                    let i = pred[j];
+                    let cost = network.cheapest_cost(i, j as NodeId).unwrap();
+                    assert!((dist[j] - dist[i as usize] - cost).abs() < EPS);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn dijkstra_matches_floyd_warshall() {
+    for network in random_networks(15) {
+        let reference = floyd_warshall(&network);
+        for source in network.nodes() {
+            let (pred, dist) = heap_dijkstra(&network, source);
+            let (vanilla_pred, vanilla_dist) = vanilla_dijkstra(&network, source);
+            for j in 0..network.num_nodes() {
+                let reachable = reference[source as usize][j].is_finite();
+                assert_eq!(reachable, is_reachable(&network, &pred, source, j));
+                assert_eq!(reachable, is_reachable(&network, &vanilla_pred, source, j));
+                if reachable {
+                    assert!((dist[j] - reference[source as usize][j]).abs() < EPS);
+                    assert!((vanilla_dist[j] - reference[source as usize][j]).abs() < EPS);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn searches_reach_the_same_nodes() {
+    for network in random_networks(25) {
+        let (dijkstra_pred, _) = heap_dijkstra(&network, 0);
+        let (bfs_pred, _) = breadth_first_search(&network, 0);
+        let (dfs_pred, _) = depth_first_search(&network, 0);
+        for j in 0..network.num_nodes() {
+            let reachable = is_reachable(&network, &dijkstra_pred, 0, j);
+            assert_eq!(reachable, is_reachable(&network, &bfs_pred, 0, j));
+            assert_eq!(reachable, is_reachable(&network, &dfs_pred, 0, j));
+        }
+    }
+}
+
+#[test]
+fn point_to_point_queries_match_floyd_warshall() {
+    for network in random_networks(15) {
+        let reference = floyd_warshall(&network);
+        let nodes: Vec<NodeId> = network.nodes().collect();
+        let table = distance_table(&network, &nodes[..5], &nodes);
+        for s in 0..5 {
+            for t in 0..network.num_nodes() {
+                match shortest_path(&network, s as NodeId, t as NodeId) {
+                    Some(path) => {
+                        assert!((path.cost() - reference[s][t]).abs() < EPS);
+                        assert!((path_cost(&network, path.nodes()) - path.cost()).abs() < EPS);
+                        assert!((table[s][t] - reference[s][t]).abs() < EPS);
+                    },
+                    None => assert!(reference[s][t].is_infinite())
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn flow_decomposition_recomposes_the_flow() {
+    for network in random_networks(20) {
+        let flow: DoubleVec = network.arcs()
+            .map(|(i, j)| ((i as usize * 7 + j as usize * 3) % 5) as f64)
+            .collect();
+        let mut recomposed = vec![0.0; flow.len()];
+        for component in decompose_flow(&network, &flow) {
+            assert!(component.flow > 0.0);
+            for a in &component.arcs {
+                recomposed[*a] += component.flow;
+            }
+        }
+        for a in 0..flow.len() {
+            assert!((flow[a] - recomposed[a]).abs() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn max_flow_equals_min_cut() {
+    for network in random_networks(8) {
+        let sink = (network.num_nodes() - 1) as NodeId;
+        let flow = edmonds_karp(&network, 0, sink);
+        assert!((flow.value - dinic(&network, 0, sink).value).abs() < EPS);
+        // the residual cut is minimal and as large as the flow
+        assert!(flow.source_side[0] && !flow.source_side[sink as usize]);
+        assert!((cut_capacity(&network, &flow.source_side) - flow.value).abs() < EPS);
+        assert!((brute_force_min_cut(&network, 0, sink) - flow.value).abs() < EPS);
+
+        let mut balance = vec![0.0; network.num_nodes()];
+        for ((i, j, _, capacity), flow) in network.arc_values().zip(&flow.flows) {
+            assert!(*flow >= -EPS && *flow <= capacity + EPS);
+            balance[i as usize] -= *flow;
+            balance[j as usize] += *flow;
+        }
+        for i in 1..sink as usize {
+            assert!(balance[i].abs() < EPS);
+        }
+        assert!((balance[sink as usize] - flow.value).abs() < EPS);
+    }
+}
+
+#[test]
+fn spanning_trees_match_brute_force() {
+    for network in random_networks(6) {
+        let arcs: Vec<(NodeId, NodeId, Cost)> = network.arc_values().map(|(i, j, cost, _)| (i, j, cost)).collect();
+        let mut components = UnionFind::new(network.num_nodes());
+        let size = arcs.iter().filter(|arc| components.union(arc.0, arc.1)).count();
+        let tree = kruskal(&network);
+        assert_eq!(size, tree.arcs.len());
+        assert!((brute_force_forest_weight(&arcs, network.num_nodes(), size) - tree.weight).abs() < EPS);
+        if size == network.num_nodes() - 1 {
+            assert!((prim(&network, 0).weight - tree.weight).abs() < EPS);
+        }
+    }
+}
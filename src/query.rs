@@ -0,0 +1,311 @@
+//! A small query language for ad hoc exploration of a loaded network, with
+//! nodes given by name:
+//!
+//! ```text
+//! path from A to B [avoiding [X, Y]] [max_cost 100]
+//! reachable from A [avoiding [X, Y]] [max_cost 100]
+//! ```
+//!
+//! Keywords are case insensitive, names with spaces or brackets can be
+//! quoted with `"`.
+//!
+//! ```
+//! use std::collections::HashMap;
+//! use network::prelude::*;
+//! use network::query::{ QueryResult, parse_query };
+//!
+//! let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (0,2,5.0,0.0)];
+//! let network = compact_star_from_edge_vec(3, &mut edges);
+//! let node_to_id: HashMap<String, NodeId> =
+//!     vec![("a".to_string(), 0), ("b".to_string(), 1), ("c".to_string(), 2)].into_iter().collect();
+//!
+//! let query = parse_query("path from a to c avoiding [b]").unwrap();
+//! match query.evaluate(&network, &node_to_id).unwrap() {
+//!     QueryResult::Path(path) => assert_eq!(Some(5.0), path.map(|p| p.cost())),
+//!     _ => unreachable!(),
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::f64;
+use std::fmt;
+
+use super::{ Cost, Network, NodeId };
+use super::algorithms::{ Path, dijkstra_with_model, shortest_path_with_model };
+
+/// A parsed query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// The shortest path from `from` to `to`.
+    Path { from: String, to: String, filter: Filter },
+    /// All nodes reachable from `from`, with their distances.
+    Reachable { from: String, filter: Filter },
+}
+
+/// The optional clauses shared by all queries.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Filter {
+    /// Nodes that must not be visited.
+    pub avoiding: Vec<String>,
+    /// The largest cost of a path.
+    pub max_cost: Option<Cost>,
+}
+
+/// The answer to a query.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryResult {
+    /// The shortest path, `None` if there is none within the filter.
+    Path(Option<Path>),
+    /// The reachable nodes and their distances, by increasing distance.
+    Reachable(Vec<(NodeId, Cost)>),
+}
+
+/// Reasons for rejecting a query. Positions are byte offsets into the
+/// query string.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryError {
+    /// A token that doesn't fit the grammar.
+    Unexpected { position: usize, found: String, expected: &'static str },
+    /// The query ended too early.
+    UnexpectedEnd { expected: &'static str },
+    /// A quoted name without its closing quote.
+    UnterminatedQuote { position: usize },
+    /// A `max_cost` that is not a number.
+    InvalidNumber { position: usize, value: String },
+    /// A node name that is not in the network.
+    UnknownNode(String),
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            QueryError::Unexpected { position, ref found, expected } =>
+                write!(f, "at {}: expected {}, found `{}`", position, expected, found),
+            QueryError::UnexpectedEnd { expected } => write!(f, "expected {}, found the end of the query", expected),
+            QueryError::UnterminatedQuote { position } => write!(f, "at {}: unterminated quote", position),
+            QueryError::InvalidNumber { position, ref value } => write!(f, "at {}: `{}` is not a number", position, value),
+            QueryError::UnknownNode(ref name) => write!(f, "unknown node {}", name),
+        }
+    }
+}
+
+impl Error for QueryError {}
+
+/// A token with its position; quoted names are never keywords.
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    text:     String,
+    position: usize,
+    quoted:   bool,
+}
+
+fn tokenize(query: &str) -> Result<Vec<Token>, QueryError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(position, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '[' || c == ']' || c == ',' {
+            chars.next();
+            tokens.push(Token { text: c.to_string(), position: position, quoted: false });
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    Some((_, c)) => text.push(c),
+                    None => return Err(QueryError::UnterminatedQuote { position: position }),
+                }
+            }
+            tokens.push(Token { text: text, position: position, quoted: true });
+        } else {
+            let mut text = String::new();
+            while let Some(&(_, c)) = chars.peek() {
+                if c.is_whitespace() || c == '[' || c == ']' || c == ',' || c == '"' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+            tokens.push(Token { text: text, position: position, quoted: false });
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent over the token list.
+struct Parser {
+    tokens: Vec<Token>,
+    next:   usize,
+}
+
+impl Parser {
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        self.tokens.get(self.next).map_or(false, |t| !t.quoted && t.text.eq_ignore_ascii_case(keyword))
+    }
+
+    fn token(&mut self, expected: &'static str) -> Result<Token, QueryError> {
+        let token = self.tokens.get(self.next).cloned().ok_or(QueryError::UnexpectedEnd { expected: expected })?;
+        self.next += 1;
+        Ok(token)
+    }
+
+    fn keyword(&mut self, keyword: &'static str) -> Result<(), QueryError> {
+        if self.peek_keyword(keyword) {
+            self.next += 1;
+            return Ok(());
+        }
+        let token = self.token(keyword)?;
+        Err(QueryError::Unexpected { position: token.position, found: token.text, expected: keyword })
+    }
+
+    fn name(&mut self) -> Result<String, QueryError> {
+        let token = self.token("a node name")?;
+        if !token.quoted && (token.text == "[" || token.text == "]" || token.text == ",") {
+            return Err(QueryError::Unexpected { position: token.position, found: token.text, expected: "a node name" });
+        }
+        Ok(token.text)
+    }
+
+    fn filter(&mut self) -> Result<Filter, QueryError> {
+        let mut filter = Filter::default();
+        loop {
+            if self.peek_keyword("avoiding") {
+                self.next += 1;
+                self.keyword("[")?;
+                while !self.peek_keyword("]") {
+                    if !filter.avoiding.is_empty() {
+                        self.keyword(",")?;
+                    }
+                    filter.avoiding.push(self.name()?);
+                }
+                self.next += 1;
+            } else if self.peek_keyword("max_cost") {
+                self.next += 1;
+                let token = self.token("a number")?;
+                let value = token.text.parse::<f64>()
+                    .map_err(|_| QueryError::InvalidNumber { position: token.position, value: token.text.clone() })?;
+                filter.max_cost = Some(value);
+            } else if let Some(token) = self.tokens.get(self.next) {
+                return Err(QueryError::Unexpected { position: token.position, found: token.text.clone(),
+                                                    expected: "avoiding, max_cost or the end of the query" });
+            } else {
+                return Ok(filter);
+            }
+        }
+    }
+}
+
+/// Parses a query, see the module documentation for the grammar.
+pub fn parse_query(query: &str) -> Result<Query, QueryError> {
+    let mut parser = Parser { tokens: tokenize(query)?, next: 0 };
+    if parser.peek_keyword("path") {
+        parser.next += 1;
+        parser.keyword("from")?;
+        let from = parser.name()?;
+        parser.keyword("to")?;
+        let to = parser.name()?;
+        let filter = parser.filter()?;
+        return Ok(Query::Path { from: from, to: to, filter: filter });
+    }
+    if parser.peek_keyword("reachable") {
+        parser.next += 1;
+        parser.keyword("from")?;
+        let from = parser.name()?;
+        let filter = parser.filter()?;
+        return Ok(Query::Reachable { from: from, filter: filter });
+    }
+    let token = parser.token("path or reachable")?;
+    Err(QueryError::Unexpected { position: token.position, found: token.text, expected: "path or reachable" })
+}
+
+impl Query {
+    /// Answers the query on `network`, whose nodes are named by
+    /// `node_to_id`.
+    /// # Errors
+    /// `QueryError::UnknownNode` for names that are not in `node_to_id`.
+    pub fn evaluate<N: Network>(&self, network: &N, node_to_id: &HashMap<String, NodeId>) -> Result<QueryResult, QueryError> {
+        let id = |name: &String| node_to_id.get(name).map(|id| *id).ok_or(QueryError::UnknownNode(name.clone()));
+        let filter = match *self {
+            Query::Path { ref filter, .. } | Query::Reachable { ref filter, .. } => filter,
+        };
+        let mut avoided = vec![false; network.num_nodes()];
+        for name in &filter.avoiding {
+            avoided[id(name)? as usize] = true;
+        }
+        // every arc with its head and cost, closed if it touches an avoided node
+        let arcs: Vec<(NodeId, NodeId, Cost)> = network.nodes()
+            .flat_map(|i| network.out_arcs(i).map(move |(j, cost, _)| (i, j, cost)))
+            .collect();
+        let model = |_: usize, &(i, j, cost): &(NodeId, NodeId, Cost)| {
+            if avoided[i as usize] || avoided[j as usize] { f64::INFINITY } else { cost }
+        };
+        let max_cost = filter.max_cost.unwrap_or(f64::INFINITY);
+
+        match *self {
+            Query::Path { ref from, ref to, .. } => {
+                let (source, target) = (id(from)?, id(to)?);
+                let path = if avoided[source as usize] || avoided[target as usize] {
+                    None
+                } else {
+                    shortest_path_with_model(network, &arcs, &model, source, target).filter(|path| path.cost() <= max_cost)
+                };
+                Ok(QueryResult::Path(path))
+            },
+            Query::Reachable { ref from, .. } => {
+                let source = id(from)?;
+                if avoided[source as usize] {
+                    return Ok(QueryResult::Reachable(Vec::new()));
+                }
+                let (_, d) = dijkstra_with_model(network, &arcs, &model, source);
+                let mut reachable: Vec<(NodeId, Cost)> = network.nodes()
+                    .map(|i| (i, d[i as usize]))
+                    .filter(|&(_, d)| d.is_finite() && d <= max_cost)
+                    .collect();
+                reachable.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap().then(a.0.cmp(&b.0)));
+                Ok(QueryResult::Reachable(reachable))
+            },
+        }
+    }
+}
+
+#[test]
+fn test_parse_query() {
+    let query = parse_query("PATH from a to \"b c\" avoiding [x, y] max_cost 100").unwrap();
+    let filter = Filter { avoiding: vec!["x".to_string(), "y".to_string()], max_cost: Some(100.0) };
+    assert_eq!(Query::Path { from: "a".to_string(), to: "b c".to_string(), filter: filter }, query);
+    assert_eq!(Query::Reachable { from: "a".to_string(), filter: Filter::default() }, parse_query("reachable from a").unwrap());
+
+    assert_eq!(Err(QueryError::Unexpected { position: 0, found: "route".to_string(), expected: "path or reachable" }),
+               parse_query("route from a to b"));
+    assert_eq!(Err(QueryError::UnexpectedEnd { expected: "to" }), parse_query("path from a"));
+    assert_eq!(Err(QueryError::InvalidNumber { position: 26, value: "ten".to_string() }),
+               parse_query("path from a to b max_cost ten"));
+    assert_eq!(Err(QueryError::UnterminatedQuote { position: 10 }), parse_query("path from \"a to b"));
+    assert_eq!("at 17: expected avoiding, max_cost or the end of the query, found `via`",
+               parse_query("path from a to b via c").unwrap_err().to_string());
+}
+
+#[test]
+fn test_evaluate_query() {
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,1.0,0.0), (1,2,1.0,0.0), (0,2,5.0,0.0)];
+    let network = compact_star_from_edge_vec(4, &mut edges);
+    let node_to_id: HashMap<String, NodeId> = ["a", "b", "c", "d"].iter()
+        .enumerate()
+        .map(|(i, name)| (name.to_string(), i as NodeId))
+        .collect();
+    let evaluate = |query: &str| parse_query(query).unwrap().evaluate(&network, &node_to_id);
+
+    let path = match evaluate("path from a to c").unwrap() {
+        QueryResult::Path(path) => path.unwrap(),
+        _ => unreachable!(),
+    };
+    assert_eq!(&vec![0,1,2], path.nodes());
+    assert_eq!(Ok(QueryResult::Path(None)), evaluate("path from a to c avoiding [b] max_cost 4"));
+    assert_eq!(Ok(QueryResult::Path(None)), evaluate("path from a to d"));
+    assert_eq!(Ok(QueryResult::Reachable(vec![(0, 0.0), (1, 1.0)])), evaluate("reachable from a max_cost 1.5"));
+    assert_eq!(Err(QueryError::UnknownNode("z".to_string())), evaluate("path from a to z"));
+}
@@ -0,0 +1,198 @@
+//! Concurrent point queries on a shared network.
+
+use std::sync::{ Arc, Mutex };
+
+use super::{ Cost, DoubleVec, Network, NodeId, NodeVec };
+use super::algorithms::Path;
+use super::compact_star::CompactStar;
+use super::heaps::{ BinaryHeap, Heap };
+
+/// Answers shortest path, isochrone and nearest target queries on a shared
+/// `CompactStar`. The engine is `Send + Sync`, so one instance, usually
+/// wrapped in an `Arc`, can serve queries from many threads at once.
+///
+/// Every query needs distance and predecessor arrays of network size.
+/// Instead of allocating them per query, the engine keeps a pool of search
+/// buffers. A query takes a buffer from the pool, or allocates one if all
+/// are in use, and puts it back afterwards, so there are never more buffers
+/// than concurrent queries. Only the entries touched by a query are reset,
+/// which keeps the cost of short queries independent of the network size.
+pub struct QueryEngine {
+    network: Arc<CompactStar>,
+    buffers: Mutex<Vec<SearchBuffers>>,
+}
+
+struct SearchBuffers {
+    dist:    DoubleVec,
+    pred:    NodeVec,
+    settled: Vec<bool>,
+    touched: NodeVec,
+    heap:    BinaryHeap,
+}
+
+impl SearchBuffers {
+    fn new(n: usize) -> SearchBuffers {
+        SearchBuffers {
+            dist:    vec![Cost::INFINITY; n],
+            pred:    vec![n as NodeId; n],
+            settled: vec![false; n],
+            touched: NodeVec::new(),
+            heap:    BinaryHeap::with_capacity(n),
+        }
+    }
+
+    fn reset(&mut self) {
+        let n = self.dist.len();
+        for i in self.touched.drain(..) {
+            self.dist[i as usize] = Cost::INFINITY;
+            self.pred[i as usize] = n as NodeId;
+            self.settled[i as usize] = false;
+        }
+        self.heap.clear();
+    }
+
+    fn path_to(&self, source: NodeId, target: NodeId) -> Path {
+        let mut nodes = vec![target];
+        let mut current = target;
+        while current != source {
+            current = self.pred[current as usize];
+            nodes.push(current);
+        }
+        nodes.reverse();
+        Path::new(nodes, self.dist[target as usize])
+    }
+}
+
+impl QueryEngine {
+    pub fn new(network: Arc<CompactStar>) -> QueryEngine {
+        QueryEngine {
+            network: network,
+            buffers: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn network(&self) -> &Arc<CompactStar> {
+        &self.network
+    }
+
+    /// The shortest path from `source` to `target`, or `None` if `target`
+    /// is unreachable.
+    pub fn shortest_path(&self, source: NodeId, target: NodeId) -> Option<Path> {
+        self.nearest(source, &[target])
+    }
+
+    /// The shortest path from `source` to the closest node in `targets`, or
+    /// `None` if no target is reachable.
+    pub fn nearest(&self, source: NodeId, targets: &[NodeId]) -> Option<Path> {
+        let mut targets = targets.to_vec();
+        targets.sort();
+        let mut found = None;
+        self.search(source, |buffers, node| {
+            if targets.binary_search(&node).is_ok() {
+                found = Some(buffers.path_to(source, node));
+                false
+            } else {
+                true
+            }
+        });
+        found
+    }
+
+    /// All nodes reachable from `source` with a cost of at most `max_cost`,
+    /// together with their distances, in order of increasing distance.
+    pub fn isochrone(&self, source: NodeId, max_cost: Cost) -> Vec<(NodeId, Cost)> {
+        let mut reached = Vec::new();
+        self.search(source, |buffers, node| {
+            let dist = buffers.dist[node as usize];
+            if dist <= max_cost {
+                reached.push((node, dist));
+                true
+            } else {
+                false
+            }
+        });
+        reached
+    }
+
+    /// Dijkstra's algorithm from `source` on pooled buffers. `visit` is
+    /// called for every settled node and stops the search by returning
+    /// `false`.
+    fn search<F>(&self, source: NodeId, mut visit: F)
+    where F: FnMut(&SearchBuffers, NodeId) -> bool {
+        let mut buffers = self.acquire();
+        buffers.dist[source as usize] = 0.0;
+        buffers.touched.push(source);
+        buffers.heap.insert(source, 0.0);
+
+        while let Some(next_node) = buffers.heap.find_min() {
+            buffers.heap.delete_min();
+            let i = next_node as usize;
+            if buffers.settled[i] {
+                continue;
+            }
+            buffers.settled[i] = true;
+            if !visit(&buffers, next_node) {
+                break;
+            }
+
+            for (adjacent_node, cost, _) in self.network.out_arcs(next_node) {
+                let j = adjacent_node as usize;
+                if buffers.dist[j] > buffers.dist[i] + cost {
+                    if buffers.dist[j] == Cost::INFINITY {
+                        buffers.touched.push(adjacent_node);
+                    }
+                    buffers.pred[j] = next_node;
+                    buffers.dist[j] = buffers.dist[i] + cost;
+                    buffers.heap.insert(adjacent_node, buffers.dist[j]);
+                }
+            }
+        }
+        self.release(buffers);
+    }
+
+    fn acquire(&self) -> SearchBuffers {
+        let pooled = self.buffers.lock().unwrap().pop();
+        pooled.unwrap_or_else(|| SearchBuffers::new(self.network.num_nodes()))
+    }
+
+    fn release(&self, mut buffers: SearchBuffers) {
+        buffers.reset();
+        self.buffers.lock().unwrap().push(buffers);
+    }
+}
+
+#[test]
+fn test_query_engine() {
+    use std::thread;
+    use super::compact_star::compact_star_from_edge_vec;
+    let mut edges = vec![(0,1,6.0,0.0),
+                         (0,2,4.0,0.0),
+                         (1,2,2.0,0.0),
+                         (1,3,2.0,0.0),
+                         (2,3,1.0,0.0),
+                         (2,4,2.0,0.0),
+                         (3,5,7.0,0.0),
+                         (4,3,1.0,0.0),
+                         (4,5,3.0,0.0)];
+    let engine = Arc::new(QueryEngine::new(Arc::new(compact_star_from_edge_vec(7, &mut edges))));
+
+    let path = engine.shortest_path(0, 5).unwrap();
+    assert_eq!(&vec![0,2,4,5], path.nodes());
+    assert_eq!(9.0, path.cost());
+    assert_eq!(None, engine.shortest_path(0, 6));
+    assert_eq!(vec![(0,0.0), (2,4.0), (3,5.0)], engine.isochrone(0, 5.0));
+    assert_eq!(Some(vec![1,3]), engine.nearest(1, &[5, 3]).map(|p| p.nodes().clone()));
+
+    let handles: Vec<_> = (0..4).map(|_| {
+        let engine = engine.clone();
+        thread::spawn(move || {
+            for _ in 0..100 {
+                assert_eq!(Some(9.0), engine.shortest_path(0, 5).map(|p| p.cost()));
+                assert_eq!(Some(5.0), engine.shortest_path(2, 5).map(|p| p.cost()));
+            }
+        })
+    }).collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
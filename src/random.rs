@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+/// Small, fast and seedable pseudo random number generator (SplitMix64).
+/// Results are reproducible for a given seed on every platform, which is
+/// all that generators, sampling and randomized algorithms in this crate
+/// need. Not suitable for cryptographic purposes.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng {
+            state: seed,
+        }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        mix(self.state)
+    }
+
+    /// A uniformly distributed value in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A uniformly distributed value in `[0, n)`.
+    /// # Panics
+    /// If `n == 0`.
+    pub fn below(&mut self, n: usize) -> usize {
+        assert!(n > 0);
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        self.next_f64() < p
+    }
+
+    /// `k` distinct values from `[0, n)` in random order, by a partial
+    /// Fisher-Yates shuffle. Only the swapped positions are stored, so this
+    /// takes `O(k)` time and memory however large `n` is.
+    pub fn sample(&mut self, n: usize, k: usize) -> Vec<usize> {
+        let k = k.min(n);
+        let mut swapped: HashMap<usize, usize> = HashMap::with_capacity(k);
+        let mut values = Vec::with_capacity(k);
+        for i in 0..k {
+            let j = i + self.below(n - i);
+            let value = *swapped.get(&j).unwrap_or(&j);
+            swapped.insert(j, *swapped.get(&i).unwrap_or(&i));
+            values.push(value);
+        }
+        values
+    }
+}
+
+/// The SplitMix64 finalizer, spreads values evenly over 64 bits.
+pub fn mix(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+#[test]
+fn test_rng() {
+    let mut first = Rng::new(42);
+    let mut second = Rng::new(42);
+    for _ in 0..100 {
+        assert_eq!(first.next_u64(), second.next_u64());
+    }
+    for _ in 0..100 {
+        let x = first.next_f64();
+        assert!(x >= 0.0 && x < 1.0);
+        assert!(first.below(7) < 7);
+    }
+    let mut counts = vec![0; 4];
+    for _ in 0..4000 {
+        counts[first.below(4)] += 1;
+    }
+    assert!(counts.iter().all(|c| *c > 900 && *c < 1100));
+}
+
+#[test]
+fn test_sample() {
+    let mut rng = Rng::new(3);
+    let mut values = rng.sample(1_000_000_000, 5);
+    assert_eq!(5, values.len());
+    values.sort();
+    values.dedup();
+    assert_eq!(5, values.len());
+    let mut all = rng.sample(10, 20);
+    all.sort();
+    assert_eq!((0..10).collect::<Vec<_>>(), all);
+}
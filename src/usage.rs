@@ -4,9 +4,16 @@ use network::NodeId;
 
 pub const DEFAULT_EPS: f64 = 1e-6;
 pub const DEFAULT_BETA: f64 = 0.2;
-pub const DEFAULT_PATTERN: &'static str = "^(?P<from>[[:alnum:]]+).(?P<to>[[:alnum:]]+)\\s+(?P<cost>\\d+.\\d+).*$";
+pub use network::parse::DEFAULT_PATTERN;
 pub const DEFAULT_SKIP: usize = 0;
 pub const DEFAULT_START_ID: NodeId = 0;
+pub const DEFAULT_K: usize = 3;
+pub const DEFAULT_WS_K: usize = 4;
+pub const DEFAULT_TOP: usize = 10;
+pub const DEFAULT_MAX_ITER: usize = 100;
+pub const DEFAULT_SCALE: f64 = 1.0;
+pub const DEFAULT_P: f64 = 0.1;
+pub const DEFAULT_SEED: u64 = 0;
 
 const USAGE: &'static str = "
 Network handling
@@ -19,28 +26,70 @@ Usage:
 Options:
     -h --help             Show this screen.
     -v --version          Show version.
-    --pattern=<p>         Rust regular expression for decoding the input file. Must specify P<from>, P<to>, P<cost>, P<capacity>. If cost or capacity are unspecified, they default to 0.0 respectively.
+    --format=<f>          Format of the input file: regex (the default) reads one arc per line with --pattern, dimacs reads a DIMACS shortest path (.gr) or maximum flow (.max) file. DIMACS nodes are named by their number; the source and sink of a maximum flow file are the default start and target nodes.
+    --pattern=<p>         Rust regular expression for decoding the input file. Must specify P<from> and P<to>, optionally P<cost> and P<cap>. If cost or capacity are unspecified, they default to 0.0 respectively.
+    --cost-attr=<name>    Use the named group <name> of the pattern instead of P<cost> as the arc costs, e.g. P<time> for travel times. Further named groups are read as arc attributes.
     --undirected          Whether the graph is undirected. If set, two arcs are added per line. Defaults to false.
     --skip=<s>            Number of header lines in the input file. Defaults to zero.
-    --start-node=<name>   The node name from which to search in a search algorithm like Dijkstra, Breadth-First-Search, or Depth-First-Search. Defaults to the first parsed node name.
-    --target-node=<name>  The node name to reach in a search algorithm like Dijkstra, Breadth-First-Search, or Depth-First-Search. In PageRank, the node name which rank we want to know. No default given.
+    --require-connected   Abort if the input network has more than one (weakly) connected component.
+    --start-node=<name>   The node name from which to search in a search algorithm like Dijkstra, Bellman-Ford, Breadth-First-Search, or Depth-First-Search. Defaults to the first parsed node name.
+    --target-node=<name>  The node name to reach in a search algorithm like Dijkstra, Breadth-First-Search, or Depth-First-Search. Dijkstra and bellman_ford print the route to it instead of the predecessor list. In PageRank and HITS, the node name which scores we want to know. In maxflow, the sink; the source is given by --start-node. No default given.
     --use-heap            Whether to use a heap to process Dijkstra's shortest path algorithm.
     --beta=<beta>         For PageRank, the teleportation probability parameter. Must be a double value in [0.0, 1.0]. Defaults to 0.2.
-    --eps=<eps>           For PageRank and other numeric algorithms, the convergence parameter. Defaults to 1e-6.
+    --eps=<eps>           For PageRank, HITS and other numeric algorithms, the convergence parameter. Defaults to 1e-6.
+    --threads=<t>         For PageRank, the number of threads. Defaults to one.
+    --k=<k>               For kshortest, the number of loopless paths from the start node to the target node. Defaults to 3. For betweenness and hits, the number of most central nodes printed. Defaults to 10. For generate, the edges per new node of ba, less than --n, and the neighbors per node of ws, an even number less than --n. Defaults to 3 for ba and 4 for ws.
+    --budget=<b>          For dijkstra, find the fastest path to the target node whose tolls sum up to at most b. The costs are the travel times, the capacities are read as tolls.
+    --weighted            For betweenness, measure path lengths by arc costs instead of counting arcs. For matching, assign every node of the smaller side at minimum total arc cost.
+    --coordinates=<file>  File with one `name longitude latitude` line per node, separated by whitespace.
+    --geojson             For kshortest, print the paths as GeoJSON feature collection. Needs --coordinates.
+    --query=<q>           For query, the query to answer, e.g. \"path from A to B avoiding [X, Y] max_cost 100\" or \"reachable from A max_cost 10\".
+    --profits=<file>      For closure, file with one `name profit` line per node; nodes without a line have profit zero.
+    --nodes=<file>        For matrix, file with one node name per line, the rows and columns of the distance matrix. Defaults to all nodes.
+    --matrix-format=<f>   For matrix, plain (the default) writes whitespace separated rows, tsplib an asymmetric TSP in TSPLIB format.
+    --threshold=<t>       For matrix, write only the pairs within distance t as `from to distance` lines instead of the full matrix.
+    --scale=<s>           For matrix in TSPLIB format, the factor of the distances before rounding them to integers. Defaults to 1.
+    --model=<m>           For generate, the random network model: gnp (Erdos-Renyi), ba (Barabasi-Albert), ws (Watts-Strogatz), complete or grid.
+    --n=<n>               For generate, the number of nodes, or the number of rows and columns of a grid. At least 1.
+    --p=<p>               For generate, the arc probability of gnp and the rewiring probability of ws, between 0 and 1. Defaults to 0.1.
+    --seed=<s>            For generate, the seed of the random number generator. Defaults to zero.
+    --max-cost=<c>        For generate, draw arc costs uniformly from [1, c) instead of giving every arc cost 1.
+    --dot-out=<file>      For bfs, dijkstra and bellman_ford, also write the network as Graphviz DOT file with the search tree highlighted, or only the path to the target node if one is given.
 ";
 
 #[derive(Debug, RustcDecodable)]
 pub struct Args {
     pub arg_algorithm: Algorithm,
     pub arg_filename: String,
+    pub flag_format: Option<String>,
     pub flag_pattern: Option<String>,
+    pub flag_cost_attr: Option<String>,
     pub flag_undirected: bool,
     pub flag_skip: Option<usize>,
+    pub flag_require_connected: bool,
     pub flag_start_node: Option<String>,
     pub flag_target_node: Option<String>,
     pub flag_use_heap: bool,
     pub flag_beta: Option<f64>,
     pub flag_eps: Option<f64>,
+    pub flag_threads: Option<usize>,
+    pub flag_k: Option<usize>,
+    pub flag_weighted: bool,
+    pub flag_budget: Option<f64>,
+    pub flag_coordinates: Option<String>,
+    pub flag_geojson: bool,
+    pub flag_dot_out: Option<String>,
+    pub flag_profits: Option<String>,
+    pub flag_nodes: Option<String>,
+    pub flag_matrix_format: Option<String>,
+    pub flag_threshold: Option<f64>,
+    pub flag_scale: Option<f64>,
+    pub flag_model: Option<String>,
+    pub flag_n: Option<usize>,
+    pub flag_p: Option<f64>,
+    pub flag_seed: Option<u64>,
+    pub flag_max_cost: Option<f64>,
+    pub flag_query: Option<String>,
 }
 
 pub fn get_args() -> Args {